@@ -3,8 +3,22 @@
 //! Some common examples of big integer algorithms.
 #![cfg(feature = "big-math")]
 
+mod der;
+mod dh;
+mod discrete_log;
+pub mod ec;
+mod elgamal;
 mod hello_bigmath;
 mod poly1305;
+mod prime;
+mod rsa;
+mod schnorr;
 
+pub use self::dh::DHGroup;
+pub use self::discrete_log::{baby_step_giant_step, pohlig_hellman, pollard_rho};
+pub use self::elgamal::ElGamalCiphertext;
 pub use self::hello_bigmath::factorial;
 pub use self::poly1305::Poly1305;
+pub use self::prime::{is_probable_prime, random_below, random_bits, random_prime};
+pub use self::rsa::{RSAPrivateKey, RSAPublicKey};
+pub use self::schnorr::SchnorrSignature;
@@ -0,0 +1,177 @@
+//! Just enough DER to read and write public and private keys
+//!
+//! Only the types found in the key formats are supported: integers, bit and
+//! octet strings, nulls, object identifiers and sequences. Reading is strict:
+//! lengths and integers must be minimally encoded, and integers non-negative.
+//!
+//! - [`X.690`](https://www.itu.int/rec/T-REC-X.690)
+
+use num_bigint::BigUint;
+
+pub(crate) const INTEGER: u8 = 0x02;
+pub(crate) const BIT_STRING: u8 = 0x03;
+pub(crate) const OCTET_STRING: u8 = 0x04;
+pub(crate) const NULL: u8 = 0x05;
+pub(crate) const OBJECT_IDENTIFIER: u8 = 0x06;
+pub(crate) const SEQUENCE: u8 = 0x30;
+
+/// Encodes a value with tag `tag`
+pub(crate) fn tlv(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut out = vec![tag];
+    if content.len() < 0x80 {
+        out.push(content.len() as u8);
+    } else {
+        let length = content.len().to_be_bytes();
+        let skip = length.iter().take_while(|&&b| b == 0).count();
+        out.push(0x80 | (length.len() - skip) as u8);
+        out.extend_from_slice(&length[skip..]);
+    }
+    out.extend_from_slice(content);
+    out
+}
+
+/// Encodes a non-negative integer
+pub(crate) fn integer(n: &BigUint) -> Vec<u8> {
+    let mut bytes = n.to_bytes_be();
+    if bytes[0] & 0x80 != 0 {
+        bytes.insert(0, 0);
+    }
+    tlv(INTEGER, &bytes)
+}
+
+/// Encodes a sequence of already encoded values
+pub(crate) fn sequence(items: &[&[u8]]) -> Vec<u8> {
+    tlv(SEQUENCE, &items.concat())
+}
+
+/// Reads the values of a DER encoding one after the other
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    /// Reads a value with tag `tag` and returns its content
+    pub fn read(&mut self, tag: u8) -> Result<&'a [u8], &'static str> {
+        let (&actual, rest) = self.data.split_first().ok_or("Unexpected end of DER")?;
+        if actual != tag {
+            return Err("Unexpected DER tag");
+        }
+        let (&first, mut rest) = rest.split_first().ok_or("Unexpected end of DER")?;
+        let length = if first < 0x80 {
+            usize::from(first)
+        } else {
+            let count = usize::from(first & 0x7f);
+            if count == 0 || count > 4 || rest.len() < count || rest[0] == 0 {
+                return Err("Invalid DER length");
+            }
+            let length = rest[..count]
+                .iter()
+                .fold(0, |length, &b| length << 8 | usize::from(b));
+            if length < 0x80 {
+                return Err("Invalid DER length");
+            }
+            rest = &rest[count..];
+            length
+        };
+        if rest.len() < length {
+            return Err("Unexpected end of DER");
+        }
+        let (content, rest) = rest.split_at(length);
+        self.data = rest;
+        Ok(content)
+    }
+
+    /// Reads a non-negative integer
+    pub fn integer(&mut self) -> Result<BigUint, &'static str> {
+        match self.read(INTEGER)? {
+            [] => Err("Empty DER integer"),
+            [first, ..] if first & 0x80 != 0 => Err("Negative DER integer"),
+            [0, second, ..] if second & 0x80 == 0 => Err("Non-minimal DER integer"),
+            bytes => Ok(BigUint::from_bytes_be(bytes)),
+        }
+    }
+
+    /// Reads a sequence, returning a reader of its items
+    pub fn sequence(&mut self) -> Result<Self, &'static str> {
+        self.read(SEQUENCE).map(Self::new)
+    }
+
+    /// Fails unless all the data was read
+    pub fn finish(self) -> Result<(), &'static str> {
+        if self.data.is_empty() {
+            Ok(())
+        } else {
+            Err("Trailing data after DER value")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lengths() {
+        for (len, header) in [
+            (0, &[0x04, 0x00][..]),
+            (0x7f, &[0x04, 0x7f]),
+            (0x80, &[0x04, 0x81, 0x80]),
+            (0xff, &[0x04, 0x81, 0xff]),
+            (0x100, &[0x04, 0x82, 0x01, 0x00]),
+            (0x12345, &[0x04, 0x83, 0x01, 0x23, 0x45]),
+        ] {
+            let content = vec![0xaa; len];
+            let encoded = tlv(OCTET_STRING, &content);
+            assert_eq!(&encoded[..header.len()], header);
+            let mut reader = Reader::new(&encoded);
+            assert_eq!(reader.read(OCTET_STRING), Ok(&content[..]));
+            reader.finish().unwrap();
+        }
+        // non-minimal lengths
+        assert!(Reader::new(&[0x04, 0x81, 0x01, 0x00])
+            .read(OCTET_STRING)
+            .is_err());
+        assert!(Reader::new(&[0x04, 0x82, 0x00, 0x80])
+            .read(OCTET_STRING)
+            .is_err());
+        // indefinite length and truncation
+        assert!(Reader::new(&[0x04, 0x80, 0x00, 0x00])
+            .read(OCTET_STRING)
+            .is_err());
+        assert!(Reader::new(&[0x04, 0x02, 0x00]).read(OCTET_STRING).is_err());
+        assert!(Reader::new(&[0x05, 0x00]).read(OCTET_STRING).is_err());
+    }
+
+    #[test]
+    fn integers() {
+        for (n, encoded) in [
+            (0_u32, &[0x02, 0x01, 0x00][..]),
+            (0x7f, &[0x02, 0x01, 0x7f]),
+            (0x80, &[0x02, 0x02, 0x00, 0x80]),
+            (65537, &[0x02, 0x03, 0x01, 0x00, 0x01]),
+        ] {
+            assert_eq!(integer(&BigUint::from(n)), encoded);
+            assert_eq!(Reader::new(encoded).integer(), Ok(BigUint::from(n)));
+        }
+        assert!(Reader::new(&[0x02, 0x00]).integer().is_err());
+        assert!(Reader::new(&[0x02, 0x01, 0x80]).integer().is_err());
+        assert!(Reader::new(&[0x02, 0x02, 0x00, 0x7f]).integer().is_err());
+    }
+
+    #[test]
+    fn sequences() {
+        let one = integer(&BigUint::from(1_u32));
+        let encoded = sequence(&[&one, &tlv(NULL, &[])]);
+        assert_eq!(encoded, [0x30, 0x05, 0x02, 0x01, 0x01, 0x05, 0x00]);
+        let mut reader = Reader::new(&encoded);
+        let mut items = reader.sequence().unwrap();
+        reader.finish().unwrap();
+        assert_eq!(items.integer(), Ok(BigUint::from(1_u32)));
+        assert!(items.finish().is_err());
+    }
+}
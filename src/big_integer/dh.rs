@@ -0,0 +1,217 @@
+//! Finite-field Diffie–Hellman
+//!
+//! Both parties pick a secret exponent `x` and publish `g^x mod p`, and each
+//! raises the public value of the other to its own secret to get the shared
+//! secret `g^(x y)`. A [`DHGroup`] is a prime `p` with a generator `g` of a
+//! subgroup of prime order `q`, so that the secrets live in `[1, q)`.
+//!
+//! The public value of the other party must be checked to be in the subgroup
+//! of `g`: otherwise it may have a small order, and the shared secret then
+//! leaks the secret exponent modulo that order (see [`pohlig_hellman`]).
+//!
+//! Note that this implementation is meant for teaching and testing: the big
+//! integer arithmetic is not constant time. Only the 2048-bit groups of the
+//! RFCs below are provided, as [`DHGroup::modp2048`] and
+//! [`DHGroup::ffdhe2048`]; other groups can be built with [`DHGroup::new`].
+//!
+//! - [`RFC3526`](https://www.rfc-editor.org/rfc/rfc3526)
+//! - [`RFC7919`](https://www.rfc-editor.org/rfc/rfc7919)
+//!
+//! [`pohlig_hellman`]: super::pohlig_hellman
+
+use num_bigint::BigUint;
+use num_integer::Integer;
+use num_traits::One;
+
+use super::prime::random_below;
+use crate::math::Generator;
+
+/// A prime modulus with a generator of a subgroup of prime order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DHGroup {
+    p: BigUint,
+    g: BigUint,
+    q: BigUint,
+}
+
+/// The 2048-bit MODP group of RFC 3526, group 14
+const MODP_2048: &str = concat!(
+    "ffffffffffffffffc90fdaa22168c234c4c6628b80dc1cd129024e088a67cc74",
+    "020bbea63b139b22514a08798e3404ddef9519b3cd3a431b302b0a6df25f1437",
+    "4fe1356d6d51c245e485b576625e7ec6f44c42e9a637ed6b0bff5cb6f406b7ed",
+    "ee386bfb5a899fa5ae9f24117c4b1fe649286651ece45b3dc2007cb8a163bf05",
+    "98da48361c55d39a69163fa8fd24cf5f83655d23dca3ad961c62f356208552bb",
+    "9ed529077096966d670c354e4abc9804f1746c08ca18217c32905e462e36ce3b",
+    "e39e772c180e86039b2783a2ec07a28fb5c55df06f4c52c9de2bcbf695581718",
+    "3995497cea956ae515d2261898fa051015728e5a8aacaa68ffffffffffffffff",
+);
+
+/// The ffdhe2048 group of RFC 7919
+const FFDHE_2048: &str = concat!(
+    "ffffffffffffffffadf85458a2bb4a9aafdc5620273d3cf1d8b9c583ce2d3695",
+    "a9e13641146433fbcc939dce249b3ef97d2fe363630c75d8f681b202aec4617a",
+    "d3df1ed5d5fd65612433f51f5f066ed0856365553ded1af3b557135e7f57c935",
+    "984f0c70e0e68b77e2a689daf3efe8721df158a136ade73530acca4f483a797a",
+    "bc0ab182b324fb61d108a94bb2c8e3fbb96adab760d7f4681d4f42a3de394df4",
+    "ae56ede76372bb190b07a7c8ee0a6d709e02fce1cdf7e2ecc03404cd28342f61",
+    "9172fe9ce98583ff8e4f1232eef28183c3fe3b1b4c6fad733bb5fcbc2ec22005",
+    "c58ef1837d1683b2c6f34a26c1b2effa886b423861285c97ffffffffffffffff",
+);
+
+impl DHGroup {
+    /// Create a group from the modulus `p`, the generator `g` and its order
+    /// `q`. The primality of `p` and `q` is not checked, but `g` must have
+    /// order `q`.
+    pub fn new(p: BigUint, g: BigUint, q: BigUint) -> Result<Self, &'static str> {
+        let one = BigUint::one();
+        if p <= BigUint::from(3_u32) || !p.is_odd() {
+            return Err("The modulus must be an odd prime");
+        }
+        if g <= one || g >= &p - 1_u32 {
+            return Err("The generator must be between 2 and p - 2");
+        }
+        if !(&p - 1_u32).is_multiple_of(&q) || g.modpow(&q, &p) != one {
+            return Err("The order of the generator must be q");
+        }
+        Ok(Self { p, g, q })
+    }
+
+    /// A safe prime `p = 2 q + 1` given in hexadecimal, with the generator 2
+    fn safe_prime(hex: &str) -> Self {
+        let p = BigUint::parse_bytes(hex.as_bytes(), 16).unwrap();
+        let q = &p >> 1;
+        Self {
+            p,
+            g: BigUint::from(2_u32),
+            q,
+        }
+    }
+
+    /// The 2048-bit MODP group of RFC 3526, group 14 of IKE
+    pub fn modp2048() -> Self {
+        Self::safe_prime(MODP_2048)
+    }
+
+    /// The ffdhe2048 group of RFC 7919, for TLS
+    pub fn ffdhe2048() -> Self {
+        Self::safe_prime(FFDHE_2048)
+    }
+
+    /// The modulus
+    pub fn p(&self) -> &BigUint {
+        &self.p
+    }
+
+    /// The generator
+    pub fn g(&self) -> &BigUint {
+        &self.g
+    }
+
+    /// The order of the generator
+    pub fn q(&self) -> &BigUint {
+        &self.q
+    }
+
+    /// Returns a random secret exponent in `[1, q)`
+    pub fn generate_secret(&self, rng: &mut impl Generator) -> BigUint {
+        random_below(&(&self.q - 1_u32), rng) + 1_u32
+    }
+
+    /// The public value `g^secret` of `secret`
+    pub fn public_key(&self, secret: &BigUint) -> BigUint {
+        self.g.modpow(secret, &self.p)
+    }
+
+    /// Checks that `public` is in `[2, p - 2]` and in the subgroup of the
+    /// generator, so that it does not have a small order
+    pub fn validate_public(&self, public: &BigUint) -> Result<(), &'static str> {
+        let one = BigUint::one();
+        if *public <= one || *public >= &self.p - 1_u32 {
+            return Err("The public value must be between 2 and p - 2");
+        }
+        if public.modpow(&self.q, &self.p) != one {
+            return Err("The public value is not in the subgroup");
+        }
+        Ok(())
+    }
+
+    /// The secret shared with the owner of the `other` public value
+    pub fn shared_secret(
+        &self,
+        secret: &BigUint,
+        other: &BigUint,
+    ) -> Result<BigUint, &'static str> {
+        self.validate_public(other)?;
+        Ok(other.modpow(secret, &self.p))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::big_integer::is_probable_prime;
+    use crate::math::ChaCha20Rng;
+
+    #[test]
+    fn standard_groups() {
+        let mut rng = ChaCha20Rng::new_default(0);
+        for group in [DHGroup::modp2048(), DHGroup::ffdhe2048()] {
+            assert_eq!(group.p().bits(), 2048);
+            assert!(is_probable_prime(group.p(), 2, &mut rng));
+            assert!(is_probable_prime(group.q(), 2, &mut rng));
+            let checked = DHGroup::new(group.p().clone(), group.g().clone(), group.q().clone());
+            assert_eq!(checked, Ok(group));
+        }
+    }
+
+    #[test]
+    fn key_agreement() {
+        let mut rng = ChaCha20Rng::new_default(1);
+        for group in [DHGroup::modp2048(), DHGroup::ffdhe2048()] {
+            let alice = group.generate_secret(&mut rng);
+            let bob = group.generate_secret(&mut rng);
+            let alice_public = group.public_key(&alice);
+            let bob_public = group.public_key(&bob);
+            let shared = group.shared_secret(&alice, &bob_public).unwrap();
+            assert_eq!(group.shared_secret(&bob, &alice_public), Ok(shared.clone()));
+            assert_eq!(shared, group.public_key(&(alice * bob)));
+        }
+    }
+
+    #[test]
+    fn public_validation() {
+        let group = DHGroup::modp2048();
+        let p = group.p();
+        for invalid in [
+            BigUint::from(0_u32),
+            BigUint::one(),
+            p - 1_u32,
+            p.clone(),
+            // 2 generates the subgroup of order q, -2 is outside of it
+            p - 2_u32,
+        ] {
+            assert!(group.validate_public(&invalid).is_err());
+            assert!(group
+                .shared_secret(&BigUint::from(5_u32), &invalid)
+                .is_err());
+        }
+        assert!(group.validate_public(&BigUint::from(4_u32)).is_ok());
+    }
+
+    #[test]
+    fn invalid_groups() {
+        let p = BigUint::from(23_u32);
+        // 2 has order 11 modulo 23, 5 has order 22
+        assert!(DHGroup::new(p.clone(), BigUint::from(2_u32), BigUint::from(11_u32)).is_ok());
+        assert!(DHGroup::new(p.clone(), BigUint::from(5_u32), BigUint::from(11_u32)).is_err());
+        assert!(DHGroup::new(p.clone(), BigUint::from(2_u32), BigUint::from(7_u32)).is_err());
+        assert!(DHGroup::new(p.clone(), BigUint::one(), BigUint::from(11_u32)).is_err());
+        assert!(DHGroup::new(p.clone(), BigUint::from(22_u32), BigUint::from(2_u32)).is_err());
+        assert!(DHGroup::new(
+            BigUint::from(24_u32),
+            BigUint::from(2_u32),
+            BigUint::from(11_u32)
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,285 @@
+//! Discrete logarithms in small groups
+//!
+//! Each function solves `g^x = h mod p` for `x`, where `g` has order `n`:
+//!
+//! - [`baby_step_giant_step`] stores `g^j` for `j < m = ceil(sqrt(n))`, then
+//!   looks for `h g^(-i m)` among them, in `O(sqrt(n))` time and memory.
+//! - [`pollard_rho`] walks a pseudo-random sequence `g^a h^b` until it
+//!   cycles, in `O(sqrt(n))` expected time and constant memory, for a prime
+//!   `n`.
+//! - [`pohlig_hellman`] splits the problem into one per prime power factor of
+//!   `n`, each solved digit by digit, so that it only takes `O(sqrt(r))` for
+//!   the largest prime factor `r`. This is why a group whose order has only
+//!   small factors is broken, and why Diffie–Hellman public values must be
+//!   checked to be in a subgroup of large prime order.
+//!
+//! - [`Wikipedia`](https://en.wikipedia.org/wiki/Discrete_logarithm)
+
+use std::collections::HashMap;
+
+use num_bigint::BigUint;
+use num_integer::Integer;
+use num_traits::{One, Zero};
+
+use super::prime::random_below;
+use crate::math::Generator;
+
+/// Solves `g^x = h mod p` where `g` has order `order`, with the baby-step
+/// giant-step algorithm. Returns the solution in `[0, order)`, or `None` if
+/// `h` is not a power of `g` or `order` is zero.
+pub fn baby_step_giant_step(
+    g: &BigUint,
+    h: &BigUint,
+    p: &BigUint,
+    order: &BigUint,
+) -> Option<BigUint> {
+    if order.is_zero() {
+        return None;
+    }
+    let mut m = order.sqrt();
+    if &m * &m < *order {
+        m += 1_u32;
+    }
+    let mut baby_steps = HashMap::new();
+    let mut power = BigUint::one();
+    let mut j = BigUint::zero();
+    while j < m {
+        baby_steps.entry(power.clone()).or_insert_with(|| j.clone());
+        power = power * g % p;
+        j += 1_u32;
+    }
+    // g^(-m) = g^(order - m)
+    let giant_step = g.modpow(&(order - &m % order), p);
+    let mut gamma = h % p;
+    let mut i = BigUint::zero();
+    while i < m {
+        if let Some(j) = baby_steps.get(&gamma) {
+            return Some((&i * &m + j) % order);
+        }
+        gamma = gamma * &giant_step % p;
+        i += 1_u32;
+    }
+    None
+}
+
+/// A point of the walk of Pollard's rho, `x = g^a h^b`
+#[derive(Debug, Clone, PartialEq)]
+struct RhoState {
+    x: BigUint,
+    a: BigUint,
+    b: BigUint,
+}
+
+/// Solves `g^x = h mod p` where `g` has prime order `order`, with Pollard's
+/// rho algorithm. Returns `None` if no solution was found after a few random
+/// starting points, which should only happen if `h` is not a power of `g`,
+/// or if `order` is zero.
+pub fn pollard_rho(
+    g: &BigUint,
+    h: &BigUint,
+    p: &BigUint,
+    order: &BigUint,
+    rng: &mut impl Generator,
+) -> Option<BigUint> {
+    const ATTEMPTS: usize = 8;
+
+    if order.is_zero() {
+        return None;
+    }
+    let h = h % p;
+    let n = order;
+    // splits the group in three parts, to square or multiply by g or h
+    let step = |state: &mut RhoState| match (&state.x % 3_u32).to_u32_digits().first() {
+        None => {
+            state.x = &state.x * &state.x % p;
+            state.a = &state.a * 2_u32 % n;
+            state.b = &state.b * 2_u32 % n;
+        }
+        Some(1) => {
+            state.x = &state.x * g % p;
+            state.a = (&state.a + 1_u32) % n;
+        }
+        Some(_) => {
+            state.x = &state.x * &h % p;
+            state.b = (&state.b + 1_u32) % n;
+        }
+    };
+
+    for _ in 0..ATTEMPTS {
+        let a = random_below(n, rng);
+        let b = random_below(n, rng);
+        let x = g.modpow(&a, p) * h.modpow(&b, p) % p;
+        let mut tortoise = RhoState { x, a, b };
+        let mut hare = tortoise.clone();
+        // Floyd's cycle detection
+        loop {
+            step(&mut tortoise);
+            step(&mut hare);
+            step(&mut hare);
+            if tortoise.x == hare.x {
+                break;
+            }
+        }
+        // g^a1 h^b1 = g^a2 h^b2, so x (b1 - b2) = a2 - a1
+        let db = (&tortoise.b + n - &hare.b) % n;
+        let da = (&hare.a + n - &tortoise.a) % n;
+        if let Some(inv) = db.modinv(n) {
+            let x = da * inv % n;
+            if g.modpow(&x, p) == h {
+                return Some(x);
+            }
+        }
+    }
+    None
+}
+
+/// Solves `g^x = h mod p` with the Pohlig–Hellman algorithm, where the order
+/// of `g` is the product of the prime powers `r^e` of `factorization`, given
+/// as pairs `(r, e)`. Returns the solution modulo the order, or `None` if `h`
+/// is not a power of `g`.
+pub fn pohlig_hellman(
+    g: &BigUint,
+    h: &BigUint,
+    p: &BigUint,
+    factorization: &[(BigUint, u32)],
+) -> Option<BigUint> {
+    let order = factorization
+        .iter()
+        .fold(BigUint::one(), |order, (r, e)| order * r.pow(*e));
+    let mut x = BigUint::zero();
+    let mut modulus = BigUint::one();
+    for (r, e) in factorization {
+        let r_e = r.pow(*e);
+        // projections on the subgroup of order r^e
+        let cofactor = &order / &r_e;
+        let g_i = g.modpow(&cofactor, p);
+        let h_i = h.modpow(&cofactor, p);
+        // g_i^(r^(e - 1)) has order r, and each base r digit of the
+        // logarithm is a logarithm in its subgroup
+        let gamma = g_i.modpow(&r.pow(e - 1), p);
+        let mut x_i = BigUint::zero();
+        let mut r_k = BigUint::one();
+        for k in 0..*e {
+            // remove the known digits: h_i g_i^(-x_i)
+            let reduced = h_i.clone() * g_i.modpow(&(&r_e - &x_i), p) % p;
+            let h_k = reduced.modpow(&r.pow(e - 1 - k), p);
+            let digit = baby_step_giant_step(&gamma, &h_k, p, r)?;
+            x_i += digit * &r_k;
+            r_k *= r;
+        }
+        // Chinese remainder theorem: x = x_i mod r^e
+        let t = (&x_i + &r_e - &x % &r_e) % &r_e * modulus.modinv(&r_e)? % &r_e;
+        x += t * &modulus;
+        modulus *= &r_e;
+    }
+    (g.modpow(&x, p) == h % p).then_some(x.mod_floor(&order))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::big_integer::DHGroup;
+    use crate::math::ChaCha20Rng;
+
+    fn n(value: u64) -> BigUint {
+        BigUint::from(value)
+    }
+
+    #[test]
+    fn small_prime() {
+        // 5 is a primitive root modulo 23
+        let (g, p, order) = (n(5), n(23), n(22));
+        for x in 0..22 {
+            let h = g.modpow(&n(x), &p);
+            assert_eq!(baby_step_giant_step(&g, &h, &p, &order), Some(n(x)));
+            let factorization = [(n(2), 1), (n(11), 1)];
+            assert_eq!(pohlig_hellman(&g, &h, &p, &factorization), Some(n(x)));
+        }
+        // 2 has order 11, and 5 is not a power of it
+        assert_eq!(baby_step_giant_step(&n(2), &n(5), &p, &n(11)), None);
+        assert_eq!(pohlig_hellman(&n(2), &n(5), &p, &[(n(11), 1)]), None);
+    }
+
+    #[test]
+    fn prime_order_subgroup() {
+        // p = 2 q + 1, and 4 generates the subgroup of prime order q
+        let (p, q, g) = (n(4901373083), n(2450686541), n(4));
+        let mut rng = ChaCha20Rng::new_default(0);
+        for x in [n(1), n(123456789), n(2450686540)] {
+            let h = g.modpow(&x, &p);
+            assert_eq!(baby_step_giant_step(&g, &h, &p, &q), Some(x.clone()));
+            assert_eq!(pollard_rho(&g, &h, &p, &q, &mut rng), Some(x));
+        }
+        // -1 has order 2, outside of the subgroup
+        assert_eq!(pollard_rho(&g, &(&p - 1_u32), &p, &q, &mut rng), None);
+        assert_eq!(pollard_rho(&g, &g, &p, &n(0), &mut rng), None);
+        assert_eq!(baby_step_giant_step(&g, &g, &p, &n(0)), None);
+    }
+
+    #[test]
+    fn smooth_order() {
+        // p - 1 = 2 3^2 5^2 7 13^2 17 19 23 37 41^2 43, and 7 is a primitive
+        // root: logarithms modulo this 54-bit prime are easy
+        let p = n(10577073197158651);
+        let factorization = [
+            (n(2), 1),
+            (n(3), 2),
+            (n(5), 2),
+            (n(7), 1),
+            (n(13), 2),
+            (n(17), 1),
+            (n(19), 1),
+            (n(23), 1),
+            (n(37), 1),
+            (n(41), 2),
+            (n(43), 1),
+        ];
+        let g = n(7);
+        let mut rng = ChaCha20Rng::new_default(1);
+        for _ in 0..10 {
+            let x = random_below(&(&p - 1_u32), &mut rng);
+            let h = g.modpow(&x, &p);
+            assert_eq!(pohlig_hellman(&g, &h, &p, &factorization), Some(x));
+        }
+    }
+
+    #[test]
+    fn small_subgroup_attack() {
+        // p - 1 = 2 q 101 103 107 109 113 for a 32-bit prime q, and g
+        // generates the subgroup of order q
+        let p = BigUint::from(64390822486369846547_u128);
+        let q = n(2348262589);
+        let group = DHGroup::new(
+            p.clone(),
+            BigUint::from(46880849456355431861_u128),
+            q.clone(),
+        )
+        .unwrap();
+        let mut rng = ChaCha20Rng::new_default(2);
+        let secret = group.generate_secret(&mut rng);
+
+        // the attacker sends a public value of small order r, and learns
+        // secret mod r from the shared secret of a victim who does not check it
+        let mut residues = Vec::new();
+        for r in [101_u64, 103, 107, 109, 113] {
+            let cofactor = (&p - 1_u32) / r;
+            let element = (2_u64..)
+                .map(|a| n(a).modpow(&cofactor, &p))
+                .find(|element| !element.is_one())
+                .unwrap();
+            assert!(group.shared_secret(&secret, &element).is_err());
+            let leaked = element.modpow(&secret, &p);
+            let residue = baby_step_giant_step(&element, &leaked, &p, &n(r)).unwrap();
+            residues.push((residue, n(r)));
+        }
+        // 101 103 107 109 113 > q, so the residues give away the secret
+        let (recovered, _) = residues
+            .into_iter()
+            .reduce(|(x, m), (x_r, r)| {
+                let t = (&x_r + &r - &x % &r) % &r * m.modinv(&r).unwrap() % &r;
+                (x + t * &m, m * r)
+            })
+            .unwrap();
+        assert_eq!(recovered, secret);
+    }
+}
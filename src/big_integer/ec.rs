@@ -0,0 +1,14 @@
+//! Elliptic curve cryptography
+//!
+//! Key exchange with [`x25519`], signatures with Ed25519 and with ECDSA on
+//! secp256k1 or P-256, all built on the arithmetic of [`PrimeField`].
+
+mod ecdsa;
+mod ed25519;
+mod field;
+mod x25519;
+
+pub use self::ecdsa::{EcdsaSignature, Point, WeierstrassCurve};
+pub use self::ed25519::{ed25519_public_key, ed25519_sign, ed25519_verify};
+pub use self::field::PrimeField;
+pub use self::x25519::{x25519, X25519_BASE_POINT};
@@ -0,0 +1,587 @@
+//! ECDSA on short Weierstrass curves
+//!
+//! Curves `y^2 = x^3 + a x + b` modulo a prime, with [secp256k1] and
+//! [P-256]. Scalar multiplication works in Jacobian coordinates, where
+//! `(X, Y, Z)` is the point `(X / Z^2, Y / Z^3)`, so that only the result has
+//! to be inverted. Signatures hash the message with [`SHA256`] and take their
+//! nonce from [`RFC6979`], so they are deterministic.
+//!
+//! Note that the scalar multiplication is not constant time.
+//!
+//! [secp256k1]: https://www.secg.org/sec2-v2.pdf
+//! [P-256]: https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.186-5.pdf
+//! [`RFC6979`]: https://www.rfc-editor.org/rfc/rfc6979
+
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+
+use super::PrimeField;
+use crate::ciphers::{HMAC, SHA256};
+
+/// A point of a [`WeierstrassCurve`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Point {
+    /// The point at infinity, the identity of the group
+    Infinity,
+    /// A point with affine coordinates
+    Affine {
+        /// The `x`-coordinate
+        x: BigUint,
+        /// The `y`-coordinate
+        y: BigUint,
+    },
+}
+
+/// An ECDSA signature
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EcdsaSignature {
+    /// The `x`-coordinate of the nonce point, modulo the group order
+    pub r: BigUint,
+    /// The proof of knowledge of the secret key
+    pub s: BigUint,
+}
+
+/// A point in Jacobian coordinates, the point at infinity having `z = 0`
+#[derive(Debug, Clone)]
+struct JacobianPoint {
+    x: BigUint,
+    y: BigUint,
+    z: BigUint,
+}
+
+/// A curve `y^2 = x^3 + a x + b` with a generator of prime order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WeierstrassCurve {
+    field: PrimeField,
+    a: BigUint,
+    b: BigUint,
+    generator: Point,
+    order: BigUint,
+}
+
+fn hex(text: &str) -> BigUint {
+    BigUint::parse_bytes(text.as_bytes(), 16).unwrap()
+}
+
+impl WeierstrassCurve {
+    /// secp256k1, the curve of Bitcoin, `y^2 = x^3 + 7`
+    pub fn secp256k1() -> Self {
+        Self {
+            field: PrimeField::secp256k1(),
+            a: BigUint::zero(),
+            b: BigUint::from(7_u32),
+            generator: Point::Affine {
+                x: hex("79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"),
+                y: hex("483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8"),
+            },
+            order: hex("fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141"),
+        }
+    }
+
+    /// NIST P-256, also known as secp256r1
+    pub fn p256() -> Self {
+        let field = PrimeField::new(hex(
+            "ffffffff00000001000000000000000000000000ffffffffffffffffffffffff",
+        ));
+        Self {
+            a: field.neg(&BigUint::from(3_u32)),
+            field,
+            b: hex("5ac635d8aa3a93e7b3ebbd55769886bc651d06b0cc53b0f63bce3c3e27d2604b"),
+            generator: Point::Affine {
+                x: hex("6b17d1f2e12c4247f8bce6e563a440f277037d812deb33a0f4a13945d898c296"),
+                y: hex("4fe342e2fe1a7f9b8ee7eb4a7c0f9e162bce33576b315ececbb6406837bf51f5"),
+            },
+            order: hex("ffffffff00000000ffffffffffffffffbce6faada7179e84f3b9cac2fc632551"),
+        }
+    }
+
+    /// The field of the coordinates
+    pub fn field(&self) -> &PrimeField {
+        &self.field
+    }
+
+    /// The generator of the group
+    pub fn generator(&self) -> &Point {
+        &self.generator
+    }
+
+    /// The order of the generator
+    pub fn order(&self) -> &BigUint {
+        &self.order
+    }
+
+    /// Whether `point` is on the curve
+    pub fn contains(&self, point: &Point) -> bool {
+        match point {
+            Point::Infinity => true,
+            Point::Affine { x, y } => {
+                let f = &self.field;
+                x < f.modulus()
+                    && y < f.modulus()
+                    && f.mul(y, y) == f.add(&f.mul(x, &f.add(&f.mul(x, x), &self.a)), &self.b)
+            }
+        }
+    }
+
+    fn to_jacobian(point: &Point) -> JacobianPoint {
+        match point {
+            Point::Infinity => JacobianPoint {
+                x: BigUint::one(),
+                y: BigUint::one(),
+                z: BigUint::zero(),
+            },
+            Point::Affine { x, y } => JacobianPoint {
+                x: x.clone(),
+                y: y.clone(),
+                z: BigUint::one(),
+            },
+        }
+    }
+
+    fn to_affine(&self, point: &JacobianPoint) -> Point {
+        let f = &self.field;
+        let Some(z_inv) = f.inv(&point.z) else {
+            return Point::Infinity;
+        };
+        let z_inv2 = f.mul(&z_inv, &z_inv);
+        Point::Affine {
+            x: f.mul(&point.x, &z_inv2),
+            y: f.mul(&point.y, &f.mul(&z_inv2, &z_inv)),
+        }
+    }
+
+    fn double(&self, p: &JacobianPoint) -> JacobianPoint {
+        let f = &self.field;
+        if p.z.is_zero() || p.y.is_zero() {
+            return Self::to_jacobian(&Point::Infinity);
+        }
+        let xx = f.mul(&p.x, &p.x);
+        let yy = f.mul(&p.y, &p.y);
+        let zz = f.mul(&p.z, &p.z);
+        // s = 4 x y^2, m = 3 x^2 + a z^4
+        let s = f.mul(&BigUint::from(4_u32), &f.mul(&p.x, &yy));
+        let m = f.add(
+            &f.mul(&BigUint::from(3_u32), &xx),
+            &f.mul(&self.a, &f.mul(&zz, &zz)),
+        );
+        let x = f.sub(&f.mul(&m, &m), &f.add(&s, &s));
+        let y = f.sub(
+            &f.mul(&m, &f.sub(&s, &x)),
+            &f.mul(&BigUint::from(8_u32), &f.mul(&yy, &yy)),
+        );
+        let z = f.mul(&BigUint::from(2_u32), &f.mul(&p.y, &p.z));
+        JacobianPoint { x, y, z }
+    }
+
+    fn add_jacobian(&self, p: &JacobianPoint, q: &JacobianPoint) -> JacobianPoint {
+        let f = &self.field;
+        if p.z.is_zero() {
+            return q.clone();
+        }
+        if q.z.is_zero() {
+            return p.clone();
+        }
+        let z1z1 = f.mul(&p.z, &p.z);
+        let z2z2 = f.mul(&q.z, &q.z);
+        let u1 = f.mul(&p.x, &z2z2);
+        let u2 = f.mul(&q.x, &z1z1);
+        let s1 = f.mul(&p.y, &f.mul(&q.z, &z2z2));
+        let s2 = f.mul(&q.y, &f.mul(&p.z, &z1z1));
+        let h = f.sub(&u2, &u1);
+        let r = f.sub(&s2, &s1);
+        if h.is_zero() {
+            return if r.is_zero() {
+                self.double(p)
+            } else {
+                Self::to_jacobian(&Point::Infinity)
+            };
+        }
+        let hh = f.mul(&h, &h);
+        let hhh = f.mul(&h, &hh);
+        let v = f.mul(&u1, &hh);
+        let x = f.sub(&f.sub(&f.mul(&r, &r), &hhh), &f.add(&v, &v));
+        let y = f.sub(&f.mul(&r, &f.sub(&v, &x)), &f.mul(&s1, &hhh));
+        let z = f.mul(&f.mul(&p.z, &q.z), &h);
+        JacobianPoint { x, y, z }
+    }
+
+    fn mul_jacobian(&self, k: &BigUint, point: &Point) -> JacobianPoint {
+        let p = Self::to_jacobian(point);
+        let mut result = Self::to_jacobian(&Point::Infinity);
+        for i in (0..k.bits()).rev() {
+            result = self.double(&result);
+            if k.bit(i) {
+                result = self.add_jacobian(&result, &p);
+            }
+        }
+        result
+    }
+
+    /// `p + q`
+    pub fn add(&self, p: &Point, q: &Point) -> Point {
+        self.to_affine(&self.add_jacobian(&Self::to_jacobian(p), &Self::to_jacobian(q)))
+    }
+
+    /// `k p`
+    pub fn mul(&self, k: &BigUint, p: &Point) -> Point {
+        self.to_affine(&self.mul_jacobian(k, p))
+    }
+
+    /// Length in bytes of the field elements
+    fn field_bytes(&self) -> usize {
+        self.field.modulus().bits().div_ceil(8) as usize
+    }
+
+    /// The SEC 1 encoding of `point`: `04 || x || y`, or `02 || x` / `03 || x`
+    /// depending on the parity of `y` if `compressed`, and `00` for the point
+    /// at infinity
+    pub fn encode_point(&self, point: &Point, compressed: bool) -> Vec<u8> {
+        let len = self.field_bytes();
+        let to_bytes = |n: &BigUint| {
+            let bytes = n.to_bytes_be();
+            let mut out = vec![0; len - bytes.len()];
+            out.extend(bytes);
+            out
+        };
+        match point {
+            Point::Infinity => vec![0],
+            Point::Affine { x, y } if compressed => {
+                let mut out = vec![2 + u8::from(y.bit(0))];
+                out.extend(to_bytes(x));
+                out
+            }
+            Point::Affine { x, y } => {
+                let mut out = vec![4];
+                out.extend(to_bytes(x));
+                out.extend(to_bytes(y));
+                out
+            }
+        }
+    }
+
+    /// Read a point from its SEC 1 encoding, failing if it is not on the curve
+    pub fn decode_point(&self, bytes: &[u8]) -> Result<Point, &'static str> {
+        let len = self.field_bytes();
+        let f = &self.field;
+        let point = match bytes {
+            [0] => Point::Infinity,
+            [4, coordinates @ ..] if coordinates.len() == 2 * len => Point::Affine {
+                x: BigUint::from_bytes_be(&coordinates[..len]),
+                y: BigUint::from_bytes_be(&coordinates[len..]),
+            },
+            [prefix @ (2 | 3), x @ ..] if x.len() == len => {
+                let x = BigUint::from_bytes_be(x);
+                if x >= *f.modulus() {
+                    return Err("Point not on the curve");
+                }
+                let rhs = f.add(&f.mul(&x, &f.add(&f.mul(&x, &x), &self.a)), &self.b);
+                let mut y = f.sqrt(&rhs).ok_or("Point not on the curve")?;
+                if y.bit(0) != (*prefix == 3) {
+                    y = f.neg(&y);
+                }
+                Point::Affine { x, y }
+            }
+            _ => return Err("Invalid point encoding"),
+        };
+        if self.contains(&point) {
+            Ok(point)
+        } else {
+            Err("Point not on the curve")
+        }
+    }
+
+    /// The public key of the `secret` key, which must be in `[1, n)` where
+    /// `n` is the order
+    pub fn public_key(&self, secret: &BigUint) -> Result<Point, &'static str> {
+        if secret.is_zero() || *secret >= self.order {
+            return Err("The secret key must be between 1 and the order");
+        }
+        Ok(self.mul(secret, &self.generator))
+    }
+
+    /// The leftmost bits of `hash`, as many as the order has, as an integer
+    fn bits_to_int(&self, hash: &[u8]) -> BigUint {
+        let value = BigUint::from_bytes_be(hash);
+        let (hash_bits, order_bits) = (8 * hash.len() as u64, self.order.bits());
+        if hash_bits > order_bits {
+            value >> (hash_bits - order_bits)
+        } else {
+            value
+        }
+    }
+
+    /// The nonce of RFC 6979 section 3.2 for the `secret` key and `hash`,
+    /// with HMAC-SHA-256. `retry` is called with every candidate, and the
+    /// first one it accepts is returned.
+    fn nonce<T>(
+        &self,
+        secret: &BigUint,
+        hash: &[u8],
+        mut retry: impl FnMut(&BigUint) -> Option<T>,
+    ) -> T {
+        let order_bytes = self.order.bits().div_ceil(8) as usize;
+        let int_to_octets = |n: &BigUint| {
+            let bytes = n.to_bytes_be();
+            let mut out = vec![0; order_bytes - bytes.len()];
+            out.extend(bytes);
+            out
+        };
+        let x = int_to_octets(secret);
+        let h = int_to_octets(&(self.bits_to_int(hash) % &self.order));
+        let hmac = |key: &[u8], parts: &[&[u8]]| {
+            let mut hmac = HMAC::<64, 32, SHA256>::new_default();
+            hmac.add_key(key).unwrap();
+            for part in parts {
+                hmac.update(part);
+            }
+            hmac.finalize()
+        };
+
+        let mut v = [1; 32];
+        let mut k = [0; 32];
+        k = hmac(&k, &[&v, &[0], &x, &h]);
+        v = hmac(&k, &[&v]);
+        k = hmac(&k, &[&v, &[1], &x, &h]);
+        v = hmac(&k, &[&v]);
+        loop {
+            let mut t = Vec::with_capacity(order_bytes + 32);
+            while t.len() < order_bytes {
+                v = hmac(&k, &[&v]);
+                t.extend_from_slice(&v);
+            }
+            let candidate = self.bits_to_int(&t);
+            if !candidate.is_zero() && candidate < self.order {
+                if let Some(result) = retry(&candidate) {
+                    return result;
+                }
+            }
+            k = hmac(&k, &[&v, &[0]]);
+            v = hmac(&k, &[&v]);
+        }
+    }
+
+    /// Sign `message`, hashed with SHA-256
+    pub fn sign(&self, secret: &BigUint, message: &[u8]) -> Result<EcdsaSignature, &'static str> {
+        let mut hasher = SHA256::new_default();
+        hasher.update(message);
+        self.sign_hash(secret, &hasher.get_hash())
+    }
+
+    /// Sign a message whose hash is `hash`, with the deterministic nonce of
+    /// RFC 6979
+    pub fn sign_hash(&self, secret: &BigUint, hash: &[u8]) -> Result<EcdsaSignature, &'static str> {
+        if secret.is_zero() || *secret >= self.order {
+            return Err("The secret key must be between 1 and the order");
+        }
+        let e = self.bits_to_int(hash);
+        let n = &self.order;
+        Ok(self.nonce(secret, hash, |k| {
+            let Point::Affine { x, .. } = self.mul(k, &self.generator) else {
+                return None;
+            };
+            let r = x % n;
+            let s = k.modinv(n)? * (&e + &r * secret) % n;
+            (!r.is_zero() && !s.is_zero()).then_some(EcdsaSignature { r, s })
+        }))
+    }
+
+    /// Whether `signature` is a valid signature of `message`, hashed with
+    /// SHA-256, by the `public` key
+    pub fn verify(&self, public: &Point, message: &[u8], signature: &EcdsaSignature) -> bool {
+        let mut hasher = SHA256::new_default();
+        hasher.update(message);
+        self.verify_hash(public, &hasher.get_hash(), signature)
+    }
+
+    /// Whether `signature` is a valid signature of a message whose hash is
+    /// `hash` by the `public` key
+    pub fn verify_hash(&self, public: &Point, hash: &[u8], signature: &EcdsaSignature) -> bool {
+        let n = &self.order;
+        let EcdsaSignature { r, s } = signature;
+        if *public == Point::Infinity || !self.contains(public) {
+            return false;
+        }
+        if r.is_zero() || s.is_zero() || r >= n || s >= n {
+            return false;
+        }
+        let w = s.modinv(n).unwrap();
+        let u1 = self.bits_to_int(hash) * &w % n;
+        let u2 = r * &w % n;
+        let point = self.add_jacobian(
+            &self.mul_jacobian(&u1, &self.generator),
+            &self.mul_jacobian(&u2, public),
+        );
+        match self.to_affine(&point) {
+            Point::Infinity => false,
+            Point::Affine { x, .. } => x % n == *r,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoding::{Encoding, Hex};
+
+    #[test]
+    fn group_law() {
+        for curve in [WeierstrassCurve::secp256k1(), WeierstrassCurve::p256()] {
+            let g = curve.generator().clone();
+            assert!(curve.contains(&g));
+            assert_eq!(curve.mul(curve.order(), &g), Point::Infinity);
+            let two_g = curve.add(&g, &g);
+            let three_g = curve.add(&two_g, &g);
+            assert_eq!(curve.mul(&BigUint::from(3_u32), &g), three_g);
+            assert!(curve.contains(&three_g));
+            let minus_g = curve.mul(&(curve.order() - 1_u32), &g);
+            assert_eq!(curve.add(&g, &minus_g), Point::Infinity);
+            assert_eq!(curve.add(&Point::Infinity, &g), g);
+            let k = BigUint::from(0xdeadbeef_u32);
+            assert_eq!(curve.mul(&k, &three_g), curve.mul(&(k * 3_u32), &g),);
+        }
+    }
+
+    #[test]
+    fn point_encoding() {
+        let curve = WeierstrassCurve::secp256k1();
+        let g = curve.generator();
+        let compressed = curve.encode_point(g, true);
+        assert_eq!(
+            Hex::LOWER.encode(&compressed),
+            "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"
+        );
+        assert_eq!(curve.decode_point(&compressed).as_ref(), Ok(g));
+        let minus_g = curve.mul(&(curve.order() - 1_u32), g);
+        let compressed = curve.encode_point(&minus_g, true);
+        assert_eq!(compressed[0], 3);
+        assert_eq!(curve.decode_point(&compressed), Ok(minus_g.clone()));
+        let uncompressed = curve.encode_point(&minus_g, false);
+        assert_eq!(uncompressed.len(), 65);
+        assert_eq!(curve.decode_point(&uncompressed), Ok(minus_g));
+        assert_eq!(curve.decode_point(&[0]), Ok(Point::Infinity));
+
+        let mut not_on_curve = uncompressed.clone();
+        not_on_curve[64] ^= 1;
+        assert!(curve.decode_point(&not_on_curve).is_err());
+        assert!(curve.decode_point(&uncompressed[1..]).is_err());
+        // x = 5 is not the coordinate of a point: 5^3 + 7 is not a square
+        let mut x = vec![2; 33];
+        x[1..].fill(0);
+        x[32] = 5;
+        assert!(curve.decode_point(&x).is_err());
+    }
+
+    #[test]
+    fn rfc6979_p256() {
+        // appendix A.2.5, with SHA-256
+        let curve = WeierstrassCurve::p256();
+        let secret = hex("c9afa9d845ba75166b5c215767b1d6934e50c3db36e89b127b8a622b120f6721");
+        let public = curve.public_key(&secret).unwrap();
+        assert_eq!(
+            public,
+            Point::Affine {
+                x: hex("60fed4ba255a9d31c961eb74c6356d68c049b8923b61fa6ce669622e60f29fb6"),
+                y: hex("7903fe1008b8bc99a41ae9e95628bc64f2f1b20c2d7e9f5177a3c294d4462299"),
+            }
+        );
+        for (message, k, r, s) in [
+            (
+                "sample",
+                "a6e3c57dd01abe90086538398355dd4c3b17aa873382b0f24d6129493d8aad60",
+                "efd48b2aacb6a8fd1140dd9cd45e81d69d2c877b56aaf991c34d0ea84eaf3716",
+                "f7cb1c942d657c41d436c7a1b6e29f65f3e900dbb9aff4064dc4ab2f843acda8",
+            ),
+            (
+                "test",
+                "d16b6ae827f17175e040871a1c7ec3500192c4c92677336ec2537acaee0008e0",
+                "f1abb023518351cd71d881567b1ea663ed3efcf6c5132b354f28d3b0b7d38367",
+                "019f4113742a2b14bd25926b49c649155f267e60d3814b4c0cc84250e46f0083",
+            ),
+        ] {
+            let mut hasher = SHA256::new_default();
+            hasher.update(message.as_bytes());
+            let nonce = curve.nonce(&secret, &hasher.get_hash(), |k| Some(k.clone()));
+            assert_eq!(nonce, hex(k));
+            let signature = curve.sign(&secret, message.as_bytes()).unwrap();
+            assert_eq!(
+                signature,
+                EcdsaSignature {
+                    r: hex(r),
+                    s: hex(s)
+                }
+            );
+            assert!(curve.verify(&public, message.as_bytes(), &signature));
+            assert!(!curve.verify(&public, b"other", &signature));
+        }
+    }
+
+    #[test]
+    fn secp256k1_signatures() {
+        // nonces from the commonly used secp256k1 RFC 6979 vectors, the
+        // signatures checked with the Python `cryptography` package
+        let curve = WeierstrassCurve::secp256k1();
+        for (secret, message, k, r, s) in [
+            (
+                "1",
+                "Satoshi Nakamoto",
+                "8f8a276c19f4149656b280621e358cce24f5f52542772691ee69063b74f15d15",
+                "934b1ea10a4b3c1757e2b0c017d0b6143ce3c9a7e6a4a49860d7a6ab210ee3d8",
+                "dbbd3162d46e9f9bef7feb87c16dc13b4f6568a87f4e83f728e2443ba586675c",
+            ),
+            (
+                "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364140",
+                "Satoshi Nakamoto",
+                "33a19b60e25fb6f4435af53a3d42d493644827367e6453928554f43e49aa6f90",
+                "fd567d121db66e382991534ada77a6bd3106f0a1098c231e47993447cd6af2d0",
+                "94c632f14e4379fc1ea610a3df5a375152549736425ee17cebe10abbc2a2826c",
+            ),
+            (
+                "f8b8af8ce3c7cca5e300d33939540c10d45ce001b8f252bfbc57ba0342904181",
+                "Alan Turing",
+                "525a82b70e67874398067543fd84c83d30c175fdc45fdeee082fe13b1d7cfdf1",
+                "7063ae83e7f62bbb171798131b4a0564b956930092b33b07b395615d9ec7e15c",
+                "a72033e1ff5ca1ea8d0c99001cb45f0272d3be7525d3049c0d9e98dc7582b857",
+            ),
+        ] {
+            let secret = hex(secret);
+            let mut hasher = SHA256::new_default();
+            hasher.update(message.as_bytes());
+            let hash = hasher.get_hash();
+            assert_eq!(curve.nonce(&secret, &hash, |k| Some(k.clone())), hex(k));
+            let signature = curve.sign_hash(&secret, &hash).unwrap();
+            assert_eq!(signature.r, hex(r));
+            assert_eq!(signature.s, hex(s));
+            let public = curve.public_key(&secret).unwrap();
+            assert!(curve.verify_hash(&public, &hash, &signature));
+            // (r, -s) is also valid, the basis of the low-s rule of Bitcoin
+            let negated = EcdsaSignature {
+                r: signature.r.clone(),
+                s: curve.order() - &signature.s,
+            };
+            assert!(curve.verify_hash(&public, &hash, &negated));
+        }
+    }
+
+    #[test]
+    fn invalid_signatures() {
+        let curve = WeierstrassCurve::secp256k1();
+        let secret = BigUint::from(42_u32);
+        let public = curve.public_key(&secret).unwrap();
+        let signature = curve.sign(&secret, b"message").unwrap();
+        assert!(curve.verify(&public, b"message", &signature));
+        let other = curve.public_key(&BigUint::from(43_u32)).unwrap();
+        assert!(!curve.verify(&other, b"message", &signature));
+        for (r, s) in [
+            (BigUint::zero(), signature.s.clone()),
+            (signature.r.clone(), BigUint::zero()),
+            (&signature.r + curve.order(), signature.s.clone()),
+            (signature.r.clone() + 1_u32, signature.s.clone()),
+        ] {
+            assert!(!curve.verify(&public, b"message", &EcdsaSignature { r, s }));
+        }
+        assert!(!curve.verify(&Point::Infinity, b"message", &signature));
+        assert!(curve.public_key(&BigUint::zero()).is_err());
+        assert!(curve.public_key(curve.order()).is_err());
+        assert!(curve.sign(curve.order(), b"message").is_err());
+    }
+}
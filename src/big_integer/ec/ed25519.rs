@@ -0,0 +1,325 @@
+//! Ed25519 signatures
+//!
+//! EdDSA on the twisted Edwards curve `-x^2 + y^2 = 1 + d x^2 y^2` modulo
+//! `2^255 - 19`, with [`SHA512`]. Points are kept in extended coordinates
+//! `(X, Y, Z, T)` with `x = X / Z`, `y = Y / Z` and `x y = T / Z`, whose
+//! addition formula also works for doubling.
+//!
+//! - [`RFC8032`](https://www.rfc-editor.org/rfc/rfc8032)
+
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+
+use super::PrimeField;
+use crate::ciphers::SHA512;
+
+/// Encoding of the base point, whose `y`-coordinate is 4/5
+const BASE_POINT: [u8; 32] = [
+    0x58, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66,
+    0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66,
+];
+
+/// A point in extended coordinates
+#[derive(Debug, Clone)]
+struct EdwardsPoint {
+    x: BigUint,
+    y: BigUint,
+    z: BigUint,
+    t: BigUint,
+}
+
+/// The field, the curve constant and the group order
+struct Curve {
+    field: PrimeField,
+    /// `2 d`, where `d = -121665 / 121666`
+    d2: BigUint,
+    /// The order of the base point, `2^252 + 27742317777372353535851937790883648493`
+    order: BigUint,
+}
+
+impl Curve {
+    fn new() -> Self {
+        let field = PrimeField::curve25519();
+        let d = field.mul(
+            &field.neg(&BigUint::from(121665_u32)),
+            &field.inv(&BigUint::from(121666_u32)).unwrap(),
+        );
+        let d2 = field.add(&d, &d);
+        let order = (BigUint::one() << 252_u32)
+            + "27742317777372353535851937790883648493"
+                .parse::<BigUint>()
+                .unwrap();
+        Self { field, d2, order }
+    }
+
+    fn identity(&self) -> EdwardsPoint {
+        EdwardsPoint {
+            x: BigUint::zero(),
+            y: BigUint::one(),
+            z: BigUint::one(),
+            t: BigUint::zero(),
+        }
+    }
+
+    fn add(&self, p: &EdwardsPoint, q: &EdwardsPoint) -> EdwardsPoint {
+        let f = &self.field;
+        let a = f.mul(&f.sub(&p.y, &p.x), &f.sub(&q.y, &q.x));
+        let b = f.mul(&f.add(&p.y, &p.x), &f.add(&q.y, &q.x));
+        let c = f.mul(&f.mul(&p.t, &self.d2), &q.t);
+        let d = f.mul(&f.add(&p.z, &p.z), &q.z);
+        let (e, f_, g, h) = (f.sub(&b, &a), f.sub(&d, &c), f.add(&d, &c), f.add(&b, &a));
+        EdwardsPoint {
+            x: f.mul(&e, &f_),
+            y: f.mul(&g, &h),
+            z: f.mul(&f_, &g),
+            t: f.mul(&e, &h),
+        }
+    }
+
+    /// `k p`, by double-and-add
+    fn mul(&self, k: &BigUint, p: &EdwardsPoint) -> EdwardsPoint {
+        let mut result = self.identity();
+        for i in (0..k.bits()).rev() {
+            result = self.add(&result, &result);
+            if k.bit(i) {
+                result = self.add(&result, p);
+            }
+        }
+        result
+    }
+
+    fn equals(&self, p: &EdwardsPoint, q: &EdwardsPoint) -> bool {
+        let f = &self.field;
+        f.mul(&p.x, &q.z) == f.mul(&q.x, &p.z) && f.mul(&p.y, &q.z) == f.mul(&q.y, &p.z)
+    }
+
+    /// The `y`-coordinate in little-endian, with the sign of `x` in the most
+    /// significant bit
+    fn encode(&self, p: &EdwardsPoint) -> [u8; 32] {
+        let f = &self.field;
+        let z_inv = f.inv(&p.z).unwrap();
+        let (x, y) = (f.mul(&p.x, &z_inv), f.mul(&p.y, &z_inv));
+        let mut out = [0; 32];
+        let bytes = y.to_bytes_le();
+        out[..bytes.len()].copy_from_slice(&bytes);
+        out[31] |= u8::from(x.bit(0)) << 7;
+        out
+    }
+
+    /// Recovers `x` from `y` and its sign, failing if `y` is not reduced or
+    /// not the coordinate of a point
+    fn decode(&self, bytes: &[u8; 32]) -> Option<EdwardsPoint> {
+        let f = &self.field;
+        let mut bytes = *bytes;
+        let sign = bytes[31] >> 7 == 1;
+        bytes[31] &= 0x7f;
+        let y = BigUint::from_bytes_le(&bytes);
+        if y >= *f.modulus() {
+            return None;
+        }
+        // x^2 = (y^2 - 1) / (d y^2 + 1)
+        let y2 = f.mul(&y, &y);
+        let d = f.mul(&self.d2, &f.inv(&BigUint::from(2_u32)).unwrap());
+        let u = f.sub(&y2, &BigUint::one());
+        let v = f.add(&f.mul(&d, &y2), &BigUint::one());
+        let mut x = f.sqrt(&f.mul(&u, &f.inv(&v)?))?;
+        if x.is_zero() && sign {
+            return None;
+        }
+        if x.bit(0) != sign {
+            x = f.neg(&x);
+        }
+        let t = f.mul(&x, &y);
+        Some(EdwardsPoint {
+            x,
+            y,
+            z: BigUint::one(),
+            t,
+        })
+    }
+
+    fn base_point(&self) -> EdwardsPoint {
+        self.decode(&BASE_POINT).unwrap()
+    }
+
+    /// SHA-512 of the concatenation of `parts`, as an integer modulo the
+    /// group order
+    fn hash_scalar(&self, parts: &[&[u8]]) -> BigUint {
+        let mut hasher = SHA512::new_default();
+        for part in parts {
+            hasher.update(part);
+        }
+        BigUint::from_bytes_le(&hasher.get_hash()) % &self.order
+    }
+}
+
+/// Expands a secret key into the secret scalar and the prefix used to derive
+/// the nonces
+fn expand(secret: &[u8; 32]) -> (BigUint, [u8; 32]) {
+    let mut hasher = SHA512::new_default();
+    hasher.update(secret);
+    let h = hasher.get_hash();
+    let mut scalar: [u8; 32] = h[..32].try_into().unwrap();
+    scalar[0] &= 248;
+    scalar[31] &= 127;
+    scalar[31] |= 64;
+    (BigUint::from_bytes_le(&scalar), h[32..].try_into().unwrap())
+}
+
+/// The public key of the 32 byte `secret` key
+pub fn ed25519_public_key(secret: &[u8; 32]) -> [u8; 32] {
+    let curve = Curve::new();
+    let (scalar, _) = expand(secret);
+    curve.encode(&curve.mul(&scalar, &curve.base_point()))
+}
+
+/// Sign `message` with the `secret` key. The signature is deterministic.
+pub fn ed25519_sign(secret: &[u8; 32], message: &[u8]) -> [u8; 64] {
+    let curve = Curve::new();
+    let base = curve.base_point();
+    let (scalar, prefix) = expand(secret);
+    let public = curve.encode(&curve.mul(&scalar, &base));
+    let r = curve.hash_scalar(&[&prefix, message]);
+    let big_r = curve.encode(&curve.mul(&r, &base));
+    let k = curve.hash_scalar(&[&big_r, &public, message]);
+    let s = (r + k * scalar) % &curve.order;
+
+    let mut signature = [0; 64];
+    signature[..32].copy_from_slice(&big_r);
+    let bytes = s.to_bytes_le();
+    signature[32..32 + bytes.len()].copy_from_slice(&bytes);
+    signature
+}
+
+/// Whether `signature` is a valid signature of `message` by the `public` key.
+///
+/// The cofactored equation `[8][S]B = [8]R + [8][k]A` is checked, and `S`
+/// must be reduced.
+pub fn ed25519_verify(public: &[u8; 32], message: &[u8], signature: &[u8; 64]) -> bool {
+    let curve = Curve::new();
+    let big_r: [u8; 32] = signature[..32].try_into().unwrap();
+    let (Some(a), Some(r)) = (curve.decode(public), curve.decode(&big_r)) else {
+        return false;
+    };
+    let s = BigUint::from_bytes_le(&signature[32..]);
+    if s >= curve.order {
+        return false;
+    }
+    let k = curve.hash_scalar(&[&big_r, public, message]);
+    let eight = BigUint::from(8_u32);
+    let left = curve.mul(&(s * &eight), &curve.base_point());
+    let right = curve.mul(&eight, &curve.add(&r, &curve.mul(&k, &a)));
+    curve.equals(&left, &right)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoding::{Encoding, Hex};
+
+    fn hex(text: &str) -> Vec<u8> {
+        Hex::LOWER.decode(text).unwrap()
+    }
+
+    /// RFC 8032 section 7.1: secret key, public key, message, signature
+    const VECTORS: [[&str; 4]; 4] = [
+        [
+            "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60",
+            "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+            "",
+            concat!(
+                "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e06522490155",
+                "5fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b"
+            ),
+        ],
+        [
+            "4ccd089b28ff96da9db6c346ec114e0f5b8a319f35aba624da8cf6ed4fb8a6fb",
+            "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c",
+            "72",
+            concat!(
+                "92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da",
+                "085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00"
+            ),
+        ],
+        [
+            "c5aa8df43f9f837bedb7442f31dcb7b166d38535076f094b85ce3a2e0b4458f7",
+            "fc51cd8e6218a1a38da47ed00230f0580816ed13ba3303ac5deb911548908025",
+            "af82",
+            concat!(
+                "6291d657deec24024827e69c3abe01a30ce548a284743a445e3680d7db5ac3ac",
+                "18ff9b538d16f290ae67f760984dc6594a7c15e9716ed28dc027beceea1ec40a"
+            ),
+        ],
+        [
+            "833fe62409237b9d62ec77587520911e9a759cec1d19755b7da901b96dca3d42",
+            "ec172b93ad5e563bf4932c70e1245034c35467ef2efd4d64ebf819683467e2bf",
+            // SHA-512 of "abc"
+            concat!(
+                "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a",
+                "2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f"
+            ),
+            concat!(
+                "dc2a4459e7369633a52b1bf277839a00201009a3efbf3ecb69bea2186c26b589",
+                "09351fc9ac90b3ecfdfbc7c66431e0303dca179c138ac17ad9bef1177331a704"
+            ),
+        ],
+    ];
+
+    #[test]
+    fn rfc8032_vectors() {
+        for [secret, public, message, signature] in VECTORS {
+            let secret: [u8; 32] = hex(secret).try_into().unwrap();
+            let public: [u8; 32] = hex(public).try_into().unwrap();
+            let signature: [u8; 64] = hex(signature).try_into().unwrap();
+            let message = hex(message);
+            assert_eq!(ed25519_public_key(&secret), public);
+            assert_eq!(ed25519_sign(&secret, &message), signature);
+            assert!(ed25519_verify(&public, &message, &signature));
+        }
+    }
+
+    #[test]
+    fn invalid_signatures() {
+        let [secret, public, _, _] = VECTORS[1];
+        let secret: [u8; 32] = hex(secret).try_into().unwrap();
+        let public: [u8; 32] = hex(public).try_into().unwrap();
+        let signature = ed25519_sign(&secret, b"message");
+        assert!(ed25519_verify(&public, b"message", &signature));
+        assert!(!ed25519_verify(&public, b"massage", &signature));
+        for i in [0, 31, 32, 63] {
+            let mut tampered = signature;
+            tampered[i] ^= 1;
+            assert!(!ed25519_verify(&public, b"message", &tampered));
+        }
+        // S + L is the same scalar, but not reduced
+        let curve = Curve::new();
+        let s = BigUint::from_bytes_le(&signature[32..]) + &curve.order;
+        let mut malleated = signature;
+        malleated[32..].copy_from_slice(&s.to_bytes_le());
+        assert!(!ed25519_verify(&public, b"message", &malleated));
+        // a public key which is not a point
+        let mut not_a_point = [0; 32];
+        not_a_point[0] = 2;
+        assert!(curve.decode(&not_a_point).is_none());
+        assert!(!ed25519_verify(&not_a_point, b"message", &signature));
+    }
+
+    #[test]
+    fn point_encoding() {
+        let curve = Curve::new();
+        let base = curve.base_point();
+        assert_eq!(curve.encode(&base), BASE_POINT);
+        assert!(!base.x.bit(0));
+        // the base point has order L
+        assert!(curve.equals(&curve.mul(&curve.order, &base), &curve.identity()));
+        let p = curve.mul(&BigUint::from(12345_u32), &base);
+        let decoded = curve.decode(&curve.encode(&p)).unwrap();
+        assert!(curve.equals(&p, &decoded));
+        // y = 1 is the identity, and x = 0 can not be negative
+        let mut one = [0; 32];
+        one[0] = 1;
+        assert!(curve.equals(&curve.decode(&one).unwrap(), &curve.identity()));
+        one[31] |= 0x80;
+        assert!(curve.decode(&one).is_none());
+    }
+}
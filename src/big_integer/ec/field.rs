@@ -0,0 +1,160 @@
+//! Arithmetic in the field of integers modulo a prime
+
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+
+/// The field of integers modulo the prime `p`. Elements are [`BigUint`]s in
+/// `[0, p)`, and every operation expects its operands to be reduced.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrimeField {
+    p: BigUint,
+}
+
+impl PrimeField {
+    /// Create a new instance for the prime `p`. Its primality is not checked.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `p` is less than 2.
+    pub fn new(p: BigUint) -> Self {
+        assert!(p > BigUint::one(), "The modulus must be a prime");
+        Self { p }
+    }
+
+    /// The field of Curve25519 and Ed25519, modulo `2^255 - 19`
+    pub fn curve25519() -> Self {
+        Self::new((BigUint::one() << 255_u32) - 19_u32)
+    }
+
+    /// The field of secp256k1, modulo `2^256 - 2^32 - 977`
+    pub fn secp256k1() -> Self {
+        Self::new((BigUint::one() << 256_u32) - (BigUint::one() << 32_u32) - 977_u32)
+    }
+
+    /// The modulus
+    pub fn modulus(&self) -> &BigUint {
+        &self.p
+    }
+
+    /// Reduces any integer into the field
+    pub fn reduce(&self, a: &BigUint) -> BigUint {
+        a % &self.p
+    }
+
+    /// `a + b`
+    pub fn add(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        let sum = a + b;
+        if sum >= self.p {
+            sum - &self.p
+        } else {
+            sum
+        }
+    }
+
+    /// `a - b`
+    pub fn sub(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        if a >= b {
+            a - b
+        } else {
+            &self.p - b + a
+        }
+    }
+
+    /// `-a`
+    pub fn neg(&self, a: &BigUint) -> BigUint {
+        self.sub(&BigUint::zero(), a)
+    }
+
+    /// `a * b`
+    pub fn mul(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        a * b % &self.p
+    }
+
+    /// `a^exponent`
+    pub fn pow(&self, a: &BigUint, exponent: &BigUint) -> BigUint {
+        a.modpow(exponent, &self.p)
+    }
+
+    /// The inverse of `a`, or `None` if `a` is zero
+    pub fn inv(&self, a: &BigUint) -> Option<BigUint> {
+        if a.is_zero() {
+            return None;
+        }
+        // Fermat's little theorem: a^(p - 1) = 1
+        Some(self.pow(a, &(&self.p - 2_u32)))
+    }
+
+    /// A square root of `a`, or `None` if `a` is not a square. The other one
+    /// is its negation.
+    ///
+    /// # Panics
+    ///
+    /// Only primes congruent to 3 modulo 4, like the prime of secp256k1, or to
+    /// 5 modulo 8, like `2^255 - 19`, are supported.
+    pub fn sqrt(&self, a: &BigUint) -> Option<BigUint> {
+        let root = if self.p.bit(0) && self.p.bit(1) {
+            // p = 3 mod 4: a^((p + 1) / 4)
+            self.pow(a, &((&self.p + 1_u32) >> 2))
+        } else {
+            assert!(
+                self.p.bit(0) && self.p.bit(2),
+                "The modulus must be 3 mod 4 or 5 mod 8"
+            );
+            // p = 5 mod 8: a^((p + 3) / 8), times a square root of -1 if
+            // its square is -a
+            let root = self.pow(a, &((&self.p + 3_u32) >> 3));
+            if self.mul(&root, &root) == *a {
+                root
+            } else {
+                let i = self.pow(&BigUint::from(2_u32), &((&self.p - 1_u32) >> 2));
+                self.mul(&root, &i)
+            }
+        };
+        (self.mul(&root, &root) == *a).then_some(root)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn small_field() {
+        for p in [7_u32, 11, 13, 29] {
+            let field = PrimeField::new(BigUint::from(p));
+            for a in 0..p {
+                let a = BigUint::from(a);
+                assert_eq!(field.add(&a, &field.neg(&a)), BigUint::zero());
+                assert_eq!(field.sub(&a, &a), BigUint::zero());
+                if let Some(inv) = field.inv(&a) {
+                    assert!(field.mul(&a, &inv).is_one());
+                } else {
+                    assert!(a.is_zero());
+                }
+                let square = field.mul(&a, &a);
+                let root = field.sqrt(&square).unwrap();
+                assert!(root == a || root == field.neg(&a));
+            }
+            // zero and (p - 1) / 2 non-zero squares
+            let squares = (0..p)
+                .filter(|&a| field.sqrt(&BigUint::from(a)).is_some())
+                .count();
+            assert_eq!(squares as u32, p.div_ceil(2));
+        }
+    }
+
+    #[test]
+    fn large_fields() {
+        for field in [PrimeField::curve25519(), PrimeField::secp256k1()] {
+            let a = field.reduce(&(BigUint::from(3_u32) << 250_u32));
+            let b = field.sub(&BigUint::from(5_u32), &a);
+            assert_eq!(field.add(&a, &b), BigUint::from(5_u32));
+            assert!(field.mul(&a, &field.inv(&a).unwrap()).is_one());
+            let root = field.sqrt(&field.mul(&b, &b)).unwrap();
+            assert!(root == b || root == field.neg(&b));
+            // -1 is a square modulo 2^255 - 19, not modulo the secp256k1 prime
+            let minus_one = field.neg(&BigUint::one());
+            assert_eq!(field.sqrt(&minus_one).is_some(), !field.p.bit(1));
+        }
+    }
+}
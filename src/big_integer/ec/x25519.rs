@@ -0,0 +1,161 @@
+//! X25519 key exchange
+//!
+//! Scalar multiplication on the Montgomery curve `v^2 = u^3 + 486662 u^2 + u`
+//! modulo `2^255 - 19`, with the Montgomery ladder on the `u`-coordinate only.
+//!
+//! - [`RFC7748`](https://www.rfc-editor.org/rfc/rfc7748)
+
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+
+use super::PrimeField;
+
+/// The `u`-coordinate of the base point, 9
+pub const X25519_BASE_POINT: [u8; 32] = {
+    let mut u = [0; 32];
+    u[0] = 9;
+    u
+};
+
+/// Multiplies the point of `u`-coordinate `u` by the clamped `scalar`, both
+/// encoded in little-endian. The public key of a secret `scalar` is its
+/// product with [`X25519_BASE_POINT`], and two parties share the product of
+/// their secret with the public key of the other.
+///
+/// As the RFC suggests, callers may check that a shared secret is not all
+/// zeros, which happens when `u` is a point of small order.
+pub fn x25519(scalar: &[u8; 32], u: &[u8; 32]) -> [u8; 32] {
+    let field = PrimeField::curve25519();
+    let mut k = *scalar;
+    k[0] &= 248;
+    k[31] &= 127;
+    k[31] |= 64;
+    let k = BigUint::from_bytes_le(&k);
+    let mut u = *u;
+    u[31] &= 127;
+    let x1 = field.reduce(&BigUint::from_bytes_le(&u));
+    let a24 = BigUint::from(121665_u32);
+
+    let (mut x2, mut z2) = (BigUint::one(), BigUint::zero());
+    let (mut x3, mut z3) = (x1.clone(), BigUint::one());
+    let mut swap = false;
+    for t in (0..255).rev() {
+        let bit = k.bit(t);
+        if swap != bit {
+            std::mem::swap(&mut x2, &mut x3);
+            std::mem::swap(&mut z2, &mut z3);
+        }
+        swap = bit;
+
+        let a = field.add(&x2, &z2);
+        let aa = field.mul(&a, &a);
+        let b = field.sub(&x2, &z2);
+        let bb = field.mul(&b, &b);
+        let e = field.sub(&aa, &bb);
+        let c = field.add(&x3, &z3);
+        let d = field.sub(&x3, &z3);
+        let da = field.mul(&d, &a);
+        let cb = field.mul(&c, &b);
+        let sum = field.add(&da, &cb);
+        let difference = field.sub(&da, &cb);
+        x3 = field.mul(&sum, &sum);
+        z3 = field.mul(&x1, &field.mul(&difference, &difference));
+        x2 = field.mul(&aa, &bb);
+        z2 = field.mul(&e, &field.add(&aa, &field.mul(&a24, &e)));
+    }
+    if swap {
+        std::mem::swap(&mut x2, &mut x3);
+        std::mem::swap(&mut z2, &mut z3);
+    }
+    // z2 is zero for the point at infinity, which is encoded as 0
+    let u = field.mul(&x2, &field.inv(&z2).unwrap_or_default());
+    let mut out = [0; 32];
+    let bytes = u.to_bytes_le();
+    out[..bytes.len()].copy_from_slice(&bytes);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoding::{Encoding, Hex};
+
+    fn hex(text: &str) -> [u8; 32] {
+        Hex::LOWER.decode(text).unwrap().try_into().unwrap()
+    }
+
+    #[test]
+    fn rfc7748_vectors() {
+        // section 5.2
+        for (scalar, u, expected) in [
+            (
+                "a546e36bf0527c9d3b16154b82465edd62144c0ac1fc5a18506a2244ba449ac4",
+                "e6db6867583030db3594c1a424b15f7c726624ec26b3353b10a903a6d0ab1c4c",
+                "c3da55379de9c6908e94ea4df28d084f32eccf03491c71f754b4075577a28552",
+            ),
+            (
+                "4b66e9d4d1b4673c5ad22691957d6af5c11b6421e0ea01d42ca4169e7918ba0d",
+                "e5210f12786811d3f4b7959d0538ae2c31dbe7106fc03c3efc4cd549c715a493",
+                "95cbde9476e8907d7aade45cb4b873f88b595a68799fa152e6f8f7647aac7957",
+            ),
+        ] {
+            assert_eq!(x25519(&hex(scalar), &hex(u)), hex(expected));
+        }
+    }
+
+    fn iterate(iterations: usize) -> [u8; 32] {
+        let (mut k, mut u) = (X25519_BASE_POINT, X25519_BASE_POINT);
+        for _ in 0..iterations {
+            (k, u) = (x25519(&k, &u), k);
+        }
+        k
+    }
+
+    #[test]
+    fn rfc7748_iterations() {
+        assert_eq!(
+            iterate(1),
+            hex("422c8e7a6227d7bca1350b3e2bb7279f7897b87bb6854b783c60e80311ae3079")
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn rfc7748_thousand_iterations() {
+        // This test case takes a few seconds in unoptimized mode
+        assert_eq!(
+            iterate(1000),
+            hex("684cf59ba83309552800ef566f2f4d3c1c3887c49360e3875f2eb94d99532c51")
+        );
+    }
+
+    #[test]
+    fn rfc7748_diffie_hellman() {
+        // section 6.1
+        let alice = hex("77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a");
+        let bob = hex("5dab087e624a8a4b79e17f8b83800ee66f3bb1292618b6fd1c2f8b27ff88e0eb");
+        let alice_public = x25519(&alice, &X25519_BASE_POINT);
+        let bob_public = x25519(&bob, &X25519_BASE_POINT);
+        assert_eq!(
+            alice_public,
+            hex("8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a")
+        );
+        assert_eq!(
+            bob_public,
+            hex("de9edb7d7b7dc1b4d35b61c2ece435373f8343c85b78674dadfc7e146f882b4f")
+        );
+        let shared = hex("4a5d9d5ba4ce2de1728e3bf480350f25e07e21c947d19e3376f09b3c1e161742");
+        assert_eq!(x25519(&alice, &bob_public), shared);
+        assert_eq!(x25519(&bob, &alice_public), shared);
+    }
+
+    #[test]
+    fn small_order_points() {
+        // 0 and 1 have order 4 and 1, their products are 0
+        let scalar = [0x42; 32];
+        assert_eq!(x25519(&scalar, &[0; 32]), [0; 32]);
+        let mut one = [0; 32];
+        one[0] = 1;
+        assert_eq!(x25519(&scalar, &one), [0; 32]);
+    }
+}
@@ -0,0 +1,129 @@
+//! ElGamal encryption
+//!
+//! The key pair is a Diffie–Hellman one: the public key is `y = g^x`. To
+//! encrypt `m`, pick a random `k` and send `(g^k, m y^k)`: the second half is
+//! the message masked with the secret shared with an ephemeral key. The owner
+//! of `x` removes the mask by dividing by `(g^k)^x`.
+//!
+//! Messages are integers in `[1, p)`, used as is. With a safe prime, the
+//! ciphertext tells whether the message is a square modulo `p`: to be
+//! semantically secure, messages must be encoded as elements of the subgroup
+//! first. Ciphertexts are also malleable, `(c1, 2 c2)` decrypting to `2 m`.
+//!
+//! - [`Wikipedia`](https://en.wikipedia.org/wiki/ElGamal_encryption)
+
+use num_bigint::BigUint;
+use num_traits::Zero;
+
+use super::DHGroup;
+use crate::math::Generator;
+
+/// An ElGamal ciphertext
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ElGamalCiphertext {
+    /// The ephemeral public key `g^k`
+    pub c1: BigUint,
+    /// The masked message `m y^k`
+    pub c2: BigUint,
+}
+
+impl DHGroup {
+    /// Encrypt `message`, in `[1, p)`, for the owner of the `public` key
+    pub fn elgamal_encrypt(
+        &self,
+        public: &BigUint,
+        message: &BigUint,
+        rng: &mut impl Generator,
+    ) -> Result<ElGamalCiphertext, &'static str> {
+        self.validate_public(public)?;
+        if message.is_zero() || message >= self.p() {
+            return Err("The message must be between 1 and p - 1");
+        }
+        let k = self.generate_secret(rng);
+        Ok(ElGamalCiphertext {
+            c1: self.public_key(&k),
+            c2: message * public.modpow(&k, self.p()) % self.p(),
+        })
+    }
+
+    /// Decrypt `ciphertext` with the `secret` key
+    pub fn elgamal_decrypt(
+        &self,
+        secret: &BigUint,
+        ciphertext: &ElGamalCiphertext,
+    ) -> Result<BigUint, &'static str> {
+        let ElGamalCiphertext { c1, c2 } = ciphertext;
+        self.validate_public(c1)?;
+        if c2.is_zero() || c2 >= self.p() {
+            return Err("Invalid ciphertext");
+        }
+        // c1 has order q, so c1^(q - x) is the inverse of c1^x
+        let unmask = c1.modpow(&(self.q() - secret % self.q()), self.p());
+        Ok(c2 * unmask % self.p())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::ChaCha20Rng;
+
+    #[test]
+    fn round_trip() {
+        let mut rng = ChaCha20Rng::new_default(0);
+        let group = DHGroup::ffdhe2048();
+        let secret = group.generate_secret(&mut rng);
+        let public = group.public_key(&secret);
+        for message in [
+            BigUint::from(1_u32),
+            BigUint::from(42_u32),
+            BigUint::from_bytes_be(b"attack at dawn"),
+            group.p() - 1_u32,
+        ] {
+            let ciphertext = group.elgamal_encrypt(&public, &message, &mut rng).unwrap();
+            let again = group.elgamal_encrypt(&public, &message, &mut rng).unwrap();
+            assert_ne!(ciphertext, again);
+            assert_eq!(
+                group.elgamal_decrypt(&secret, &ciphertext),
+                Ok(message.clone())
+            );
+            assert_eq!(group.elgamal_decrypt(&secret, &again), Ok(message));
+        }
+    }
+
+    #[test]
+    fn malleability() {
+        let mut rng = ChaCha20Rng::new_default(1);
+        let group = DHGroup::modp2048();
+        let secret = group.generate_secret(&mut rng);
+        let public = group.public_key(&secret);
+        let message = BigUint::from(1000_u32);
+        let mut ciphertext = group.elgamal_encrypt(&public, &message, &mut rng).unwrap();
+        ciphertext.c2 = ciphertext.c2 * 3_u32 % group.p();
+        assert_eq!(
+            group.elgamal_decrypt(&secret, &ciphertext),
+            Ok(BigUint::from(3000_u32))
+        );
+    }
+
+    #[test]
+    fn invalid_inputs() {
+        let mut rng = ChaCha20Rng::new_default(2);
+        let group = DHGroup::modp2048();
+        let secret = group.generate_secret(&mut rng);
+        let public = group.public_key(&secret);
+        for message in [BigUint::zero(), group.p().clone()] {
+            assert!(group.elgamal_encrypt(&public, &message, &mut rng).is_err());
+        }
+        let message = BigUint::from(7_u32);
+        assert!(group
+            .elgamal_encrypt(&(group.p() - 1_u32), &message, &mut rng)
+            .is_err());
+        let ciphertext = group.elgamal_encrypt(&public, &message, &mut rng).unwrap();
+        let invalid = ElGamalCiphertext {
+            c1: group.p() - 2_u32,
+            c2: ciphertext.c2,
+        };
+        assert!(group.elgamal_decrypt(&secret, &invalid).is_err());
+    }
+}
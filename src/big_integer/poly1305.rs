@@ -3,6 +3,8 @@
 //! This implementation is based on RFC8439.
 //! Note that the Big Integer library we are using may not be suitable for
 //! cryptographic applications due to non constant time operations.
+//! [`crate::ciphers::Poly1305`] is a constant time implementation, which is
+//! tested against this one.
 
 use num_bigint::BigUint;
 use num_traits::{Num, Zero};
@@ -80,14 +82,10 @@ impl Poly1305 {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::fmt::Write;
+    use crate::encoding::{Encoding, Hex};
 
     fn get_tag_hex(tag: &[u8]) -> String {
-        let mut result = String::new();
-        for &x in tag {
-            write!(result, "{x:02x}").unwrap();
-        }
-        result
+        Hex::LOWER.encode(tag)
     }
 
     #[test]
@@ -0,0 +1,173 @@
+//! Random big integers and probable primes
+//!
+//! The public-key algorithms draw their randomness from a
+//! [`Generator`](crate::math::Generator), so that seeding it makes key
+//! generation and signing reproducible. Primality is decided with trial
+//! division by the small primes, then the Miller–Rabin test with random bases.
+//!
+//! - [`Wikipedia`](https://en.wikipedia.org/wiki/Miller%E2%80%93Rabin_primality_test)
+
+use num_bigint::BigUint;
+use num_traits::One;
+
+use crate::math::Generator;
+
+/// The primes below 256, used to sieve candidates before Miller–Rabin
+const SMALL_PRIMES: [u32; 54] = [
+    2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97,
+    101, 103, 107, 109, 113, 127, 131, 137, 139, 149, 151, 157, 163, 167, 173, 179, 181, 191, 193,
+    197, 199, 211, 223, 227, 229, 233, 239, 241, 251,
+];
+
+/// Returns a uniformly distributed integer in `[0, 2^bits)`.
+pub fn random_bits(bits: u64, rng: &mut impl Generator) -> BigUint {
+    let mut bytes = vec![0; bits.div_ceil(8) as usize];
+    rng.fill_bytes(&mut bytes);
+    if !bits.is_multiple_of(8) {
+        bytes[0] &= 0xff >> (8 - bits % 8);
+    }
+    BigUint::from_bytes_be(&bytes)
+}
+
+/// Returns a uniformly distributed integer in `[0, bound)`.
+///
+/// # Panics
+///
+/// Panics if `bound` is zero.
+pub fn random_below(bound: &BigUint, rng: &mut impl Generator) -> BigUint {
+    assert!(bound.bits() > 0, "Bound must be non-zero");
+    loop {
+        let candidate = random_bits(bound.bits(), rng);
+        if &candidate < bound {
+            return candidate;
+        }
+    }
+}
+
+/// Whether `n` is a probable prime, after `rounds` rounds of Miller–Rabin with
+/// random bases. A composite passes with probability at most `4^-rounds`.
+pub fn is_probable_prime(n: &BigUint, rounds: usize, rng: &mut impl Generator) -> bool {
+    for &p in &SMALL_PRIMES {
+        if *n == BigUint::from(p) {
+            return true;
+        }
+        if (n % p).bits() == 0 {
+            return false;
+        }
+    }
+    // a composite with no factor below 256 is at least 257^2
+    if *n < BigUint::from(257_u32 * 257) {
+        return *n > BigUint::one();
+    }
+    let n_minus_one = n - 1_u32;
+    let s = n_minus_one.trailing_zeros().unwrap();
+    let d = &n_minus_one >> s;
+    let three = BigUint::from(3_u32);
+    'rounds: for _ in 0..rounds {
+        let a = random_below(&(n - &three), rng) + 2_u32;
+        let mut x = a.modpow(&d, n);
+        if x.is_one() || x == n_minus_one {
+            continue;
+        }
+        for _ in 1..s {
+            x = &x * &x % n;
+            if x == n_minus_one {
+                continue 'rounds;
+            }
+        }
+        return false;
+    }
+    true
+}
+
+/// Returns a random prime of exactly `bits` bits, whose two most significant
+/// bits are set, so that the product of two such primes has exactly `2 * bits`
+/// bits.
+///
+/// The number of Miller–Rabin rounds follows table C.2 of FIPS 186-4 for an
+/// error probability below `2^-100`.
+///
+/// # Panics
+///
+/// Panics if `bits` is less than 2.
+pub fn random_prime(bits: u64, rng: &mut impl Generator) -> BigUint {
+    assert!(bits >= 2, "A prime has at least 2 bits");
+    let rounds = match bits {
+        1024.. => 4,
+        512.. => 7,
+        256.. => 20,
+        _ => 40,
+    };
+    loop {
+        let mut candidate = random_bits(bits, rng);
+        candidate.set_bit(bits - 1, true);
+        candidate.set_bit(bits.saturating_sub(2), true);
+        candidate.set_bit(0, true);
+        if is_probable_prime(&candidate, rounds, rng) {
+            return candidate;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::ChaCha20Rng;
+
+    #[test]
+    fn small_numbers() {
+        let mut rng = ChaCha20Rng::new_default(0);
+        let sieve = crate::math::prime_numbers(5000);
+        for n in 0..5000_u32 {
+            assert_eq!(
+                is_probable_prime(&BigUint::from(n), 10, &mut rng),
+                sieve.contains(&(n as usize)),
+                "{n}"
+            );
+        }
+    }
+
+    #[test]
+    fn large_numbers() {
+        let mut rng = ChaCha20Rng::new_default(0);
+        let mersenne = (BigUint::one() << 127_u32) - 1_u32;
+        assert!(is_probable_prime(&mersenne, 20, &mut rng));
+        // the seventh Fermat number is composite, with no small factor
+        let fermat = (BigUint::one() << 128_u32) + 1_u32;
+        assert!(!is_probable_prime(&fermat, 20, &mut rng));
+        // Carmichael numbers fool the Fermat test, not Miller-Rabin
+        for carmichael in [75361_u32, 278545, 1152271, 10024561] {
+            assert!(!is_probable_prime(&BigUint::from(carmichael), 20, &mut rng));
+        }
+        assert!(!is_probable_prime(&(&mersenne * &mersenne), 20, &mut rng));
+    }
+
+    #[test]
+    fn random_primes() {
+        let mut rng = ChaCha20Rng::new_default(1);
+        for bits in [2, 3, 10, 64, 100, 256] {
+            let p = random_prime(bits, &mut rng);
+            assert_eq!(p.bits(), bits);
+            assert!(p.bit(bits - 2));
+            assert!(is_probable_prime(&p, 40, &mut rng));
+        }
+        let (mut a, mut b) = (ChaCha20Rng::new_default(2), ChaCha20Rng::new_default(2));
+        assert_eq!(random_prime(128, &mut a), random_prime(128, &mut b));
+    }
+
+    #[test]
+    fn random_ranges() {
+        let mut rng = ChaCha20Rng::new_default(3);
+        let bound = BigUint::from(1000_u32);
+        let mut seen = [false; 1000];
+        for _ in 0..20000 {
+            let x = random_below(&bound, &mut rng);
+            assert!(x < bound);
+            seen[x.to_u32_digits().first().copied().unwrap_or(0) as usize] = true;
+        }
+        assert!(seen.iter().all(|&s| s));
+        for bits in [1, 7, 8, 9, 65] {
+            assert!(random_bits(bits, &mut rng).bits() <= bits);
+        }
+    }
+}
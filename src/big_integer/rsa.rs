@@ -0,0 +1,821 @@
+//! RSA encryption and signatures
+//!
+//! This implementation is based on PKCS #1 v2.2 ([`RFC8017`]), with
+//! [`SHA256`] as the hash function of every padding scheme: PKCS #1 v1.5 and
+//! OAEP for encryption, PKCS #1 v1.5 and PSS for signatures. Private key
+//! operations use the Chinese Remainder Theorem.
+//!
+//! Keys are generated from a [`Generator`], so a seeded generator always gives
+//! the same key. They can be read and written in the PKCS #1 and PKCS #8 DER
+//! formats, and public keys as X.509 `SubjectPublicKeyInfo`.
+//!
+//! Note that this implementation is meant for teaching and testing: the big
+//! integer arithmetic is not constant time, and nothing is done against side
+//! channels.
+//!
+//! [`RFC8017`]: https://www.rfc-editor.org/rfc/rfc8017
+
+use num_bigint::BigUint;
+use num_integer::Integer;
+use num_traits::One;
+
+use super::der;
+use super::prime::random_prime;
+use crate::ciphers::SHA256;
+use crate::math::Generator;
+
+/// Length of a SHA-256 hash, which is also the length of PSS salts
+const HASH_BYTES: usize = 32;
+
+/// DER encoding of the `DigestInfo` of a SHA-256 hash, without the hash
+const SHA256_DIGEST_INFO: [u8; 19] = [
+    0x30, 0x31, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01, 0x05,
+    0x00, 0x04, 0x20,
+];
+
+/// Content of the object identifier `rsaEncryption` (1.2.840.113549.1.1.1)
+const RSA_ENCRYPTION: [u8; 9] = [0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x01];
+
+const DECRYPTION_ERROR: &str = "Decryption error";
+
+fn sha256(parts: &[&[u8]]) -> [u8; HASH_BYTES] {
+    let mut hasher = SHA256::new_default();
+    for part in parts {
+        hasher.update(part);
+    }
+    hasher.get_hash()
+}
+
+/// XORs `data` with the MGF1 mask generated from `seed`
+fn mgf1_xor(seed: &[u8], data: &mut [u8]) {
+    for (counter, chunk) in data.chunks_mut(HASH_BYTES).enumerate() {
+        let mask = sha256(&[seed, &(counter as u32).to_be_bytes()]);
+        for (byte, mask) in chunk.iter_mut().zip(mask) {
+            *byte ^= mask;
+        }
+    }
+}
+
+/// The big-endian encoding of `n` in exactly `len` bytes (I2OSP)
+fn to_bytes(n: &BigUint, len: usize) -> Vec<u8> {
+    let bytes = n.to_bytes_be();
+    let mut out = vec![0; len - bytes.len()];
+    out.extend(bytes);
+    out
+}
+
+/// The `AlgorithmIdentifier` of RSA keys
+fn algorithm_identifier() -> Vec<u8> {
+    der::sequence(&[
+        &der::tlv(der::OBJECT_IDENTIFIER, &RSA_ENCRYPTION),
+        &der::tlv(der::NULL, &[]),
+    ])
+}
+
+/// Reads the `AlgorithmIdentifier` of RSA keys
+fn read_algorithm_identifier(reader: &mut der::Reader) -> Result<(), &'static str> {
+    let mut algorithm = reader.sequence()?;
+    if algorithm.read(der::OBJECT_IDENTIFIER)? != RSA_ENCRYPTION {
+        return Err("Not an RSA key");
+    }
+    if !algorithm.read(der::NULL)?.is_empty() {
+        return Err("Invalid RSA algorithm parameters");
+    }
+    algorithm.finish()
+}
+
+/// RSA public key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RSAPublicKey {
+    n: BigUint,
+    e: BigUint,
+}
+
+impl RSAPublicKey {
+    /// Create a new instance from the modulus `n` and the public exponent
+    /// `e`. Fails unless `n` and `e` are odd and `2 < e < n`.
+    pub fn new(n: BigUint, e: BigUint) -> Result<Self, &'static str> {
+        if n.is_even() || n.bits() < 2 {
+            return Err("The modulus must be odd");
+        }
+        if e.is_even() || e.is_one() || e >= n {
+            return Err("The public exponent must be odd and between 2 and the modulus");
+        }
+        Ok(Self { n, e })
+    }
+
+    /// The modulus
+    pub fn n(&self) -> &BigUint {
+        &self.n
+    }
+
+    /// The public exponent
+    pub fn e(&self) -> &BigUint {
+        &self.e
+    }
+
+    /// Length in bytes of the modulus, and of ciphertexts and signatures
+    pub fn size(&self) -> usize {
+        self.n.bits().div_ceil(8) as usize
+    }
+
+    /// The RSA encryption primitive, `m^e mod n`. Fails unless `m < n`.
+    pub fn encrypt_raw(&self, m: &BigUint) -> Result<BigUint, &'static str> {
+        if *m >= self.n {
+            return Err("Message representative out of range");
+        }
+        Ok(m.modpow(&self.e, &self.n))
+    }
+
+    /// Applies the public key to a ciphertext or signature of [`size`](Self::size)
+    /// bytes, returning the result in `len` bytes
+    fn apply(&self, input: &[u8], len: usize) -> Option<Vec<u8>> {
+        if input.len() != self.size() {
+            return None;
+        }
+        let m = self.encrypt_raw(&BigUint::from_bytes_be(input)).ok()?;
+        if m.bits() > 8 * len as u64 {
+            return None;
+        }
+        Some(to_bytes(&m, len))
+    }
+
+    /// Encrypt `message` with the PKCS #1 v1.5 padding, whose random bytes are
+    /// taken from `rng`. Fails if the message is longer than
+    /// [`size`](Self::size) minus 11 bytes.
+    pub fn encrypt_pkcs1v15(
+        &self,
+        message: &[u8],
+        rng: &mut impl Generator,
+    ) -> Result<Vec<u8>, &'static str> {
+        let k = self.size();
+        if message.len() + 11 > k {
+            return Err("Message too long");
+        }
+        let mut em = vec![0, 2];
+        em.extend((0..k - message.len() - 3).map(|_| 1 + rng.gen_below(255) as u8));
+        em.push(0);
+        em.extend_from_slice(message);
+        let c = self.encrypt_raw(&BigUint::from_bytes_be(&em))?;
+        Ok(to_bytes(&c, k))
+    }
+
+    /// Encrypt `message` with the OAEP padding, MGF1 and the `label`, whose
+    /// seed is taken from `rng`. Fails if the message is longer than
+    /// [`size`](Self::size) minus 66 bytes.
+    pub fn encrypt_oaep(
+        &self,
+        message: &[u8],
+        label: &[u8],
+        rng: &mut impl Generator,
+    ) -> Result<Vec<u8>, &'static str> {
+        let mut seed = [0; HASH_BYTES];
+        rng.fill_bytes(&mut seed);
+        self.encrypt_oaep_with_seed(message, label, &seed)
+    }
+
+    fn encrypt_oaep_with_seed(
+        &self,
+        message: &[u8],
+        label: &[u8],
+        seed: &[u8; HASH_BYTES],
+    ) -> Result<Vec<u8>, &'static str> {
+        let k = self.size();
+        if message.len() + 2 * HASH_BYTES + 2 > k {
+            return Err("Message too long");
+        }
+        let mut em = vec![0; k];
+        let (masked_seed, db) = em[1..].split_at_mut(HASH_BYTES);
+        let start = db.len() - message.len();
+        db[..HASH_BYTES].copy_from_slice(&sha256(&[label]));
+        db[start - 1] = 1;
+        db[start..].copy_from_slice(message);
+        mgf1_xor(seed, db);
+        masked_seed.copy_from_slice(seed);
+        mgf1_xor(db, masked_seed);
+        let c = self.encrypt_raw(&BigUint::from_bytes_be(&em))?;
+        Ok(to_bytes(&c, k))
+    }
+
+    /// Whether `signature` is a valid PKCS #1 v1.5 signature of `message`
+    pub fn verify_pkcs1v15(&self, message: &[u8], signature: &[u8]) -> bool {
+        let k = self.size();
+        match self.apply(signature, k) {
+            Some(em) => Ok(em) == pkcs1v15_signature_encoding(message, k),
+            None => false,
+        }
+    }
+
+    /// Whether `signature` is a valid PSS signature of `message`, with MGF1
+    /// and a salt of 32 bytes
+    pub fn verify_pss(&self, message: &[u8], signature: &[u8]) -> bool {
+        let em_bits = self.n.bits() - 1;
+        let em_len = em_bits.div_ceil(8) as usize;
+        if em_len < 2 * HASH_BYTES + 2 {
+            return false;
+        }
+        let Some(mut em) = self.apply(signature, em_len) else {
+            return false;
+        };
+        if em[em_len - 1] != 0xbc {
+            return false;
+        }
+        let (db, h) = em[..em_len - 1].split_at_mut(em_len - HASH_BYTES - 1);
+        let unused = 8 * em_len as u64 - em_bits;
+        if u16::from(db[0]) >> (8 - unused) != 0 {
+            return false;
+        }
+        mgf1_xor(h, db);
+        db[0] &= 0xff >> unused;
+        let (padding, salt) = db.split_at(db.len() - HASH_BYTES);
+        let (&one, zeros) = padding.split_last().unwrap();
+        if one != 1 || zeros.iter().any(|&b| b != 0) {
+            return false;
+        }
+        *h == sha256(&[&[0; 8], &sha256(&[message]), salt])
+    }
+
+    /// The PKCS #1 `RSAPublicKey` DER encoding of this key
+    pub fn to_pkcs1_der(&self) -> Vec<u8> {
+        der::sequence(&[&der::integer(&self.n), &der::integer(&self.e)])
+    }
+
+    /// Read a key from its PKCS #1 `RSAPublicKey` DER encoding
+    pub fn from_pkcs1_der(der: &[u8]) -> Result<Self, &'static str> {
+        let mut reader = der::Reader::new(der);
+        let mut key = reader.sequence()?;
+        reader.finish()?;
+        let (n, e) = (key.integer()?, key.integer()?);
+        key.finish()?;
+        Self::new(n, e)
+    }
+
+    /// The X.509 `SubjectPublicKeyInfo` DER encoding of this key
+    pub fn to_spki_der(&self) -> Vec<u8> {
+        let mut key = vec![0];
+        key.extend(self.to_pkcs1_der());
+        der::sequence(&[&algorithm_identifier(), &der::tlv(der::BIT_STRING, &key)])
+    }
+
+    /// Read a key from its X.509 `SubjectPublicKeyInfo` DER encoding
+    pub fn from_spki_der(der: &[u8]) -> Result<Self, &'static str> {
+        let mut reader = der::Reader::new(der);
+        let mut info = reader.sequence()?;
+        reader.finish()?;
+        read_algorithm_identifier(&mut info)?;
+        let key = info.read(der::BIT_STRING)?;
+        info.finish()?;
+        match key.split_first() {
+            Some((0, key)) => Self::from_pkcs1_der(key),
+            _ => Err("Invalid DER bit string"),
+        }
+    }
+}
+
+/// The PKCS #1 v1.5 encoding of the signature of `message` in `k` bytes
+fn pkcs1v15_signature_encoding(message: &[u8], k: usize) -> Result<Vec<u8>, &'static str> {
+    let t_len = SHA256_DIGEST_INFO.len() + HASH_BYTES;
+    if k < t_len + 11 {
+        return Err("Modulus too short");
+    }
+    let mut em = vec![0, 1];
+    em.resize(k - t_len - 1, 0xff);
+    em.push(0);
+    em.extend_from_slice(&SHA256_DIGEST_INFO);
+    em.extend_from_slice(&sha256(&[message]));
+    Ok(em)
+}
+
+/// RSA private key, with the factors of the modulus and the values used by
+/// the Chinese Remainder Theorem
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RSAPrivateKey {
+    public: RSAPublicKey,
+    d: BigUint,
+    p: BigUint,
+    q: BigUint,
+    /// `d mod (p - 1)`
+    dp: BigUint,
+    /// `d mod (q - 1)`
+    dq: BigUint,
+    /// `q^-1 mod p`
+    q_inv: BigUint,
+}
+
+impl RSAPrivateKey {
+    /// Generate a key whose modulus has exactly `bits` bits, with the public
+    /// exponent 65537.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits` is less than 32.
+    pub fn generate(bits: u64, rng: &mut impl Generator) -> Self {
+        assert!(bits >= 32, "The modulus must have at least 32 bits");
+        let e = BigUint::from(65537_u32);
+        loop {
+            let p = random_prime(bits - bits / 2, rng);
+            let q = random_prime(bits / 2, rng);
+            if let Ok(key) = Self::from_primes(p, q, e.clone()) {
+                return key;
+            }
+        }
+    }
+
+    /// Create a key from the primes `p` and `q` and the public exponent `e`,
+    /// the private exponent being the inverse of `e` modulo
+    /// `lcm(p - 1, q - 1)`. Fails if `p == q` or `e` has no such inverse; the
+    /// primality of `p` and `q` is not checked.
+    pub fn from_primes(p: BigUint, q: BigUint, e: BigUint) -> Result<Self, &'static str> {
+        if p == q {
+            return Err("The primes must be distinct");
+        }
+        let lambda = (&p - 1_u32).lcm(&(&q - 1_u32));
+        let d = e
+            .modinv(&lambda)
+            .ok_or("The public exponent is not invertible")?;
+        Self::from_components(&p * &q, e, d, p, q)
+    }
+
+    /// Create a key from all its components. Fails unless `n == p * q` and
+    /// `d` is the inverse of `e` modulo `p - 1` and `q - 1`.
+    pub fn from_components(
+        n: BigUint,
+        e: BigUint,
+        d: BigUint,
+        p: BigUint,
+        q: BigUint,
+    ) -> Result<Self, &'static str> {
+        let public = RSAPublicKey::new(n, e)?;
+        if p.bits() < 2 || q.bits() < 2 || p == q || &p * &q != public.n {
+            return Err("The primes do not match the modulus");
+        }
+        let (p_1, q_1) = (&p - 1_u32, &q - 1_u32);
+        let (dp, dq) = (&d % &p_1, &d % &q_1);
+        if !(&public.e * &dp % &p_1).is_one() || !(&public.e * &dq % &q_1).is_one() {
+            return Err("The private exponent does not match the public exponent");
+        }
+        let q_inv = q.modinv(&p).ok_or("The primes do not match the modulus")?;
+        Ok(Self {
+            public,
+            d,
+            p,
+            q,
+            dp,
+            dq,
+            q_inv,
+        })
+    }
+
+    /// The public key
+    pub fn public_key(&self) -> &RSAPublicKey {
+        &self.public
+    }
+
+    /// The private exponent
+    pub fn d(&self) -> &BigUint {
+        &self.d
+    }
+
+    /// The prime factors `(p, q)` of the modulus
+    pub fn primes(&self) -> (&BigUint, &BigUint) {
+        (&self.p, &self.q)
+    }
+
+    /// The RSA decryption primitive, `c^d mod n`, computed modulo `p` and `q`.
+    /// Fails unless `c < n`.
+    pub fn decrypt_raw(&self, c: &BigUint) -> Result<BigUint, &'static str> {
+        if *c >= self.public.n {
+            return Err("Ciphertext representative out of range");
+        }
+        let m1 = c.modpow(&self.dp, &self.p);
+        let m2 = c.modpow(&self.dq, &self.q);
+        let h = (&self.q_inv * (&m1 + &self.p - &m2 % &self.p)) % &self.p;
+        Ok(m2 + h * &self.q)
+    }
+
+    /// Applies the private key to a message representative of
+    /// [`size`](RSAPublicKey::size) bytes
+    fn apply(&self, input: &[u8]) -> Result<Vec<u8>, &'static str> {
+        let k = self.public.size();
+        if input.len() != k {
+            return Err(DECRYPTION_ERROR);
+        }
+        let m = self.decrypt_raw(&BigUint::from_bytes_be(input))?;
+        Ok(to_bytes(&m, k))
+    }
+
+    /// Decrypt a ciphertext with the PKCS #1 v1.5 padding
+    pub fn decrypt_pkcs1v15(&self, ciphertext: &[u8]) -> Result<Vec<u8>, &'static str> {
+        if self.public.size() < 11 {
+            return Err(DECRYPTION_ERROR);
+        }
+        let em = self.apply(ciphertext).map_err(|_| DECRYPTION_ERROR)?;
+        let separator = em.iter().skip(2).position(|&b| b == 0);
+        match (em[0], em[1], separator) {
+            (0, 2, Some(padding)) if padding >= 8 => Ok(em[padding + 3..].to_vec()),
+            _ => Err(DECRYPTION_ERROR),
+        }
+    }
+
+    /// Decrypt a ciphertext with the OAEP padding, MGF1 and the `label`
+    pub fn decrypt_oaep(&self, ciphertext: &[u8], label: &[u8]) -> Result<Vec<u8>, &'static str> {
+        if self.public.size() < 2 * HASH_BYTES + 2 {
+            return Err(DECRYPTION_ERROR);
+        }
+        let mut em = self.apply(ciphertext).map_err(|_| DECRYPTION_ERROR)?;
+        let (y, rest) = em.split_first_mut().unwrap();
+        let (seed, db) = rest.split_at_mut(HASH_BYTES);
+        mgf1_xor(db, seed);
+        mgf1_xor(seed, db);
+        let (l_hash, rest) = db.split_at(HASH_BYTES);
+        let separator = rest.iter().position(|&b| b != 0);
+        match separator {
+            Some(i) if *y == 0 && rest[i] == 1 && *l_hash == sha256(&[label]) => {
+                Ok(rest[i + 1..].to_vec())
+            }
+            _ => Err(DECRYPTION_ERROR),
+        }
+    }
+
+    /// Sign `message` with the PKCS #1 v1.5 padding. Fails if the modulus is
+    /// shorter than 62 bytes.
+    pub fn sign_pkcs1v15(&self, message: &[u8]) -> Result<Vec<u8>, &'static str> {
+        let em = pkcs1v15_signature_encoding(message, self.public.size())?;
+        self.apply(&em)
+    }
+
+    /// Sign `message` with the PSS padding, MGF1 and a salt of 32 bytes taken
+    /// from `rng`. Fails if the modulus is shorter than 67 bytes.
+    pub fn sign_pss(
+        &self,
+        message: &[u8],
+        rng: &mut impl Generator,
+    ) -> Result<Vec<u8>, &'static str> {
+        let mut salt = [0; HASH_BYTES];
+        rng.fill_bytes(&mut salt);
+        self.sign_pss_with_salt(message, &salt)
+    }
+
+    fn sign_pss_with_salt(
+        &self,
+        message: &[u8],
+        salt: &[u8; HASH_BYTES],
+    ) -> Result<Vec<u8>, &'static str> {
+        let em_bits = self.public.n.bits() - 1;
+        let em_len = em_bits.div_ceil(8) as usize;
+        if em_len < 2 * HASH_BYTES + 2 {
+            return Err("Modulus too short");
+        }
+        let h = sha256(&[&[0; 8], &sha256(&[message]), salt]);
+        let mut em = vec![0; em_len];
+        let db_len = em_len - HASH_BYTES - 1;
+        em[db_len - HASH_BYTES - 1] = 1;
+        em[db_len - HASH_BYTES..db_len].copy_from_slice(salt);
+        mgf1_xor(&h, &mut em[..db_len]);
+        em[0] &= 0xff >> (8 * em_len as u64 - em_bits);
+        em[db_len..em_len - 1].copy_from_slice(&h);
+        em[em_len - 1] = 0xbc;
+        let s = self.decrypt_raw(&BigUint::from_bytes_be(&em))?;
+        Ok(to_bytes(&s, self.public.size()))
+    }
+
+    /// The PKCS #1 `RSAPrivateKey` DER encoding of this key
+    pub fn to_pkcs1_der(&self) -> Vec<u8> {
+        der::sequence(&[
+            &der::integer(&BigUint::from(0_u32)),
+            &der::integer(&self.public.n),
+            &der::integer(&self.public.e),
+            &der::integer(&self.d),
+            &der::integer(&self.p),
+            &der::integer(&self.q),
+            &der::integer(&self.dp),
+            &der::integer(&self.dq),
+            &der::integer(&self.q_inv),
+        ])
+    }
+
+    /// Read a key from its PKCS #1 `RSAPrivateKey` DER encoding. Fails if the
+    /// components are not consistent, or if the key has more than two primes.
+    pub fn from_pkcs1_der(der: &[u8]) -> Result<Self, &'static str> {
+        let mut reader = der::Reader::new(der);
+        let mut items = reader.sequence()?;
+        reader.finish()?;
+        if items.integer()? != BigUint::from(0_u32) {
+            return Err("Unsupported RSA private key version");
+        }
+        let mut values = Vec::with_capacity(8);
+        for _ in 0..8 {
+            values.push(items.integer()?);
+        }
+        items.finish()?;
+        let [n, e, d, p, q, dp, dq, q_inv]: [BigUint; 8] = values.try_into().unwrap();
+        let key = Self::from_components(n, e, d, p, q)?;
+        if key.dp != dp || key.dq != dq || key.q_inv != q_inv {
+            return Err("The CRT values do not match the key");
+        }
+        Ok(key)
+    }
+
+    /// The PKCS #8 `PrivateKeyInfo` DER encoding of this key
+    pub fn to_pkcs8_der(&self) -> Vec<u8> {
+        der::sequence(&[
+            &der::integer(&BigUint::from(0_u32)),
+            &algorithm_identifier(),
+            &der::tlv(der::OCTET_STRING, &self.to_pkcs1_der()),
+        ])
+    }
+
+    /// Read a key from its PKCS #8 `PrivateKeyInfo` DER encoding
+    pub fn from_pkcs8_der(der: &[u8]) -> Result<Self, &'static str> {
+        let mut reader = der::Reader::new(der);
+        let mut info = reader.sequence()?;
+        reader.finish()?;
+        if info.integer()? != BigUint::from(0_u32) {
+            return Err("Unsupported PKCS #8 version");
+        }
+        read_algorithm_identifier(&mut info)?;
+        let key = info.read(der::OCTET_STRING)?;
+        info.finish()?;
+        Self::from_pkcs1_der(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoding::{Encoding, Hex};
+    use crate::math::ChaCha20Rng;
+
+    fn hex(text: &str) -> Vec<u8> {
+        Hex::LOWER.decode(text).unwrap()
+    }
+
+    /// A 1024 bit key generated by the Python `cryptography` package, which
+    /// also produced the expected values of the tests
+    fn reference_key() -> RSAPrivateKey {
+        let p = concat!(
+            "fa0e788b518f488918014c4bf7112587a38c4b2136686068628dbe31cc55c5af",
+            "5ad588a8d5ba5f1bc9a8e36f9e855b41943cb83c9e67df360f9fd51c390f266f",
+        );
+        let q = concat!(
+            "c0073ce01391f3c6607dbba503b9029ed7a51ba6f3782fbc7c6dd4a0aef71a6b",
+            "069a394ea1747a26b628797644f05fd0bca2a2c6a908296c3933ba26ac7c9037",
+        );
+        RSAPrivateKey::from_primes(
+            BigUint::from_bytes_be(&hex(p)),
+            BigUint::from_bytes_be(&hex(q)),
+            BigUint::from(65537_u32),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn reference_signatures() {
+        let key = reference_key();
+        let public = key.public_key();
+        let expected = hex(concat!(
+            "1e5ec744fdfb4d6adaf4b7cf2351e397595518d981d0e303227091edadd3ed04",
+            "a0259e8092ec5956f6b1281cc2c1f88de1af44bae10d6fb683d1bbc64b642aa3",
+            "17f6d31b11dff7cb43b7b5eba91b922b9dc4b02abe4d722150c1706920578d0a",
+            "b0ff0e4d732232be9b3f688de39d4ea9208deafbf4e89f7d2b1e9d75dea12783",
+        ));
+        assert_eq!(key.sign_pkcs1v15(b"abc").unwrap(), expected);
+        assert!(public.verify_pkcs1v15(b"abc", &expected));
+        assert!(!public.verify_pkcs1v15(b"abd", &expected));
+
+        let random_salt = hex(concat!(
+            "1fbb97a303ad16defb2066ebf1f69555415b7a0b01bd61eb75ceabac559fbab4",
+            "fb6858e2327f5ae519781dfe9f4d63b038c5eb746c18d85ff4542003c8dcef94",
+            "8f4bcca4bd7493d7960ced82bd1ed23bbe4fc646eb34297d7a62dd6d66b30641",
+            "d7a13e42f11a1b1436ac7d918e40eb19b16d272af65a984e5767e28f855dbae4",
+        ));
+        assert!(public.verify_pss(b"abc", &random_salt));
+        assert!(!public.verify_pss(b"abd", &random_salt));
+
+        let salt: [u8; 32] = std::array::from_fn(|i| i as u8);
+        let expected = hex(concat!(
+            "8832327fa36843673347b894a38e139c322b8ded9ae565d6a15af132934a549d",
+            "dc988dd32b82523ab5d375bdf470113d5766fb39bb350ae9320d42c4577ca5a4",
+            "202c2e76f7741ab69e7f36ca1bb0d3c292a5f15b3b2b6c7f861718d5fde59266",
+            "951e08f324bd23e4eb5f3778fe23821eced23f937f186a4749d6f27359e85b09",
+        ));
+        assert_eq!(key.sign_pss_with_salt(b"abc", &salt).unwrap(), expected);
+    }
+
+    #[test]
+    fn reference_ciphertexts() {
+        let key = reference_key();
+        let ciphertext = hex(concat!(
+            "2b40d4beb39ac663e13dd209d958ee94209cedeca9873a719668a2e2c368007c",
+            "50a20227b3ca6bec4e33a63afd938a2d3675ae139e2791c3e66f5e17ac68a858",
+            "3181ab5e7d3d1d3eac4b772ce66270828c2e8dbc7564436786a130209d3727e9",
+            "5e6ebc8052f54d340cd8adf3500847bc77c41b6cbc420c5a5db3f7e00525d08c",
+        ));
+        assert_eq!(
+            key.decrypt_pkcs1v15(&ciphertext).as_deref(),
+            Ok(&b"attack at dawn"[..])
+        );
+
+        let ciphertext = hex(concat!(
+            "05a63c1dbeeeea7e14293842d01441b873b7f5d33003d7a2da3cccbecee29041",
+            "ef30311a2198bf1221c9fca1b8b31edcb50b9c236d773b456483e141141673cd",
+            "41235ed0f10ec872186f36262f182de125d425df2b445514c334a553a687b4a3",
+            "4fb616d53908bd7364b904fe072019e1b86685e378ae0e7cbb2b06e9685ebdc9",
+        ));
+        assert_eq!(
+            key.decrypt_oaep(&ciphertext, b"label").as_deref(),
+            Ok(&b"attack at dawn"[..])
+        );
+        assert!(key.decrypt_oaep(&ciphertext, b"other label").is_err());
+
+        let seed: [u8; 32] = std::array::from_fn(|i| 100 + i as u8);
+        let expected = hex(concat!(
+            "b53797d3d2eb1b2d58db8255561e7658279e6c41bd7db176e8a323133675cc6f",
+            "eb38a52adb66a0c085d65eb3e1c99820367bc6fa20fcb967663e930bfcc18fe3",
+            "21081d5bb5576e5e0d45d4958802aff75bffae8501abc975b650d02cf0f770e8",
+            "caeb1fdd6790a79e71cf6ffdf1d2e11764ca76590206492558fbf410fecdc107",
+        ));
+        let ciphertext = key
+            .public_key()
+            .encrypt_oaep_with_seed(b"attack at dawn", b"label", &seed)
+            .unwrap();
+        assert_eq!(ciphertext, expected);
+    }
+
+    #[test]
+    fn reference_der() {
+        // SHA-256 of the encodings of the reference key by `cryptography`
+        let key = reference_key();
+        let public = key.public_key();
+        for (der, expected) in [
+            (
+                key.to_pkcs1_der(),
+                "9a41880a60e9ed50113e2ff2d6aa4474ce458ba92dfc5d86a53ce4731dcc567a",
+            ),
+            (
+                key.to_pkcs8_der(),
+                "0bb6ecf26177667196f002cb318f057be000fbbb3bce1f6fc921ef7dc9ad8386",
+            ),
+            (
+                public.to_pkcs1_der(),
+                "56cb725d45f8e95f0aac938696bc13719afc312910bf60e03f97c44da1e7ff80",
+            ),
+            (
+                public.to_spki_der(),
+                "d38271ec03e00130d8defe379d5171932e02ddb799bb92f105c59ce1fd189852",
+            ),
+        ] {
+            assert_eq!(Hex::LOWER.encode(&sha256(&[&der])), expected);
+        }
+        assert_eq!(
+            RSAPrivateKey::from_pkcs1_der(&key.to_pkcs1_der()),
+            Ok(key.clone())
+        );
+        assert_eq!(
+            RSAPrivateKey::from_pkcs8_der(&key.to_pkcs8_der()),
+            Ok(key.clone())
+        );
+        assert_eq!(
+            RSAPublicKey::from_pkcs1_der(&public.to_pkcs1_der()).as_ref(),
+            Ok(public)
+        );
+        assert_eq!(
+            RSAPublicKey::from_spki_der(&public.to_spki_der()).as_ref(),
+            Ok(public)
+        );
+
+        // the formats are not interchangeable
+        assert!(RSAPrivateKey::from_pkcs1_der(&key.to_pkcs8_der()).is_err());
+        assert!(RSAPublicKey::from_spki_der(&public.to_pkcs1_der()).is_err());
+        // trailing data, truncation, inconsistent CRT values
+        let mut der = key.to_pkcs8_der();
+        der.push(0);
+        assert!(RSAPrivateKey::from_pkcs8_der(&der).is_err());
+        assert!(RSAPrivateKey::from_pkcs8_der(&der[..der.len() - 2]).is_err());
+        let mut der = key.to_pkcs1_der();
+        let last = der.len() - 1;
+        der[last] ^= 2;
+        assert!(RSAPrivateKey::from_pkcs1_der(&der).is_err());
+    }
+
+    #[test]
+    fn generated_keys() {
+        let mut rng = ChaCha20Rng::new_default(7);
+        for bits in [656, 657, 663, 664] {
+            let key = RSAPrivateKey::generate(bits, &mut rng);
+            let public = key.public_key();
+            assert_eq!(public.n().bits(), bits);
+            let (p, q) = key.primes();
+            assert_eq!(p * q, *public.n());
+
+            let m = BigUint::from(123456789_u32);
+            let c = public.encrypt_raw(&m).unwrap();
+            assert_eq!(key.decrypt_raw(&c), Ok(m));
+            assert!(public.encrypt_raw(public.n()).is_err());
+
+            let message = b"attack at dawn";
+            let ciphertext = public.encrypt_pkcs1v15(message, &mut rng).unwrap();
+            assert_eq!(
+                key.decrypt_pkcs1v15(&ciphertext).as_deref(),
+                Ok(&message[..])
+            );
+            let ciphertext = public.encrypt_oaep(message, b"", &mut rng).unwrap();
+            assert_eq!(
+                key.decrypt_oaep(&ciphertext, b"").as_deref(),
+                Ok(&message[..])
+            );
+            assert!(key.decrypt_pkcs1v15(&ciphertext).is_err());
+
+            let signature = key.sign_pss(message, &mut rng).unwrap();
+            assert!(public.verify_pss(message, &signature));
+            assert!(!public.verify_pkcs1v15(message, &signature));
+            let signature = key.sign_pkcs1v15(message).unwrap();
+            assert!(public.verify_pkcs1v15(message, &signature));
+            assert!(!public.verify_pss(message, &signature));
+        }
+        // seeding the generator makes everything reproducible
+        let (mut a, mut b) = (ChaCha20Rng::new_default(8), ChaCha20Rng::new_default(8));
+        let key = RSAPrivateKey::generate(512, &mut a);
+        assert_eq!(key, RSAPrivateKey::generate(512, &mut b));
+        assert_eq!(key.sign_pss(b"abc", &mut a), key.sign_pss(b"abc", &mut b));
+    }
+
+    #[test]
+    fn message_limits() {
+        let key = reference_key();
+        let public = key.public_key();
+        let mut rng = ChaCha20Rng::new_default(9);
+        assert!(public.encrypt_pkcs1v15(&[1; 117], &mut rng).is_ok());
+        assert!(public.encrypt_pkcs1v15(&[1; 118], &mut rng).is_err());
+        let ciphertext = public.encrypt_oaep(&[1; 62], b"", &mut rng).unwrap();
+        assert_eq!(key.decrypt_oaep(&ciphertext, b""), Ok(vec![1; 62]));
+        assert!(public.encrypt_oaep(&[1; 63], b"", &mut rng).is_err());
+        let ciphertext = public.encrypt_pkcs1v15(&[], &mut rng).unwrap();
+        assert_eq!(key.decrypt_pkcs1v15(&ciphertext), Ok(vec![]));
+
+        // wrong lengths, tampering
+        assert!(key.decrypt_pkcs1v15(&ciphertext[1..]).is_err());
+        let mut tampered = ciphertext.clone();
+        tampered[5] ^= 1;
+        assert!(key.decrypt_pkcs1v15(&tampered).is_err());
+        let signature = key.sign_pkcs1v15(b"abc").unwrap();
+        assert!(!public.verify_pkcs1v15(b"abc", &signature[1..]));
+        assert!(!public.verify_pss(b"abc", &[0xff; 128]));
+
+        // too small for the paddings
+        let small = RSAPrivateKey::generate(256, &mut rng);
+        assert!(small.sign_pkcs1v15(b"abc").is_err());
+        assert!(small.sign_pss(b"abc", &mut rng).is_err());
+        assert!(small.public_key().encrypt_oaep(b"", b"", &mut rng).is_err());
+    }
+
+    #[test]
+    fn invalid_keys() {
+        let (p, q) = (BigUint::from(61_u32), BigUint::from(53_u32));
+        let e = BigUint::from(17_u32);
+        let key = RSAPrivateKey::from_primes(p.clone(), q.clone(), e.clone()).unwrap();
+        // lcm(60, 52) = 780
+        assert_eq!(*key.d(), BigUint::from(413_u32));
+        assert!(RSAPrivateKey::from_primes(p.clone(), p.clone(), e.clone()).is_err());
+        assert!(RSAPrivateKey::from_primes(p.clone(), q.clone(), BigUint::from(3_u32)).is_err());
+        let n = &p * &q;
+        let d = BigUint::from(413_u32);
+        assert!(RSAPrivateKey::from_components(
+            n.clone(),
+            e.clone(),
+            d.clone(),
+            p.clone(),
+            q.clone()
+        )
+        .is_ok());
+        assert!(RSAPrivateKey::from_components(
+            n.clone(),
+            e.clone(),
+            d + 1_u32,
+            p.clone(),
+            q.clone()
+        )
+        .is_err());
+        assert!(RSAPrivateKey::from_components(
+            n.clone() + 2_u32,
+            e.clone(),
+            BigUint::from(413_u32),
+            p,
+            q
+        )
+        .is_err());
+        assert!(RSAPublicKey::new(n.clone() + 1_u32, e.clone()).is_err());
+        assert!(RSAPublicKey::new(n.clone(), BigUint::from(16_u32)).is_err());
+        assert!(RSAPublicKey::new(n.clone(), BigUint::from(1_u32)).is_err());
+        assert!(RSAPublicKey::new(n.clone(), n).is_err());
+        // A one byte modulus cannot hold any padding
+        let tiny = RSAPrivateKey::from_primes(
+            BigUint::from(11_u32),
+            BigUint::from(13_u32),
+            BigUint::from(7_u32),
+        )
+        .unwrap();
+        assert_eq!(tiny.decrypt_pkcs1v15(&[2]), Err(DECRYPTION_ERROR));
+        assert_eq!(tiny.decrypt_oaep(&[2], b""), Err(DECRYPTION_ERROR));
+    }
+}
@@ -0,0 +1,125 @@
+//! Schnorr signatures
+//!
+//! With the key pair `y = g^x` of a [`DHGroup`], a signature of `m` is made
+//! from a random nonce `k`: `e = H(g^k || m)` and `s = k - x e mod q`. The
+//! verifier recomputes `g^k` as `g^s y^e`, and checks that it hashes to `e`.
+//! The hash `H` is [`SHA256`], reduced modulo `q`, of the commitment `g^k`
+//! encoded in big-endian on as many bytes as `p`, followed by the message.
+//!
+//! Reusing a nonce for two messages reveals the secret key, as shown in the
+//! tests.
+//!
+//! - [`Wikipedia`](https://en.wikipedia.org/wiki/Schnorr_signature)
+
+use num_bigint::BigUint;
+
+use super::DHGroup;
+use crate::ciphers::SHA256;
+use crate::math::Generator;
+
+/// A Schnorr signature
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchnorrSignature {
+    /// The challenge, the hash of the commitment and the message
+    pub e: BigUint,
+    /// The response `k - x e mod q`
+    pub s: BigUint,
+}
+
+impl DHGroup {
+    /// The challenge for the `commitment` `g^k` and `message`
+    fn schnorr_challenge(&self, commitment: &BigUint, message: &[u8]) -> BigUint {
+        let len = self.p().bits().div_ceil(8) as usize;
+        let bytes = commitment.to_bytes_be();
+        let mut hasher = SHA256::new_default();
+        hasher.update(&vec![0; len - bytes.len()]);
+        hasher.update(&bytes);
+        hasher.update(message);
+        BigUint::from_bytes_be(&hasher.get_hash()) % self.q()
+    }
+
+    fn schnorr_sign_with_nonce(
+        &self,
+        secret: &BigUint,
+        message: &[u8],
+        k: &BigUint,
+    ) -> SchnorrSignature {
+        let q = self.q();
+        let e = self.schnorr_challenge(&self.public_key(k), message);
+        // k - x e, kept non-negative
+        let s = (k + q * q - secret % q * &e) % q;
+        SchnorrSignature { e, s }
+    }
+
+    /// Sign `message` with the `secret` key
+    pub fn schnorr_sign(
+        &self,
+        secret: &BigUint,
+        message: &[u8],
+        rng: &mut impl Generator,
+    ) -> SchnorrSignature {
+        let k = self.generate_secret(rng);
+        self.schnorr_sign_with_nonce(secret, message, &k)
+    }
+
+    /// Whether `signature` is a valid signature of `message` by the owner of
+    /// the `public` key
+    pub fn schnorr_verify(
+        &self,
+        public: &BigUint,
+        message: &[u8],
+        signature: &SchnorrSignature,
+    ) -> bool {
+        let SchnorrSignature { e, s } = signature;
+        if self.validate_public(public).is_err() || e >= self.q() || s >= self.q() {
+            return false;
+        }
+        let commitment = self.g().modpow(s, self.p()) * public.modpow(e, self.p()) % self.p();
+        self.schnorr_challenge(&commitment, message) == *e
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::ChaCha20Rng;
+
+    #[test]
+    fn sign_and_verify() {
+        let mut rng = ChaCha20Rng::new_default(0);
+        let group = DHGroup::ffdhe2048();
+        let secret = group.generate_secret(&mut rng);
+        let public = group.public_key(&secret);
+        let signature = group.schnorr_sign(&secret, b"message", &mut rng);
+        assert!(group.schnorr_verify(&public, b"message", &signature));
+        assert!(!group.schnorr_verify(&public, b"massage", &signature));
+        let other = group.public_key(&(secret + 1_u32));
+        assert!(!group.schnorr_verify(&other, b"message", &signature));
+        let tampered = SchnorrSignature {
+            e: signature.e.clone(),
+            s: (&signature.s + 1_u32) % group.q(),
+        };
+        assert!(!group.schnorr_verify(&public, b"message", &tampered));
+        let out_of_range = SchnorrSignature {
+            e: signature.e + group.q(),
+            s: signature.s,
+        };
+        assert!(!group.schnorr_verify(&public, b"message", &out_of_range));
+    }
+
+    #[test]
+    fn nonce_reuse() {
+        let mut rng = ChaCha20Rng::new_default(1);
+        let group = DHGroup::modp2048();
+        let q = group.q();
+        let secret = group.generate_secret(&mut rng);
+        let k = group.generate_secret(&mut rng);
+        let first = group.schnorr_sign_with_nonce(&secret, b"first", &k);
+        let second = group.schnorr_sign_with_nonce(&secret, b"second", &k);
+        // s1 - s2 = x (e2 - e1), so x = (s1 - s2) / (e2 - e1)
+        let ds = (&first.s + q - &second.s) % q;
+        let de = (&second.e + q - &first.e) % q;
+        let recovered = ds * de.modinv(q).unwrap() % q;
+        assert_eq!(recovered, secret);
+    }
+}
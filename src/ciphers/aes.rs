@@ -0,0 +1,226 @@
+//! AES block cipher
+//!
+//! This implementation is based on [`FIPS 197`]. It uses no lookup tables:
+//! the S-box is computed as the inverse in GF(2^8) followed by an affine map,
+//! and every multiplication is branch-free, so the running time does not depend
+//! on the key or the data. This makes it slow, but safe from cache-timing
+//! attacks without any hardware support.
+//!
+//! [`FIPS 197`]: https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.197-upd1.pdf
+
+use super::BlockCipher;
+
+/// Multiplication by `x` in GF(2^8) modulo `x^8 + x^4 + x^3 + x + 1`
+#[inline]
+fn xtime(a: u8) -> u8 {
+    (a << 1) ^ (0x1b & (a >> 7).wrapping_neg())
+}
+
+/// Multiplication in GF(2^8), without branches on the operands
+fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut result = 0;
+    for _ in 0..8 {
+        result ^= a & (b & 1).wrapping_neg();
+        a = xtime(a);
+        b >>= 1;
+    }
+    result
+}
+
+/// The multiplicative inverse in GF(2^8), computed as `x^254`, with 0 mapped to
+/// itself
+fn gf_inverse(x: u8) -> u8 {
+    let mut result = 1;
+    // the exponent is public, so branching on its bits is fine
+    for bit in (0..8).rev() {
+        result = gf_mul(result, result);
+        if (254 >> bit) & 1 == 1 {
+            result = gf_mul(result, x);
+        }
+    }
+    result
+}
+
+fn sub_byte(x: u8) -> u8 {
+    let b = gf_inverse(x);
+    b ^ b.rotate_left(1) ^ b.rotate_left(2) ^ b.rotate_left(3) ^ b.rotate_left(4) ^ 0x63
+}
+
+fn inv_sub_byte(x: u8) -> u8 {
+    gf_inverse(x.rotate_left(1) ^ x.rotate_left(3) ^ x.rotate_left(6) ^ 0x05)
+}
+
+// The state is stored column by column, as in the input block, so the byte in
+// row `r` and column `c` is at index `4 * c + r`.
+
+fn sub_bytes(state: &mut [u8; 16]) {
+    state.iter_mut().for_each(|b| *b = sub_byte(*b));
+}
+
+fn inv_sub_bytes(state: &mut [u8; 16]) {
+    state.iter_mut().for_each(|b| *b = inv_sub_byte(*b));
+}
+
+fn shift_rows(state: &mut [u8; 16]) {
+    let old = *state;
+    for (i, b) in state.iter_mut().enumerate() {
+        let (c, r) = (i / 4, i % 4);
+        *b = old[4 * ((c + r) % 4) + r];
+    }
+}
+
+fn inv_shift_rows(state: &mut [u8; 16]) {
+    let old = *state;
+    for (i, &b) in old.iter().enumerate() {
+        let (c, r) = (i / 4, i % 4);
+        state[4 * ((c + r) % 4) + r] = b;
+    }
+}
+
+fn mix_columns(state: &mut [u8; 16]) {
+    for column in state.chunks_exact_mut(4) {
+        let [a0, a1, a2, a3] = [column[0], column[1], column[2], column[3]];
+        let all = a0 ^ a1 ^ a2 ^ a3;
+        // 2a + 3b + c + d = a + (a + b + c + d) + 2(a + b)
+        column[0] ^= all ^ xtime(a0 ^ a1);
+        column[1] ^= all ^ xtime(a1 ^ a2);
+        column[2] ^= all ^ xtime(a2 ^ a3);
+        column[3] ^= all ^ xtime(a3 ^ a0);
+    }
+}
+
+fn inv_mix_columns(state: &mut [u8; 16]) {
+    for column in state.chunks_exact_mut(4) {
+        let a: [u8; 4] = (&*column).try_into().unwrap();
+        for (r, b) in column.iter_mut().enumerate() {
+            *b = gf_mul(a[r], 14)
+                ^ gf_mul(a[(r + 1) % 4], 11)
+                ^ gf_mul(a[(r + 2) % 4], 13)
+                ^ gf_mul(a[(r + 3) % 4], 9);
+        }
+    }
+}
+
+fn add_round_key(state: &mut [u8; 16], round_key: &[u8; 16]) {
+    for (b, k) in state.iter_mut().zip(round_key) {
+        *b ^= k;
+    }
+}
+
+/// AES block cipher with a 128, 192 or 256-bit key, see the
+/// [module documentation](self)
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, Copy)]
+pub struct AES {
+    round_keys: [[u8; 16]; 15],
+    rounds: usize,
+}
+
+impl AES {
+    /// Create a new instance with the given key, which must be 16, 24 or 32
+    /// bytes long
+    pub fn new(key: &[u8]) -> Result<Self, &'static str> {
+        let key_words = match key.len() {
+            16 | 24 | 32 => key.len() / 4,
+            _ => return Err("Key must be 16, 24 or 32 bytes long"),
+        };
+        let rounds = key_words + 6;
+        let mut words = [[0u8; 4]; 60];
+        for (word, chunk) in words.iter_mut().zip(key.chunks_exact(4)) {
+            word.copy_from_slice(chunk);
+        }
+        let mut round_constant = 1;
+        for i in key_words..4 * (rounds + 1) {
+            let mut temp = words[i - 1];
+            if i % key_words == 0 {
+                temp.rotate_left(1);
+                temp = temp.map(sub_byte);
+                temp[0] ^= round_constant;
+                round_constant = xtime(round_constant);
+            } else if key_words > 6 && i % key_words == 4 {
+                temp = temp.map(sub_byte);
+            }
+            for (t, w) in temp.iter_mut().zip(words[i - key_words]) {
+                *t ^= w;
+            }
+            words[i] = temp;
+        }
+        let mut round_keys = [[0u8; 16]; 15];
+        for (round_key, chunk) in round_keys.iter_mut().zip(words.chunks_exact(4)) {
+            round_key.copy_from_slice(chunk.as_flattened());
+        }
+        Ok(Self { round_keys, rounds })
+    }
+}
+
+impl BlockCipher<16> for AES {
+    fn encrypt_block(&self, block: &mut [u8; 16]) {
+        add_round_key(block, &self.round_keys[0]);
+        for round_key in &self.round_keys[1..self.rounds] {
+            sub_bytes(block);
+            shift_rows(block);
+            mix_columns(block);
+            add_round_key(block, round_key);
+        }
+        sub_bytes(block);
+        shift_rows(block);
+        add_round_key(block, &self.round_keys[self.rounds]);
+    }
+
+    fn decrypt_block(&self, block: &mut [u8; 16]) {
+        add_round_key(block, &self.round_keys[self.rounds]);
+        for round_key in self.round_keys[1..self.rounds].iter().rev() {
+            inv_shift_rows(block);
+            inv_sub_bytes(block);
+            add_round_key(block, round_key);
+            inv_mix_columns(block);
+        }
+        inv_shift_rows(block);
+        inv_sub_bytes(block);
+        add_round_key(block, &self.round_keys[0]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_utils::{from_hex, get_hash_string};
+    use super::*;
+
+    #[test]
+    fn s_box() {
+        assert_eq!(sub_byte(0x00), 0x63);
+        assert_eq!(sub_byte(0x53), 0xed);
+        assert_eq!(sub_byte(0xff), 0x16);
+        for x in 0..=255 {
+            assert_eq!(inv_sub_byte(sub_byte(x)), x);
+        }
+    }
+
+    #[test]
+    fn fips197_vectors() {
+        // FIPS 197, appendix C
+        let plaintext: [u8; 16] = from_hex("00112233445566778899aabbccddeeff")
+            .try_into()
+            .unwrap();
+        for (key_bytes, expected) in [
+            (16, "69c4e0d86a7b0430d8cdb78070b4c55a"),
+            (24, "dda97ca4864cdfe06eaf70a0ec0d7191"),
+            (32, "8ea2b7ca516745bfeafc49904b496089"),
+        ] {
+            let key: Vec<u8> = (0..key_bytes).collect();
+            let aes = AES::new(&key).unwrap();
+            let mut block = plaintext;
+            aes.encrypt_block(&mut block);
+            assert_eq!(get_hash_string(&block), expected);
+            aes.decrypt_block(&mut block);
+            assert_eq!(block, plaintext);
+        }
+    }
+
+    #[test]
+    fn key_length() {
+        assert!(AES::new(&[0; 15]).is_err());
+        assert!(AES::new(&[0; 20]).is_err());
+        assert!(AES::new(&[0; 33]).is_err());
+    }
+}
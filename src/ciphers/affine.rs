@@ -0,0 +1,106 @@
+//! Affine cipher and Atbash
+//!
+//! The affine cipher maps the letter with index `x` in the alphabet to
+//! `a * x + b mod 26`, which can be undone if `a` is coprime with 26. The
+//! Caesar cipher is the special case `a = 1`, and Atbash, which reverses the
+//! alphabet, is `a = b = 25`.
+//!
+//! Only ASCII letters are encrypted and they keep their case.
+//!
+//! - [`Wikipedia`](https://en.wikipedia.org/wiki/Affine_cipher)
+
+use super::classical_cipher::map_letters;
+use super::ClassicalCipher;
+use crate::math::modular::mod_inv;
+
+/// Affine cipher, see the [module documentation](self)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Affine {
+    a: u8,
+    b: u8,
+    /// The inverse of `a` modulo 26
+    a_inverse: u8,
+}
+
+impl ClassicalCipher for Affine {
+    /// The multiplier `a`, which must be coprime with 26, and the shift `b`
+    type Key = (u8, u8);
+
+    fn new((a, b): (u8, u8)) -> Result<Self, &'static str> {
+        let a_inverse = mod_inv(i16::from(a), 26).ok_or("Multiplier must be coprime with 26")?;
+        Ok(Self {
+            a: a % 26,
+            b: b % 26,
+            a_inverse: a_inverse as u8,
+        })
+    }
+
+    fn encrypt_bytes(&self, data: &[u8]) -> Vec<u8> {
+        map_letters(data, |x| {
+            ((u16::from(self.a) * u16::from(x) + u16::from(self.b)) % 26) as u8
+        })
+    }
+
+    fn decrypt_bytes(&self, data: &[u8]) -> Vec<u8> {
+        map_letters(data, |y| {
+            (u16::from(self.a_inverse) * u16::from(y + 26 - self.b) % 26) as u8
+        })
+    }
+}
+
+/// Atbash, which replaces `A` with `Z`, `B` with `Y` and so on, and is its own
+/// inverse
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Atbash;
+
+impl ClassicalCipher for Atbash {
+    type Key = ();
+
+    fn new(_: ()) -> Result<Self, &'static str> {
+        Ok(Self)
+    }
+
+    fn encrypt_bytes(&self, data: &[u8]) -> Vec<u8> {
+        map_letters(data, |x| 25 - x)
+    }
+
+    fn decrypt_bytes(&self, data: &[u8]) -> Vec<u8> {
+        self.encrypt_bytes(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::classical_cipher::tests::round_trips;
+    use super::*;
+    use quickcheck_macros::quickcheck;
+
+    #[test]
+    fn affine() {
+        let cipher = Affine::new((5, 8)).unwrap();
+        assert_eq!(cipher.encrypt("AFFINE cipher"), "IHHWVC swfrcp");
+        assert_eq!(cipher.decrypt("IHHWVC swfrcp"), "AFFINE cipher");
+        assert!(Affine::new((13, 1)).is_err());
+        assert!(Affine::new((0, 1)).is_err());
+        // Atbash is an affine cipher
+        let text = "The quick brown fox jumps over the lazy dog";
+        assert_eq!(
+            Affine::new((25, 25)).unwrap().encrypt(text),
+            Atbash.encrypt(text)
+        );
+    }
+
+    #[test]
+    fn atbash() {
+        assert_eq!(Atbash.encrypt("Hello, World!"), "Svool, Dliow!");
+        assert_eq!(Atbash.decrypt("Svool, Dliow!"), "Hello, World!");
+    }
+
+    #[quickcheck]
+    fn round_trip(a: u8, b: u8, text: String, bytes: Vec<u8>) -> bool {
+        let a = a | 1;
+        let a = if a.is_multiple_of(13) { a + 2 } else { a };
+        round_trips(&Affine::new((a, b)).unwrap(), &text, &bytes)
+            && round_trips(&Atbash, &text, &bytes)
+    }
+}
@@ -0,0 +1,582 @@
+//! Cryptanalysis of the classical ciphers
+//!
+//! Statistics which tell English apart from random text, and attacks built on
+//! them:
+//!
+//! - [`NgramFitness`] scores text by the log probability of its n-grams in
+//!   English, [`chi_squared`] compares its letter frequencies with English and
+//!   [`index_of_coincidence`] measures how uneven they are.
+//! - [`break_caesar`] and [`break_affine`] try every key and rank the
+//!   decryptions by fitness.
+//! - [`kasiski`] and [`vigenere_key_lengths`] guess the key length of a
+//!   Vigenère ciphertext, and [`break_vigenere`] recovers the key by solving
+//!   every column as a Caesar cipher.
+//! - [`break_single_byte_xor`] and [`break_repeating_xor`] do the same for
+//!   XOR with a single byte, as in [`xor_bytes`](super::xor_bytes), or a
+//!   repeating key, whose size is guessed from the Hamming distance between
+//!   blocks by [`xor_key_sizes`].
+//!
+//! All of these need enough ciphertext to work: a few dozen letters for the
+//! single alphabet ciphers, and a few dozen per key byte or letter for the
+//! others.
+
+use std::collections::HashMap;
+
+use super::{Affine, Caesar, ClassicalCipher, Vigenere};
+
+/// Relative frequencies of the letters in English text
+pub const ENGLISH_FREQUENCIES: [f64; 26] = [
+    0.08167, 0.01492, 0.02782, 0.04253, 0.12702, 0.02228, 0.02015, 0.06094, 0.06966, 0.00153,
+    0.00772, 0.04025, 0.02406, 0.06749, 0.07507, 0.01929, 0.00095, 0.05987, 0.06327, 0.09056,
+    0.02758, 0.00978, 0.02360, 0.00150, 0.01974, 0.00074,
+];
+
+/// Index of coincidence of English text. It is `1 / 26 ≈ 0.0385` for
+/// uniformly random letters.
+pub const ENGLISH_INDEX_OF_COINCIDENCE: f64 = 0.0667;
+
+/// English text the n-gram statistics of [`NgramFitness::english`] are taken
+/// from
+const ENGLISH_CORPUS: &str = "\
+When in the course of human events it becomes necessary for one people to dissolve the \
+political bands which have connected them with another, and to assume among the powers of the \
+earth the separate and equal station to which the laws of nature and of nature's God entitle \
+them, a decent respect to the opinions of mankind requires that they should declare the causes \
+which impel them to the separation. We hold these truths to be self-evident, that all men are \
+created equal, that they are endowed by their creator with certain unalienable rights, that \
+among these are life, liberty and the pursuit of happiness. That to secure these rights, \
+governments are instituted among men, deriving their just powers from the consent of the \
+governed. The old lighthouse stood at the end of a long and narrow point of rock, and for more \
+than a hundred years its keepers had watched the ships go by. In the winter the wind came in \
+from the north and the waves broke over the wall, so that the keeper had to walk very carefully \
+when he went out to the lamp at night. His daughter was not afraid of the storms. She would sit \
+by the window with a book in her lap and count the seconds between the flashes of lightning and \
+the thunder that followed them, and she knew the name of every vessel that passed the point. \
+Most of the work of a scientist is not spent in great moments of discovery but in the patient \
+repetition of small experiments. A question is asked, a method is chosen, and the results are \
+written down whether or not they agree with what was expected. Only after many such attempts \
+does a pattern begin to appear, and even then it must be tested again by other people who have \
+no interest in the outcome. This is why the history of science is full of ideas that seemed \
+obvious for a time and were later found to be wrong. The market in the square opened early every \
+morning. Farmers brought their vegetables from the villages in the hills, the baker set out \
+bread that was still warm, and the children ran between the stalls looking for something sweet. \
+By noon the heat made everyone slow and quiet, and most of the traders packed their goods away \
+and went home to rest until the evening, when the square would fill again with music and the \
+smell of food cooking over open fires. It is a truth universally acknowledged that a single man \
+in possession of a good fortune must be in want of a wife. However little known the feelings or \
+views of such a man may be on his first entering a neighbourhood, this truth is so well fixed in \
+the minds of the surrounding families that he is considered the rightful property of some one or \
+other of their daughters. Learning a new language takes time, and there are no real shortcuts. \
+You have to listen to people speak, read as much as you can, make mistakes and be corrected, and \
+slowly the strange sounds become words and the words become thoughts of your own. Those who \
+succeed are rarely the most talented, but they are almost always the ones who keep going after \
+everybody else has decided that it is too hard.";
+
+/// The indices in the alphabet of the ASCII letters of `text`
+fn letters(text: &str) -> Vec<u8> {
+    text.bytes()
+        .filter(u8::is_ascii_alphabetic)
+        .map(|b| b.to_ascii_uppercase() - b'A')
+        .collect()
+}
+
+fn letter_counts(letters: &[u8]) -> [usize; 26] {
+    let mut counts = [0; 26];
+    for &letter in letters {
+        counts[usize::from(letter)] += 1;
+    }
+    counts
+}
+
+fn index_of_coincidence_of(letters: &[u8]) -> f64 {
+    let n = letters.len();
+    if n < 2 {
+        return 0.0;
+    }
+    let coincidences: usize = letter_counts(letters)
+        .iter()
+        .map(|c| c * c.saturating_sub(1))
+        .sum();
+    coincidences as f64 / (n * (n - 1)) as f64
+}
+
+fn chi_squared_of(letters: &[u8]) -> f64 {
+    if letters.is_empty() {
+        return f64::INFINITY;
+    }
+    let n = letters.len() as f64;
+    letter_counts(letters)
+        .iter()
+        .zip(ENGLISH_FREQUENCIES)
+        .map(|(&count, frequency)| {
+            let expected = n * frequency;
+            (count as f64 - expected).powi(2) / expected
+        })
+        .sum()
+}
+
+/// Index of coincidence of the ASCII letters of `text`, ignoring case: the
+/// probability that two letters at different positions are the same. It is 0
+/// for less than two letters.
+pub fn index_of_coincidence(text: &str) -> f64 {
+    index_of_coincidence_of(&letters(text))
+}
+
+/// Chi-squared statistic of the frequencies of the ASCII letters of `text`,
+/// ignoring case, against [`ENGLISH_FREQUENCIES`]. The lower it is, the more
+/// the frequencies look like English. It is infinite if there are no letters.
+pub fn chi_squared(text: &str) -> f64 {
+    chi_squared_of(&letters(text))
+}
+
+/// Fitness of text as English, based on the frequencies of its n-grams of
+/// letters
+#[derive(Debug, Clone)]
+pub struct NgramFitness {
+    n: usize,
+    /// Base 10 logarithm of the probability of every n-gram in the corpus,
+    /// indexed by the n-gram as a number in base 26
+    log_probabilities: HashMap<u32, f64>,
+    /// Log probability of the n-grams which are not in the corpus
+    floor: f64,
+}
+
+impl NgramFitness {
+    /// Create a new instance with the frequencies of the n-grams of the
+    /// ASCII letters of `corpus`, ignoring case
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is not between 1 and 6.
+    pub fn from_corpus(corpus: &str, n: usize) -> Self {
+        assert!((1..=6).contains(&n), "n must be between 1 and 6");
+        let mut counts: HashMap<u32, usize> = HashMap::new();
+        let letters = letters(corpus);
+        for ngram in letters.windows(n) {
+            *counts.entry(Self::index(ngram)).or_default() += 1;
+        }
+        let total = letters.len().saturating_sub(n - 1).max(1) as f64;
+        Self {
+            n,
+            log_probabilities: counts
+                .into_iter()
+                .map(|(ngram, count)| (ngram, (count as f64 / total).log10()))
+                .collect(),
+            floor: (0.01 / total).log10(),
+        }
+    }
+
+    /// Create a new instance with the n-grams of a built-in English corpus.
+    /// Bigrams and trigrams work best with it, as it is only a few thousand
+    /// letters long.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is not between 1 and 6.
+    pub fn english(n: usize) -> Self {
+        Self::from_corpus(ENGLISH_CORPUS, n)
+    }
+
+    fn index(ngram: &[u8]) -> u32 {
+        ngram
+            .iter()
+            .fold(0, |index, &letter| 26 * index + u32::from(letter))
+    }
+
+    /// Average log probability of the n-grams of the ASCII letters of `text`,
+    /// ignoring case. The higher it is, the more the text looks like English.
+    pub fn score(&self, text: &str) -> f64 {
+        let letters = letters(text);
+        if letters.len() < self.n {
+            return self.floor;
+        }
+        let windows = letters.windows(self.n);
+        let count = windows.len() as f64;
+        windows
+            .map(|ngram| {
+                *self
+                    .log_probabilities
+                    .get(&Self::index(ngram))
+                    .unwrap_or(&self.floor)
+            })
+            .sum::<f64>()
+            / count
+    }
+}
+
+/// A possible decryption
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candidate<K> {
+    /// The key
+    pub key: K,
+    /// The decryption with `key`
+    pub plaintext: String,
+    /// How much the decryption looks like English, the higher the better
+    pub score: f64,
+}
+
+/// Sort `candidates` from the best to the worst score
+fn ranked<K>(mut candidates: Vec<Candidate<K>>) -> Vec<Candidate<K>> {
+    candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+    candidates
+}
+
+/// Decrypt `ciphertext` with all 26 Caesar shifts, ranked by `fitness`
+pub fn break_caesar(ciphertext: &str, fitness: &NgramFitness) -> Vec<Candidate<u8>> {
+    ranked(
+        (0..26)
+            .map(|shift| {
+                let plaintext = Caesar::new(shift).unwrap().decrypt(ciphertext);
+                Candidate {
+                    key: shift,
+                    score: fitness.score(&plaintext),
+                    plaintext,
+                }
+            })
+            .collect(),
+    )
+}
+
+/// Decrypt `ciphertext` with all 312 affine keys `(a, b)`, ranked by
+/// `fitness`
+pub fn break_affine(ciphertext: &str, fitness: &NgramFitness) -> Vec<Candidate<(u8, u8)>> {
+    let mut candidates = Vec::with_capacity(312);
+    for a in (1..26).step_by(2).filter(|&a| a != 13) {
+        for b in 0..26 {
+            let plaintext = Affine::new((a, b)).unwrap().decrypt(ciphertext);
+            candidates.push(Candidate {
+                key: (a, b),
+                score: fitness.score(&plaintext),
+                plaintext,
+            });
+        }
+    }
+    ranked(candidates)
+}
+
+/// Kasiski examination: finds the sequences of three letters which are
+/// repeated in `ciphertext`, and counts how many of the distances between
+/// repetitions every key length from 2 to `max_key_len` divides. Returns the
+/// key lengths with a positive count, from the highest to the lowest count.
+pub fn kasiski(ciphertext: &str, max_key_len: usize) -> Vec<(usize, usize)> {
+    let letters = letters(ciphertext);
+    let mut last_seen: HashMap<&[u8], usize> = HashMap::new();
+    let mut counts = vec![0; max_key_len + 1];
+    for (position, trigram) in letters.windows(3).enumerate() {
+        if let Some(previous) = last_seen.insert(trigram, position) {
+            let distance = position - previous;
+            for (len, count) in counts.iter_mut().enumerate().skip(2) {
+                if distance.is_multiple_of(len) {
+                    *count += 1;
+                }
+            }
+        }
+    }
+    let mut result: Vec<(usize, usize)> = counts
+        .into_iter()
+        .enumerate()
+        .filter(|&(_, count)| count > 0)
+        .collect();
+    result.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    result
+}
+
+/// The letters at positions `column`, `column + len`, `column + 2 * len`, ...
+fn column(letters: &[u8], column: usize, len: usize) -> Vec<u8> {
+    letters.iter().skip(column).step_by(len).copied().collect()
+}
+
+/// Guess the key length of a Vigenère ciphertext: for every length from 1 to
+/// `max_key_len`, the average index of coincidence of the letters which were
+/// encrypted with the same key letter. Returns the lengths from the closest
+/// to the farthest from [`ENGLISH_INDEX_OF_COINCIDENCE`]. Multiples of the key
+/// length score well too.
+pub fn vigenere_key_lengths(ciphertext: &str, max_key_len: usize) -> Vec<(usize, f64)> {
+    let letters = letters(ciphertext);
+    let mut result: Vec<(usize, f64)> = (1..=max_key_len.min(letters.len() / 2))
+        .map(|len| {
+            let total: f64 = (0..len)
+                .map(|i| index_of_coincidence_of(&column(&letters, i, len)))
+                .sum();
+            (len, total / len as f64)
+        })
+        .collect();
+    result.sort_by(|a, b| {
+        (a.1 - ENGLISH_INDEX_OF_COINCIDENCE)
+            .abs()
+            .total_cmp(&(b.1 - ENGLISH_INDEX_OF_COINCIDENCE).abs())
+    });
+    result
+}
+
+/// The shortest prefix of `key` which repeats to `key`
+fn shortest_period<T: PartialEq>(key: &[T]) -> &[T] {
+    let len = (1..key.len())
+        .find(|&len| key.len().is_multiple_of(len) && key.chunks(len).all(|c| c == &key[..len]))
+        .unwrap_or(key.len());
+    &key[..len]
+}
+
+/// Recover the key of a Vigenère ciphertext, trying the five most likely key
+/// lengths up to `max_key_len`. For every length, every column is solved as
+/// a Caesar cipher with [`chi_squared`], and the best decryption according to
+/// `fitness` is returned. Returns `None` if there are less than two letters.
+pub fn break_vigenere(
+    ciphertext: &str,
+    max_key_len: usize,
+    fitness: &NgramFitness,
+) -> Option<Candidate<String>> {
+    let letters = letters(ciphertext);
+    vigenere_key_lengths(ciphertext, max_key_len)
+        .into_iter()
+        .take(5)
+        .map(|(len, _)| {
+            let key: Vec<u8> = (0..len)
+                .map(|i| {
+                    let column = column(&letters, i, len);
+                    (0..26)
+                        .min_by(|&a, &b| {
+                            let shifted = |shift| {
+                                chi_squared_of(
+                                    &column
+                                        .iter()
+                                        .map(|c| (c + 26 - shift) % 26)
+                                        .collect::<Vec<_>>(),
+                                )
+                            };
+                            shifted(a).total_cmp(&shifted(b))
+                        })
+                        .unwrap()
+                })
+                .collect();
+            let key: String = shortest_period(&key)
+                .iter()
+                .map(|&k| char::from(b'A' + k))
+                .collect();
+            let plaintext = Vigenere::new(key.clone()).unwrap().decrypt(ciphertext);
+            Candidate {
+                key,
+                score: fitness.score(&plaintext),
+                plaintext,
+            }
+        })
+        .max_by(|a, b| a.score.total_cmp(&b.score))
+}
+
+/// Number of bits which differ between `a` and `b`
+///
+/// # Panics
+///
+/// Panics if `a` and `b` have different lengths.
+pub fn hamming_distance(a: &[u8], b: &[u8]) -> u32 {
+    assert_eq!(a.len(), b.len(), "Inputs must have the same length");
+    a.iter().zip(b).map(|(x, y)| (x ^ y).count_ones()).sum()
+}
+
+/// How much `data` looks like English text: the average over its bytes of
+/// the frequency of letters, with spaces being the most frequent, and a
+/// penalty for bytes which are not printable ASCII
+fn english_byte_score(data: &[u8]) -> f64 {
+    let total: f64 = data
+        .iter()
+        .map(|&b| match b {
+            b'a'..=b'z' => ENGLISH_FREQUENCIES[usize::from(b - b'a')],
+            b'A'..=b'Z' => ENGLISH_FREQUENCIES[usize::from(b - b'A')] / 2.0,
+            b' ' => 0.15,
+            b'\n' | b'!'..=b'@' => 0.0,
+            _ => -0.1,
+        })
+        .sum();
+    total / data.len().max(1) as f64
+}
+
+/// Decrypt `ciphertext` with all 256 single byte XOR keys, ranked by how
+/// much the decryption looks like English text
+pub fn break_single_byte_xor(ciphertext: &[u8]) -> Vec<Candidate<u8>> {
+    ranked(
+        (0..=255)
+            .map(|key| {
+                let plaintext: Vec<u8> = ciphertext.iter().map(|b| b ^ key).collect();
+                Candidate {
+                    key,
+                    score: english_byte_score(&plaintext),
+                    plaintext: String::from_utf8_lossy(&plaintext).into_owned(),
+                }
+            })
+            .collect(),
+    )
+}
+
+/// Guess the key size of a repeating key XOR ciphertext: for every size from
+/// 1 to `max_key_size`, the average Hamming distance between consecutive
+/// blocks of that size, divided by the size. Text encrypted with the same key
+/// bytes differs less than random bytes do, so the most likely sizes come
+/// first. Sizes which do not fit twice in `ciphertext` are skipped.
+pub fn xor_key_sizes(ciphertext: &[u8], max_key_size: usize) -> Vec<(usize, f64)> {
+    let mut result: Vec<(usize, f64)> = (1..=max_key_size.min(ciphertext.len() / 2))
+        .map(|size| {
+            let blocks: Vec<&[u8]> = ciphertext.chunks_exact(size).collect();
+            let pairs = blocks.len() - 1;
+            let total: u32 = blocks
+                .windows(2)
+                .map(|pair| hamming_distance(pair[0], pair[1]))
+                .sum();
+            (size, f64::from(total) / (pairs * size) as f64)
+        })
+        .collect();
+    result.sort_by(|a, b| a.1.total_cmp(&b.1));
+    result
+}
+
+/// Recover the key of a repeating key XOR ciphertext, trying the three most
+/// likely key sizes up to `max_key_size`. For every size, every byte of the
+/// key is found with [`break_single_byte_xor`], and the decryption which looks
+/// the most like English text is returned. Returns `None` if `ciphertext` is
+/// shorter than 2 bytes.
+pub fn break_repeating_xor(ciphertext: &[u8], max_key_size: usize) -> Option<Candidate<Vec<u8>>> {
+    xor_key_sizes(ciphertext, max_key_size)
+        .into_iter()
+        .take(3)
+        .map(|(size, _)| {
+            let key: Vec<u8> = (0..size)
+                .map(|i| {
+                    let column: Vec<u8> =
+                        ciphertext.iter().skip(i).step_by(size).copied().collect();
+                    break_single_byte_xor(&column)[0].key
+                })
+                .collect();
+            let key = shortest_period(&key).to_vec();
+            let plaintext: Vec<u8> = ciphertext
+                .iter()
+                .zip(key.iter().cycle())
+                .map(|(b, k)| b ^ k)
+                .collect();
+            Candidate {
+                key,
+                score: english_byte_score(&plaintext),
+                plaintext: String::from_utf8_lossy(&plaintext).into_owned(),
+            }
+        })
+        .max_by(|a, b| a.score.total_cmp(&b.score))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A text which is not part of the corpus
+    const MESSAGE: &str = "The river had been rising for three days before anyone in the town \
+        began to worry about it. At first it was only a little higher than usual, and the \
+        fishermen joked that they would soon be able to cast their lines from their own front \
+        doors. Then the rain started again in the mountains, and by the fourth morning the water \
+        was lapping at the steps of the church and the old bridge could no longer be crossed. \
+        The mayor called a meeting in the school, and for the first time in many years every \
+        family in the valley sent someone to listen to what he had to say.";
+
+    #[test]
+    fn statistics() {
+        let shifted = Caesar::new(3).unwrap().encrypt(MESSAGE);
+        assert!(index_of_coincidence(MESSAGE) > 0.06);
+        // a substitution does not change the index of coincidence
+        assert_eq!(
+            index_of_coincidence(MESSAGE),
+            index_of_coincidence(&shifted)
+        );
+        let vigenere = Vigenere::new("KEYWORD".to_owned())
+            .unwrap()
+            .encrypt(MESSAGE);
+        assert!(index_of_coincidence(&vigenere) < 0.05);
+        assert_eq!(index_of_coincidence("a"), 0.0);
+        assert!(chi_squared(MESSAGE) < 50.0);
+        assert!(chi_squared(&shifted) > 500.0);
+        assert_eq!(chi_squared(""), f64::INFINITY);
+        assert_eq!(chi_squared("42, !"), f64::INFINITY);
+        let fitness = NgramFitness::english(3);
+        assert!(fitness.score(MESSAGE) > fitness.score(&shifted));
+        assert!(fitness.score(MESSAGE) > fitness.score(&vigenere));
+    }
+
+    #[test]
+    fn caesar() {
+        let ciphertext = Caesar::new(7).unwrap().encrypt(MESSAGE);
+        let ranked = break_caesar(&ciphertext, &NgramFitness::english(2));
+        assert_eq!(ranked.len(), 26);
+        assert_eq!(ranked[0].key, 7);
+        assert_eq!(ranked[0].plaintext, MESSAGE);
+        assert!(ranked.windows(2).all(|w| w[0].score >= w[1].score));
+        // short messages work too
+        let ciphertext = Caesar::new(20)
+            .unwrap()
+            .encrypt("Meet me at the old bridge tonight");
+        assert_eq!(
+            break_caesar(&ciphertext, &NgramFitness::english(3))[0].key,
+            20
+        );
+    }
+
+    #[test]
+    fn affine() {
+        let ciphertext = Affine::new((5, 8)).unwrap().encrypt(MESSAGE);
+        let ranked = break_affine(&ciphertext, &NgramFitness::english(3));
+        assert_eq!(ranked.len(), 312);
+        assert_eq!(ranked[0].key, (5, 8));
+        assert_eq!(ranked[0].plaintext, MESSAGE);
+    }
+
+    #[test]
+    fn kasiski_examination() {
+        let ciphertext = Vigenere::new("LEMON".to_owned()).unwrap().encrypt(MESSAGE);
+        let lengths = kasiski(&ciphertext, 12);
+        assert_eq!(lengths[0].0, 5);
+        assert!(kasiski("abcdef", 10).is_empty());
+    }
+
+    #[test]
+    fn vigenere() {
+        let fitness = NgramFitness::english(3);
+        for key in ["LEMON", "CIPHER", "KEYWORD", "Q"] {
+            let ciphertext = Vigenere::new(key.to_owned()).unwrap().encrypt(MESSAGE);
+            let lengths = vigenere_key_lengths(&ciphertext, 20);
+            assert!(lengths[..3]
+                .iter()
+                .any(|&(len, _)| len.is_multiple_of(key.len())));
+            let broken = break_vigenere(&ciphertext, 20, &fitness).unwrap();
+            assert_eq!(broken.key, key);
+            assert_eq!(broken.plaintext, MESSAGE);
+        }
+        assert_eq!(break_vigenere("", 10, &fitness), None);
+    }
+
+    #[test]
+    fn hamming() {
+        assert_eq!(hamming_distance(b"this is a test", b"wokka wokka!!!"), 37);
+        assert_eq!(hamming_distance(b"", b""), 0);
+    }
+
+    #[test]
+    fn single_byte_xor() {
+        let ciphertext = super::super::xor(MESSAGE, 0x5a);
+        let ranked = break_single_byte_xor(&ciphertext);
+        assert_eq!(ranked.len(), 256);
+        assert_eq!(ranked[0].key, 0x5a);
+        assert_eq!(ranked[0].plaintext, MESSAGE);
+    }
+
+    #[test]
+    fn repeating_xor() {
+        for key in [&b"ICE"[..], b"secret key", b"\x00\xff\x10\x80"] {
+            let ciphertext: Vec<u8> = MESSAGE
+                .bytes()
+                .zip(key.iter().cycle())
+                .map(|(b, k)| b ^ k)
+                .collect();
+            assert!(xor_key_sizes(&ciphertext, 20)[..3]
+                .iter()
+                .any(|&(size, _)| size.is_multiple_of(key.len())));
+            let broken = break_repeating_xor(&ciphertext, 20).unwrap();
+            assert_eq!(broken.key, key);
+            assert_eq!(broken.plaintext, MESSAGE);
+        }
+        assert_eq!(break_repeating_xor(b"a", 10), None);
+    }
+}
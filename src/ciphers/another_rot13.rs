@@ -1,15 +1,10 @@
 //! rot13 or rotate 13 cipher algorithm
 
+use super::{ClassicalCipher, Rot13};
+
+/// ROT13 of `text`, keeping the case of the letters
 pub fn another_rot13<T: AsRef<str>>(text: T) -> String {
-    const INPUT: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
-    const OUTPUT: &str = "NOPQRSTUVWXYZABCDEFGHIJKLMnopqrstuvwxyzabcdefghijklm";
-    text.as_ref()
-        .chars()
-        .map(|c| match INPUT.find(c) {
-            Some(i) => OUTPUT.chars().nth(i).unwrap(),
-            None => c,
-        })
-        .collect()
+    Rot13.encrypt(text.as_ref())
 }
 
 #[cfg(test)]
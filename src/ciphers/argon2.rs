@@ -0,0 +1,484 @@
+//! Argon2 implementation
+//!
+//! This implementation is based on [`RFC9106`]. Argon2 fills a matrix of
+//! 1 KiB blocks with `p` rows (lanes), where every block is computed from the
+//! previous one and a reference block chosen from the blocks computed so far.
+//! The lanes are split into 4 slices, and a block only references blocks of
+//! other lanes in finished slices, so the lanes could be computed in parallel.
+//!
+//! Argon2d chooses reference blocks based on the data, which makes it most
+//! resistant to GPU cracking but leaks timing information, Argon2i chooses them
+//! independently of the data, and Argon2id, the recommended variant, uses the
+//! latter for the first half of the first pass and the former afterwards.
+//!
+//! [`RFC9106`]: https://www.rfc-editor.org/rfc/rfc9106
+
+use super::Blake2b;
+
+const VERSION: u32 = 0x13;
+const SYNC_POINTS: usize = 4;
+const BLOCK_WORDS: usize = 128;
+const BLOCK_BYTES: usize = 8 * BLOCK_WORDS;
+
+type Block = [u64; BLOCK_WORDS];
+
+/// The variants of Argon2
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Argon2Variant {
+    /// Data-dependent memory access
+    Argon2d,
+    /// Data-independent memory access
+    Argon2i,
+    /// Data-independent memory access for the first half of the first pass,
+    /// data-dependent afterwards
+    Argon2id,
+}
+
+/// Parameters of [`argon2`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Argon2Params {
+    memory_kib: u32,
+    iterations: u32,
+    parallelism: u32,
+}
+
+impl Argon2Params {
+    /// Create new parameters using `memory_kib` KiB of memory, `iterations`
+    /// passes over the memory and `parallelism` lanes.
+    ///
+    /// As required by the RFC, the parallelism must be between 1 and
+    /// `2^24 - 1`, the memory at least 8 KiB per lane, and the number of
+    /// passes positive. The memory is rounded down to a multiple of 4 KiB per
+    /// lane.
+    pub fn new(memory_kib: u32, iterations: u32, parallelism: u32) -> Result<Self, &'static str> {
+        if parallelism == 0 || parallelism >= 1 << 24 {
+            return Err("Parallelism must be between 1 and 2^24 - 1");
+        }
+        if (memory_kib as u64) < 8 * parallelism as u64 {
+            return Err("Memory must be at least 8 KiB per lane");
+        }
+        if iterations == 0 {
+            return Err("Number of passes must be positive");
+        }
+        Ok(Self {
+            memory_kib,
+            iterations,
+            parallelism,
+        })
+    }
+
+    /// Returns the memory size in KiB
+    pub fn memory_kib(&self) -> u32 {
+        self.memory_kib
+    }
+
+    /// Returns the number of passes over the memory
+    pub fn iterations(&self) -> u32 {
+        self.iterations
+    }
+
+    /// Returns the number of lanes
+    pub fn parallelism(&self) -> u32 {
+        self.parallelism
+    }
+}
+
+/// The variable-length hash function H' of the RFC, filling `output` with the
+/// hash of the concatenation of `inputs`
+fn blake2b_long(output: &mut [u8], inputs: &[&[u8]]) {
+    let len = output.len();
+    let mut hasher = Blake2b::with_digest_len(len.min(64));
+    hasher.update(&(len as u32).to_le_bytes());
+    for input in inputs {
+        hasher.update(input);
+    }
+    let mut v = hasher.get_hash();
+    if len <= 64 {
+        output.copy_from_slice(&v[..len]);
+        return;
+    }
+    // the first half of each intermediate hash is used, except for the last
+    // hash, which has the remaining length
+    output[..32].copy_from_slice(&v[..32]);
+    let mut position = 32;
+    while len - position > 64 {
+        let mut hasher = Blake2b::<64>::new_default();
+        hasher.update(&v);
+        v = hasher.get_hash();
+        output[position..position + 32].copy_from_slice(&v[..32]);
+        position += 32;
+    }
+    let last = len - position;
+    let mut hasher = Blake2b::with_digest_len(last);
+    hasher.update(&v);
+    output[position..].copy_from_slice(&hasher.get_hash()[..last]);
+}
+
+/// The BLAKE2b round function, with the multiplications of BlaMka added to
+/// the additions
+fn permute(v: &mut Block, indices: [usize; 16]) {
+    fn g(v: &mut Block, a: usize, b: usize, c: usize, d: usize) {
+        let bla_mka = |x: u64, y: u64| {
+            x.wrapping_add(y).wrapping_add(
+                2u64.wrapping_mul(x & 0xffffffff)
+                    .wrapping_mul(y & 0xffffffff),
+            )
+        };
+        v[a] = bla_mka(v[a], v[b]);
+        v[d] = (v[d] ^ v[a]).rotate_right(32);
+        v[c] = bla_mka(v[c], v[d]);
+        v[b] = (v[b] ^ v[c]).rotate_right(24);
+        v[a] = bla_mka(v[a], v[b]);
+        v[d] = (v[d] ^ v[a]).rotate_right(16);
+        v[c] = bla_mka(v[c], v[d]);
+        v[b] = (v[b] ^ v[c]).rotate_right(63);
+    }
+    let i = indices;
+    g(v, i[0], i[4], i[8], i[12]);
+    g(v, i[1], i[5], i[9], i[13]);
+    g(v, i[2], i[6], i[10], i[14]);
+    g(v, i[3], i[7], i[11], i[15]);
+    g(v, i[0], i[5], i[10], i[15]);
+    g(v, i[1], i[6], i[11], i[12]);
+    g(v, i[2], i[7], i[8], i[13]);
+    g(v, i[3], i[4], i[9], i[14]);
+}
+
+/// The compression function G
+fn compress(x: &Block, y: &Block) -> Block {
+    let mut r = [0; BLOCK_WORDS];
+    for (r, (x, y)) in r.iter_mut().zip(x.iter().zip(y)) {
+        *r = x ^ y;
+    }
+    let mut z = r;
+    // the block is an 8x8 matrix of 16 byte registers, which are permuted row
+    // by row and then column by column
+    for row in 0..8 {
+        permute(&mut z, std::array::from_fn(|k| 16 * row + k));
+    }
+    for column in 0..8 {
+        permute(
+            &mut z,
+            std::array::from_fn(|k| 2 * column + 16 * (k / 2) + k % 2),
+        );
+    }
+    for (z, r) in z.iter_mut().zip(r) {
+        *z ^= r;
+    }
+    z
+}
+
+/// The position within its lane of the block referenced by block `index` of
+/// the segment, given the lower 32 bits `j1` of its pseudo-random value
+fn reference_index(
+    pass: usize,
+    slice: usize,
+    index: usize,
+    same_lane: bool,
+    j1: u64,
+    segment_len: usize,
+    lane_len: usize,
+) -> usize {
+    // the blocks that may be referenced: all finished segments (in the current
+    // pass, or the last 3 of the previous one), and the current segment if the
+    // lane is the same, excluding the previous block
+    let finished = if pass == 0 {
+        slice * segment_len
+    } else {
+        lane_len - segment_len
+    };
+    let area = if same_lane {
+        finished + index - 1
+    } else {
+        finished - usize::from(index == 0)
+    };
+    // the distribution favors recent blocks
+    let x = (j1 * j1) >> 32;
+    let y = (area as u64 * x) >> 32;
+    let relative = area - 1 - y as usize;
+    let start = if pass == 0 || slice == SYNC_POINTS - 1 {
+        0
+    } else {
+        (slice + 1) * segment_len
+    };
+    (start + relative) % lane_len
+}
+
+/// Runs the given variant of Argon2 on `password` and `salt`, with an
+/// optional `secret` key and `associated_data`, filling `output` with the tag.
+/// The salt must be at least 8 bytes, and the tag at least 4 bytes long.
+pub fn argon2(
+    variant: Argon2Variant,
+    password: &[u8],
+    salt: &[u8],
+    secret: &[u8],
+    associated_data: &[u8],
+    params: &Argon2Params,
+    output: &mut [u8],
+) -> Result<(), &'static str> {
+    if output.len() < 4 {
+        return Err("Tag must be at least 4 bytes long");
+    }
+    if salt.len() < 8 {
+        return Err("Salt must be at least 8 bytes long");
+    }
+    let length = |input: &[u8]| u32::try_from(input.len()).map_err(|_| "Input is too long");
+    let lanes = params.parallelism as usize;
+    let segment_len = params.memory_kib as usize / (SYNC_POINTS * lanes);
+    let lane_len = SYNC_POINTS * segment_len;
+    let passes = params.iterations as usize;
+    let variant_id = variant as u32;
+
+    let mut h0 = Blake2b::<64>::new_default();
+    for value in [
+        params.parallelism,
+        length(output)?,
+        params.memory_kib,
+        params.iterations,
+        VERSION,
+        variant_id,
+    ] {
+        h0.update(&value.to_le_bytes());
+    }
+    for input in [password, salt, secret, associated_data] {
+        h0.update(&length(input)?.to_le_bytes());
+        h0.update(input);
+    }
+    let h0 = h0.get_hash();
+
+    let mut memory = vec![[0u64; BLOCK_WORDS]; lanes * lane_len];
+    let mut bytes = [0u8; BLOCK_BYTES];
+    for lane in 0..lanes {
+        for i in 0..2 {
+            blake2b_long(
+                &mut bytes,
+                &[&h0, &(i as u32).to_le_bytes(), &(lane as u32).to_le_bytes()],
+            );
+            for (word, chunk) in memory[lane * lane_len + i]
+                .iter_mut()
+                .zip(bytes.chunks_exact(8))
+            {
+                *word = u64::from_le_bytes(chunk.try_into().unwrap());
+            }
+        }
+    }
+
+    let zero = [0u64; BLOCK_WORDS];
+    for pass in 0..passes {
+        for slice in 0..SYNC_POINTS {
+            let data_independent = match variant {
+                Argon2Variant::Argon2d => false,
+                Argon2Variant::Argon2i => true,
+                Argon2Variant::Argon2id => pass == 0 && slice < SYNC_POINTS / 2,
+            };
+            for lane in 0..lanes {
+                // the pseudo-random values of data-independent addressing are
+                // generated 128 at a time by compressing a counter
+                let mut input = [0u64; BLOCK_WORDS];
+                input[..6].copy_from_slice(&[
+                    pass as u64,
+                    lane as u64,
+                    slice as u64,
+                    (lanes * lane_len) as u64,
+                    passes as u64,
+                    variant_id as u64,
+                ]);
+                let mut addresses = [0u64; BLOCK_WORDS];
+                let mut next_addresses = |addresses: &mut Block| {
+                    input[6] += 1;
+                    *addresses = compress(&zero, &compress(&zero, &input));
+                };
+                // the first two blocks of each lane are already filled
+                let start = if pass == 0 && slice == 0 { 2 } else { 0 };
+                if data_independent && start == 2 {
+                    next_addresses(&mut addresses);
+                }
+                for index in start..segment_len {
+                    let offset = lane * lane_len + slice * segment_len + index;
+                    let previous = if offset.is_multiple_of(lane_len) {
+                        offset + lane_len - 1
+                    } else {
+                        offset - 1
+                    };
+                    let pseudo_random = if data_independent {
+                        if index.is_multiple_of(BLOCK_WORDS) {
+                            next_addresses(&mut addresses);
+                        }
+                        addresses[index % BLOCK_WORDS]
+                    } else {
+                        memory[previous][0]
+                    };
+                    let reference_lane = if pass == 0 && slice == 0 {
+                        lane
+                    } else {
+                        (pseudo_random >> 32) as usize % lanes
+                    };
+                    let reference = reference_lane * lane_len
+                        + reference_index(
+                            pass,
+                            slice,
+                            index,
+                            reference_lane == lane,
+                            pseudo_random & 0xffffffff,
+                            segment_len,
+                            lane_len,
+                        );
+                    let block = compress(&memory[previous], &memory[reference]);
+                    if pass == 0 {
+                        memory[offset] = block;
+                    } else {
+                        // later passes XOR the new block into the old one
+                        for (word, new) in memory[offset].iter_mut().zip(block) {
+                            *word ^= new;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    let mut last = [0u64; BLOCK_WORDS];
+    for lane in 0..lanes {
+        for (word, block_word) in last.iter_mut().zip(memory[(lane + 1) * lane_len - 1]) {
+            *word ^= block_word;
+        }
+    }
+    for (chunk, word) in bytes.chunks_exact_mut(8).zip(last) {
+        chunk.copy_from_slice(&word.to_le_bytes());
+    }
+    blake2b_long(output, &[&bytes]);
+    Ok(())
+}
+
+/// Runs Argon2id on `password` and `salt` without secret or associated data,
+/// filling `output` with the tag
+pub fn argon2id(
+    password: &[u8],
+    salt: &[u8],
+    params: &Argon2Params,
+    output: &mut [u8],
+) -> Result<(), &'static str> {
+    argon2(
+        Argon2Variant::Argon2id,
+        password,
+        salt,
+        &[],
+        &[],
+        params,
+        output,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_utils::get_hash_string;
+    use super::*;
+
+    fn rfc9106(variant: Argon2Variant) -> String {
+        let params = Argon2Params::new(32, 3, 4).unwrap();
+        let mut tag = [0; 32];
+        argon2(
+            variant,
+            &[0x01; 32],
+            &[0x02; 16],
+            &[0x03; 8],
+            &[0x04; 12],
+            &params,
+            &mut tag,
+        )
+        .unwrap();
+        get_hash_string(&tag)
+    }
+
+    #[test]
+    fn rfc9106_vectors() {
+        assert_eq!(
+            rfc9106(Argon2Variant::Argon2d),
+            "512b391b6f1162975371d30919734294f868e3be3984f3c1a13a4db9fabe4acb"
+        );
+        assert_eq!(
+            rfc9106(Argon2Variant::Argon2i),
+            "c814d9d1dc7f37aa13f0d77f2494bda1c8de6b016dd388d29952a4c4672b6ce8"
+        );
+        assert_eq!(
+            rfc9106(Argon2Variant::Argon2id),
+            "0d640df58d78766c08c037a34a8b53c9d01ef0452d75b65eb52520e96b01e659"
+        );
+    }
+
+    #[test]
+    fn variable_length_hash() {
+        // tags of at most 64 bytes are a single BLAKE2b hash
+        let mut short = [0; 40];
+        blake2b_long(&mut short, &[b"abc"]);
+        let mut hasher = Blake2b::with_digest_len(40);
+        hasher.update(&40u32.to_le_bytes());
+        hasher.update(b"abc");
+        assert_eq!(short, hasher.get_hash()[..40]);
+        // longer tags start with the first half of the 64 byte hash
+        let mut long = [0; 100];
+        blake2b_long(&mut long, &[b"a", b"bc"]);
+        let mut hasher = Blake2b::<64>::new_default();
+        hasher.update(&100u32.to_le_bytes());
+        hasher.update(b"abc");
+        assert_eq!(long[..32], hasher.get_hash()[..32]);
+    }
+
+    fn reference(variant: Argon2Variant) -> String {
+        let params = Argon2Params::new(1 << 16, 2, 1).unwrap();
+        let mut tag = [0; 32];
+        argon2(
+            variant,
+            b"password",
+            b"somesalt",
+            &[],
+            &[],
+            &params,
+            &mut tag,
+        )
+        .unwrap();
+        get_hash_string(&tag)
+    }
+
+    #[test]
+    fn reference_vectors() {
+        // From the test suite of the reference implementation. Segments of
+        // 16384 blocks need more than one block of data independent addresses.
+        assert_eq!(
+            reference(Argon2Variant::Argon2i),
+            "c1628832147d9720c5bd1cfd61367078729f6dfb6f8fea9ff98158e0d7816ed0"
+        );
+        assert_eq!(
+            reference(Argon2Variant::Argon2id),
+            "09316115d5cf24ed5a15a31a3ba326e5cf32edc24702987c02b6566f61913cf7"
+        );
+    }
+
+    #[test]
+    fn long_tags() {
+        // computed with Python's `cryptography` package
+        let params = Argon2Params::new(256, 2, 2).unwrap();
+        let mut tag = [0; 100];
+        argon2id(b"password", b"somesalt", &params, &mut tag).unwrap();
+        assert_eq!(
+            get_hash_string(&tag),
+            concat!(
+                "e31a8bd8ef0b8cd159aadd943911bd82be6e6f760423f16e6b8737d82fa1aa05",
+                "2adedecec0dbb6c5ab6b3acd5d83ec60b1be36e5511b1eeaa454237f8d8ddb99",
+                "8d5b99a19f3b47a3a5e730fb9ea581b1e2980ce9f3c5d9e4dc7375f051c71b7b",
+                "285716c6"
+            )
+        );
+    }
+
+    #[test]
+    fn validation() {
+        assert!(Argon2Params::new(32, 3, 0).is_err());
+        assert!(Argon2Params::new(31, 3, 4).is_err());
+        assert!(Argon2Params::new(32, 0, 4).is_err());
+        assert!(Argon2Params::new(32, 1, 1 << 24).is_err());
+        let params = Argon2Params::new(32, 1, 4).unwrap();
+        assert!(argon2id(b"password", b"short", &params, &mut [0; 32]).is_err());
+        assert!(argon2id(b"password", b"somesalt", &params, &mut [0; 3]).is_err());
+    }
+}
@@ -0,0 +1,358 @@
+//! BLAKE2b and BLAKE2s
+//!
+//! This implementation is based on [`RFC7693`]. Both variants share the same
+//! structure: BLAKE2b works on 64 bit words and 128 byte blocks and produces
+//! up to 64 bytes, BLAKE2s works on 32 bit words and 64 byte blocks and
+//! produces up to 32 bytes. The digest length is the const parameter `N`, and
+//! an optional key turns the hash into a MAC.
+//!
+//! [`RFC7693`]: https://www.rfc-editor.org/rfc/rfc7693
+
+use super::Hasher;
+
+/// Message word permutations, one per round (the last two rounds of BLAKE2b
+/// reuse the first two)
+const SIGMA: [[usize; 16]; 10] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+#[rustfmt::skip]
+const IV_B: [u64; 8] = [
+    0x6a09e667f3bcc908, 0xbb67ae8584caa73b, 0x3c6ef372fe94f82b, 0xa54ff53a5f1d36f1,
+    0x510e527fade682d1, 0x9b05688c2b3e6c1f, 0x1f83d9abfb41bd6b, 0x5be0cd19137e2179,
+];
+
+const IV_S: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+macro_rules! blake2 {
+    (
+        $(#[$meta:meta])*
+        $name:ident,
+        word: $word:ty,
+        counter: $counter:ty,
+        block: $block:literal,
+        rounds: $rounds:literal,
+        iv: $iv:expr,
+        rotations: [$r1:literal, $r2:literal, $r3:literal, $r4:literal],
+        salt: $salt:literal
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy)]
+        pub struct $name<const N: usize> {
+            /// The current hash value
+            h: [$word; 8],
+            /// Number of bytes compressed so far
+            t: $counter,
+            /// Bytes not compressed yet. A full block is only compressed once
+            /// more data arrives, because the last block is compressed differently
+            buffer: [u8; $block],
+            buffer_len: usize,
+        }
+
+        impl<const N: usize> $name<N> {
+            /// Create a new unkeyed instance.
+            ///
+            /// # Panics
+            ///
+            #[doc = concat!("Panics if `N` is not between 1 and ", stringify!($block), " / 2.")]
+            pub fn new_default() -> Self {
+                Self::with_params(&[], &[0; $salt], &[0; $salt]).unwrap()
+            }
+
+            /// Create a new instance computing a MAC with `key`
+            pub fn new_keyed(key: &[u8]) -> Result<Self, &'static str> {
+                Self::with_params(key, &[0; $salt], &[0; $salt])
+            }
+
+            /// Create a new instance with a (possibly empty) key, a salt and a
+            /// personalization string
+            pub fn with_params(
+                key: &[u8],
+                salt: &[u8; $salt],
+                personal: &[u8; $salt],
+            ) -> Result<Self, &'static str> {
+                if N == 0 || N > $block / 2 {
+                    return Err("Invalid digest length");
+                }
+                if key.len() > $block / 2 {
+                    return Err("Key is too long");
+                }
+                let mut h = $iv;
+                // the parameter block: digest length, key length, fanout and
+                // depth of 1 (sequential mode), the salt and the personalization
+                h[0] ^= 0x01010000 ^ ((key.len() as $word) << 8) ^ N as $word;
+                let word_bytes = std::mem::size_of::<$word>();
+                for (i, bytes) in salt.chunks_exact(word_bytes).enumerate() {
+                    h[4 + i] ^= <$word>::from_le_bytes(bytes.try_into().unwrap());
+                }
+                for (i, bytes) in personal.chunks_exact(word_bytes).enumerate() {
+                    h[6 + i] ^= <$word>::from_le_bytes(bytes.try_into().unwrap());
+                }
+                let mut result = Self {
+                    h,
+                    t: 0,
+                    buffer: [0; $block],
+                    buffer_len: 0,
+                };
+                if !key.is_empty() {
+                    // the key is padded to a full block and processed first
+                    result.buffer[..key.len()].copy_from_slice(key);
+                    result.buffer_len = $block;
+                }
+                Ok(result)
+            }
+
+            #[inline]
+            #[allow(clippy::too_many_arguments)]
+            fn g(v: &mut [$word; 16], a: usize, b: usize, c: usize, d: usize, x: $word, y: $word) {
+                v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+                v[d] = (v[d] ^ v[a]).rotate_right($r1);
+                v[c] = v[c].wrapping_add(v[d]);
+                v[b] = (v[b] ^ v[c]).rotate_right($r2);
+                v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+                v[d] = (v[d] ^ v[a]).rotate_right($r3);
+                v[c] = v[c].wrapping_add(v[d]);
+                v[b] = (v[b] ^ v[c]).rotate_right($r4);
+            }
+
+            fn compress(h: &mut [$word; 8], block: &[u8; $block], t: $counter, last: bool) {
+                let word_bytes = std::mem::size_of::<$word>();
+                let mut m: [$word; 16] = [0; 16];
+                for (word, bytes) in m.iter_mut().zip(block.chunks_exact(word_bytes)) {
+                    *word = <$word>::from_le_bytes(bytes.try_into().unwrap());
+                }
+                let mut v: [$word; 16] = [0; 16];
+                v[..8].copy_from_slice(h);
+                v[8..].copy_from_slice(&$iv);
+                v[12] ^= t as $word;
+                v[13] ^= (t >> (8 * word_bytes)) as $word;
+                if last {
+                    v[14] = !v[14];
+                }
+                for round in 0..$rounds {
+                    let s = &SIGMA[round % 10];
+                    Self::g(&mut v, 0, 4, 8, 12, m[s[0]], m[s[1]]);
+                    Self::g(&mut v, 1, 5, 9, 13, m[s[2]], m[s[3]]);
+                    Self::g(&mut v, 2, 6, 10, 14, m[s[4]], m[s[5]]);
+                    Self::g(&mut v, 3, 7, 11, 15, m[s[6]], m[s[7]]);
+                    Self::g(&mut v, 0, 5, 10, 15, m[s[8]], m[s[9]]);
+                    Self::g(&mut v, 1, 6, 11, 12, m[s[10]], m[s[11]]);
+                    Self::g(&mut v, 2, 7, 8, 13, m[s[12]], m[s[13]]);
+                    Self::g(&mut v, 3, 4, 9, 14, m[s[14]], m[s[15]]);
+                }
+                for i in 0..8 {
+                    h[i] ^= v[i] ^ v[i + 8];
+                }
+            }
+
+            /// Update the hash with the given data
+            pub fn update(&mut self, mut data: &[u8]) {
+                while !data.is_empty() {
+                    if self.buffer_len == $block {
+                        self.t = self.t.wrapping_add($block);
+                        Self::compress(&mut self.h, &self.buffer, self.t, false);
+                        self.buffer_len = 0;
+                    }
+                    let take = data.len().min($block - self.buffer_len);
+                    self.buffer[self.buffer_len..self.buffer_len + take]
+                        .copy_from_slice(&data[..take]);
+                    self.buffer_len += take;
+                    data = &data[take..];
+                }
+            }
+
+            /// Returns the hash of the data so far. This does not consume the
+            /// state, so more data can still be added afterwards.
+            pub fn get_hash(&self) -> [u8; N] {
+                let mut h = self.h;
+                let mut block = self.buffer;
+                block[self.buffer_len..].fill(0);
+                let t = self.t.wrapping_add(self.buffer_len as $counter);
+                Self::compress(&mut h, &block, t, true);
+                let mut result = [0u8; N];
+                let bytes: Vec<u8> = h.iter().flat_map(|word| word.to_le_bytes()).collect();
+                result.copy_from_slice(&bytes[..N]);
+                result
+            }
+        }
+
+        impl<const N: usize> Hasher<N> for $name<N> {
+            fn new_default() -> Self {
+                $name::new_default()
+            }
+
+            fn update(&mut self, data: &[u8]) {
+                self.update(data);
+            }
+
+            fn get_hash(&mut self) -> [u8; N] {
+                $name::get_hash(self)
+            }
+        }
+    };
+}
+
+blake2!(
+    /// BLAKE2b, optimized for 64 bit platforms, with an `N` byte digest (at
+    /// most 64) and 16 byte salt and personalization
+    Blake2b,
+    word: u64,
+    counter: u128,
+    block: 128,
+    rounds: 12,
+    iv: IV_B,
+    rotations: [32, 24, 16, 63],
+    salt: 16
+);
+
+blake2!(
+    /// BLAKE2s, optimized for 8 to 32 bit platforms, with an `N` byte digest
+    /// (at most 32) and 8 byte salt and personalization
+    Blake2s,
+    word: u32,
+    counter: u64,
+    block: 64,
+    rounds: 10,
+    iv: IV_S,
+    rotations: [16, 12, 8, 7],
+    salt: 8
+);
+
+impl Blake2b<64> {
+    /// Create an unkeyed instance with a digest length only known at runtime,
+    /// as needed by Argon2. The digest is the first `len` bytes of
+    /// [`get_hash`](Self::get_hash).
+    pub(crate) fn with_digest_len(len: usize) -> Self {
+        assert!((1..=64).contains(&len), "Invalid digest length");
+        let mut result = Self::new_default();
+        // only the digest length in the parameter block differs
+        result.h[0] ^= 64 ^ len as u64;
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_utils::get_hash_string;
+    use super::*;
+
+    /// The official keyed test vectors use the key `0, 1, 2, ...` and the
+    /// messages `0, 1, 2, ..., len - 1` for every `len` below 256. Returns the
+    /// hash of the last one and the hash of all of them.
+    fn keyed_test_vectors<const N: usize, H: Hasher<N>>(
+        new_keyed: impl Fn(&[u8]) -> H,
+    ) -> (String, String) {
+        let key: Vec<u8> = (0..N as u8).collect();
+        let message: Vec<u8> = (0..=255).collect();
+        let mut outer = H::new_default();
+        let mut last = [0; N];
+        for len in 0..256 {
+            let mut hasher = new_keyed(&key);
+            hasher.update(&message[..len]);
+            last = hasher.get_hash();
+            outer.update(&last);
+        }
+        (get_hash_string(&last), get_hash_string(&outer.get_hash()))
+    }
+
+    #[test]
+    fn rfc7693_examples() {
+        let mut blake = Blake2b::<64>::new_default();
+        blake.update(b"abc");
+        assert_eq!(
+            get_hash_string(&blake.get_hash()),
+            "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d17d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923"
+        );
+        let mut blake = Blake2s::<32>::new_default();
+        blake.update(b"abc");
+        assert_eq!(
+            get_hash_string(&blake.get_hash()),
+            "508c5e8c327c14e2e1a72ba34eeb452f37458b209ed63a294d999b4c86675982"
+        );
+    }
+
+    #[test]
+    fn digest_lengths() {
+        assert_eq!(
+            get_hash_string(&Blake2b::<32>::new_default().get_hash()),
+            "0e5751c026e543b2e8ab2eb06099daa1d1e5df47778f7787faab45cdf12fe3a8"
+        );
+        assert_eq!(
+            get_hash_string(&Blake2s::<16>::new_default().get_hash()),
+            "64550d6ffe2c0a01a14aba1eade0200c"
+        );
+        assert!(Blake2b::<65>::new_keyed(&[]).is_err());
+        assert!(Blake2s::<0>::new_keyed(&[]).is_err());
+        assert!(Blake2s::<32>::new_keyed(&[0; 33]).is_err());
+        let mut blake = Blake2b::with_digest_len(32);
+        blake.update(b"abc");
+        let mut expected = Blake2b::<32>::new_default();
+        expected.update(b"abc");
+        assert_eq!(blake.get_hash()[..32], expected.get_hash());
+    }
+
+    #[test]
+    fn keyed_kat() {
+        let (last, all) = keyed_test_vectors(|key| Blake2b::<64>::new_keyed(key).unwrap());
+        assert_eq!(
+            last,
+            "142709d62e28fcccd0af97fad0f8465b971e82201dc51070faa0372aa43e92484be1c1e73ba10906d5d1853db6a4106e0a7bf9800d373d6dee2d46d62ef2a461"
+        );
+        assert_eq!(
+            all,
+            "79a51f891c56679c1fff4cff93a7f1ed2922d668560b880f2debecf630d3cdaff59d3bb380d18c3c24d91e214c20085e6b4b081adccb8fc3025a96e520eb7c34"
+        );
+        let (last, all) = keyed_test_vectors(|key| Blake2s::<32>::new_keyed(key).unwrap());
+        assert_eq!(
+            last,
+            "3fb735061abc519dfe979e54c1ee5bfad0a9d858b3315bad34bde999efd724dd"
+        );
+        assert_eq!(
+            all,
+            "2582f7b18a91f66ef22aa9455384ada69b2590306a6470ee8588df75ee982f02"
+        );
+    }
+
+    #[test]
+    fn salt_and_personalization() {
+        let mut blake =
+            Blake2b::<32>::with_params(b"secret", b"0123456789abcdef", b"my-app-v1-person")
+                .unwrap();
+        blake.update(b"hello");
+        assert_eq!(
+            get_hash_string(&blake.get_hash()),
+            "cbf8a62cbcfe4187428a0e52ce255ce20fc06314be7ad82108099ae4b2401994"
+        );
+        let mut blake = Blake2s::<32>::with_params(b"secret", b"01234567", b"myapp-v1").unwrap();
+        blake.update(b"hello");
+        assert_eq!(
+            get_hash_string(&blake.get_hash()),
+            "d28ccf41755ea1eac500933afb20dc7ad0a796c7377eebc77083e01dfa6ea3b5"
+        );
+    }
+
+    #[test]
+    fn split_updates() {
+        let message: Vec<u8> = (0..1000_u32).map(|i| (i * 13) as u8).collect();
+        let mut whole = Blake2b::<64>::new_default();
+        whole.update(&message);
+        let mut pieces = Blake2b::<64>::new_default();
+        for chunk in message.chunks(128) {
+            pieces.update(chunk);
+            // getting the hash in between must not disturb the state
+            pieces.get_hash();
+        }
+        assert_eq!(pieces.get_hash(), whole.get_hash());
+    }
+}
@@ -0,0 +1,573 @@
+//! BLAKE3
+//!
+//! This implementation follows the [`BLAKE3 specification`]. The input is split
+//! into 1 KiB chunks, each chunk is hashed with a chain of compressions, and
+//! the chaining values of the chunks are combined pairwise into a binary tree
+//! whose root produces the output, which can be extended to any length.
+//! Because the subtrees are independent, large inputs can be hashed in
+//! parallel with [`Blake3::update_rayon`] when the `rayon` feature is enabled.
+//!
+//! [`BLAKE3 specification`]: https://github.com/BLAKE3-team/BLAKE3-specs/blob/master/blake3.pdf
+
+use super::Hasher;
+
+const OUT_LEN: usize = 32;
+const BLOCK_LEN: usize = 64;
+const CHUNK_LEN: usize = 1024;
+
+const CHUNK_START: u32 = 1 << 0;
+const CHUNK_END: u32 = 1 << 1;
+const PARENT: u32 = 1 << 2;
+const ROOT: u32 = 1 << 3;
+const KEYED_HASH: u32 = 1 << 4;
+const DERIVE_KEY_CONTEXT: u32 = 1 << 5;
+const DERIVE_KEY_MATERIAL: u32 = 1 << 6;
+
+const IV: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+const MSG_PERMUTATION: [usize; 16] = [2, 6, 3, 10, 7, 0, 4, 13, 1, 11, 12, 5, 9, 14, 15, 8];
+
+#[inline]
+fn g(state: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize, mx: u32, my: u32) {
+    state[a] = state[a].wrapping_add(state[b]).wrapping_add(mx);
+    state[d] = (state[d] ^ state[a]).rotate_right(16);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_right(12);
+    state[a] = state[a].wrapping_add(state[b]).wrapping_add(my);
+    state[d] = (state[d] ^ state[a]).rotate_right(8);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_right(7);
+}
+
+fn round(state: &mut [u32; 16], m: &[u32; 16]) {
+    // mix the columns
+    g(state, 0, 4, 8, 12, m[0], m[1]);
+    g(state, 1, 5, 9, 13, m[2], m[3]);
+    g(state, 2, 6, 10, 14, m[4], m[5]);
+    g(state, 3, 7, 11, 15, m[6], m[7]);
+    // mix the diagonals
+    g(state, 0, 5, 10, 15, m[8], m[9]);
+    g(state, 1, 6, 11, 12, m[10], m[11]);
+    g(state, 2, 7, 8, 13, m[12], m[13]);
+    g(state, 3, 4, 9, 14, m[14], m[15]);
+}
+
+fn compress(
+    chaining_value: &[u32; 8],
+    block_words: &[u32; 16],
+    counter: u64,
+    block_len: u32,
+    flags: u32,
+) -> [u32; 16] {
+    let mut state = [0u32; 16];
+    state[..8].copy_from_slice(chaining_value);
+    state[8..12].copy_from_slice(&IV[..4]);
+    state[12] = counter as u32;
+    state[13] = (counter >> 32) as u32;
+    state[14] = block_len;
+    state[15] = flags;
+    let mut block = *block_words;
+    for i in 0..7 {
+        round(&mut state, &block);
+        if i < 6 {
+            block = MSG_PERMUTATION.map(|j| block[j]);
+        }
+    }
+    for i in 0..8 {
+        state[i] ^= state[i + 8];
+        state[i + 8] ^= chaining_value[i];
+    }
+    state
+}
+
+fn words<const W: usize>(bytes: &[u8]) -> [u32; W] {
+    let mut result = [0u32; W];
+    for (word, bytes) in result.iter_mut().zip(bytes.chunks_exact(4)) {
+        *word = u32::from_le_bytes(bytes.try_into().unwrap());
+    }
+    result
+}
+
+fn first_8_words(compression_output: [u32; 16]) -> [u32; 8] {
+    compression_output[..8].try_into().unwrap()
+}
+
+/// The inputs of a compression that has not been done yet, since it is either
+/// a chaining value or (with the `ROOT` flag) the output
+#[derive(Debug, Clone, Copy)]
+struct Output {
+    input_chaining_value: [u32; 8],
+    block_words: [u32; 16],
+    counter: u64,
+    block_len: u32,
+    flags: u32,
+}
+
+impl Output {
+    fn chaining_value(&self) -> [u32; 8] {
+        first_8_words(compress(
+            &self.input_chaining_value,
+            &self.block_words,
+            self.counter,
+            self.block_len,
+            self.flags,
+        ))
+    }
+
+    fn root_output_block(&self, output_block_counter: u64) -> [u32; 16] {
+        compress(
+            &self.input_chaining_value,
+            &self.block_words,
+            output_block_counter,
+            self.block_len,
+            self.flags | ROOT,
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ChunkState {
+    chaining_value: [u32; 8],
+    chunk_counter: u64,
+    block: [u8; BLOCK_LEN],
+    block_len: usize,
+    blocks_compressed: usize,
+    flags: u32,
+}
+
+impl ChunkState {
+    fn new(key_words: [u32; 8], chunk_counter: u64, flags: u32) -> Self {
+        Self {
+            chaining_value: key_words,
+            chunk_counter,
+            block: [0; BLOCK_LEN],
+            block_len: 0,
+            blocks_compressed: 0,
+            flags,
+        }
+    }
+
+    fn len(&self) -> usize {
+        BLOCK_LEN * self.blocks_compressed + self.block_len
+    }
+
+    fn start_flag(&self) -> u32 {
+        if self.blocks_compressed == 0 {
+            CHUNK_START
+        } else {
+            0
+        }
+    }
+
+    fn update(&mut self, mut input: &[u8]) {
+        while !input.is_empty() {
+            // the last block of the chunk is compressed by `output`
+            if self.block_len == BLOCK_LEN {
+                self.chaining_value = first_8_words(compress(
+                    &self.chaining_value,
+                    &words(&self.block),
+                    self.chunk_counter,
+                    BLOCK_LEN as u32,
+                    self.flags | self.start_flag(),
+                ));
+                self.blocks_compressed += 1;
+                self.block = [0; BLOCK_LEN];
+                self.block_len = 0;
+            }
+            let take = (BLOCK_LEN - self.block_len).min(input.len());
+            self.block[self.block_len..self.block_len + take].copy_from_slice(&input[..take]);
+            self.block_len += take;
+            input = &input[take..];
+        }
+    }
+
+    fn output(&self) -> Output {
+        Output {
+            input_chaining_value: self.chaining_value,
+            block_words: words(&self.block),
+            counter: self.chunk_counter,
+            block_len: self.block_len as u32,
+            flags: self.flags | self.start_flag() | CHUNK_END,
+        }
+    }
+}
+
+fn parent_output(
+    left_child_cv: [u32; 8],
+    right_child_cv: [u32; 8],
+    key_words: [u32; 8],
+    flags: u32,
+) -> Output {
+    let mut block_words = [0; 16];
+    block_words[..8].copy_from_slice(&left_child_cv);
+    block_words[8..].copy_from_slice(&right_child_cv);
+    Output {
+        input_chaining_value: key_words,
+        block_words,
+        counter: 0,
+        block_len: BLOCK_LEN as u32,
+        flags: PARENT | flags,
+    }
+}
+
+/// Chaining value of a complete subtree of `input.len() / CHUNK_LEN` chunks,
+/// a power of two, hashing both halves in parallel
+#[cfg(feature = "rayon")]
+fn hash_subtree(input: &[u8], key_words: [u32; 8], chunk_counter: u64, flags: u32) -> [u32; 8] {
+    if input.len() == CHUNK_LEN {
+        let mut chunk = ChunkState::new(key_words, chunk_counter, flags);
+        chunk.update(input);
+        return chunk.output().chaining_value();
+    }
+    let (left, right) = input.split_at(input.len() / 2);
+    let right_counter = chunk_counter + (left.len() / CHUNK_LEN) as u64;
+    let (left_cv, right_cv) = rayon::join(
+        || hash_subtree(left, key_words, chunk_counter, flags),
+        || hash_subtree(right, key_words, right_counter, flags),
+    );
+    parent_output(left_cv, right_cv, key_words, flags).chaining_value()
+}
+
+/// BLAKE3 hash, keyed hash and key derivation
+///
+/// ```
+/// use algorithms::ciphers::Blake3;
+/// let mut blake = Blake3::new_default();
+/// blake.update(b"abc");
+/// assert_eq!(blake.get_hash()[..4], [0x64, 0x37, 0xb3, 0xac]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Blake3 {
+    chunk_state: ChunkState,
+    key_words: [u32; 8],
+    /// Chaining values of complete subtrees, merged lazily
+    cv_stack: Vec<[u32; 8]>,
+    flags: u32,
+}
+
+impl Blake3 {
+    fn new_internal(key_words: [u32; 8], flags: u32) -> Self {
+        Self {
+            chunk_state: ChunkState::new(key_words, 0, flags),
+            key_words,
+            cv_stack: Vec::new(),
+            flags,
+        }
+    }
+
+    /// Create a new instance for the default hash function
+    pub fn new_default() -> Self {
+        Self::new_internal(IV, 0)
+    }
+
+    /// Create a new instance for the keyed hash function
+    pub fn new_keyed(key: &[u8; 32]) -> Self {
+        Self::new_internal(words(key), KEYED_HASH)
+    }
+
+    /// Create a new instance for the key derivation function. The context
+    /// string should be hardcoded, globally unique and application specific.
+    pub fn new_derive_key(context: &str) -> Self {
+        let mut context_hasher = Self::new_internal(IV, DERIVE_KEY_CONTEXT);
+        context_hasher.update(context.as_bytes());
+        let context_key = context_hasher.get_hash();
+        Self::new_internal(words(&context_key), DERIVE_KEY_MATERIAL)
+    }
+
+    /// Merges subtrees until the stack holds one entry per set bit of
+    /// `total_chunks`, i.e. only subtrees that can not get a sibling anymore
+    fn merge_cv_stack(cv_stack: &mut Vec<[u32; 8]>, total_chunks: u64, key: [u32; 8], flags: u32) {
+        while cv_stack.len() > total_chunks.count_ones() as usize {
+            let right = cv_stack.pop().unwrap();
+            let left = cv_stack.pop().unwrap();
+            cv_stack.push(parent_output(left, right, key, flags).chaining_value());
+        }
+    }
+
+    /// Adds the chaining value of a complete subtree starting at chunk `chunk_counter`
+    fn push_cv(&mut self, cv: [u32; 8], chunk_counter: u64) {
+        Self::merge_cv_stack(
+            &mut self.cv_stack,
+            chunk_counter,
+            self.key_words,
+            self.flags,
+        );
+        self.cv_stack.push(cv);
+    }
+
+    /// Adds the chaining value of the current, full chunk and starts the next one
+    fn finish_chunk(&mut self) {
+        let chunk_cv = self.chunk_state.output().chaining_value();
+        let chunk_counter = self.chunk_state.chunk_counter;
+        self.push_cv(chunk_cv, chunk_counter);
+        self.chunk_state = ChunkState::new(self.key_words, chunk_counter + 1, self.flags);
+    }
+
+    /// Update the hash with the given data
+    pub fn update(&mut self, mut input: &[u8]) {
+        while !input.is_empty() {
+            // a full chunk is only finished once more input arrives, since
+            // the last chunk may be the root
+            if self.chunk_state.len() == CHUNK_LEN {
+                self.finish_chunk();
+            }
+            let take = (CHUNK_LEN - self.chunk_state.len()).min(input.len());
+            self.chunk_state.update(&input[..take]);
+            input = &input[take..];
+        }
+    }
+
+    /// Update the hash with the given data, hashing whole subtrees of chunks
+    /// in parallel. The result is the same as with [`Blake3::update`].
+    #[cfg(feature = "rayon")]
+    pub fn update_rayon(&mut self, mut input: &[u8]) {
+        while !input.is_empty() {
+            let pending = self.chunk_state.len();
+            if pending == CHUNK_LEN {
+                self.finish_chunk();
+            } else if pending > 0 || input.len() <= CHUNK_LEN {
+                let take = (CHUNK_LEN - pending).min(input.len());
+                self.chunk_state.update(&input[..take]);
+                input = &input[take..];
+            } else {
+                // the largest subtree that leaves some input for the last
+                // chunk and is aligned with the chunks hashed so far
+                let chunk_counter = self.chunk_state.chunk_counter;
+                let max_chunks = ((input.len() - 1) / CHUNK_LEN) as u64;
+                let mut chunks = 1 << (63 - max_chunks.leading_zeros());
+                while !chunk_counter.is_multiple_of(chunks) {
+                    chunks /= 2;
+                }
+                let len = chunks as usize * CHUNK_LEN;
+                let cv = hash_subtree(&input[..len], self.key_words, chunk_counter, self.flags);
+                self.push_cv(cv, chunk_counter);
+                self.chunk_state =
+                    ChunkState::new(self.key_words, chunk_counter + chunks, self.flags);
+                input = &input[len..];
+            }
+        }
+    }
+
+    fn final_output(&self) -> Output {
+        let mut cv_stack = self.cv_stack.clone();
+        Self::merge_cv_stack(
+            &mut cv_stack,
+            self.chunk_state.chunk_counter,
+            self.key_words,
+            self.flags,
+        );
+        let mut output = self.chunk_state.output();
+        while let Some(left) = cv_stack.pop() {
+            output = parent_output(left, output.chaining_value(), self.key_words, self.flags);
+        }
+        output
+    }
+
+    /// Returns the 32 byte hash of the data so far. This does not consume the
+    /// state, so more data can still be added afterwards.
+    pub fn get_hash(&self) -> [u8; OUT_LEN] {
+        let mut result = [0; OUT_LEN];
+        self.finalize_xof().read(&mut result);
+        result
+    }
+
+    /// Returns a reader for output of any length on the data so far
+    pub fn finalize_xof(&self) -> Blake3OutputReader {
+        Blake3OutputReader {
+            output: self.final_output(),
+            block_counter: 0,
+            position: 0,
+        }
+    }
+}
+
+impl Hasher<OUT_LEN> for Blake3 {
+    fn new_default() -> Self {
+        Blake3::new_default()
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.update(data);
+    }
+
+    fn get_hash(&mut self) -> [u8; OUT_LEN] {
+        Blake3::get_hash(self)
+    }
+}
+
+/// Reads the extended output of [`Blake3`], any amount at a time
+#[derive(Debug, Clone, Copy)]
+pub struct Blake3OutputReader {
+    output: Output,
+    block_counter: u64,
+    /// Position inside the current output block
+    position: usize,
+}
+
+impl Blake3OutputReader {
+    /// Fill `out` with the next `out.len()` bytes of output
+    pub fn read(&mut self, mut out: &mut [u8]) {
+        while !out.is_empty() {
+            let block = self.output.root_output_block(self.block_counter);
+            let bytes: Vec<u8> = block.iter().flat_map(|word| word.to_le_bytes()).collect();
+            let take = (BLOCK_LEN - self.position).min(out.len());
+            out[..take].copy_from_slice(&bytes[self.position..self.position + take]);
+            out = &mut out[take..];
+            self.position += take;
+            if self.position == BLOCK_LEN {
+                self.block_counter += 1;
+                self.position = 0;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_utils::get_hash_string;
+    use super::*;
+
+    const KEY: &[u8; 32] = b"whats the Elvish word for friend";
+    const CONTEXT: &str = "BLAKE3 2019-12-27 16:29:52 test vectors context";
+
+    /// The official test vectors use the input `0, 1, ..., 250, 0, 1, ...`
+    fn input(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 251) as u8).collect()
+    }
+
+    #[test]
+    fn known_hashes() {
+        assert_eq!(
+            get_hash_string(&Blake3::new_default().get_hash()),
+            "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262"
+        );
+        let mut blake = Blake3::new_default();
+        blake.update(b"abc");
+        assert_eq!(
+            get_hash_string(&blake.get_hash()),
+            "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85"
+        );
+    }
+
+    #[test]
+    fn official_vectors() {
+        // (input length, hash, last 8 bytes of the 131 byte extended hash,
+        // keyed hash, derived key)
+        let vectors = [
+            (
+                1,
+                "2d3adedff11b61f14c886e35afa036736dcd87a74d27b5c1510225d0f592e213",
+                "51f3f1da786545e5",
+                "6d7878dfff2f485635d39013278ae14f1454b8c0a3a2d34bc1ab38228a80c95b",
+                "b3e2e340a117a499c6cf2398a19ee0d29cca2bb7404c73063382693bf66cb06c",
+            ),
+            (
+                1023,
+                "10108970eeda3eb932baac1428c7a2163b0e924c9a9e25b35bba72b28f70bd11",
+                "9c8d822f2f28f485",
+                "c951ecdf03288d0fcc96ee3413563d8a6d3589547f2c2fb36d9786470f1b9d6e",
+                "74a16c1c3d44368a86e1ca6df64be6a2f64cce8f09220787450722d85725dea5",
+            ),
+            (
+                1024,
+                "42214739f095a406f3fc83deb889744ac00df831c10daa55189b5d121c855af7",
+                "441427ed9afa684e",
+                "75c46f6f3d9eb4f55ecaaee480db732e6c2105546f1e675003687c31719c7ba4",
+                "7356cd7720d5b66b6d0697eb3177d9f8d73a4a5c5e968896eb6a689684302706",
+            ),
+            (
+                1025,
+                "d00278ae47eb27b34faecf67b4fe263f82d5412916c1ffd97c8cb7fb814b8444",
+                "ae68ff12800ab67a",
+                "357dc55de0c7e382c900fd6e320acc04146be01db6a8ce7210b7189bd664ea69",
+                "effaa245f065fbf82ac186839a249707c3bddf6d3fdda22d1b95a3c970379bcb",
+            ),
+            (
+                3073,
+                "7124b49501012f81cc7f11ca069ec9226cecb8a2c850cfe644e327d22d3e1cd3",
+                "4ed72ea952b603bf",
+                "68dede9bef00ba89e43f31a6825f4cf433389fedae75c04ee9f0cf16a427c95a",
+                "72613c9ec9ff7e40f8f5c173784c532ad852e827dba2bf85b2ab4b76f7079081",
+            ),
+            (
+                8192,
+                "aae792484c8efe4f19e2ca7d371d8c467ffb10748d8a5a1ae579948f718a2a63",
+                "da2839ba71309bbf",
+                "dc9637c8845a770b4cbf76b8daec0eebf7dc2eac11498517f08d44c8fc00d58a",
+                "ad01d7ae4ad059b0d33baa3c01319dcf8088094d0359e5fd45d6aeaa8b2d0c3d",
+            ),
+            (
+                31744,
+                "62b6960e1a44bcc1eb1a611a8d6235b6b4b78f32e7abc4fb4c6cdcce94895c47",
+                "a8ec470f4013226f",
+                "efa53b389ab67c593dba624d898d0f7353ab99e4ac9d42302ee64cbf9939a419",
+                "39772aef80e0ebe60596361e45b061e8f417429d529171b6764468c22928e28e",
+            ),
+        ];
+        for (len, hash, extended_tail, keyed_hash, derived_key) in vectors {
+            let input = input(len);
+            let mut blake = Blake3::new_default();
+            blake.update(&input);
+            assert_eq!(get_hash_string(&blake.get_hash()), hash);
+            let mut extended = [0; 131];
+            blake.finalize_xof().read(&mut extended);
+            assert_eq!(get_hash_string(&extended[..32]), hash);
+            assert_eq!(get_hash_string(&extended[123..]), extended_tail);
+
+            let mut blake = Blake3::new_keyed(KEY);
+            blake.update(&input);
+            assert_eq!(get_hash_string(&blake.get_hash()), keyed_hash);
+
+            let mut blake = Blake3::new_derive_key(CONTEXT);
+            blake.update(&input);
+            assert_eq!(get_hash_string(&blake.get_hash()), derived_key);
+        }
+    }
+
+    #[test]
+    fn split_updates_and_reads() {
+        let input = input(5121);
+        let mut whole = Blake3::new_default();
+        whole.update(&input);
+        let mut pieces = Blake3::new_default();
+        for chunk in input.chunks(100) {
+            pieces.update(chunk);
+        }
+        assert_eq!(
+            get_hash_string(&pieces.get_hash()),
+            "628bd2cb2004694adaab7bbd778a25df25c47b9d4155a55f8fbd79f2fe154cff"
+        );
+        let mut extended = [0; 300];
+        whole.finalize_xof().read(&mut extended);
+        let mut reader = pieces.finalize_xof();
+        for chunk in extended.chunks(7) {
+            let mut out = vec![0; chunk.len()];
+            reader.read(&mut out);
+            assert_eq!(out, chunk);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "rayon")]
+    fn rayon_matches_sequential() {
+        let input = input(102400);
+        let mut blake = Blake3::new_default();
+        blake.update_rayon(&input);
+        assert_eq!(
+            get_hash_string(&blake.get_hash()),
+            "bc3e3d41a1146b069abffad3c0d44860cf664390afce4d9661f7902e7943e085"
+        );
+        // start in the middle of a chunk and at unaligned chunk counters
+        for split in [1, 1024, 3 * 1024, 5 * 1024 + 7] {
+            let mut sequential = Blake3::new_keyed(KEY);
+            sequential.update(&input);
+            let mut parallel = Blake3::new_keyed(KEY);
+            parallel.update(&input[..split]);
+            parallel.update_rayon(&input[split..50000]);
+            parallel.update_rayon(&input[50000..]);
+            assert_eq!(parallel.get_hash(), sequential.get_hash());
+        }
+    }
+}
@@ -0,0 +1,253 @@
+//! Block ciphers and their modes of operation
+//!
+//! The modes are based on [`NIST SP 800-38A`]. ECB encrypts every block
+//! independently, which leaks which blocks are equal, so it is only useful as a
+//! building block. CBC chains every block with the previous ciphertext block
+//! and pads the message with [`PKCS#7`], and CTR turns the block cipher into a
+//! stream cipher. Neither authenticates the data, see [`GCM`](super::GCM) for
+//! that.
+//!
+//! [`NIST SP 800-38A`]: https://nvlpubs.nist.gov/nistpubs/Legacy/SP/nistspecialpublication800-38a.pdf
+//! [`PKCS#7`]: https://www.rfc-editor.org/rfc/rfc5652#section-6.3
+
+/// [`BlockCipher`] is a trait that defines the interface of a block cipher,
+/// i.e. a keyed permutation of blocks of `BLOCK_BYTES` bytes
+pub trait BlockCipher<const BLOCK_BYTES: usize> {
+    /// Encrypt a single block in place
+    fn encrypt_block(&self, block: &mut [u8; BLOCK_BYTES]);
+
+    /// Decrypt a single block in place
+    fn decrypt_block(&self, block: &mut [u8; BLOCK_BYTES]);
+}
+
+fn blocks<const BLOCK_BYTES: usize>(
+    data: &mut [u8],
+) -> Result<impl Iterator<Item = &mut [u8; BLOCK_BYTES]>, &'static str> {
+    if !data.len().is_multiple_of(BLOCK_BYTES) {
+        return Err("Data is not a whole number of blocks");
+    }
+    Ok(data
+        .chunks_exact_mut(BLOCK_BYTES)
+        .map(|block| block.try_into().unwrap()))
+}
+
+/// Encrypt `data`, which must be a whole number of blocks, in ECB mode
+pub fn ecb_encrypt<const BLOCK_BYTES: usize, C: BlockCipher<BLOCK_BYTES>>(
+    cipher: &C,
+    data: &mut [u8],
+) -> Result<(), &'static str> {
+    blocks(data)?.for_each(|block| cipher.encrypt_block(block));
+    Ok(())
+}
+
+/// Decrypt `data`, which must be a whole number of blocks, in ECB mode
+pub fn ecb_decrypt<const BLOCK_BYTES: usize, C: BlockCipher<BLOCK_BYTES>>(
+    cipher: &C,
+    data: &mut [u8],
+) -> Result<(), &'static str> {
+    blocks(data)?.for_each(|block| cipher.decrypt_block(block));
+    Ok(())
+}
+
+/// Returns the encryption of `plaintext`, padded with PKCS#7, in CBC mode.
+/// The initialization vector `iv` must be unpredictable.
+pub fn cbc_encrypt<const BLOCK_BYTES: usize, C: BlockCipher<BLOCK_BYTES>>(
+    cipher: &C,
+    iv: &[u8; BLOCK_BYTES],
+    plaintext: &[u8],
+) -> Vec<u8> {
+    // at least one byte of padding is always added, so it can be removed
+    // unambiguously
+    let padding = BLOCK_BYTES - plaintext.len() % BLOCK_BYTES;
+    let mut result = plaintext.to_vec();
+    result.resize(plaintext.len() + padding, padding as u8);
+    let mut previous = *iv;
+    for block in blocks(&mut result).unwrap() {
+        for (b, p) in block.iter_mut().zip(previous) {
+            *b ^= p;
+        }
+        cipher.encrypt_block(block);
+        previous = *block;
+    }
+    result
+}
+
+/// Decrypt the output of [`cbc_encrypt`], checking and removing the padding.
+///
+/// Without authentication, revealing whether the padding was valid allows
+/// decrypting any message (a padding oracle attack), so the error must not
+/// reach an attacker.
+pub fn cbc_decrypt<const BLOCK_BYTES: usize, C: BlockCipher<BLOCK_BYTES>>(
+    cipher: &C,
+    iv: &[u8; BLOCK_BYTES],
+    ciphertext: &[u8],
+) -> Result<Vec<u8>, &'static str> {
+    if ciphertext.is_empty() {
+        return Err("Ciphertext is empty");
+    }
+    let mut result = ciphertext.to_vec();
+    let mut previous = *iv;
+    for block in blocks(&mut result)? {
+        let ciphertext_block = *block;
+        cipher.decrypt_block(block);
+        for (b, p) in block.iter_mut().zip(previous) {
+            *b ^= p;
+        }
+        previous = ciphertext_block;
+    }
+    let padding = result[result.len() - 1] as usize;
+    if padding == 0
+        || padding > BLOCK_BYTES
+        || result[result.len() - padding..]
+            .iter()
+            .any(|&b| b as usize != padding)
+    {
+        return Err("Invalid padding");
+    }
+    result.truncate(result.len() - padding);
+    Ok(result)
+}
+
+/// XOR `data` with the encryptions of the successive counter blocks, starting
+/// with `counter`, which is incremented by `increment` after every block
+pub(crate) fn ctr_with<const BLOCK_BYTES: usize, C: BlockCipher<BLOCK_BYTES>>(
+    cipher: &C,
+    counter: &[u8; BLOCK_BYTES],
+    data: &mut [u8],
+    increment: impl Fn(&mut [u8; BLOCK_BYTES]),
+) {
+    let mut counter = *counter;
+    for chunk in data.chunks_mut(BLOCK_BYTES) {
+        let mut keystream = counter;
+        cipher.encrypt_block(&mut keystream);
+        for (b, k) in chunk.iter_mut().zip(keystream) {
+            *b ^= k;
+        }
+        increment(&mut counter);
+    }
+}
+
+/// Encrypt or decrypt `data` in CTR mode, starting with the counter block
+/// `counter`, which is incremented as a big endian integer. A counter block
+/// must never be used twice with the same key.
+pub fn ctr<const BLOCK_BYTES: usize, C: BlockCipher<BLOCK_BYTES>>(
+    cipher: &C,
+    counter: &[u8; BLOCK_BYTES],
+    data: &mut [u8],
+) {
+    ctr_with(cipher, counter, data, |counter| {
+        for byte in counter.iter_mut().rev() {
+            *byte = byte.wrapping_add(1);
+            if *byte != 0 {
+                break;
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_utils::{from_hex, get_hash_string};
+    use super::super::AES;
+    use super::*;
+
+    /// The plaintext of the NIST SP 800-38A examples
+    const PLAINTEXT: &str = "6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e5130c81c46a35ce411e5fbc1191a0a52eff69f2445df4f9b17ad2b417be66c3710";
+
+    const KEYS: [&str; 3] = [
+        "2b7e151628aed2a6abf7158809cf4f3c",
+        "8e73b0f7da0e6452c810f32b809079e562f8ead2522c6b7b",
+        "603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4",
+    ];
+
+    const IV: [u8; 16] = [
+        0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e,
+        0x0f,
+    ];
+
+    #[test]
+    fn sp800_38a_ecb() {
+        for (key, expected) in KEYS.into_iter().zip([
+            "3ad77bb40d7a3660a89ecaf32466ef97f5d3d58503b9699de785895a96fdbaaf43b1cd7f598ece23881b00e3ed0306887b0c785e27e8ad3f8223207104725dd4",
+            "bd334f1d6e45f25ff712a214571fa5cc974104846d0ad3ad7734ecb3ecee4eefef7afd2270e2e60adce0ba2face6444e9a4b41ba738d6c72fb16691603c18e0e",
+            "f3eed1bdb5d2a03c064b5a7e3db181f8591ccb10d410ed26dc5ba74a31362870b6ed21b99ca6f4f9f153e7b1beafed1d23304b7a39f9f3ff067d8d8f9e24ecc7",
+        ]) {
+            let aes = AES::new(&from_hex(key)).unwrap();
+            let mut data = from_hex(PLAINTEXT);
+            ecb_encrypt(&aes, &mut data).unwrap();
+            assert_eq!(get_hash_string(&data), expected);
+            ecb_decrypt(&aes, &mut data).unwrap();
+            assert_eq!(get_hash_string(&data), PLAINTEXT);
+            assert!(ecb_encrypt(&aes, &mut data[1..]).is_err());
+        }
+    }
+
+    #[test]
+    fn sp800_38a_cbc() {
+        for (key, expected) in KEYS.into_iter().zip([
+            "7649abac8119b246cee98e9b12e9197d5086cb9b507219ee95db113a917678b273bed6b8e3c1743b7116e69e222295163ff1caa1681fac09120eca307586e1a7",
+            "4f021db243bc633d7178183a9fa071e8b4d9ada9ad7dedf4e5e738763f69145a571b242012fb7ae07fa9baac3df102e008b0e27988598881d920a9e64f5615cd",
+            "f58c4c04d6e5f1ba779eabfb5f7bfbd69cfc4e967edb808d679f777bc6702c7d39f23369a9d9bacfa530e26304231461b2eb05e2c39be9fcda6c19078c6a9d1b",
+        ]) {
+            let aes = AES::new(&from_hex(key)).unwrap();
+            let plaintext = from_hex(PLAINTEXT);
+            let ciphertext = cbc_encrypt(&aes, &IV, &plaintext);
+            // the NIST examples have no padding, which adds a whole block here
+            assert_eq!(ciphertext.len(), 80);
+            assert_eq!(get_hash_string(&ciphertext[..64]), expected);
+            assert_eq!(cbc_decrypt(&aes, &IV, &ciphertext).unwrap(), plaintext);
+        }
+    }
+
+    #[test]
+    fn cbc_padding() {
+        let aes = AES::new(&from_hex(KEYS[0])).unwrap();
+        let message = b"The quick brown fox jumps over the lazy dog";
+        let ciphertext = cbc_encrypt(&aes, &IV, message);
+        assert_eq!(
+            get_hash_string(&ciphertext),
+            "bd13204f67d8167f20211c99b0a7cc0506d5c703eafb01a7d0473b5cc999aaa24dc316ca580592ee0001df0bdbf4d33a"
+        );
+        assert_eq!(cbc_decrypt(&aes, &IV, &ciphertext).unwrap(), message);
+        for len in 0..=32 {
+            let message = vec![0xab; len];
+            let ciphertext = cbc_encrypt(&aes, &IV, &message);
+            assert_eq!(ciphertext.len(), (len / 16 + 1) * 16);
+            assert_eq!(cbc_decrypt(&aes, &IV, &ciphertext).unwrap(), message);
+        }
+        assert!(cbc_decrypt(&aes, &IV, &[]).is_err());
+        assert!(cbc_decrypt(&aes, &IV, &ciphertext[..47]).is_err());
+        // flipping a bit of the second to last block garbles the padding
+        let mut tampered = ciphertext.clone();
+        tampered[31] ^= 0x01;
+        assert!(cbc_decrypt(&aes, &IV, &tampered).is_err());
+    }
+
+    #[test]
+    fn sp800_38a_ctr() {
+        let counter: [u8; 16] = std::array::from_fn(|i| 0xf0 + i as u8);
+        for (key, expected) in KEYS.into_iter().zip([
+            "874d6191b620e3261bef6864990db6ce9806f66b7970fdff8617187bb9fffdff5ae4df3edbd5d35e5b4f09020db03eab1e031dda2fbe03d1792170a0f3009cee",
+            "1abc932417521ca24f2b0459fe7e6e0b090339ec0aa6faefd5ccc2c6f4ce8e941e36b26bd1ebc670d1bd1d665620abf74f78a7f6d29809585a97daec58c6b050",
+            "601ec313775789a5b7a7f504bbf3d228f443e3ca4d62b59aca84e990cacaf5c52b0930daa23de94ce87017ba2d84988ddfc9c58db67aada613c2dd08457941a6",
+        ]) {
+            let aes = AES::new(&from_hex(key)).unwrap();
+            let mut data = from_hex(PLAINTEXT);
+            ctr(&aes, &counter, &mut data);
+            assert_eq!(get_hash_string(&data), expected);
+            ctr(&aes, &counter, &mut data);
+            assert_eq!(get_hash_string(&data), PLAINTEXT);
+        }
+    }
+
+    #[test]
+    fn ctr_wraps_around() {
+        let aes = AES::new(&from_hex(KEYS[0])).unwrap();
+        let mut data = [0; 40];
+        ctr(&aes, &[0xff; 16], &mut data);
+        assert_eq!(
+            get_hash_string(&data),
+            "8af2860142f786f409307c1a3f7eaaac7df76b0c1ab899b33e42f047b91b546f57127d4034b1bebf"
+        );
+    }
+}
@@ -6,22 +6,54 @@
 //! Rotate each ascii character by shift. The most basic example is ROT 13, which rotates 'a' to
 //! 'n'. This implementation does not rotate unicode characters.
 
+use super::ClassicalCipher;
+
+/// Rotate `byte` by `shift` places if it is an ASCII letter, keeping its case
+pub(crate) fn rotate_letter(byte: u8, shift: u8) -> u8 {
+    // reduce first, so the sum cannot overflow
+    let shift = shift % 26;
+    match byte {
+        b'A'..=b'Z' => b'A' + (byte - b'A' + shift) % 26,
+        b'a'..=b'z' => b'a' + (byte - b'a' + shift) % 26,
+        _ => byte,
+    }
+}
+
+/// Caesar cipher, which rotates ASCII letters by a fixed shift
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Caesar {
+    shift: u8,
+}
+
+impl ClassicalCipher for Caesar {
+    /// The shift, which is used modulo 26
+    type Key = u8;
+
+    fn new(shift: u8) -> Result<Self, &'static str> {
+        Ok(Self { shift: shift % 26 })
+    }
+
+    fn encrypt_bytes(&self, data: &[u8]) -> Vec<u8> {
+        data.iter().map(|&b| rotate_letter(b, self.shift)).collect()
+    }
+
+    fn decrypt_bytes(&self, data: &[u8]) -> Vec<u8> {
+        data.iter()
+            .map(|&b| rotate_letter(b, 26 - self.shift))
+            .collect()
+    }
+}
+
 /// Caesar cipher to rotate cipher text by shift and return an owned String.
 pub fn caesar(cipher: impl AsRef<str>, shift: u8) -> String {
-    cipher
-        .as_ref()
-        .chars()
-        .map(|c| match c {
-            'A'..='Z' => char::from(b'A' + (c as u8 + shift - b'A') % 26),
-            'a'..='z' => char::from(b'a' + (c as u8 + shift - b'a') % 26),
-            _ => c,
-        })
-        .collect()
+    Caesar::new(shift).unwrap().encrypt(cipher.as_ref())
 }
 
 #[cfg(test)]
 mod tests {
+    use super::super::classical_cipher::tests::round_trips;
     use super::*;
+    use quickcheck_macros::quickcheck;
 
     #[test]
     fn empty() {
@@ -37,4 +69,20 @@ mod tests {
     fn caesar_unicode() {
         assert_eq!(caesar("attack at dawn 攻", 5), "fyyfhp fy ifbs 攻");
     }
+
+    #[test]
+    fn large_shift() {
+        assert_eq!(caesar("xyz XYZ", 255), "stu STU");
+        assert_eq!(caesar("abc", 26), "abc");
+        let cipher = Caesar::new(200).unwrap();
+        assert_eq!(
+            cipher.decrypt(&cipher.encrypt("Hello, World!")),
+            "Hello, World!"
+        );
+    }
+
+    #[quickcheck]
+    fn round_trip(shift: u8, text: String, bytes: Vec<u8>) -> bool {
+        round_trips(&Caesar::new(shift).unwrap(), &text, &bytes)
+    }
 }
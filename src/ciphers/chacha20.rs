@@ -0,0 +1,237 @@
+//! ChaCha20 and XChaCha20 stream ciphers
+//!
+//! This implementation is based on [`RFC8439`]. The ChaCha20 block function
+//! turns a 256-bit key, a 32-bit block counter and a 96-bit nonce into 64
+//! bytes of keystream, which is XORed with the data. XChaCha20, specified in
+//! [`draft-irtf-cfrg-xchacha`], first derives a subkey from the key and the
+//! first 128 bits of a 192-bit nonce with HChaCha20, so nonces are long enough
+//! to be chosen at random.
+//!
+//! [`RFC8439`]: https://www.rfc-editor.org/rfc/rfc8439
+//! [`draft-irtf-cfrg-xchacha`]: https://datatracker.ietf.org/doc/html/draft-irtf-cfrg-xchacha
+
+/// "expand 32-byte k"
+const SIGMA: [u32; 4] = [0x61707865, 0x3320646e, 0x79622d32, 0x6b206574];
+
+#[inline]
+fn quarter_round(x: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    x[a] = x[a].wrapping_add(x[b]);
+    x[d] = (x[d] ^ x[a]).rotate_left(16);
+    x[c] = x[c].wrapping_add(x[d]);
+    x[b] = (x[b] ^ x[c]).rotate_left(12);
+    x[a] = x[a].wrapping_add(x[b]);
+    x[d] = (x[d] ^ x[a]).rotate_left(8);
+    x[c] = x[c].wrapping_add(x[d]);
+    x[b] = (x[b] ^ x[c]).rotate_left(7);
+}
+
+/// The 20 rounds of ChaCha20 on the state built from `key` and the last four
+/// words, without the final addition of the input
+fn rounds(key: &[u32; 8], tail: &[u32; 4]) -> ([u32; 16], [u32; 16]) {
+    let mut input = [0u32; 16];
+    input[..4].copy_from_slice(&SIGMA);
+    input[4..12].copy_from_slice(key);
+    input[12..].copy_from_slice(tail);
+    let mut x = input;
+    for _ in 0..10 {
+        quarter_round(&mut x, 0, 4, 8, 12);
+        quarter_round(&mut x, 1, 5, 9, 13);
+        quarter_round(&mut x, 2, 6, 10, 14);
+        quarter_round(&mut x, 3, 7, 11, 15);
+        quarter_round(&mut x, 0, 5, 10, 15);
+        quarter_round(&mut x, 1, 6, 11, 12);
+        quarter_round(&mut x, 2, 7, 8, 13);
+        quarter_round(&mut x, 3, 4, 9, 14);
+    }
+    (input, x)
+}
+
+/// The ChaCha20 block function. `tail` holds the last four words of the
+/// input, i.e. the block counter and the nonce.
+pub(crate) fn chacha20_block(key: &[u32; 8], tail: &[u32; 4]) -> [u32; 16] {
+    let (input, mut x) = rounds(key, tail);
+    for (o, i) in x.iter_mut().zip(input.iter()) {
+        *o = o.wrapping_add(*i);
+    }
+    x
+}
+
+fn words<const N: usize>(bytes: &[u8]) -> [u32; N] {
+    let mut result = [0u32; N];
+    for (word, chunk) in result.iter_mut().zip(bytes.chunks_exact(4)) {
+        *word = u32::from_le_bytes(chunk.try_into().unwrap());
+    }
+    result
+}
+
+/// HChaCha20, which derives a subkey from `key` and a 128-bit nonce
+pub fn hchacha20(key: &[u8; 32], nonce: &[u8; 16]) -> [u8; 32] {
+    let (_, x) = rounds(&words(key), &words(nonce));
+    let mut result = [0u8; 32];
+    for (bytes, word) in result
+        .chunks_exact_mut(4)
+        .zip(x[..4].iter().chain(&x[12..]))
+    {
+        bytes.copy_from_slice(&word.to_le_bytes());
+    }
+    result
+}
+
+/// ChaCha20 stream cipher
+///
+/// This implementation is based on [`RFC8439`]. Encryption and decryption are
+/// the same operation, see [`ChaCha20::apply_keystream`].
+///
+/// [`RFC8439`]: https://www.rfc-editor.org/rfc/rfc8439
+#[derive(Debug, Clone, Copy)]
+pub struct ChaCha20 {
+    key: [u32; 8],
+    nonce: [u32; 3],
+    /// The next block, which may be one past the last one
+    counter: u64,
+    keystream: [u8; 64],
+    /// Position in `keystream`, which is used up when it is 64
+    position: usize,
+}
+
+impl ChaCha20 {
+    /// Create a new instance, whose keystream starts at block `counter`
+    pub fn new(key: &[u8; 32], nonce: &[u8; 12], counter: u32) -> Self {
+        Self {
+            key: words(key),
+            nonce: words(nonce),
+            counter: counter.into(),
+            keystream: [0; 64],
+            position: 64,
+        }
+    }
+
+    /// XOR `data` with the next bytes of the keystream.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the block counter overflows, i.e. after 256 GiB of keystream.
+    pub fn apply_keystream(&mut self, data: &mut [u8]) {
+        for byte in data {
+            if self.position == 64 {
+                let counter = u32::try_from(self.counter).expect("ChaCha20 block counter overflow");
+                let [n0, n1, n2] = self.nonce;
+                let block = chacha20_block(&self.key, &[counter, n0, n1, n2]);
+                for (bytes, word) in self.keystream.chunks_exact_mut(4).zip(block) {
+                    bytes.copy_from_slice(&word.to_le_bytes());
+                }
+                self.counter += 1;
+                self.position = 0;
+            }
+            *byte ^= self.keystream[self.position];
+            self.position += 1;
+        }
+    }
+}
+
+/// XChaCha20 stream cipher, ChaCha20 with a 192-bit nonce
+///
+/// This implementation is based on [`draft-irtf-cfrg-xchacha`].
+///
+/// [`draft-irtf-cfrg-xchacha`]: https://datatracker.ietf.org/doc/html/draft-irtf-cfrg-xchacha
+#[derive(Debug, Clone, Copy)]
+pub struct XChaCha20(ChaCha20);
+
+impl XChaCha20 {
+    /// Create a new instance, whose keystream starts at block `counter`
+    pub fn new(key: &[u8; 32], nonce: &[u8; 24], counter: u32) -> Self {
+        let subkey = hchacha20(key, nonce[..16].try_into().unwrap());
+        let mut chacha_nonce = [0u8; 12];
+        chacha_nonce[4..].copy_from_slice(&nonce[16..]);
+        Self(ChaCha20::new(&subkey, &chacha_nonce, counter))
+    }
+
+    /// XOR `data` with the next bytes of the keystream.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the block counter overflows, i.e. after 256 GiB of keystream.
+    pub fn apply_keystream(&mut self, data: &mut [u8]) {
+        self.0.apply_keystream(data);
+    }
+
+    /// The underlying ChaCha20 instance
+    pub(crate) fn into_inner(self) -> ChaCha20 {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_utils::get_hash_string;
+    use super::*;
+
+    const SUNSCREEN: &[u8] = b"Ladies and Gentlemen of the class of '99: If I could offer you \
+        only one tip for the future, sunscreen would be it.";
+
+    #[test]
+    fn rfc8439_block_function() {
+        // RFC8439 2.3.2
+        let key: [u8; 32] = std::array::from_fn(|i| i as u8);
+        let nonce = [0, 0, 0, 0x09, 0, 0, 0, 0x4a, 0, 0, 0, 0];
+        let mut cipher = ChaCha20::new(&key, &nonce, 1);
+        let mut block = [0u8; 64];
+        cipher.apply_keystream(&mut block);
+        assert_eq!(
+            get_hash_string(&block),
+            "10f1e7e4d13b5915500fdd1fa32071c4c7d1f4c733c068030422aa9ac3d46c4ed2826446079faa0914c2d705d98b02a2b5129cd1de164eb9cbd083e8a2503c4e"
+        );
+    }
+
+    #[test]
+    fn rfc8439_encryption() {
+        // RFC8439 2.4.2
+        let key: [u8; 32] = std::array::from_fn(|i| i as u8);
+        let nonce = [0, 0, 0, 0, 0, 0, 0, 0x4a, 0, 0, 0, 0];
+        let mut data = SUNSCREEN.to_vec();
+        ChaCha20::new(&key, &nonce, 1).apply_keystream(&mut data);
+        assert_eq!(
+            get_hash_string(&data),
+            "6e2e359a2568f98041ba0728dd0d6981e97e7aec1d4360c20a27afccfd9fae0bf91b65c5524733ab8f593dabcd62b3571639d624e65152ab8f530c359f0861d807ca0dbf500d6a6156a38e088a22b65e52bc514d16ccf806818ce91ab77937365af90bbf74a35be6b40b8eedf2785e42874d"
+        );
+        // applying the keystream in pieces gives the same result, and decrypts
+        let mut cipher = ChaCha20::new(&key, &nonce, 1);
+        for chunk in data.chunks_mut(7) {
+            cipher.apply_keystream(chunk);
+        }
+        assert_eq!(data, SUNSCREEN);
+    }
+
+    #[test]
+    fn hchacha20_vector() {
+        // draft-irtf-cfrg-xchacha 2.2.1
+        let key: [u8; 32] = std::array::from_fn(|i| i as u8);
+        let nonce = [
+            0, 0, 0, 0x09, 0, 0, 0, 0x4a, 0, 0, 0, 0, 0x31, 0x41, 0x59, 0x27,
+        ];
+        assert_eq!(
+            get_hash_string(&hchacha20(&key, &nonce)),
+            "82413b4227b27bfed30e42508a877d73a0f9e4d58a74a853c12ec41326d3ecdc"
+        );
+    }
+
+    #[test]
+    fn xchacha20_keystream() {
+        let key: [u8; 32] = std::array::from_fn(|i| 0x80 + i as u8);
+        let nonce: [u8; 24] = std::array::from_fn(|i| 0x40 + i as u8);
+        let mut keystream = [0u8; 32];
+        XChaCha20::new(&key, &nonce, 0).apply_keystream(&mut keystream);
+        assert_eq!(
+            get_hash_string(&keystream),
+            "7b191f80f361f099094f6f4b8fb97df847cc6873a8f2b190dd73807183f907d5"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "counter overflow")]
+    fn counter_overflow() {
+        let mut cipher = ChaCha20::new(&[0; 32], &[0; 12], u32::MAX);
+        cipher.apply_keystream(&mut [0; 64]);
+        cipher.apply_keystream(&mut [0; 1]);
+    }
+}
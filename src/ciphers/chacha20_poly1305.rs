@@ -0,0 +1,244 @@
+//! ChaCha20-Poly1305 authenticated encryption with associated data
+//!
+//! This implementation is based on [`RFC8439`]. The plaintext is encrypted
+//! with [`ChaCha20`] starting at block 1, and block 0 of the keystream is the
+//! one-time key of a [`Poly1305`] MAC over the associated data and the
+//! ciphertext. XChaCha20-Poly1305 is the same construction on top of
+//! [`XChaCha20`].
+//!
+//! Never encrypt two messages with the same key and nonce: this reveals the
+//! XOR of the plaintexts and allows forging tags.
+//!
+//! [`RFC8439`]: https://www.rfc-editor.org/rfc/rfc8439#section-2.8
+
+use super::chacha20::{ChaCha20, XChaCha20};
+use super::hashing_traits::constant_time_eq;
+use super::Poly1305;
+
+/// Length of the authentication tag in bytes
+const TAG_BYTES: usize = 16;
+
+/// Returns the Poly1305 key, the first 32 bytes of block 0. The cipher is
+/// left at block 1.
+fn poly1305_key(cipher: &mut ChaCha20) -> [u8; 32] {
+    let mut block = [0u8; 64];
+    cipher.apply_keystream(&mut block);
+    block[..32].try_into().unwrap()
+}
+
+/// The Poly1305 tag of the associated data and the ciphertext, each padded
+/// with zeros to a multiple of 16 bytes, followed by their lengths
+fn compute_tag(key: &[u8; 32], associated_data: &[u8], ciphertext: &[u8]) -> [u8; TAG_BYTES] {
+    let mut mac = Poly1305::new(key);
+    for data in [associated_data, ciphertext] {
+        mac.update(data);
+        mac.update(&[0; 16][..(16 - data.len() % 16) % 16]);
+    }
+    mac.update(&(associated_data.len() as u64).to_le_bytes());
+    mac.update(&(ciphertext.len() as u64).to_le_bytes());
+    mac.finalize()
+}
+
+fn seal_in_place(mut cipher: ChaCha20, associated_data: &[u8], buffer: &mut [u8]) -> [u8; 16] {
+    let key = poly1305_key(&mut cipher);
+    cipher.apply_keystream(buffer);
+    compute_tag(&key, associated_data, buffer)
+}
+
+fn open_in_place(
+    mut cipher: ChaCha20,
+    associated_data: &[u8],
+    buffer: &mut [u8],
+    tag: &[u8; 16],
+) -> Result<(), &'static str> {
+    let key = poly1305_key(&mut cipher);
+    // the ciphertext is only decrypted once it is authenticated
+    if !constant_time_eq(&compute_tag(&key, associated_data, buffer), tag) {
+        return Err("Authentication failed");
+    }
+    cipher.apply_keystream(buffer);
+    Ok(())
+}
+
+macro_rules! aead {
+    (
+        $(#[$meta:meta])*
+        $name:ident,
+        nonce: $nonce:literal,
+        $cipher:expr
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy)]
+        pub struct $name {
+            key: [u8; 32],
+        }
+
+        impl $name {
+            /// Create a new instance with the given key
+            pub fn new(key: &[u8; 32]) -> Self {
+                Self { key: *key }
+            }
+
+            /// Encrypt `buffer` in place, returning the tag authenticating it
+            /// together with `associated_data`
+            pub fn seal_in_place_detached(
+                &self,
+                nonce: &[u8; $nonce],
+                associated_data: &[u8],
+                buffer: &mut [u8],
+            ) -> [u8; 16] {
+                seal_in_place($cipher(&self.key, nonce), associated_data, buffer)
+            }
+
+            /// Check `tag` against `buffer` and `associated_data`, and decrypt
+            /// `buffer` in place if it is authentic. Otherwise `buffer` is left
+            /// as it is.
+            pub fn open_in_place_detached(
+                &self,
+                nonce: &[u8; $nonce],
+                associated_data: &[u8],
+                buffer: &mut [u8],
+                tag: &[u8; 16],
+            ) -> Result<(), &'static str> {
+                open_in_place($cipher(&self.key, nonce), associated_data, buffer, tag)
+            }
+
+            /// Returns the encryption of `plaintext` followed by the tag
+            /// authenticating it together with `associated_data`
+            pub fn seal(
+                &self,
+                nonce: &[u8; $nonce],
+                associated_data: &[u8],
+                plaintext: &[u8],
+            ) -> Vec<u8> {
+                let mut result = plaintext.to_vec();
+                let tag = self.seal_in_place_detached(nonce, associated_data, &mut result);
+                result.extend_from_slice(&tag);
+                result
+            }
+
+            /// Decrypt the output of [`seal`](Self::seal), if it is authentic
+            pub fn open(
+                &self,
+                nonce: &[u8; $nonce],
+                associated_data: &[u8],
+                ciphertext: &[u8],
+            ) -> Result<Vec<u8>, &'static str> {
+                if ciphertext.len() < TAG_BYTES {
+                    return Err("Ciphertext is shorter than the tag");
+                }
+                let (ciphertext, tag) = ciphertext.split_at(ciphertext.len() - TAG_BYTES);
+                let mut result = ciphertext.to_vec();
+                self.open_in_place_detached(
+                    nonce,
+                    associated_data,
+                    &mut result,
+                    tag.try_into().unwrap(),
+                )?;
+                Ok(result)
+            }
+        }
+    };
+}
+
+aead!(
+    /// ChaCha20-Poly1305 AEAD with a 96-bit nonce, see the
+    /// [module documentation](self)
+    ChaCha20Poly1305,
+    nonce: 12,
+    |key, nonce| ChaCha20::new(key, nonce, 0)
+);
+
+aead!(
+    /// XChaCha20-Poly1305 AEAD with a 192-bit nonce, which is long enough to
+    /// be chosen at random
+    XChaCha20Poly1305,
+    nonce: 24,
+    |key, nonce| XChaCha20::new(key, nonce, 0).into_inner()
+);
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_utils::get_hash_string;
+    use super::*;
+
+    const SUNSCREEN: &[u8] = b"Ladies and Gentlemen of the class of '99: If I could offer you \
+        only one tip for the future, sunscreen would be it.";
+    const AAD: [u8; 12] = [
+        0x50, 0x51, 0x52, 0x53, 0xc0, 0xc1, 0xc2, 0xc3, 0xc4, 0xc5, 0xc6, 0xc7,
+    ];
+
+    fn key() -> [u8; 32] {
+        std::array::from_fn(|i| 0x80 + i as u8)
+    }
+
+    #[test]
+    fn rfc8439_aead() {
+        // RFC8439 2.8.2
+        let aead = ChaCha20Poly1305::new(&key());
+        let nonce = [
+            0x07, 0, 0, 0, 0x40, 0x41, 0x42, 0x43, 0x44, 0x45, 0x46, 0x47,
+        ];
+        let sealed = aead.seal(&nonce, &AAD, SUNSCREEN);
+        let (ciphertext, tag) = sealed.split_at(SUNSCREEN.len());
+        assert_eq!(
+            get_hash_string(ciphertext),
+            "d31a8d34648e60db7b86afbc53ef7ec2a4aded51296e08fea9e2b5a736ee62d63dbea45e8ca9671282fafb69da92728b1a71de0a9e060b2905d6a5b67ecd3b3692ddbd7f2d778b8c9803aee328091b58fab324e4fad675945585808b4831d7bc3ff4def08e4b7a9de576d26586cec64b6116"
+        );
+        assert_eq!(get_hash_string(tag), "1ae10b594f09e26a7e902ecbd0600691");
+        assert_eq!(aead.open(&nonce, &AAD, &sealed).unwrap(), SUNSCREEN);
+    }
+
+    #[test]
+    fn xchacha20_poly1305() {
+        // draft-irtf-cfrg-xchacha A.3.1
+        let aead = XChaCha20Poly1305::new(&key());
+        let nonce: [u8; 24] = std::array::from_fn(|i| 0x40 + i as u8);
+        let mut buffer = SUNSCREEN.to_vec();
+        let tag = aead.seal_in_place_detached(&nonce, &AAD, &mut buffer);
+        assert_eq!(
+            get_hash_string(&buffer),
+            "bd6d179d3e83d43b9576579493c0e939572a1700252bfaccbed2902c21396cbb731c7f1b0b4aa6440bf3a82f4eda7e39ae64c6708c54c216cb96b72e1213b4522f8c9ba40db5d945b11b69b982c1bb9e3f3fac2bc369488f76b2383565d3fff921f9664c97637da9768812f615c68b13b52e"
+        );
+        assert_eq!(get_hash_string(&tag), "c0875924c1c7987947deafd8780acf49");
+        aead.open_in_place_detached(&nonce, &AAD, &mut buffer, &tag)
+            .unwrap();
+        assert_eq!(buffer, SUNSCREEN);
+    }
+
+    #[test]
+    fn empty_message() {
+        let aead = ChaCha20Poly1305::new(&key());
+        let sealed = aead.seal(&[0; 12], &[], &[]);
+        assert_eq!(get_hash_string(&sealed), "3ae5d3f2a376d317eaea5aef0215ba54");
+        assert_eq!(aead.open(&[0; 12], &[], &sealed).unwrap(), []);
+        assert!(aead.open(&[0; 12], &[], &sealed[1..]).is_err());
+    }
+
+    #[test]
+    fn tampering() {
+        let aead = ChaCha20Poly1305::new(&key());
+        let nonce = [1; 12];
+        let sealed = aead.seal(&nonce, &AAD, SUNSCREEN);
+        for i in [0, 50, sealed.len() - 1] {
+            let mut tampered = sealed.clone();
+            tampered[i] ^= 0x20;
+            assert!(aead.open(&nonce, &AAD, &tampered).is_err());
+        }
+        assert!(aead.open(&nonce, b"other data", &sealed).is_err());
+        assert!(aead.open(&[2; 12], &AAD, &sealed).is_err());
+        assert!(ChaCha20Poly1305::new(&[0; 32])
+            .open(&nonce, &AAD, &sealed)
+            .is_err());
+        // a failed check leaves the buffer encrypted
+        let mut buffer = SUNSCREEN.to_vec();
+        let tag = aead.seal_in_place_detached(&nonce, &AAD, &mut buffer);
+        let encrypted = buffer.clone();
+        let mut wrong = tag;
+        wrong[0] ^= 1;
+        assert!(aead
+            .open_in_place_detached(&nonce, &AAD, &mut buffer, &wrong)
+            .is_err());
+        assert_eq!(buffer, encrypted);
+    }
+}
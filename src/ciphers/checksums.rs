@@ -0,0 +1,236 @@
+//! Non-cryptographic checksums
+//!
+//! These detect accidental corruption cheaply, but unlike a [`Hasher`] they
+//! offer no protection against deliberate changes.
+//!
+//! [`Hasher`]: super::Hasher
+
+/// [`Checksum`] is a trait that defines the interface of a non-cryptographic
+/// checksum
+pub trait Checksum {
+    /// The type of the checksum value
+    type Output;
+
+    /// Create a new instance with default parameters
+    fn new_default() -> Self;
+
+    /// Add new data
+    fn update(&mut self, data: &[u8]);
+
+    /// Returns the checksum of the data so far
+    fn get_checksum(&self) -> Self::Output;
+}
+
+/// Defines a reflected CRC (least significant bit first), which is how CRC-32,
+/// CRC-32C and CRC-64/XZ are specified, with an initial value and final XOR of
+/// all ones
+macro_rules! crc {
+    ($(#[$meta:meta])* $name:ident, $word:ty, $poly:literal) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy)]
+        pub struct $name {
+            crc: $word,
+        }
+
+        impl $name {
+            /// Reversed generator polynomial
+            pub const POLYNOMIAL: $word = $poly;
+
+            /// `TABLES[k][b]` is the CRC of the byte `b` followed by `k` zero
+            /// bytes, which allows processing 8 bytes at a time ("slicing-by-8")
+            const TABLES: [[$word; 256]; 8] = {
+                let mut tables = [[0; 256]; 8];
+                let mut b = 0;
+                while b < 256 {
+                    let mut crc = b as $word;
+                    let mut bit = 0;
+                    while bit < 8 {
+                        crc = if crc & 1 == 1 { (crc >> 1) ^ $poly } else { crc >> 1 };
+                        bit += 1;
+                    }
+                    tables[0][b] = crc;
+                    b += 1;
+                }
+                let mut k = 1;
+                while k < 8 {
+                    let mut b = 0;
+                    while b < 256 {
+                        let previous = tables[k - 1][b];
+                        tables[k][b] = (previous >> 8) ^ tables[0][(previous & 0xff) as usize];
+                        b += 1;
+                    }
+                    k += 1;
+                }
+                tables
+            };
+
+            #[doc = concat!("Create a new ", stringify!($name), " instance")]
+            pub fn new_default() -> Self {
+                Self { crc: <$word>::MAX }
+            }
+
+            /// Update the checksum with the given data
+            pub fn update(&mut self, data: &[u8]) {
+                let tables = &Self::TABLES;
+                let mut chunks = data.chunks_exact(8);
+                for chunk in &mut chunks {
+                    let x = u64::from(self.crc) ^ u64::from_le_bytes(chunk.try_into().unwrap());
+                    let mut crc = 0;
+                    for (i, table) in tables.iter().rev().enumerate() {
+                        crc ^= table[((x >> (8 * i)) & 0xff) as usize];
+                    }
+                    self.crc = crc;
+                }
+                for &byte in chunks.remainder() {
+                    let index = ((self.crc ^ byte as $word) & 0xff) as usize;
+                    self.crc = (self.crc >> 8) ^ tables[0][index];
+                }
+            }
+
+            /// Returns the checksum of the data so far
+            pub fn get_checksum(&self) -> $word {
+                !self.crc
+            }
+        }
+
+        impl Checksum for $name {
+            type Output = $word;
+
+            fn new_default() -> Self {
+                $name::new_default()
+            }
+
+            fn update(&mut self, data: &[u8]) {
+                self.update(data);
+            }
+
+            fn get_checksum(&self) -> $word {
+                $name::get_checksum(self)
+            }
+        }
+    };
+}
+
+crc!(
+    /// CRC-32 as used by Ethernet, zlib, gzip and PNG
+    CRC32,
+    u32,
+    0xedb88320
+);
+
+crc!(
+    /// CRC-32C (Castagnoli) as used by iSCSI, ext4 and SCTP
+    CRC32C,
+    u32,
+    0x82f63b78
+);
+
+crc!(
+    /// CRC-64/XZ, based on the ECMA-182 polynomial, as used by xz
+    CRC64,
+    u64,
+    0xc96c5795d7870f42
+);
+
+/// Adler-32 checksum, as used by zlib
+#[derive(Debug, Clone, Copy)]
+pub struct Adler32 {
+    a: u32,
+    b: u32,
+}
+
+impl Adler32 {
+    /// Largest prime below 2^16
+    const MODULUS: u32 = 65521;
+    /// Number of bytes that can be summed before `b` could overflow
+    const MAX_RUN: usize = 5552;
+
+    /// Create a new Adler32 instance
+    pub fn new_default() -> Self {
+        Self { a: 1, b: 0 }
+    }
+
+    /// Update the checksum with the given data
+    pub fn update(&mut self, data: &[u8]) {
+        for run in data.chunks(Self::MAX_RUN) {
+            for &byte in run {
+                self.a += byte as u32;
+                self.b += self.a;
+            }
+            self.a %= Self::MODULUS;
+            self.b %= Self::MODULUS;
+        }
+    }
+
+    /// Returns the checksum of the data so far
+    pub fn get_checksum(&self) -> u32 {
+        (self.b << 16) | self.a
+    }
+}
+
+impl Checksum for Adler32 {
+    type Output = u32;
+
+    fn new_default() -> Self {
+        Adler32::new_default()
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.update(data);
+    }
+
+    fn get_checksum(&self) -> u32 {
+        Adler32::get_checksum(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checksum<C: Checksum>(data: &[u8]) -> C::Output {
+        let mut checksum = C::new_default();
+        checksum.update(data);
+        checksum.get_checksum()
+    }
+
+    fn message() -> Vec<u8> {
+        (0..300_u32).map(|i| (i * 7 + 3) as u8).collect()
+    }
+
+    #[test]
+    fn check_values() {
+        // the "check" value of the CRC catalogue is the CRC of "123456789"
+        assert_eq!(checksum::<CRC32>(b"123456789"), 0xcbf43926);
+        assert_eq!(checksum::<CRC32C>(b"123456789"), 0xe3069283);
+        assert_eq!(checksum::<CRC64>(b"123456789"), 0x995dc9bbdf1939fa);
+        assert_eq!(checksum::<Adler32>(b"Wikipedia"), 0x11e60398);
+        assert_eq!(checksum::<CRC32>(b""), 0);
+        assert_eq!(checksum::<Adler32>(b""), 1);
+    }
+
+    #[test]
+    fn longer_messages() {
+        assert_eq!(checksum::<CRC32>(&message()), 0xde0e57ce);
+        assert_eq!(checksum::<CRC32C>(&message()), 0x35130228);
+        assert_eq!(checksum::<CRC64>(&message()), 0xd6c85fa00f379455);
+        assert_eq!(checksum::<Adler32>(&message()), 0xa0b492e3);
+        // large sums have to be reduced before they overflow
+        assert_eq!(checksum::<Adler32>(&[0xff; 100_000]), 0x149a302c);
+    }
+
+    #[test]
+    fn streaming() {
+        let message = message();
+        for split in [0, 1, 7, 8, 9, 150, 299] {
+            let mut crc = CRC32::new_default();
+            crc.update(&message[..split]);
+            crc.update(&message[split..]);
+            assert_eq!(crc.get_checksum(), 0xde0e57ce);
+            let mut adler = Adler32::new_default();
+            adler.update(&message[..split]);
+            adler.update(&message[split..]);
+            assert_eq!(adler.get_checksum(), 0xa0b492e3);
+        }
+    }
+}
@@ -0,0 +1,80 @@
+//! Common interface of the classical ciphers
+
+/// [`ClassicalCipher`] is a trait that defines the interface of the classical
+/// (pre-computer) ciphers, which work on text or bytes with a small key.
+///
+/// Decryption undoes encryption, i.e. `decrypt(&encrypt(x)) == x` and
+/// `decrypt_bytes(&encrypt_bytes(x)) == x`, with two documented exceptions:
+///
+/// - Ciphers which normalize their input, like [`Playfair`](super::Playfair)
+///   and [`Polybius`](super::Polybius), only round-trip normalized input and
+///   otherwise decrypt to the normalized message.
+/// - Block ciphers, like [`Hill`](super::Hill), panic if the input is not a
+///   whole number of blocks. They provide fallible methods to check it first.
+///
+/// None of these ciphers is secure.
+pub trait ClassicalCipher {
+    /// The type of the key
+    type Key;
+
+    /// Create a new instance with the given key, or return an error if the
+    /// key is invalid
+    fn new(key: Self::Key) -> Result<Self, &'static str>
+    where
+        Self: Sized;
+
+    /// Encrypt `data`
+    fn encrypt_bytes(&self, data: &[u8]) -> Vec<u8>;
+
+    /// Decrypt `data`
+    fn decrypt_bytes(&self, data: &[u8]) -> Vec<u8>;
+
+    /// Encrypt `text`.
+    ///
+    /// By default, this encrypts the bytes of `text`, which must give valid
+    /// UTF-8 again. This holds for ciphers which only change ASCII bytes into
+    /// other ASCII bytes, in place. Other ciphers must override this method.
+    fn encrypt(&self, text: &str) -> String {
+        String::from_utf8(self.encrypt_bytes(text.as_bytes()))
+            .expect("cipher must preserve UTF-8 or override encrypt")
+    }
+
+    /// Decrypt `text`, see [`encrypt`](Self::encrypt)
+    fn decrypt(&self, text: &str) -> String {
+        String::from_utf8(self.decrypt_bytes(text.as_bytes()))
+            .expect("cipher must preserve UTF-8 or override decrypt")
+    }
+}
+
+/// Replace every ASCII letter of `data` by `f` of its index in the alphabet,
+/// keeping its case, and leave the other bytes as they are. `f` is called on
+/// the letters in order and must return an index less than 26.
+pub(crate) fn map_letters(data: &[u8], mut f: impl FnMut(u8) -> u8) -> Vec<u8> {
+    data.iter()
+        .map(|&b| match b {
+            b'A'..=b'Z' => b'A' + f(b - b'A'),
+            b'a'..=b'z' => b'a' + f(b - b'a'),
+            _ => b,
+        })
+        .collect()
+}
+
+/// The indices in the alphabet of the letters of `key`, which must be
+/// non-empty and only contain ASCII letters
+pub(crate) fn letter_key(key: &str) -> Result<Vec<u8>, &'static str> {
+    if key.is_empty() || !key.bytes().all(|b| b.is_ascii_alphabetic()) {
+        return Err("Key must be a non-empty string of ASCII letters");
+    }
+    Ok(key.bytes().map(|b| b.to_ascii_uppercase() - b'A').collect())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Whether `cipher` decrypts the encryption of both `text` and `bytes`
+    pub(crate) fn round_trips(cipher: &impl ClassicalCipher, text: &str, bytes: &[u8]) -> bool {
+        cipher.decrypt(&cipher.encrypt(text)) == text
+            && cipher.decrypt_bytes(&cipher.encrypt_bytes(bytes)) == bytes
+    }
+}
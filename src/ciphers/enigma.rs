@@ -0,0 +1,554 @@
+//! Enigma machine
+//!
+//! The Enigma is a rotor machine: every key press first advances the rotors
+//! like an odometer, then sends the letter through the plugboard, the rotors
+//! from right to left, the reflector, the rotors from left to right and the
+//! plugboard again. Since the reflector pairs letters, encryption and
+//! decryption are the same operation, and a letter is never encrypted to
+//! itself.
+//!
+//! The rightmost rotor advances on every key press, and the middle rotor when
+//! the rightmost one passes its notch. When the middle rotor reaches its own
+//! notch, it advances again on the next key press, together with the leftmost
+//! rotor: this is the double stepping anomaly. The fourth rotor of the M4,
+//! left of the others, never moves.
+//!
+//! Every rotor has a ring setting, which turns its wiring relative to the
+//! letters on its ring, and a starting position. Both are given as letters,
+//! `A` for 1 and `Z` for 26. The plugboard swaps pairs of letters, which are
+//! given separated by spaces, e.g. `"AV BS CG"`.
+//!
+//! Only ASCII letters are encrypted, and they keep their case. The other
+//! characters are left as they are and do not advance the rotors. Every call
+//! to [`encrypt`](ClassicalCipher::encrypt) starts from the starting
+//! positions.
+//!
+//! - [`Wikipedia`](https://en.wikipedia.org/wiki/Enigma_machine)
+//! - [`Enigma rotor details`](https://en.wikipedia.org/wiki/Enigma_rotor_details)
+
+use super::classical_cipher::map_letters;
+use super::ClassicalCipher;
+
+/// An Enigma model, which decides the rotors and reflectors it accepts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnigmaModel {
+    /// The army and air force Enigma I, with rotors I to V and reflectors A,
+    /// B and C
+    EnigmaI,
+    /// The navy M3, with rotors I to VIII and reflectors B and C
+    M3,
+    /// The navy M4, with a fourth, fixed, Beta or Gamma rotor, rotors I to
+    /// VIII and the thin reflectors B and C
+    M4,
+}
+
+/// The rotors used in the Enigma I, M3 and M4
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rotor {
+    /// Rotor I
+    I,
+    /// Rotor II
+    II,
+    /// Rotor III
+    III,
+    /// Rotor IV
+    IV,
+    /// Rotor V
+    V,
+    /// Rotor VI
+    VI,
+    /// Rotor VII
+    VII,
+    /// Rotor VIII
+    VIII,
+    /// The first fourth rotor of the M4
+    Beta,
+    /// The second fourth rotor of the M4
+    Gamma,
+}
+
+impl Rotor {
+    /// The wiring, and the positions at which the rotor on its left advances
+    fn details(self) -> (&'static [u8; 26], &'static [u8]) {
+        match self {
+            Rotor::I => (b"EKMFLGDQVZNTOWYHXUSPAIBRCJ", b"Q"),
+            Rotor::II => (b"AJDKSIRUXBLHWTMCQGZNPYFVOE", b"E"),
+            Rotor::III => (b"BDFHJLCPRTXVZNYEIWGAKMUSQO", b"V"),
+            Rotor::IV => (b"ESOVPZJAYQUIRHXLNFTGKDCMWB", b"J"),
+            Rotor::V => (b"VZBRGITYUPSDNHLXAWMJQOFECK", b"Z"),
+            Rotor::VI => (b"JPGVOUMFYQBENHZRDKASXLICTW", b"ZM"),
+            Rotor::VII => (b"NZJHGRCXMYSWBOUFAIVLPEKQDT", b"ZM"),
+            Rotor::VIII => (b"FKQHTLXOCBJSPDZRAMEWNIUYGV", b"ZM"),
+            Rotor::Beta => (b"LEYJVCNIXWPBQMDRTAKZGFUHOS", b""),
+            Rotor::Gamma => (b"FSOKANUERHMBTIYCWLQPZXVGJD", b""),
+        }
+    }
+
+    fn is_greek(self) -> bool {
+        matches!(self, Rotor::Beta | Rotor::Gamma)
+    }
+}
+
+/// The reflectors used in the Enigma I, M3 and M4
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reflector {
+    /// Reflector A
+    A,
+    /// Reflector B
+    B,
+    /// Reflector C
+    C,
+    /// The thin reflector B of the M4
+    BThin,
+    /// The thin reflector C of the M4
+    CThin,
+}
+
+impl Reflector {
+    fn wiring(self) -> &'static [u8; 26] {
+        match self {
+            Reflector::A => b"EJMZALYXVBWFCRQUONTSPIKHGD",
+            Reflector::B => b"YRUHQSLDPXNGOKMIEBFZCWVJAT",
+            Reflector::C => b"FVPJIAOYEDRZXWGCTKUQSBNMHL",
+            Reflector::BThin => b"ENKQAUYWJICOPBLMDXZVFTHRGS",
+            Reflector::CThin => b"RDOBJNTKVEHMLFCWZAXGYIPSUQ",
+        }
+    }
+}
+
+/// The settings of an [`Enigma`], built from one of [`enigma_i`](Self::enigma_i),
+/// [`m3`](Self::m3) or [`m4`](Self::m4). The ring settings and starting
+/// positions default to all `A`, the plugboard to no pairs and the reflector
+/// to B or thin B. They are checked by [`Enigma::new`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnigmaSettings {
+    model: EnigmaModel,
+    /// From left to right
+    rotors: Vec<Rotor>,
+    reflector: Reflector,
+    rings: String,
+    positions: String,
+    plugboard: String,
+}
+
+impl EnigmaSettings {
+    fn with_rotors(model: EnigmaModel, rotors: Vec<Rotor>, reflector: Reflector) -> Self {
+        let len = rotors.len();
+        Self {
+            model,
+            rotors,
+            reflector,
+            rings: "A".repeat(len),
+            positions: "A".repeat(len),
+            plugboard: String::new(),
+        }
+    }
+
+    /// Settings for an Enigma I with the given rotors, from left to right
+    pub fn enigma_i(left: Rotor, middle: Rotor, right: Rotor) -> Self {
+        Self::with_rotors(
+            EnigmaModel::EnigmaI,
+            vec![left, middle, right],
+            Reflector::B,
+        )
+    }
+
+    /// Settings for an M3 with the given rotors, from left to right
+    pub fn m3(left: Rotor, middle: Rotor, right: Rotor) -> Self {
+        Self::with_rotors(EnigmaModel::M3, vec![left, middle, right], Reflector::B)
+    }
+
+    /// Settings for an M4 with the given rotors, from left to right. `greek`
+    /// must be [`Rotor::Beta`] or [`Rotor::Gamma`].
+    pub fn m4(greek: Rotor, left: Rotor, middle: Rotor, right: Rotor) -> Self {
+        Self::with_rotors(
+            EnigmaModel::M4,
+            vec![greek, left, middle, right],
+            Reflector::BThin,
+        )
+    }
+
+    /// Use `reflector`
+    pub fn reflector(mut self, reflector: Reflector) -> Self {
+        self.reflector = reflector;
+        self
+    }
+
+    /// Use the ring settings `rings`, one letter per rotor from left to right
+    pub fn rings(mut self, rings: &str) -> Self {
+        self.rings = rings.to_owned();
+        self
+    }
+
+    /// Use the starting positions `positions`, one letter per rotor from left
+    /// to right
+    pub fn positions(mut self, positions: &str) -> Self {
+        self.positions = positions.to_owned();
+        self
+    }
+
+    /// Swap the pairs of letters in `plugboard`, separated by spaces
+    pub fn plugboard(mut self, plugboard: &str) -> Self {
+        self.plugboard = plugboard.to_owned();
+        self
+    }
+}
+
+/// A rotor in the machine
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct RotorSlot {
+    forward: [u8; 26],
+    backward: [u8; 26],
+    notches: [bool; 26],
+    ring: u8,
+    position: u8,
+}
+
+impl RotorSlot {
+    fn new(rotor: Rotor, ring: u8, position: u8) -> Self {
+        let (wiring, notches) = rotor.details();
+        let mut slot = Self {
+            forward: [0; 26],
+            backward: [0; 26],
+            notches: [false; 26],
+            ring,
+            position,
+        };
+        for (i, &letter) in wiring.iter().enumerate() {
+            slot.forward[i] = letter - b'A';
+            slot.backward[usize::from(letter - b'A')] = i as u8;
+        }
+        for &notch in notches {
+            slot.notches[usize::from(notch - b'A')] = true;
+        }
+        slot
+    }
+
+    fn at_notch(&self) -> bool {
+        self.notches[usize::from(self.position)]
+    }
+
+    fn step(&mut self) {
+        self.position = (self.position + 1) % 26;
+    }
+
+    /// Send `letter` through `wiring`, taking the position and ring setting
+    /// into account
+    fn map(&self, wiring: &[u8; 26], letter: u8) -> u8 {
+        let offset = (self.position + 26 - self.ring) % 26;
+        (wiring[usize::from((letter + offset) % 26)] + 26 - offset) % 26
+    }
+}
+
+/// Enigma machine, see the [module documentation](self)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Enigma {
+    /// From left to right, at the starting positions
+    rotors: Vec<RotorSlot>,
+    reflector: [u8; 26],
+    plugboard: [u8; 26],
+}
+
+/// The indices in the alphabet of `letters`, one per rotor
+fn rotor_letters(letters: &str, rotors: usize) -> Result<Vec<u8>, &'static str> {
+    if letters.len() != rotors || !letters.bytes().all(|b| b.is_ascii_alphabetic()) {
+        return Err("Ring settings and positions must be one letter per rotor");
+    }
+    Ok(letters
+        .bytes()
+        .map(|b| b.to_ascii_uppercase() - b'A')
+        .collect())
+}
+
+impl Enigma {
+    /// Advance the rotors, see the [module documentation](self)
+    fn step(rotors: &mut [RotorSlot]) {
+        let n = rotors.len();
+        let (left, middle, right) = (n - 3, n - 2, n - 1);
+        if rotors[middle].at_notch() {
+            rotors[middle].step();
+            rotors[left].step();
+        } else if rotors[right].at_notch() {
+            rotors[middle].step();
+        }
+        rotors[right].step();
+    }
+
+    /// Encrypt one letter, after advancing `rotors`
+    fn press(&self, rotors: &mut [RotorSlot], letter: u8) -> u8 {
+        Self::step(rotors);
+        let mut letter = self.plugboard[usize::from(letter)];
+        for rotor in rotors.iter().rev() {
+            letter = rotor.map(&rotor.forward, letter);
+        }
+        letter = self.reflector[usize::from(letter)];
+        for rotor in rotors.iter() {
+            letter = rotor.map(&rotor.backward, letter);
+        }
+        self.plugboard[usize::from(letter)]
+    }
+}
+
+impl ClassicalCipher for Enigma {
+    /// The settings of the machine
+    type Key = EnigmaSettings;
+
+    fn new(settings: EnigmaSettings) -> Result<Self, &'static str> {
+        let EnigmaSettings {
+            model,
+            rotors,
+            reflector,
+            rings,
+            positions,
+            plugboard,
+        } = settings;
+        let (stepping, valid_reflector) = match model {
+            EnigmaModel::EnigmaI => (
+                &rotors[..],
+                matches!(reflector, Reflector::A | Reflector::B | Reflector::C),
+            ),
+            EnigmaModel::M3 => (
+                &rotors[..],
+                matches!(reflector, Reflector::B | Reflector::C),
+            ),
+            EnigmaModel::M4 => {
+                if !rotors[0].is_greek() {
+                    return Err("Fourth rotor of the M4 must be Beta or Gamma");
+                }
+                (
+                    &rotors[1..],
+                    matches!(reflector, Reflector::BThin | Reflector::CThin),
+                )
+            }
+        };
+        if !valid_reflector {
+            return Err("Reflector is not available for this model");
+        }
+        let max_rotor = if model == EnigmaModel::EnigmaI {
+            Rotor::V
+        } else {
+            Rotor::VIII
+        };
+        if stepping
+            .iter()
+            .any(|&rotor| rotor.is_greek() || rotor as u8 > max_rotor as u8)
+        {
+            return Err("Rotor is not available for this model");
+        }
+        if (1..stepping.len()).any(|i| stepping[..i].contains(&stepping[i])) {
+            return Err("Rotors must be different");
+        }
+
+        let mut board: [u8; 26] = std::array::from_fn(|i| i as u8);
+        for pair in plugboard.split_whitespace() {
+            let pair = rotor_letters(pair, 2).map_err(|_| "Plugboard pairs must be two letters")?;
+            let (a, b) = (usize::from(pair[0]), usize::from(pair[1]));
+            if a == b || board[a] != pair[0] || board[b] != pair[1] {
+                return Err("Plugboard letters must be different and used once");
+            }
+            board.swap(a, b);
+        }
+
+        let rings = rotor_letters(&rings, rotors.len())?;
+        let positions = rotor_letters(&positions, rotors.len())?;
+        Ok(Self {
+            rotors: rotors
+                .iter()
+                .zip(rings)
+                .zip(positions)
+                .map(|((&rotor, ring), position)| RotorSlot::new(rotor, ring, position))
+                .collect(),
+            reflector: std::array::from_fn(|i| reflector.wiring()[i] - b'A'),
+            plugboard: board,
+        })
+    }
+
+    fn encrypt_bytes(&self, data: &[u8]) -> Vec<u8> {
+        let mut rotors = self.rotors.clone();
+        map_letters(data, |letter| self.press(&mut rotors, letter))
+    }
+
+    fn decrypt_bytes(&self, data: &[u8]) -> Vec<u8> {
+        self.encrypt_bytes(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::classical_cipher::tests::round_trips;
+    use super::*;
+    use quickcheck_macros::quickcheck;
+
+    fn positions(enigma: &Enigma, presses: usize) -> String {
+        let mut rotors = enigma.rotors.clone();
+        for _ in 0..presses {
+            Enigma::step(&mut rotors);
+        }
+        rotors
+            .iter()
+            .map(|rotor| char::from(b'A' + rotor.position))
+            .collect()
+    }
+
+    #[test]
+    fn basic() {
+        let enigma =
+            Enigma::new(EnigmaSettings::enigma_i(Rotor::I, Rotor::II, Rotor::III)).unwrap();
+        assert_eq!(enigma.encrypt("AAAAA"), "BDZGO");
+        assert_eq!(enigma.decrypt("BDZGO"), "AAAAA");
+        assert_eq!(enigma.encrypt("aa, aa a"), "bd, zg o");
+        let enigma = Enigma::new(
+            EnigmaSettings::enigma_i(Rotor::I, Rotor::II, Rotor::III)
+                .rings("BBB")
+                .positions("AAA"),
+        )
+        .unwrap();
+        assert_eq!(enigma.encrypt("AAAAA"), "EWTYX");
+    }
+
+    #[test]
+    fn double_stepping() {
+        let enigma =
+            Enigma::new(EnigmaSettings::enigma_i(Rotor::I, Rotor::II, Rotor::III).positions("ADU"))
+                .unwrap();
+        assert_eq!(positions(&enigma, 1), "ADV");
+        assert_eq!(positions(&enigma, 2), "AEW");
+        assert_eq!(positions(&enigma, 3), "BFX");
+        // rotors VI to VIII have two notches, and the M4's fourth rotor never
+        // moves
+        let enigma = Enigma::new(
+            EnigmaSettings::m4(Rotor::Gamma, Rotor::I, Rotor::VI, Rotor::VIII).positions("ZAYY"),
+        )
+        .unwrap();
+        assert_eq!(positions(&enigma, 1), "ZAYZ");
+        assert_eq!(positions(&enigma, 2), "ZAZA");
+        assert_eq!(positions(&enigma, 3), "ZBAB");
+        assert_eq!(positions(&enigma, 15), "ZBBN");
+        assert_eq!(positions(&enigma, 16), "ZBBO");
+    }
+
+    #[test]
+    fn barbarossa() {
+        // Message from Operation Barbarossa, 1941
+        let settings = EnigmaSettings::m3(Rotor::II, Rotor::IV, Rotor::V)
+            .reflector(Reflector::B)
+            .rings("BUL")
+            .plugboard("AV BS CG DL FU HZ IN KM OW RX");
+        let message_key = Enigma::new(settings.clone().positions("WXC")).unwrap();
+        assert_eq!(message_key.decrypt("KCH"), "BLA");
+        let enigma = Enigma::new(settings.positions("BLA")).unwrap();
+        assert_eq!(
+            enigma.decrypt(
+                "EDPUD NRGYS ZRCXN UYTPO MRMBO FKTBZ REZKM LXLVE FGUEY SIOZV EQMIK UBPMM \
+                 YLKLT TDEIS MDICA GYKUA CTCDO MOHWX MUUIA UBSTS LRNBZ SZWNR FXWFY SSXJZ \
+                 VIJHI DISHP RKLKA YUPAD TXQSP INQMA TLPIF SVKDA SCTAC DPBOP VHJK"
+            ),
+            "AUFKL XABTE ILUNG XVONX KURTI NOWAX KURTI NOWAX NORDW ESTLX SEBEZ XSEBE \
+             ZXUAF FLIEG ERSTR ASZER IQTUN GXDUB ROWKI XDUBR OWKIX OPOTS CHKAX OPOTS \
+             CHKAX UMXEI NSAQT DREIN ULLXU HRANG ETRET ENXAN GRIFF XINFX RGTX"
+        );
+    }
+
+    #[test]
+    fn donitz() {
+        // Message from the M4 announcing Karl Dönitz as successor, 1945
+        let enigma = Enigma::new(
+            EnigmaSettings::m4(Rotor::Beta, Rotor::II, Rotor::IV, Rotor::I)
+                .reflector(Reflector::BThin)
+                .rings("AAAV")
+                .positions("VJNA")
+                .plugboard("AT BL DF GJ HM NW OP QY RZ VX"),
+        )
+        .unwrap();
+        assert_eq!(
+            enigma.decrypt(
+                "NCZW VUSX PNYM INHZ XMQX SFWX WLKJ AHSH NMCO CCAK UQPM KCSM HKSE INJU \
+                 SBLK IOSX CKUB HMLL XCSJ USRR DVKO HULX WCCB GVLI YXEO AHXR HKKF VDRE \
+                 WEZL XOBA FGYU JQUK GRTV UKAM EURB VEKS UHHV OYHA BCJW MAKL FKLM YFVN \
+                 RIZR VVRT KOFD ANJM OLBG FFLE OPRG TFLV RHOW OPBE KVWM UQFM PWPA RMFH \
+                 AGKX IIBG"
+            ),
+            "VONV ONJL OOKS JHFF TTTE INSE INSD REIZ WOYY QNNS NEUN INHA LTXX BEIA \
+             NGRI FFUN TERW ASSE RGED RUEC KTYW ABOS XLET ZTER GEGN ERST ANDN ULAC \
+             HTDR EINU LUHR MARQ UANT ONJO TANE UNAC HTSE YHSD REIY ZWOZ WONU LGRA \
+             DYAC HTSM YSTO SSEN ACHX EKNS VIER MBFA ELLT YNNN NNNO OOVI ERYS ICHT \
+             EINS NULL"
+        );
+    }
+
+    #[test]
+    fn m4_compatibility() {
+        // the M4 with Beta at A and the thin reflector B is an M3 with the
+        // reflector B, which let it talk to the M3
+        let m3 = Enigma::new(
+            EnigmaSettings::m3(Rotor::VI, Rotor::VII, Rotor::VIII)
+                .rings("CQE")
+                .positions("XMY")
+                .plugboard("QW ER TY"),
+        )
+        .unwrap();
+        let m4 = Enigma::new(
+            EnigmaSettings::m4(Rotor::Beta, Rotor::VI, Rotor::VII, Rotor::VIII)
+                .rings("ACQE")
+                .positions("AXMY")
+                .plugboard("QW ER TY"),
+        )
+        .unwrap();
+        let message = "THEQUICKBROWNFOXJUMPSOVERTHELAZYDOG".repeat(30);
+        assert_eq!(m3.encrypt(&message), m4.encrypt(&message));
+    }
+
+    #[test]
+    fn never_encrypts_a_letter_to_itself() {
+        let enigma = Enigma::new(
+            EnigmaSettings::enigma_i(Rotor::V, Rotor::III, Rotor::I).reflector(Reflector::A),
+        )
+        .unwrap();
+        let message = "E".repeat(20000);
+        assert!(!enigma.encrypt(&message).contains('E'));
+    }
+
+    #[test]
+    fn invalid_settings() {
+        let settings = EnigmaSettings::enigma_i(Rotor::I, Rotor::II, Rotor::III);
+        assert!(Enigma::new(EnigmaSettings::enigma_i(Rotor::I, Rotor::II, Rotor::VI)).is_err());
+        assert!(Enigma::new(EnigmaSettings::m3(Rotor::I, Rotor::II, Rotor::I)).is_err());
+        assert!(Enigma::new(EnigmaSettings::m3(Rotor::Beta, Rotor::II, Rotor::I)).is_err());
+        assert!(Enigma::new(EnigmaSettings::m4(
+            Rotor::I,
+            Rotor::II,
+            Rotor::III,
+            Rotor::IV
+        ))
+        .is_err());
+        assert!(Enigma::new(settings.clone().reflector(Reflector::BThin)).is_err());
+        assert!(Enigma::new(
+            EnigmaSettings::m4(Rotor::Beta, Rotor::II, Rotor::III, Rotor::IV)
+                .reflector(Reflector::B)
+        )
+        .is_err());
+        assert!(Enigma::new(settings.clone().rings("AA")).is_err());
+        assert!(Enigma::new(settings.clone().positions("A1B")).is_err());
+        assert!(Enigma::new(settings.clone().plugboard("AB CA")).is_err());
+        assert!(Enigma::new(settings.clone().plugboard("AA")).is_err());
+        assert!(Enigma::new(settings.clone().plugboard("ABC")).is_err());
+        assert!(Enigma::new(settings.plugboard(" ab  cd ")).is_ok());
+    }
+
+    #[quickcheck]
+    fn round_trip(positions: (u8, u8, u8), text: String, bytes: Vec<u8>) -> bool {
+        let positions: String = [positions.0, positions.1, positions.2]
+            .iter()
+            .map(|p| char::from(b'A' + p % 26))
+            .collect();
+        let enigma = Enigma::new(
+            EnigmaSettings::m3(Rotor::VIII, Rotor::II, Rotor::V)
+                .rings("QED")
+                .positions(&positions)
+                .plugboard("PO ML IU KJ NH YT"),
+        )
+        .unwrap();
+        round_trips(&enigma, &text, &bytes)
+    }
+}
@@ -0,0 +1,364 @@
+//! Galois/Counter Mode authenticated encryption with associated data
+//!
+//! This implementation is based on [`NIST SP 800-38D`]. The plaintext is
+//! encrypted in CTR mode with a 32-bit counter, and the tag is the GHASH of the
+//! associated data and the ciphertext, masked with the encryption of the first
+//! counter block. GHASH evaluates a polynomial at `H`, the encryption of the
+//! zero block, in GF(2^128). The multiplication here is bitwise and
+//! branch-free rather than table-based, so it does not leak `H` through the
+//! cache.
+//!
+//! Never encrypt two messages with the same key and nonce: this reveals `H`
+//! and allows forging tags. Messages are limited to `2^39 - 256` bits, so
+//! the 32-bit counter never repeats a keystream block.
+//!
+//! [`NIST SP 800-38D`]: https://nvlpubs.nist.gov/nistpubs/Legacy/SP/nistspecialpublication800-38d.pdf
+
+use super::block_cipher::ctr_with;
+use super::hashing_traits::constant_time_eq;
+use super::BlockCipher;
+
+/// Length of the full authentication tag in bytes
+const TAG_BYTES: usize = 16;
+
+/// Shortest tag accepted by [`GCM::open_in_place_detached`]
+const MIN_TAG_BYTES: usize = 12;
+
+/// Longest plaintext, `2^39 - 256` bits. Its `2^32 - 2` blocks use every
+/// counter value but the first one, which masks the tag.
+const MAX_PLAINTEXT_BYTES: u64 = (1 << 36) - 32;
+
+/// Multiplication in GF(2^128) with the bit order of GCM, where the most
+/// significant bit is the constant term
+fn gf128_mul(x: u128, y: u128) -> u128 {
+    let mut result = 0;
+    let mut v = y;
+    for i in (0..128).rev() {
+        result ^= v & ((x >> i) & 1).wrapping_neg();
+        v = (v >> 1) ^ ((0xe1 << 120) & (v & 1).wrapping_neg());
+    }
+    result
+}
+
+/// GHASH of `data` padded with zeros to a multiple of 16 bytes, continuing
+/// from `state`
+fn ghash(h: u128, mut state: u128, data: &[u8]) -> u128 {
+    for chunk in data.chunks(16) {
+        let mut block = [0u8; 16];
+        block[..chunk.len()].copy_from_slice(chunk);
+        state = gf128_mul(state ^ u128::from_be_bytes(block), h);
+    }
+    state
+}
+
+/// Increment the last 32 bits of `counter`, wrapping around
+fn increment32(counter: &mut [u8; 16]) {
+    let low = u32::from_be_bytes(counter[12..].try_into().unwrap());
+    counter[12..].copy_from_slice(&low.wrapping_add(1).to_be_bytes());
+}
+
+/// Fail if a plaintext of `len` bytes would reuse a counter value
+fn check_length(len: usize) -> Result<(), &'static str> {
+    if len as u64 > MAX_PLAINTEXT_BYTES {
+        return Err("Plaintext is longer than 2^39 - 256 bits");
+    }
+    Ok(())
+}
+
+/// GCM authenticated encryption on top of a 128-bit block cipher, see the
+/// [module documentation](self)
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, Copy)]
+pub struct GCM<C: BlockCipher<16>> {
+    cipher: C,
+    h: u128,
+}
+
+impl<C: BlockCipher<16>> GCM<C> {
+    /// Create a new instance using `cipher`, which holds the key
+    pub fn new(cipher: C) -> Self {
+        let mut h = [0u8; 16];
+        cipher.encrypt_block(&mut h);
+        Self {
+            cipher,
+            h: u128::from_be_bytes(h),
+        }
+    }
+
+    /// The first counter block. 96-bit nonces are used as they are, others
+    /// are hashed.
+    fn initial_counter(&self, nonce: &[u8]) -> Result<[u8; 16], &'static str> {
+        if nonce.is_empty() {
+            return Err("Nonce is empty");
+        }
+        if nonce.len() == 12 {
+            let mut counter = [0u8; 16];
+            counter[..12].copy_from_slice(nonce);
+            counter[15] = 1;
+            return Ok(counter);
+        }
+        let state = ghash(self.h, 0, nonce);
+        let length = u128::from(nonce.len() as u64 * 8);
+        Ok(gf128_mul(state ^ length, self.h).to_be_bytes())
+    }
+
+    fn compute_tag(
+        &self,
+        initial_counter: &[u8; 16],
+        associated_data: &[u8],
+        ciphertext: &[u8],
+    ) -> [u8; TAG_BYTES] {
+        let state = ghash(self.h, 0, associated_data);
+        let state = ghash(self.h, state, ciphertext);
+        let lengths = (u128::from(associated_data.len() as u64 * 8) << 64)
+            | u128::from(ciphertext.len() as u64 * 8);
+        let mut mask = *initial_counter;
+        self.cipher.encrypt_block(&mut mask);
+        (gf128_mul(state ^ lengths, self.h) ^ u128::from_be_bytes(mask)).to_be_bytes()
+    }
+
+    fn apply_keystream(&self, initial_counter: &[u8; 16], buffer: &mut [u8]) {
+        let mut counter = *initial_counter;
+        increment32(&mut counter);
+        ctr_with(&self.cipher, &counter, buffer, increment32);
+    }
+
+    /// Encrypt `buffer` in place, returning the tag authenticating it
+    /// together with `associated_data`. The tag may be truncated to no less
+    /// than 12 bytes. Fails if `buffer` is longer than `2^36 - 32` bytes.
+    pub fn seal_in_place_detached(
+        &self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<[u8; TAG_BYTES], &'static str> {
+        check_length(buffer.len())?;
+        let initial_counter = self.initial_counter(nonce)?;
+        self.apply_keystream(&initial_counter, buffer);
+        Ok(self.compute_tag(&initial_counter, associated_data, buffer))
+    }
+
+    /// Check `tag`, which may be truncated to no less than 12 bytes, against
+    /// `buffer` and `associated_data`, and decrypt `buffer` in place if it is
+    /// authentic. Otherwise `buffer` is left as it is.
+    pub fn open_in_place_detached(
+        &self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        buffer: &mut [u8],
+        tag: &[u8],
+    ) -> Result<(), &'static str> {
+        if !(MIN_TAG_BYTES..=TAG_BYTES).contains(&tag.len()) {
+            return Err("Invalid tag length");
+        }
+        check_length(buffer.len())?;
+        let initial_counter = self.initial_counter(nonce)?;
+        let expected = self.compute_tag(&initial_counter, associated_data, buffer);
+        // the ciphertext is only decrypted once it is authenticated
+        if !constant_time_eq(&expected[..tag.len()], tag) {
+            return Err("Authentication failed");
+        }
+        self.apply_keystream(&initial_counter, buffer);
+        Ok(())
+    }
+
+    /// Returns the encryption of `plaintext` followed by the full tag
+    /// authenticating it together with `associated_data`
+    pub fn seal(
+        &self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        plaintext: &[u8],
+    ) -> Result<Vec<u8>, &'static str> {
+        let mut result = plaintext.to_vec();
+        let tag = self.seal_in_place_detached(nonce, associated_data, &mut result)?;
+        result.extend_from_slice(&tag);
+        Ok(result)
+    }
+
+    /// Decrypt the output of [`seal`](Self::seal), if it is authentic
+    pub fn open(
+        &self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        ciphertext: &[u8],
+    ) -> Result<Vec<u8>, &'static str> {
+        if ciphertext.len() < TAG_BYTES {
+            return Err("Ciphertext is shorter than the tag");
+        }
+        let (ciphertext, tag) = ciphertext.split_at(ciphertext.len() - TAG_BYTES);
+        let mut result = ciphertext.to_vec();
+        self.open_in_place_detached(nonce, associated_data, &mut result, tag)?;
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_utils::{from_hex, get_hash_string};
+    use super::super::AES;
+    use super::*;
+
+    // The test cases of "The Galois/Counter Mode of Operation (GCM)" by McGrew
+    // and Viega, which are also used by NIST
+    const KEY: &str = "feffe9928665731c6d6a8f9467308308";
+    const PLAINTEXT: &str = "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a721c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b39";
+    const AAD: &str = "feedfacedeadbeeffeedfacedeadbeefabaddad2";
+    const NONCE: &str = "cafebabefacedbaddecaf888";
+
+    fn check(key: &str, nonce: &str, associated_data: &str, plaintext: &str, expected: &str) {
+        let gcm = GCM::new(AES::new(&from_hex(key)).unwrap());
+        let (nonce, associated_data) = (from_hex(nonce), from_hex(associated_data));
+        let plaintext = from_hex(plaintext);
+        let sealed = gcm.seal(&nonce, &associated_data, &plaintext).unwrap();
+        assert_eq!(get_hash_string(&sealed), expected);
+        assert_eq!(
+            gcm.open(&nonce, &associated_data, &sealed).unwrap(),
+            plaintext
+        );
+    }
+
+    #[test]
+    fn zero_key() {
+        // test cases 1 and 2
+        let zero_key = "00000000000000000000000000000000";
+        let zero_nonce = "000000000000000000000000";
+        check(
+            zero_key,
+            zero_nonce,
+            "",
+            "",
+            "58e2fccefa7e3061367f1d57a4e7455a",
+        );
+        check(
+            zero_key,
+            zero_nonce,
+            "",
+            zero_key,
+            "0388dace60b6a392f328c2b971b2fe78ab6e47d42cec13bdf53a67b21257bddf",
+        );
+    }
+
+    #[test]
+    fn aes_128() {
+        // test cases 3 to 6
+        check(
+            KEY,
+            NONCE,
+            "",
+            &(PLAINTEXT.to_owned() + "1aafd255"),
+            "42831ec2217774244b7221b784d0d49ce3aa212f2c02a4e035c17e2329aca12e21d514b25466931c7d8f6a5aac84aa051ba30b396a0aac973d58e091473f59854d5c2af327cd64a62cf35abd2ba6fab4",
+        );
+        check(
+            KEY,
+            NONCE,
+            AAD,
+            PLAINTEXT,
+            "42831ec2217774244b7221b784d0d49ce3aa212f2c02a4e035c17e2329aca12e21d514b25466931c7d8f6a5aac84aa051ba30b396a0aac973d58e0915bc94fbc3221a5db94fae95ae7121a47",
+        );
+        check(
+            KEY,
+            "cafebabefacedbad",
+            AAD,
+            PLAINTEXT,
+            "61353b4c2806934a777ff51fa22a4755699b2a714fcdc6f83766e5f97b6c742373806900e49f24b22b097544d4896b424989b5e1ebac0f07c23f45983612d2e79e3b0785561be14aaca2fccb",
+        );
+        check(
+            KEY,
+            "9313225df88406e555909c5aff5269aa6a7a9538534f7da1e4c303d2a318a728c3c0c95156809539fcf0e2429a6b525416aedbf5a0de6a57a637b39b",
+            AAD,
+            PLAINTEXT,
+            "8ce24998625615b603a033aca13fb894be9112a5c3a211a8ba262a3cca7e2ca701e4a9a4fba43c90ccdcb281d48c7c6fd62875d2aca417034c34aee5619cc5aefffe0bfa462af43c1699d050",
+        );
+    }
+
+    #[test]
+    fn aes_192_and_256() {
+        // test cases 10 and 16
+        check(
+            &(KEY.to_owned() + &KEY[..16]),
+            NONCE,
+            AAD,
+            PLAINTEXT,
+            "3980ca0b3c00e841eb06fac4872a2757859e1ceaa6efd984628593b40ca1e19c7d773d00c144c525ac619d18c84a3f4718e2448b2fe324d9ccda27102519498e80f1478f37ba55bd6d27618c",
+        );
+        check(
+            &(KEY.to_owned() + KEY),
+            NONCE,
+            AAD,
+            PLAINTEXT,
+            "522dc1f099567d07f47f37a32a84427d643a8cdcbfe5c0c97598a2bd2555d1aa8cb08e48590dbb3da7b08b1056828838c5f61e6393ba7a0abcc9f66276fc6ece0f4e1768cddf8853bb2d551b",
+        );
+    }
+
+    #[test]
+    fn tampering() {
+        let gcm = GCM::new(AES::new(&from_hex(KEY)).unwrap());
+        let (nonce, aad) = (from_hex(NONCE), from_hex(AAD));
+        let sealed = gcm.seal(&nonce, &aad, &from_hex(PLAINTEXT)).unwrap();
+        for i in [0, 30, sealed.len() - 1] {
+            let mut tampered = sealed.clone();
+            tampered[i] ^= 0x10;
+            assert!(gcm.open(&nonce, &aad, &tampered).is_err());
+        }
+        assert!(gcm.open(&nonce, b"other data", &sealed).is_err());
+        assert!(gcm.open(&nonce[..8], &aad, &sealed).is_err());
+        assert!(gcm.open(&nonce, &aad, &sealed[..15]).is_err());
+        assert!(gcm.seal(&[], &aad, b"").is_err());
+        // a failed check leaves the buffer encrypted
+        let mut buffer = from_hex(PLAINTEXT);
+        let tag = gcm
+            .seal_in_place_detached(&nonce, &aad, &mut buffer)
+            .unwrap();
+        let encrypted = buffer.clone();
+        let mut wrong = tag;
+        wrong[0] ^= 1;
+        assert!(gcm
+            .open_in_place_detached(&nonce, &aad, &mut buffer, &wrong)
+            .is_err());
+        assert_eq!(buffer, encrypted);
+    }
+
+    #[test]
+    fn truncated_tags() {
+        let gcm = GCM::new(AES::new(&from_hex(KEY)).unwrap());
+        let nonce = from_hex(NONCE);
+        let mut buffer = b"attack at dawn".to_vec();
+        let tag = gcm
+            .seal_in_place_detached(&nonce, &[], &mut buffer)
+            .unwrap();
+        let encrypted = buffer.clone();
+        for len in [0, 4, 8, 11] {
+            assert!(gcm
+                .open_in_place_detached(&nonce, &[], &mut buffer, &tag[..len])
+                .is_err());
+        }
+        for len in 12..=16 {
+            let mut buffer = encrypted.clone();
+            gcm.open_in_place_detached(&nonce, &[], &mut buffer, &tag[..len])
+                .unwrap();
+            assert_eq!(buffer, b"attack at dawn");
+        }
+    }
+
+    #[test]
+    fn length_limit() {
+        assert!(check_length(MAX_PLAINTEXT_BYTES as usize).is_ok());
+        assert!(check_length(MAX_PLAINTEXT_BYTES as usize + 1).is_err());
+        // with a 96-bit nonce the first counter is 1, and the last block of the
+        // longest plaintext uses u32::MAX, so no counter is used twice
+        assert_eq!(1 + MAX_PLAINTEXT_BYTES / 16, u64::from(u32::MAX));
+        // near u32::MAX the counter wraps around in its last 32 bits
+        let gcm = GCM::new(AES::new(&from_hex(KEY)).unwrap());
+        let mut initial_counter = [0xab; 16];
+        initial_counter[12..].copy_from_slice(&(u32::MAX - 2).to_be_bytes());
+        let mut buffer = [0; 48];
+        gcm.apply_keystream(&initial_counter, &mut buffer);
+        for (block, low) in buffer.chunks_exact(16).zip([u32::MAX - 1, u32::MAX, 0]) {
+            let mut counter = initial_counter;
+            counter[12..].copy_from_slice(&low.to_be_bytes());
+            gcm.cipher.encrypt_block(&mut counter);
+            assert_eq!(block, counter);
+        }
+    }
+}
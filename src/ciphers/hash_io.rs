@@ -0,0 +1,420 @@
+//! Hashing of streams and files
+//!
+//! Every [`Hasher`] of this crate implements [`std::io::Write`], so data can
+//! be copied into it with [`std::io::copy`] or written with `write!`.
+//! [`HashingReader`] hashes the bytes read through it, which lets a stream be
+//! hashed while it is processed, and [`digest_file`] hashes a whole file.
+//!
+//! [`parse_checksums`] reads the check files written by `sha256sum` and the
+//! other GNU coreutils checksum tools, in their default format
+//! (`<hex digest>  <path>`, or `<hex digest> *<path>` in binary mode) or in the
+//! BSD format written with `--tag` (`SHA256 (<path>) = <hex digest>`), and
+//! [`verify_checksums`] checks the files they list, like `sha256sum -c`.
+//!
+//! - [`GNU coreutils`](https://www.gnu.org/software/coreutils/manual/html_node/md5sum-invocation.html)
+
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+use super::{
+    Blake2b, Blake2s, Blake3, Hasher, MD5, RIPEMD160, SHA1, SHA224, SHA256, SHA384, SHA3_224,
+    SHA3_256, SHA3_384, SHA3_512, SHA512, SHA512_224, SHA512_256,
+};
+use crate::encoding::{Encoding, Hex};
+
+/// Implement [`Write`] for hashers with a fixed digest size
+macro_rules! impl_write {
+    ($($name:ident: $bytes:literal),* $(,)?) => {
+        $(
+            impl Write for $name {
+                fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                    <Self as Hasher<$bytes>>::update(self, buf);
+                    Ok(buf.len())
+                }
+
+                fn flush(&mut self) -> io::Result<()> {
+                    Ok(())
+                }
+            }
+        )*
+    };
+}
+
+impl_write!(
+    MD5: 16,
+    RIPEMD160: 20,
+    SHA1: 20,
+    SHA224: 28,
+    SHA256: 32,
+    SHA384: 48,
+    SHA512: 64,
+    SHA512_224: 28,
+    SHA512_256: 32,
+    SHA3_224: 28,
+    SHA3_256: 32,
+    SHA3_384: 48,
+    SHA3_512: 64,
+    Blake3: 32,
+);
+
+/// Implement [`Write`] for the BLAKE2 hashers, whose digest size is generic
+macro_rules! impl_write_blake2 {
+    ($($name:ident),*) => {
+        $(
+            impl<const N: usize> Write for $name<N> {
+                fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                    <Self as Hasher<N>>::update(self, buf);
+                    Ok(buf.len())
+                }
+
+                fn flush(&mut self) -> io::Result<()> {
+                    Ok(())
+                }
+            }
+        )*
+    };
+}
+
+impl_write_blake2!(Blake2b, Blake2s);
+
+/// A reader which hashes the bytes read from an inner reader
+#[derive(Debug, Clone)]
+pub struct HashingReader<const DIGEST_BYTES: usize, H: Hasher<DIGEST_BYTES>, R> {
+    inner: R,
+    hasher: H,
+}
+
+impl<const DIGEST_BYTES: usize, H: Hasher<DIGEST_BYTES>, R: Read>
+    HashingReader<DIGEST_BYTES, H, R>
+{
+    /// Create a new instance reading from `inner`, with a hasher with
+    /// default parameters
+    pub fn new(inner: R) -> Self {
+        Self::with_hasher(inner, H::new_default())
+    }
+
+    /// Create a new instance reading from `inner` and adding the bytes read
+    /// to `hasher`, which may already hold data
+    pub fn with_hasher(inner: R, hasher: H) -> Self {
+        Self { inner, hasher }
+    }
+
+    /// Returns a reference to the inner reader. Reading from it directly
+    /// bypasses the hasher.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Returns the hash of the bytes read so far, and the inner reader
+    pub fn finalize(mut self) -> ([u8; DIGEST_BYTES], R) {
+        (self.hasher.get_hash(), self.inner)
+    }
+}
+
+impl<const DIGEST_BYTES: usize, H: Hasher<DIGEST_BYTES>, R: Read> Read
+    for HashingReader<DIGEST_BYTES, H, R>
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.hasher.update(&buf[..n]);
+        Ok(n)
+    }
+}
+
+/// Returns the hash of the contents of the file at `path`
+pub fn digest_file<const DIGEST_BYTES: usize, H: Hasher<DIGEST_BYTES>>(
+    path: impl AsRef<Path>,
+) -> io::Result<[u8; DIGEST_BYTES]> {
+    let mut reader = HashingReader::<DIGEST_BYTES, H, _>::new(File::open(path)?);
+    io::copy(&mut reader, &mut io::sink())?;
+    Ok(reader.finalize().0)
+}
+
+/// An entry of a check file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChecksumEntry {
+    /// The expected digest
+    pub digest: Vec<u8>,
+    /// The path of the file
+    pub path: String,
+    /// `true` if the file was read in binary mode, which makes no difference
+    /// on POSIX systems. Always `false` in the BSD format.
+    pub binary: bool,
+}
+
+fn parse_hex(hex: &str) -> Result<Vec<u8>, &'static str> {
+    match Hex::LOWER.decode(hex) {
+        Ok(digest) if !digest.is_empty() => Ok(digest),
+        _ => Err("Digest must be a non-empty string of hexadecimal digits"),
+    }
+}
+
+/// Undo the escaping of `\` and line breaks in a path, which is used when the
+/// line starts with `\`
+fn unescape(path: &str) -> Result<String, &'static str> {
+    let mut result = String::with_capacity(path.len());
+    let mut chars = path.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            result.push(match chars.next() {
+                Some('\\') => '\\',
+                Some('n') => '\n',
+                Some('r') => '\r',
+                _ => return Err("Invalid escape sequence in path"),
+            });
+        } else {
+            result.push(c);
+        }
+    }
+    Ok(result)
+}
+
+/// Parse a line in the BSD format, `<algorithm> (<path>) = <hex digest>`
+fn parse_bsd_line(line: &str) -> Option<Result<ChecksumEntry, &'static str>> {
+    let (algorithm, rest) = line.split_once(" (")?;
+    if algorithm.is_empty()
+        || !algorithm
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'-')
+    {
+        return None;
+    }
+    let (path, hex) = rest.rsplit_once(") = ")?;
+    Some(parse_hex(hex).map(|digest| ChecksumEntry {
+        digest,
+        path: path.to_owned(),
+        binary: false,
+    }))
+}
+
+/// Parse a check file written by `sha256sum` or a similar tool, see the
+/// [module documentation](self). Empty lines are skipped, and any other line
+/// which is not a valid entry is an error.
+pub fn parse_checksums(text: &str) -> Result<Vec<ChecksumEntry>, &'static str> {
+    let mut entries = Vec::new();
+    for line in text.lines() {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        if line.is_empty() {
+            continue;
+        }
+        let (escaped, line) = match line.strip_prefix('\\') {
+            Some(line) => (true, line),
+            None => (false, line),
+        };
+        if !escaped {
+            if let Some(entry) = parse_bsd_line(line) {
+                entries.push(entry?);
+                continue;
+            }
+        }
+        let (hex, rest) = line.split_once(' ').ok_or("Improperly formatted line")?;
+        let binary = match rest.as_bytes().first() {
+            Some(b' ') => false,
+            Some(b'*') => true,
+            _ => return Err("Improperly formatted line"),
+        };
+        let path = &rest[1..];
+        if path.is_empty() {
+            return Err("Improperly formatted line");
+        }
+        entries.push(ChecksumEntry {
+            digest: parse_hex(hex)?,
+            path: if escaped {
+                unescape(path)?
+            } else {
+                path.to_owned()
+            },
+            binary,
+        });
+    }
+    Ok(entries)
+}
+
+/// Format an entry of a check file in the default format of `sha256sum`,
+/// escaping the path if it contains `\` or a line break
+pub fn format_checksum(digest: &[u8], path: &str) -> String {
+    let hex = Hex::LOWER.encode(digest);
+    if path.contains(['\\', '\n', '\r']) {
+        let path = path
+            .replace('\\', "\\\\")
+            .replace('\n', "\\n")
+            .replace('\r', "\\r");
+        format!("\\{hex}  {path}")
+    } else {
+        format!("{hex}  {path}")
+    }
+}
+
+/// The result of checking a file
+#[derive(Debug)]
+pub enum ChecksumStatus {
+    /// The file has the expected digest
+    Ok,
+    /// The file has a different digest
+    Mismatch,
+    /// The file could not be read
+    Unreadable(io::Error),
+}
+
+/// Check the files listed in the check file `text`, like `sha256sum -c`.
+/// Relative paths are resolved against `dir`. Returns an error if the check
+/// file can not be parsed or has a digest which is not `DIGEST_BYTES` long,
+/// and the status of every file otherwise.
+pub fn verify_checksums<const DIGEST_BYTES: usize, H: Hasher<DIGEST_BYTES>>(
+    text: &str,
+    dir: impl AsRef<Path>,
+) -> Result<Vec<(ChecksumEntry, ChecksumStatus)>, &'static str> {
+    let entries = parse_checksums(text)?;
+    if entries
+        .iter()
+        .any(|entry| entry.digest.len() != DIGEST_BYTES)
+    {
+        return Err("Digest has the wrong length for this hash function");
+    }
+    Ok(entries
+        .into_iter()
+        .map(|entry| {
+            let status = match digest_file::<DIGEST_BYTES, H>(dir.as_ref().join(&entry.path)) {
+                Ok(digest) if digest[..] == entry.digest[..] => ChecksumStatus::Ok,
+                Ok(_) => ChecksumStatus::Mismatch,
+                Err(error) => ChecksumStatus::Unreadable(error),
+            };
+            (entry, status)
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_utils::get_hash_string;
+    use super::*;
+    use std::path::PathBuf;
+
+    const ABC_SHA256: &str = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+    const EMPTY_SHA256: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+    /// A new empty directory in the temporary directory
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("algorithms-hash-io-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn write() {
+        let mut hasher = SHA256::new_default();
+        let middle = 'b';
+        write!(hasher, "a{middle}c").unwrap();
+        assert_eq!(get_hash_string(&hasher.get_hash()), ABC_SHA256);
+
+        let data = vec![7u8; 100_000];
+        let mut hasher = Blake2b::<32>::new_default();
+        io::copy(&mut &data[..], &mut hasher).unwrap();
+        let mut expected = Blake2b::<32>::new_default();
+        Hasher::update(&mut expected, &data);
+        assert_eq!(hasher.get_hash(), expected.get_hash());
+
+        let mut hasher = MD5::new_default();
+        hasher.write_all(b"abc").unwrap();
+        hasher.flush().unwrap();
+        assert_eq!(
+            get_hash_string(&hasher.get_hash()),
+            "900150983cd24fb0d6963f7d28e17f72"
+        );
+    }
+
+    #[test]
+    fn hashing_reader() {
+        let mut reader = HashingReader::<32, SHA256, _>::new(&b"abc"[..]);
+        let mut buf = [0; 2];
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"ab");
+        assert_eq!(reader.get_ref(), b"c");
+        let mut rest = Vec::new();
+        reader.read_to_end(&mut rest).unwrap();
+        assert_eq!(rest, b"c");
+        let (hash, inner) = reader.finalize();
+        assert_eq!(get_hash_string(&hash), ABC_SHA256);
+        assert!(inner.is_empty());
+
+        let mut hasher = SHA256::new_default();
+        Hasher::update(&mut hasher, b"a");
+        let mut reader = HashingReader::<32, _, _>::with_hasher(&b"bc"[..], hasher);
+        io::copy(&mut reader, &mut io::sink()).unwrap();
+        assert_eq!(get_hash_string(&reader.finalize().0), ABC_SHA256);
+    }
+
+    #[test]
+    fn parse() {
+        let text = format!(
+            "{ABC_SHA256}  abc.txt\n\
+             {EMPTY_SHA256} *dir/empty file\r\n\
+             \n\
+             SHA256 (tagged (1).txt) = {ABC_SHA256}\n\
+             \\{EMPTY_SHA256}  a\\\\b\\nc\n"
+        );
+        let entries = parse_checksums(&text).unwrap();
+        assert_eq!(entries.len(), 4);
+        assert_eq!(entries[0].path, "abc.txt");
+        assert_eq!(get_hash_string(&entries[0].digest), ABC_SHA256);
+        assert!(!entries[0].binary);
+        assert_eq!(entries[1].path, "dir/empty file");
+        assert!(entries[1].binary);
+        assert_eq!(entries[2].path, "tagged (1).txt");
+        assert_eq!(entries[3].path, "a\\b\nc");
+        assert_eq!(
+            format_checksum(&entries[3].digest, &entries[3].path),
+            format!("\\{EMPTY_SHA256}  a\\\\b\\nc")
+        );
+        assert_eq!(
+            format_checksum(&entries[0].digest, "abc.txt"),
+            format!("{ABC_SHA256}  abc.txt")
+        );
+
+        for line in [
+            "abc.txt",
+            "xyz  abc.txt",
+            "abc  abc.txt",
+            &format!("{ABC_SHA256}  "),
+            &format!("{ABC_SHA256}-abc.txt"),
+            &format!("\\{ABC_SHA256}  a\\b"),
+            "SHA256 (abc.txt) = xyz",
+        ] {
+            assert!(parse_checksums(line).is_err(), "{line}");
+        }
+    }
+
+    #[test]
+    fn files() {
+        let dir = temp_dir("files");
+        std::fs::write(dir.join("abc.txt"), "abc").unwrap();
+        std::fs::write(dir.join("empty"), "").unwrap();
+        std::fs::write(dir.join("changed"), "abd").unwrap();
+        assert_eq!(
+            get_hash_string(&digest_file::<32, SHA256>(dir.join("abc.txt")).unwrap()),
+            ABC_SHA256
+        );
+        assert!(digest_file::<32, SHA256>(dir.join("missing")).is_err());
+
+        let text = format!(
+            "{ABC_SHA256}  abc.txt\n\
+             {EMPTY_SHA256} *empty\n\
+             {ABC_SHA256}  changed\n\
+             {ABC_SHA256}  missing\n"
+        );
+        let results = verify_checksums::<32, SHA256>(&text, &dir).unwrap();
+        assert_eq!(results.len(), 4);
+        assert!(matches!(results[0].1, ChecksumStatus::Ok));
+        assert!(matches!(results[1].1, ChecksumStatus::Ok));
+        assert!(matches!(results[2].1, ChecksumStatus::Mismatch));
+        assert!(matches!(
+            &results[3].1,
+            ChecksumStatus::Unreadable(error) if error.kind() == io::ErrorKind::NotFound
+        ));
+        assert!(verify_checksums::<20, SHA1>(&text, &dir).is_err());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,5 +1,4 @@
 //! Traits to help with Hashing
-use std::cmp::Ordering;
 
 /// [`Hasher`] is a trait that defines the interface of a cryptographic hash
 pub trait Hasher<const DIGEST_BYTES: usize> {
@@ -16,16 +15,25 @@ pub trait Hasher<const DIGEST_BYTES: usize> {
 }
 
 /// HMAC based on RFC2104, applicable to many cryptographic hash functions
-#[derive(Debug)]
+///
+/// `KEY_BYTES` is the block size of the hash function, e.g. 64 for SHA-256.
+/// Once a key is added, the keyed state is kept so that the instance can be
+/// [`reset`](HMAC::reset) or cloned to authenticate many messages with the
+/// same key without hashing it again.
+#[derive(Debug, Clone)]
 #[allow(clippy::upper_case_acronyms)]
 pub struct HMAC<const KEY_BYTES: usize, const DIGEST_BYTES: usize, H: Hasher<DIGEST_BYTES>> {
     /// inner state
     pub inner_internal_state: H,
     /// outer state
     pub outer_internal_state: H,
+    /// inner state right after the key was added
+    inner_keyed_state: H,
+    /// outer state right after the key was added
+    outer_keyed_state: H,
 }
 
-impl<const KEY_BYTES: usize, const DIGEST_BYTES: usize, H: Hasher<DIGEST_BYTES>>
+impl<const KEY_BYTES: usize, const DIGEST_BYTES: usize, H: Hasher<DIGEST_BYTES> + Clone>
     HMAC<KEY_BYTES, DIGEST_BYTES, H>
 {
     /// Create a new instance with default parameters
@@ -33,36 +41,48 @@ impl<const KEY_BYTES: usize, const DIGEST_BYTES: usize, H: Hasher<DIGEST_BYTES>>
         Self {
             inner_internal_state: H::new_default(),
             outer_internal_state: H::new_default(),
+            inner_keyed_state: H::new_default(),
+            outer_keyed_state: H::new_default(),
         }
     }
 
-    /// Note that `key` must be no longer than `KEY_BYTES`. According to RFC,
-    /// if it is so, you should replace it with its hash. We do not do this
-    /// automatically due to fear of `DIGEST_BYTES` not being the same as
-    /// `KEY_BYTES` or even being longer than it
+    /// Set the key, discarding any data added so far. As required by the RFC,
+    /// a key longer than `KEY_BYTES` is replaced with its hash, which fails
+    /// only if `DIGEST_BYTES` is longer than `KEY_BYTES`.
     pub fn add_key(&mut self, key: &[u8]) -> Result<(), &'static str> {
-        match key.len().cmp(&KEY_BYTES) {
-            Ordering::Less | Ordering::Equal => {
-                let mut tmp_key = [0u8; KEY_BYTES];
-                for (d, s) in tmp_key.iter_mut().zip(key.iter()) {
-                    *d = *s;
-                }
-                // key ^ 0x363636.. should be used as inner key
-                for b in tmp_key.iter_mut() {
-                    *b ^= 0x36;
-                }
-                self.inner_internal_state.update(&tmp_key);
-                // key ^ 0x5c5c5c.. should be used as outer key, but the key is
-                // already XORed with 0x363636.. , so it must now be XORed with
-                // 0x6a6a6a..
-                for b in tmp_key.iter_mut() {
-                    *b ^= 0x6a;
-                }
-                self.outer_internal_state.update(&tmp_key);
-                Ok(())
+        let mut tmp_key = [0u8; KEY_BYTES];
+        if key.len() > KEY_BYTES {
+            if DIGEST_BYTES > KEY_BYTES {
+                return Err("Key is longer than `KEY_BYTES` and cannot be hashed");
             }
-            _ => Err("Key is longer than `KEY_BYTES`"),
+            let mut hasher = H::new_default();
+            hasher.update(key);
+            tmp_key[..DIGEST_BYTES].copy_from_slice(&hasher.get_hash());
+        } else {
+            tmp_key[..key.len()].copy_from_slice(key);
         }
+        // key ^ 0x363636.. should be used as inner key
+        for b in tmp_key.iter_mut() {
+            *b ^= 0x36;
+        }
+        self.inner_keyed_state = H::new_default();
+        self.inner_keyed_state.update(&tmp_key);
+        // key ^ 0x5c5c5c.. should be used as outer key, but the key is
+        // already XORed with 0x363636.. , so it must now be XORed with
+        // 0x6a6a6a..
+        for b in tmp_key.iter_mut() {
+            *b ^= 0x6a;
+        }
+        self.outer_keyed_state = H::new_default();
+        self.outer_keyed_state.update(&tmp_key);
+        self.reset();
+        Ok(())
+    }
+
+    /// Discard the data added so far, keeping the key
+    pub fn reset(&mut self) {
+        self.inner_internal_state = self.inner_keyed_state.clone();
+        self.outer_internal_state = self.outer_keyed_state.clone();
     }
 
     /// Add new data
@@ -70,19 +90,59 @@ impl<const KEY_BYTES: usize, const DIGEST_BYTES: usize, H: Hasher<DIGEST_BYTES>>
         self.inner_internal_state.update(data);
     }
 
-    /// Returns the finalize of this [`HMAC<KEY_BYTES, DIGEST_BYTES, H>`].
+    /// Returns the tag of the data added so far, and resets the instance so
+    /// that it can authenticate another message with the same key.
     pub fn finalize(&mut self) -> [u8; DIGEST_BYTES] {
         self.outer_internal_state
             .update(&self.inner_internal_state.get_hash());
-        self.outer_internal_state.get_hash()
+        let tag = self.outer_internal_state.get_hash();
+        self.reset();
+        tag
+    }
+
+    /// Like [`finalize`](HMAC::finalize), but only returns the leftmost
+    /// `TAG_BYTES` bytes of the tag.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `TAG_BYTES` is longer than `DIGEST_BYTES`.
+    pub fn finalize_truncated<const TAG_BYTES: usize>(&mut self) -> [u8; TAG_BYTES] {
+        assert!(TAG_BYTES <= DIGEST_BYTES, "Tag is longer than the digest");
+        let mut tag = [0u8; TAG_BYTES];
+        tag.copy_from_slice(&self.finalize()[..TAG_BYTES]);
+        tag
+    }
+
+    /// Checks `tag` against the tag of the data added so far in constant time,
+    /// and resets the instance like [`finalize`](HMAC::finalize).
+    ///
+    /// Truncated tags are accepted, but following section 5 of the RFC they
+    /// must be at least half as long as the digest and at least 10 bytes.
+    pub fn verify(&mut self, tag: &[u8]) -> bool {
+        let expected = self.finalize();
+        let min_len = (DIGEST_BYTES / 2).max(10).min(DIGEST_BYTES);
+        if tag.len() < min_len || tag.len() > DIGEST_BYTES {
+            return false;
+        }
+        constant_time_eq(&expected[..tag.len()], tag)
     }
 }
 
+/// Whether `a` and `b` are equal, in time only depending on their lengths
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    // accumulate the differences instead of returning early, so the time
+    // taken does not depend on where the first difference is
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
 #[cfg(test)]
 mod tests {
-    use super::super::sha256::tests::get_hash_string;
-    use super::super::SHA256;
-    use super::HMAC;
+    use super::super::test_utils::get_hash_string;
+    use super::super::{
+        Blake2b, Blake2s, Blake3, MD5, RIPEMD160, SHA1, SHA224, SHA256, SHA384, SHA3_224, SHA3_256,
+        SHA3_384, SHA3_512, SHA512, SHA512_224, SHA512_256,
+    };
+    use super::{Hasher, HMAC};
 
     #[test]
     fn sha256_basic() {
@@ -97,4 +157,352 @@ mod tests {
             "f585fc4536e8e7f378437465b65b6c2eb79036409b18a7d28b6d4c46d3a156f8"
         );
     }
+
+    /// Runs the 7 test cases of RFC 4231, which lists the tags of the SHA-2
+    /// family. Test case 5 is truncated to 128 bits, test cases 6 and 7 use a
+    /// key longer than the block size.
+    fn rfc4231<const KEY_BYTES: usize, const DIGEST_BYTES: usize, H>(expected: [&str; 7])
+    where
+        H: Hasher<DIGEST_BYTES> + Clone,
+    {
+        let key_4: Vec<u8> = (1..=25).collect();
+        let cases: [(&[u8], &[u8]); 7] = [
+            (&[0x0b; 20], b"Hi There"),
+            (b"Jefe", b"what do ya want for nothing?"),
+            (&[0xaa; 20], &[0xdd; 50]),
+            (&key_4, &[0xcd; 50]),
+            (&[0x0c; 20], b"Test With Truncation"),
+            (
+                &[0xaa; 131],
+                b"Test Using Larger Than Block-Size Key - Hash Key First",
+            ),
+            (
+                &[0xaa; 131],
+                b"This is a test using a larger than block-size key and a larger than \
+                block-size data. The key needs to be hashed before being used by the HMAC \
+                algorithm.",
+            ),
+        ];
+        for (i, ((key, data), expected)) in cases.into_iter().zip(expected).enumerate() {
+            let mut hmac: HMAC<KEY_BYTES, DIGEST_BYTES, H> = HMAC::new_default();
+            hmac.add_key(key).unwrap();
+            hmac.update(data);
+            let tag = if i == 4 {
+                get_hash_string(&hmac.finalize_truncated::<16>())
+            } else {
+                get_hash_string(&hmac.finalize())
+            };
+            assert_eq!(tag, expected, "test case {}", i + 1);
+        }
+    }
+
+    #[test]
+    fn rfc4231_vectors() {
+        rfc4231::<64, 28, SHA224>([
+            "896fb1128abbdf196832107cd49df33f47b4b1169912ba4f53684b22",
+            "a30e01098bc6dbbf45690f3a7e9e6d0f8bbea2a39e6148008fd05e44",
+            "7fb3cb3588c6c1f6ffa9694d7d6ad2649365b0c1f65d69d1ec8333ea",
+            "6c11506874013cac6a2abc1bb382627cec6a90d86efc012de7afec5a",
+            "0e2aea68a90c8d37c988bcdb9fca6fa8",
+            "95e9a0db962095adaebe9b2d6f0dbce2d499f112f2d2b7273fa6870e",
+            "3a854166ac5d9f023f54d517d0b39dbd946770db9c2b95c9f6f565d1",
+        ]);
+        rfc4231::<64, 32, SHA256>([
+            "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7",
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+            "773ea91e36800e46854db8ebd09181a72959098b3ef8c122d9635514ced565fe",
+            "82558a389a443c0ea4cc819899f2083a85f0faa3e578f8077a2e3ff46729665b",
+            "a3b6167473100ee06e0c796c2955552b",
+            "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54",
+            "9b09ffa71b942fcb27635fbcd5b0e944bfdc63644f0713938a7f51535c3a35e2",
+        ]);
+        rfc4231::<128, 48, SHA384>([
+            "afd03944d84895626b0825f4ab46907f15f9dadbe4101ec682aa034c7cebc59cfaea9ea9076ede7f4af152e8b2fa9cb6",
+            "af45d2e376484031617f78d2b58a6b1b9c7ef464f5a01b47e42ec3736322445e8e2240ca5e69e2c78b3239ecfab21649",
+            "88062608d3e6ad8a0aa2ace014c8a86f0aa635d947ac9febe83ef4e55966144b2a5ab39dc13814b94e3ab6e101a34f27",
+            "3e8a69b7783c25851933ab6290af6ca77a9981480850009cc5577c6e1f573b4e6801dd23c4a7d679ccf8a386c674cffb",
+            "3abf34c3503b2a23a46efc619baef897",
+            "4ece084485813e9088d2c63a041bc5b44f9ef1012a2b588f3cd11f05033ac4c60c2ef6ab4030fe8296248df163f44952",
+            "6617178e941f020d351e2f254e8fd32c602420feb0b8fb9adccebb82461e99c5a678cc31e799176d3860e6110c46523e",
+        ]);
+        rfc4231::<128, 64, SHA512>([
+            "87aa7cdea5ef619d4ff0b4241a1d6cb02379f4e2ce4ec2787ad0b30545e17cdedaa833b7d6b8a702038b274eaea3f4e4be9d914eeb61f1702e696c203a126854",
+            "164b7a7bfcf819e2e395fbe73b56e0a387bd64222e831fd610270cd7ea2505549758bf75c05a994a6d034f65f8f0e6fdcaeab1a34d4a6b4b636e070a38bce737",
+            "fa73b0089d56a284efb0f0756c890be9b1b5dbdd8ee81a3655f83e33b2279d39bf3e848279a722c806b485a47e67c807b946a337bee8942674278859e13292fb",
+            "b0ba465637458c6990e5a8c5f61d4af7e576d97ff94b872de76f8050361ee3dba91ca5c11aa25eb4d679275cc5788063a5f19741120c4f2de2adebeb10a298dd",
+            "415fad6271580a531d4179bc891d87a6",
+            "80b24263c7c1a3ebb71493c1dd7be8b49b46d1f41b4aeec1121b013783f8f3526b56d037e05f2598bd0fd2215d6a1e5295e64f73f63f0aec8b915a985d786598",
+            "e37b6a775dc87dbaa4dfa9f96e5e3ffddebd71f8867289865df5a32d20cdc944b6022cac3c4982b10d5eeb55c3e4de15134676fb6de0446065c97440fa8c6a58",
+        ]);
+    }
+
+    /// Runs the 7 test cases of RFC 2202 (MD5 and SHA-1), which RFC 2286
+    /// reuses for RIPEMD-160. Test case 5 is truncated to 96 bits.
+    fn rfc2202<const DIGEST_BYTES: usize, H>(expected: [&str; 7])
+    where
+        H: Hasher<DIGEST_BYTES> + Clone,
+    {
+        let key_4: Vec<u8> = (1..=25).collect();
+        let cases: [(&[u8], &[u8]); 7] = [
+            (&[0x0b; DIGEST_BYTES], b"Hi There"),
+            (b"Jefe", b"what do ya want for nothing?"),
+            (&[0xaa; DIGEST_BYTES], &[0xdd; 50]),
+            (&key_4, &[0xcd; 50]),
+            (&[0x0c; DIGEST_BYTES], b"Test With Truncation"),
+            (
+                &[0xaa; 80],
+                b"Test Using Larger Than Block-Size Key - Hash Key First",
+            ),
+            (
+                &[0xaa; 80],
+                b"Test Using Larger Than Block-Size Key and Larger Than One Block-Size Data",
+            ),
+        ];
+        for (i, ((key, data), expected)) in cases.into_iter().zip(expected).enumerate() {
+            let mut hmac: HMAC<64, DIGEST_BYTES, H> = HMAC::new_default();
+            hmac.add_key(key).unwrap();
+            hmac.update(data);
+            let tag = if i == 4 {
+                get_hash_string(&hmac.finalize_truncated::<12>())
+            } else {
+                get_hash_string(&hmac.finalize())
+            };
+            assert_eq!(tag, expected, "test case {}", i + 1);
+        }
+    }
+
+    #[test]
+    fn rfc2202_vectors() {
+        rfc2202::<16, MD5>([
+            "9294727a3638bb1c13f48ef8158bfc9d",
+            "750c783e6ab0b503eaa86e310a5db738",
+            "56be34521d144c88dbb8c733f0e8b3f6",
+            "697eaf0aca3a3aea3a75164746ffaa79",
+            "56461ef2342edc00f9bab995",
+            "6b1ab7fe4bd7bf8f0b62e6ce61b9d0cd",
+            "6f630fad67cda0ee1fb1f562db3aa53e",
+        ]);
+        rfc2202::<20, SHA1>([
+            "b617318655057264e28bc0b6fb378c8ef146be00",
+            "effcdf6ae5eb2fa2d27416d5f184df9c259a7c79",
+            "125d7342b9ac11cd91a39af48aa17b4f63f175d3",
+            "4c9007f4026250c6bc8414f9bf50c86c2d7235da",
+            "4c1a03424b55e07fe7f27be1",
+            "aa4ae5e15272d00e95705637ce8a3b55ed402112",
+            "e8e99d0f45237d786d6bbaa7965c7808bbff1a91",
+        ]);
+    }
+
+    #[test]
+    fn rfc2286_vectors() {
+        rfc2202::<20, RIPEMD160>([
+            "24cb4bd67d20fc1a5d2ed7732dcc39377f0a5668",
+            "dda6c0213a485a9e24f4742064a7f033b43c4069",
+            "b0b105360de759960ab4f35298e116e295d8e7c1",
+            "d5ca862f4d21d5e610e18b4cf1beb97a4365ecf4",
+            "7619693978f91d90539ae786",
+            "6466ca07ac5eac29e1bd523e5ada7605b791fd8b",
+            "69ea60798d71616cce5fd0871e23754cd75d5a0a",
+        ]);
+    }
+
+    /// Runs the messages of the NIST HMAC examples. Their keys are the bytes
+    /// `0, 1, 2, ...` and are as long as the digest, as long as a block, and
+    /// `long_key` bytes
+    fn sample_messages<const KEY_BYTES: usize, const DIGEST_BYTES: usize, H>(
+        long_key: usize,
+        expected: [&str; 3],
+    ) where
+        H: Hasher<DIGEST_BYTES> + Clone,
+    {
+        let cases = [
+            (DIGEST_BYTES, "keylen<blocklen"),
+            (KEY_BYTES, "keylen=blocklen"),
+            (long_key, "keylen>blocklen"),
+        ];
+        for ((key_bytes, case), expected) in cases.into_iter().zip(expected) {
+            let key: Vec<u8> = (0..key_bytes).map(|i| i as u8).collect();
+            let mut hmac: HMAC<KEY_BYTES, DIGEST_BYTES, H> = HMAC::new_default();
+            hmac.add_key(&key).unwrap();
+            hmac.update(format!("Sample message for {case}").as_bytes());
+            assert_eq!(get_hash_string(&hmac.finalize()), expected, "{case}");
+        }
+    }
+
+    #[test]
+    fn nist_sha3_vectors() {
+        sample_messages::<144, 28, SHA3_224>(
+            172,
+            [
+                "332cfd59347fdb8e576e77260be4aba2d6dc53117b3bfb52c6d18c04",
+                "d8b733bcf66c644a12323d564e24dcf3fc75f231f3b67968359100c7",
+                "078695eecc227c636ad31d063a15dd05a7e819a66ec6d8de1e193e59",
+            ],
+        );
+        sample_messages::<136, 32, SHA3_256>(
+            168,
+            [
+                "4fe8e202c4f058e8dddc23d8c34e467343e23555e24fc2f025d598f558f67205",
+                "68b94e2e538a9be4103bebb5aa016d47961d4d1aa906061313b557f8af2c3faa",
+                "9bcf2c238e235c3ce88404e813bd2f3a97185ac6f238c63d6229a00b07974258",
+            ],
+        );
+        sample_messages::<104, 48, SHA3_384>(
+            152,
+            [
+                "d588a3c51f3f2d906e8298c1199aa8ff6296218127f6b38a90b6afe2c5617725bc99987f79b22a557b6520db710b7f42",
+                "a27d24b592e8c8cbf6d4ce6fc5bf62d8fc98bf2d486640d9eb8099e24047837f5f3bffbe92dcce90b4ed5b1e7e44fa90",
+                "e5ae4c739f455279368ebf36d4f5354c95aa184c899d3870e460ebc288ef1f9470053f73f7c6da2a71bcaec38ce7d6ac",
+            ],
+        );
+        sample_messages::<72, 64, SHA3_512>(
+            136,
+            [
+                "4efd629d6c71bf86162658f29943b1c308ce27cdfa6db0d9c3ce81763f9cbce5f7ebe9868031db1a8f8eb7b6b95e5c5e3f657a8996c86a2f6527e307f0213196",
+                "544e257ea2a3e5ea19a590e6a24b724ce6327757723fe2751b75bf007d80f6b360744bf1b7a88ea585f9765b47911976d3191cf83c039f5ffab0d29cc9d9b6da",
+                "5f464f5e5b7848e3885e49b2c385f0694985d0e38966242dc4a5fe3fea4b37d46b65ceced5dcf59438dd840bab22269f0ba7febdb9fcf74602a35666b2a32915",
+            ],
+        );
+    }
+
+    #[test]
+    fn other_sample_messages() {
+        // tags computed with Python's `hmac` and `hashlib` modules
+        sample_messages::<128, 28, SHA512_224>(
+            200,
+            [
+                "fb4ebd56303fdaea9f3e32ed474ceb44c867fb9c80adaf9552490523",
+                "40ee7e692cb14386134f125f57c2dd9f4501545eb1adc217a9ce9843",
+                "e7d6b954bdf1a1fa383df233449d4ac17a3d32757c8bbef8e6cc3a31",
+            ],
+        );
+        sample_messages::<128, 32, SHA512_256>(
+            200,
+            [
+                "28ed65f176c5ecb28c7ff70c106490a2865f20c317048c0960d70404d181338e",
+                "25a3d3f3693d21aaddf3c8e3242f7b3ebc72e9c641a4501fd3d52f35e84cb46b",
+                "c3236c3b336e1352ad39fbd54a9c7d760033d7e3f425d829af52f2cb7c797433",
+            ],
+        );
+        sample_messages::<128, 64, Blake2b<64>>(
+            200,
+            [
+                "ace8cd990083e1bcb1d4b5327eaeddd4041434b0813e4e3f218ec65f907f2400d2cd19e37006cb50f04d7fc51537a31c7d82fe39bfe70cedccc6cea4742127c2",
+                "0e3ba122ac143b3f2c685aa5faaa40d90fb6e3e8f47ab7428b5d1f4ac77e8e6b34a3174ef5b69e6a3dc300cb7936b0505207201d903e25d922630cd9155cb0dd",
+                "3cc7ef93830552768438bb14ba88cbe597842778f460112fc0dd7e3f70d437a58e0b55053c05f10421278ac5527f459199b69d7424a6176d3cf5318109be31cd",
+            ],
+        );
+        sample_messages::<64, 32, Blake2s<32>>(
+            100,
+            [
+                "6ade25cf50b7ca951bdfa1f5579800c6a20ae2b1b53fc88a7e0617340feb752a",
+                "f6f11e6f9bd2295b612dee47fd9f613472996547e065c61fd3a0e448a627f679",
+                "08dd17f71760c8bcf83121017d95db8e2e7dad04f17add30bb9589e5b1edb29c",
+            ],
+        );
+    }
+
+    /// HMAC composed by hand from `H`, as defined by RFC 2104
+    fn composed<const KEY_BYTES: usize, const DIGEST_BYTES: usize, H>(
+        key: &[u8],
+        message: &[u8],
+    ) -> [u8; DIGEST_BYTES]
+    where
+        H: Hasher<DIGEST_BYTES>,
+    {
+        let mut block = [0; KEY_BYTES];
+        if key.len() > KEY_BYTES {
+            let mut hasher = H::new_default();
+            hasher.update(key);
+            block[..DIGEST_BYTES].copy_from_slice(&hasher.get_hash());
+        } else {
+            block[..key.len()].copy_from_slice(key);
+        }
+        let mut inner = H::new_default();
+        inner.update(&block.map(|b| b ^ 0x36));
+        inner.update(message);
+        let mut outer = H::new_default();
+        outer.update(&block.map(|b| b ^ 0x5c));
+        outer.update(&inner.get_hash());
+        outer.get_hash()
+    }
+
+    fn check_composition<const KEY_BYTES: usize, const DIGEST_BYTES: usize, H>()
+    where
+        H: Hasher<DIGEST_BYTES> + Clone,
+    {
+        for key_bytes in [0, DIGEST_BYTES, KEY_BYTES, KEY_BYTES + 1, 3 * KEY_BYTES] {
+            let key: Vec<u8> = (0..key_bytes).map(|i| (7 * i) as u8).collect();
+            for message in [&b""[..], b"Hi There", &[0xcd; 300]] {
+                let mut hmac: HMAC<KEY_BYTES, DIGEST_BYTES, H> = HMAC::new_default();
+                hmac.add_key(&key).unwrap();
+                hmac.update(message);
+                assert_eq!(
+                    hmac.finalize(),
+                    composed::<KEY_BYTES, DIGEST_BYTES, H>(&key, message),
+                    "{key_bytes} byte key, {} byte message",
+                    message.len()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn composition() {
+        check_composition::<128, 28, SHA512_224>();
+        check_composition::<128, 32, SHA512_256>();
+        check_composition::<128, 64, Blake2b<64>>();
+        check_composition::<64, 32, Blake2s<32>>();
+        check_composition::<64, 32, Blake3>();
+    }
+
+    #[test]
+    fn long_key_longer_than_digest() {
+        let mut hmac: HMAC<16, 32, SHA256> = HMAC::new_default();
+        assert!(hmac.add_key(&[0; 16]).is_ok());
+        assert!(hmac.add_key(&[0; 17]).is_err());
+    }
+
+    #[test]
+    fn reuse() {
+        let mut hmac: HMAC<64, 32, SHA256> = HMAC::new_default();
+        hmac.add_key(b"Jefe").unwrap();
+        let keyed = hmac.clone();
+        hmac.update(b"what do ya want for nothing?");
+        let expected = hmac.finalize();
+        // finalize resets to the keyed state
+        hmac.update(b"what do ya want for nothing?");
+        assert_eq!(hmac.finalize(), expected);
+        // reset discards data
+        hmac.update(b"something else");
+        hmac.reset();
+        hmac.update(b"what do ya want for nothing?");
+        assert_eq!(hmac.finalize(), expected);
+        // a clone of the keyed state does not need the key again
+        let mut clone = keyed.clone();
+        clone.update(b"what do ya want for nothing?");
+        assert_eq!(clone.finalize(), expected);
+    }
+
+    #[test]
+    fn verify() {
+        let mut hmac: HMAC<64, 32, SHA256> = HMAC::new_default();
+        hmac.add_key(b"Jefe").unwrap();
+        hmac.update(b"what do ya want for nothing?");
+        let tag = hmac.finalize();
+        for (len, valid) in [(32, true), (16, true), (15, false), (0, false)] {
+            hmac.update(b"what do ya want for nothing?");
+            assert_eq!(hmac.verify(&tag[..len]), valid);
+        }
+        let mut wrong = tag;
+        wrong[31] ^= 1;
+        hmac.update(b"what do ya want for nothing?");
+        assert!(!hmac.verify(&wrong));
+        let mut longer = tag.to_vec();
+        longer.push(0);
+        hmac.update(b"what do ya want for nothing?");
+        assert!(!hmac.verify(&longer));
+        hmac.update(b"what do ya want for nothing!");
+        assert!(!hmac.verify(&tag));
+    }
 }
@@ -0,0 +1,188 @@
+//! Hill cipher
+//!
+//! The Hill cipher splits the letters of the message into blocks of `n`, and
+//! multiplies every block, as a vector of indices in the alphabet, by an `n`x`n`
+//! key matrix modulo 26. The key must be invertible modulo 26, i.e. its
+//! determinant must be coprime with 26, so the ciphertext can be multiplied by
+//! the inverse to decrypt it.
+//!
+//! Only ASCII letters are encrypted and they keep their case and position. The
+//! number of letters must be a multiple of `n`: [`Hill::try_encrypt_bytes`]
+//! and [`Hill::try_decrypt_bytes`] return an error otherwise, and the methods
+//! of [`ClassicalCipher`] panic. Shorter messages have to be padded before
+//! encryption, traditionally with `X`s, which decryption then keeps.
+//!
+//! - [`Wikipedia`](https://en.wikipedia.org/wiki/Hill_cipher)
+
+use super::ClassicalCipher;
+use crate::math::Matrix;
+
+/// Hill cipher, see the [module documentation](self)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hill {
+    key: Matrix<i64>,
+    inverse: Matrix<i64>,
+}
+
+impl Hill {
+    /// Multiply every block of letters of `data` by `matrix`, or fail if the
+    /// number of letters is not a multiple of the block size
+    fn apply(matrix: &Matrix<i64>, data: &[u8]) -> Result<Vec<u8>, &'static str> {
+        let n = matrix.rows();
+        let mut result = data.to_vec();
+        let positions: Vec<usize> = (0..data.len())
+            .filter(|&i| data[i].is_ascii_alphabetic())
+            .collect();
+        if !positions.len().is_multiple_of(n) {
+            return Err("The number of letters must be a multiple of the key size");
+        }
+        for block in positions.chunks_exact(n) {
+            let vector = Matrix::new(
+                block
+                    .iter()
+                    .map(|&i| i64::from(result[i].to_ascii_uppercase() - b'A'))
+                    .collect(),
+                n,
+                1,
+            );
+            let product = matrix * &vector;
+            for (row, &i) in block.iter().enumerate() {
+                let base = if result[i].is_ascii_uppercase() {
+                    b'A'
+                } else {
+                    b'a'
+                };
+                result[i] = base + product[[row, 0]].rem_euclid(26) as u8;
+            }
+        }
+        Ok(result)
+    }
+
+    /// Encrypt `data`, or fail if its number of letters is not a multiple of
+    /// the key size
+    pub fn try_encrypt_bytes(&self, data: &[u8]) -> Result<Vec<u8>, &'static str> {
+        Self::apply(&self.key, data)
+    }
+
+    /// Decrypt `data`, or fail if its number of letters is not a multiple of
+    /// the key size
+    pub fn try_decrypt_bytes(&self, data: &[u8]) -> Result<Vec<u8>, &'static str> {
+        Self::apply(&self.inverse, data)
+    }
+}
+
+impl ClassicalCipher for Hill {
+    /// The key matrix, which must be square and invertible modulo 26. Its
+    /// entries are taken modulo 26.
+    type Key = Matrix<i64>;
+
+    fn new(key: Matrix<i64>) -> Result<Self, &'static str> {
+        if key.rows() == 0 || key.rows() != key.cols() {
+            return Err("Key must be a non-empty square matrix");
+        }
+        let n = key.rows();
+        let key = Matrix::new(
+            (0..n * n)
+                .map(|i| key[[i / n, i % n]].rem_euclid(26))
+                .collect(),
+            n,
+            n,
+        );
+        let inverse = key
+            .inverse_mod(26)
+            .ok_or("Key must be invertible modulo 26")?;
+        Ok(Self { key, inverse })
+    }
+
+    /// # Panics
+    ///
+    /// Panics if the number of letters is not a multiple of the key size, see
+    /// [`Hill::try_encrypt_bytes`]
+    fn encrypt_bytes(&self, data: &[u8]) -> Vec<u8> {
+        self.try_encrypt_bytes(data).unwrap()
+    }
+
+    /// # Panics
+    ///
+    /// Panics if the number of letters is not a multiple of the key size, see
+    /// [`Hill::try_decrypt_bytes`]
+    fn decrypt_bytes(&self, data: &[u8]) -> Vec<u8> {
+        self.try_decrypt_bytes(data).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matrix;
+    use quickcheck_macros::quickcheck;
+
+    fn gybnqkurp() -> Hill {
+        Hill::new(matrix![[6, 24, 1], [13, 16, 10], [20, 17, 15]]).unwrap()
+    }
+
+    #[test]
+    fn wikipedia_examples() {
+        let cipher = gybnqkurp();
+        assert_eq!(cipher.encrypt("ACT"), "POH");
+        assert_eq!(cipher.encrypt("cat"), "fin");
+        assert_eq!(cipher.decrypt("POH"), "ACT");
+        let cipher = Hill::new(matrix![[3, 3], [2, 5]]).unwrap();
+        assert_eq!(cipher.encrypt("HELP"), "HIAT");
+        assert_eq!(cipher.decrypt("HIAT"), "HELP");
+    }
+
+    #[test]
+    fn keeps_positions() {
+        let cipher = gybnqkurp();
+        let encrypted = cipher.encrypt("Act, now!");
+        assert_eq!(encrypted.len(), 9);
+        assert_eq!(cipher.decrypt(&encrypted), "Act, now!");
+    }
+
+    #[test]
+    fn partial_blocks() {
+        let cipher = gybnqkurp();
+        assert!(cipher.try_encrypt_bytes(b"Act no!").is_err());
+        assert!(cipher.try_decrypt_bytes(b"Act no!").is_err());
+        assert_eq!(cipher.try_encrypt_bytes(b"").unwrap(), b"");
+        let padded = cipher.try_encrypt_bytes(b"Act nox!").unwrap();
+        assert_eq!(cipher.try_decrypt_bytes(&padded).unwrap(), b"Act nox!");
+    }
+
+    #[test]
+    #[should_panic]
+    fn partial_block_panics() {
+        gybnqkurp().encrypt("Act no!");
+    }
+
+    #[test]
+    fn large_key_entries() {
+        let key = matrix![[6, 24, 1], [13, 16, 10], [20, 17, 15]];
+        let shifted = &key + &Matrix::new(vec![26 * (i64::MAX / 26 - 30); 9], 3, 3);
+        let negative = &key + &Matrix::new(vec![-26 * 1_000_000_000; 9], 3, 3);
+        for key in [shifted, negative] {
+            let cipher = Hill::new(key).unwrap();
+            assert_eq!(cipher, gybnqkurp());
+            assert_eq!(cipher.encrypt("ACT"), "POH");
+        }
+    }
+
+    #[test]
+    fn invalid_keys() {
+        assert!(Hill::new(matrix![[2, 4], [1, 3]]).is_err());
+        assert!(Hill::new(matrix![[1, 2, 3], [4, 5, 6]]).is_err());
+        assert!(Hill::new(Matrix::zero(0, 0)).is_err());
+    }
+
+    #[quickcheck]
+    fn round_trip(text: String) -> bool {
+        let cipher = gybnqkurp();
+        let letters = text.chars().filter(char::is_ascii_alphabetic).count();
+        let text: String = text
+            .chars()
+            .chain("XYZ".chars().take((3 - letters % 3) % 3))
+            .collect();
+        cipher.decrypt(&cipher.encrypt(&text)) == text
+    }
+}
@@ -0,0 +1,158 @@
+//! HKDF implementation
+//!
+//! This implementation is based on [`RFC5869`]. HKDF first extracts a
+//! pseudorandom key (PRK) from input keying material that may not be uniformly
+//! random, e.g. a Diffie-Hellman shared secret, and then expands it into as
+//! many output bytes as needed, bound to some context information.
+//!
+//! [`RFC5869`]: https://www.rfc-editor.org/rfc/rfc5869
+
+use super::{Hasher, HMAC};
+
+/// HKDF based on RFC5869, applicable to any hash function usable with
+/// [`HMAC`]. An instance holds a pseudorandom key, and can expand it into
+/// output keying material for any number of contexts.
+#[derive(Debug, Clone)]
+#[allow(clippy::upper_case_acronyms)]
+pub struct HKDF<const KEY_BYTES: usize, const DIGEST_BYTES: usize, H: Hasher<DIGEST_BYTES>> {
+    /// HMAC keyed with the pseudorandom key
+    hmac: HMAC<KEY_BYTES, DIGEST_BYTES, H>,
+}
+
+impl<const KEY_BYTES: usize, const DIGEST_BYTES: usize, H: Hasher<DIGEST_BYTES> + Clone>
+    HKDF<KEY_BYTES, DIGEST_BYTES, H>
+{
+    /// Extract a pseudorandom key from the input keying material `ikm`. An
+    /// empty `salt` is replaced with `DIGEST_BYTES` zeros.
+    pub fn extract(salt: &[u8], ikm: &[u8]) -> Result<[u8; DIGEST_BYTES], &'static str> {
+        let mut hmac: HMAC<KEY_BYTES, DIGEST_BYTES, H> = HMAC::new_default();
+        if salt.is_empty() {
+            hmac.add_key(&[0; DIGEST_BYTES])?;
+        } else {
+            hmac.add_key(salt)?;
+        }
+        hmac.update(ikm);
+        Ok(hmac.finalize())
+    }
+
+    /// Create a new instance from input keying material, i.e. run the extract
+    /// step
+    pub fn new(salt: &[u8], ikm: &[u8]) -> Result<Self, &'static str> {
+        Self::from_prk(&Self::extract(salt, ikm)?)
+    }
+
+    /// Create a new instance from a pseudorandom key, skipping the extract
+    /// step. `prk` must be at least `DIGEST_BYTES` long.
+    pub fn from_prk(prk: &[u8]) -> Result<Self, &'static str> {
+        if prk.len() < DIGEST_BYTES {
+            return Err("Pseudorandom key is shorter than the digest");
+        }
+        let mut hmac = HMAC::new_default();
+        hmac.add_key(prk)?;
+        Ok(Self { hmac })
+    }
+
+    /// Fill `okm` with output keying material for the context `info`. At most
+    /// `255 * DIGEST_BYTES` bytes can be derived.
+    pub fn expand(&self, info: &[u8], okm: &mut [u8]) -> Result<(), &'static str> {
+        if okm.len() > 255 * DIGEST_BYTES {
+            return Err("Output is longer than 255 digests");
+        }
+        let mut hmac = self.hmac.clone();
+        let mut previous: &[u8] = &[];
+        let mut block;
+        for (counter, chunk) in (1..=255u8).zip(okm.chunks_mut(DIGEST_BYTES)) {
+            // T(i) = HMAC(PRK, T(i - 1) | info | i)
+            hmac.update(previous);
+            hmac.update(info);
+            hmac.update(&[counter]);
+            block = hmac.finalize();
+            chunk.copy_from_slice(&block[..chunk.len()]);
+            previous = &block;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_utils::get_hash_string;
+    use super::super::{SHA1, SHA256};
+    use super::*;
+
+    fn rfc5869<const KEY_BYTES: usize, const DIGEST_BYTES: usize, H>(
+        salt: &[u8],
+        ikm: &[u8],
+        info: &[u8],
+        prk: &str,
+        okm: &str,
+    ) where
+        H: Hasher<DIGEST_BYTES> + Clone,
+    {
+        let extracted = HKDF::<KEY_BYTES, DIGEST_BYTES, H>::extract(salt, ikm).unwrap();
+        assert_eq!(get_hash_string(&extracted), prk);
+        let hkdf = HKDF::<KEY_BYTES, DIGEST_BYTES, H>::new(salt, ikm).unwrap();
+        let mut output = vec![0; okm.len() / 2];
+        hkdf.expand(info, &mut output).unwrap();
+        assert_eq!(get_hash_string(&output), okm);
+    }
+
+    #[test]
+    fn sha256_vectors() {
+        let long_salt: Vec<u8> = (0x60..=0xaf).collect();
+        let long_ikm: Vec<u8> = (0x00..=0x4f).collect();
+        let long_info: Vec<u8> = (0xb0..=0xff).collect();
+        // test case 1
+        rfc5869::<64, 32, SHA256>(
+            &(0x00..=0x0c).collect::<Vec<u8>>(),
+            &[0x0b; 22],
+            &(0xf0..=0xf9).collect::<Vec<u8>>(),
+            "077709362c2e32df0ddc3f0dc47bba6390b6c73bb50f9c3122ec844ad7c2b3e5",
+            "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf34007208d5b887185865",
+        );
+        // test case 2
+        rfc5869::<64, 32, SHA256>(
+            &long_salt,
+            &long_ikm,
+            &long_info,
+            "06a6b88c5853361a06104c9ceb35b45cef760014904671014a193f40c15fc244",
+            "b11e398dc80327a1c8e7f78c596a49344f012eda2d4efad8a050cc4c19afa97c59045a99cac7827271cb41c65e590e09da3275600c2f09b8367793a9aca3db71cc30c58179ec3e87c14c01d5c1f3434f1d87",
+        );
+        // test case 3
+        rfc5869::<64, 32, SHA256>(
+            &[],
+            &[0x0b; 22],
+            &[],
+            "19ef24a32c717b167f33a91d6f648bdf96596776afdb6377ac434c1c293ccb04",
+            "8da4e775a563c18f715f802a063c5a31b8a11f5c5ee1879ec3454e5f3c738d2d9d201395faa4b61a96c8",
+        );
+    }
+
+    #[test]
+    fn sha1_vectors() {
+        // test case 4
+        rfc5869::<64, 20, SHA1>(
+            &(0x00..=0x0c).collect::<Vec<u8>>(),
+            &[0x0b; 11],
+            &(0xf0..=0xf9).collect::<Vec<u8>>(),
+            "9b6c18c432a7bf8f0e71c8eb88f4b30baa2ba243",
+            "085a01ea1b10f36933068b56efa5ad81a4f14b822f5b091568a9cdd4f155fda2c22e422478d305f3f896",
+        );
+        // test case 7
+        rfc5869::<64, 20, SHA1>(
+            &[],
+            &[0x0c; 22],
+            &[],
+            "2adccada18779e7c2077ad2eb19d3f3e731385dd",
+            "2c91117204d745f3500d636a62f64f0ab3bae548aa53d423b0d1f27ebba6f5e5673a081d70cce7acfc48",
+        );
+    }
+
+    #[test]
+    fn validation() {
+        assert!(HKDF::<64, 32, SHA256>::from_prk(&[0; 31]).is_err());
+        let hkdf = HKDF::<64, 32, SHA256>::from_prk(&[0; 32]).unwrap();
+        assert!(hkdf.expand(b"", &mut [0; 255 * 32]).is_ok());
+        assert!(hkdf.expand(b"", &mut [0; 255 * 32 + 1]).is_err());
+    }
+}
@@ -0,0 +1,164 @@
+//! MD5 implementation
+//!
+//! This implementation is based on [`RFC1321`]. MD5 is broken as a
+//! cryptographic hash, collisions can be found in seconds, so it should only
+//! be used to check data against digests produced by other systems.
+//!
+//! [`RFC1321`]: https://www.rfc-editor.org/rfc/rfc1321
+
+use super::merkle_damgard::{BlockBuffer, Endianness};
+use super::Hasher;
+
+const H0: [u32; 4] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476];
+
+/// Per round shift amounts
+const S: [u32; 64] = [
+    7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22, 5, 9, 14, 20, 5, 9, 14, 20, 5, 9,
+    14, 20, 5, 9, 14, 20, 4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23, 6, 10, 15,
+    21, 6, 10, 15, 21, 6, 10, 15, 21, 6, 10, 15, 21,
+];
+
+/// `K[i]` is the integer part of `abs(sin(i + 1)) * 2^32`
+const K: [u32; 64] = [
+    0xd76aa478, 0xe8c7b756, 0x242070db, 0xc1bdceee, 0xf57c0faf, 0x4787c62a, 0xa8304613, 0xfd469501,
+    0x698098d8, 0x8b44f7af, 0xffff5bb1, 0x895cd7be, 0x6b901122, 0xfd987193, 0xa679438e, 0x49b40821,
+    0xf61e2562, 0xc040b340, 0x265e5a51, 0xe9b6c7aa, 0xd62f105d, 0x02441453, 0xd8a1e681, 0xe7d3fbc8,
+    0x21e1cde6, 0xc33707d6, 0xf4d50d87, 0x455a14ed, 0xa9e3e905, 0xfcefa3f8, 0x676f02d9, 0x8d2a4c8a,
+    0xfffa3942, 0x8771f681, 0x6d9d6122, 0xfde5380c, 0xa4beea44, 0x4bdecfa9, 0xf6bb4b60, 0xbebfbc70,
+    0x289b7ec6, 0xeaa127fa, 0xd4ef3085, 0x04881d05, 0xd9d4d039, 0xe6db99e5, 0x1fa27cf8, 0xc4ac5665,
+    0xf4292244, 0x432aff97, 0xab9423a7, 0xfc93a039, 0x655b59c3, 0x8f0ccc92, 0xffeff47d, 0x85845dd1,
+    0x6fa87e4f, 0xfe2ce6e0, 0xa3014314, 0x4e0811a1, 0xf7537e82, 0xbd3af235, 0x2ad7d2bb, 0xeb86d391,
+];
+
+fn process_block(h: &mut [u32; 4], block: &[u8; 64]) {
+    let mut m = [0u32; 16];
+    for (word, bytes) in m.iter_mut().zip(block.chunks_exact(4)) {
+        *word = u32::from_le_bytes(bytes.try_into().unwrap());
+    }
+    let [mut a, mut b, mut c, mut d] = *h;
+    for i in 0..64 {
+        let (f, g) = match i / 16 {
+            0 => ((b & c) | (!b & d), i),
+            1 => ((d & b) | (!d & c), (5 * i + 1) % 16),
+            2 => (b ^ c ^ d, (3 * i + 5) % 16),
+            _ => (c ^ (b | !d), (7 * i) % 16),
+        };
+        let f = f.wrapping_add(a).wrapping_add(K[i]).wrapping_add(m[g]);
+        a = d;
+        d = c;
+        c = b;
+        b = b.wrapping_add(f.rotate_left(S[i]));
+    }
+    for (h, v) in h.iter_mut().zip([a, b, c, d]) {
+        *h = h.wrapping_add(v);
+    }
+}
+
+/// MD5 implementation
+///
+/// This implementation is based on [`RFC1321`].
+///
+/// [`RFC1321`]: https://www.rfc-editor.org/rfc/rfc1321
+#[derive(Debug, Clone, Copy)]
+pub struct MD5 {
+    buffer: BlockBuffer<64>,
+    h: [u32; 4],
+}
+
+impl MD5 {
+    /// Create a new MD5 instance with the default initial hash value
+    pub fn new_default() -> Self {
+        Self {
+            buffer: BlockBuffer::new(),
+            h: H0,
+        }
+    }
+
+    /// Update the hash with the given data
+    pub fn update(&mut self, data: &[u8]) {
+        let h = &mut self.h;
+        self.buffer.update(data, |block| process_block(h, block));
+    }
+
+    /// Returns the hash of the data so far. This does not consume the state,
+    /// so more data can still be added afterwards.
+    pub fn get_hash(&self) -> [u8; 16] {
+        let mut h = self.h;
+        let mut buffer = self.buffer;
+        buffer.pad(8, Endianness::Little, |block| process_block(&mut h, block));
+        let mut result = [0u8; 16];
+        for (bytes, word) in result.chunks_exact_mut(4).zip(h) {
+            bytes.copy_from_slice(&word.to_le_bytes());
+        }
+        result
+    }
+}
+
+impl Hasher<16> for MD5 {
+    fn new_default() -> Self {
+        MD5::new_default()
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.update(data);
+    }
+
+    fn get_hash(&mut self) -> [u8; 16] {
+        MD5::get_hash(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_utils::{get_hash_string, nist_vectors};
+    use super::super::HMAC;
+    use super::*;
+
+    #[test]
+    fn rfc1321_suite() {
+        for (message, expected) in [
+            ("", "d41d8cd98f00b204e9800998ecf8427e"),
+            ("a", "0cc175b9c0f1b6a831c399e269772661"),
+            ("message digest", "f96b697d7cb7938d525a2f31aaf161d0"),
+            (
+                "abcdefghijklmnopqrstuvwxyz",
+                "c3fcd3d76192e4007dfb496cca67e13b",
+            ),
+            (
+                "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789",
+                "d174ab98d277d9f5a5611c2c9f419d9f",
+            ),
+            (
+                "12345678901234567890123456789012345678901234567890123456789012345678901234567890",
+                "57edf4a22be3c955ac49da2e2107b67a",
+            ),
+        ] {
+            let mut md5 = MD5::new_default();
+            md5.update(message.as_bytes());
+            assert_eq!(get_hash_string(&md5.get_hash()), expected);
+        }
+    }
+
+    #[test]
+    fn long_messages() {
+        nist_vectors::<16, MD5>([
+            "d41d8cd98f00b204e9800998ecf8427e",
+            "900150983cd24fb0d6963f7d28e17f72",
+            "8215ef0796a20bcaaae116d3876c664a",
+            "03dd8807a93175fb062dfb55dc7d359c",
+            "7707d6ae4e027c70eea2a935c2296f21",
+        ]);
+    }
+
+    #[test]
+    fn hmac() {
+        // RFC 2104 test case 2
+        let mut hmac: HMAC<64, 16, MD5> = HMAC::new_default();
+        hmac.add_key(b"Jefe").unwrap();
+        hmac.update(b"what do ya want for nothing?");
+        assert_eq!(
+            get_hash_string(&hmac.finalize()),
+            "750c783e6ab0b503eaa86e310a5db738"
+        );
+    }
+}
@@ -0,0 +1,275 @@
+//! Message buffering and padding shared by the Merkle–Damgård hash functions
+//!
+//! All of them split the message into fixed size blocks and finish it by
+//! appending a single `1` bit, enough `0` bits and the message length in bits,
+//! so that the padded message is a multiple of the block size.
+
+/// Byte order of the encoded message length
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Endianness {
+    Big,
+    Little,
+}
+
+/// Collects the message into blocks of `BLOCK_BYTES` and keeps track of its
+/// length, handing every complete block to a compression function.
+///
+/// The message does not have to be a whole number of bytes: while its length
+/// is not a multiple of 8, the pending bits are held in the most significant
+/// bits of `block[position]`, the other ones being zero.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct BlockBuffer<const BLOCK_BYTES: usize> {
+    block: [u8; BLOCK_BYTES],
+    /// Number of bytes currently held in `block`
+    position: usize,
+    /// Length (bits) of the message processed so far
+    length: u128,
+}
+
+impl<const BLOCK_BYTES: usize> BlockBuffer<BLOCK_BYTES> {
+    pub const fn new() -> Self {
+        Self {
+            block: [0; BLOCK_BYTES],
+            position: 0,
+            length: 0,
+        }
+    }
+
+    /// Restores a buffer holding `pending`, the end of a message of `length`
+    /// bits which was not compressed yet. Fails if `pending` does not have the
+    /// right length or has bits set after the end of the message.
+    pub fn from_parts(pending: &[u8], length: u128) -> Result<Self, &'static str> {
+        let bits = (length % (BLOCK_BYTES as u128 * 8)) as usize;
+        if pending.len() != bits.div_ceil(8) {
+            return Err("The pending data does not match the message length");
+        }
+        if !bits.is_multiple_of(8) && pending[bits / 8] << (bits % 8) != 0 {
+            return Err("The pending data has bits set after the end of the message");
+        }
+        let mut block = [0; BLOCK_BYTES];
+        block[..pending.len()].copy_from_slice(pending);
+        Ok(Self {
+            block,
+            position: bits / 8,
+            length,
+        })
+    }
+
+    /// Length (bits) of the message processed so far
+    pub const fn length(&self) -> u128 {
+        self.length
+    }
+
+    /// The end of the message which was not compressed yet, including the
+    /// incomplete last byte if any
+    pub fn pending(&self) -> &[u8] {
+        let partial = usize::from(!self.length.is_multiple_of(8));
+        &self.block[..self.position + partial]
+    }
+
+    /// Whether there are no pending bits, i.e. the message so far is a whole
+    /// number of blocks
+    pub const fn is_empty(&self) -> bool {
+        self.position == 0 && self.length.is_multiple_of(8)
+    }
+
+    /// Accounts for a block that was compressed without going through the buffer
+    pub fn add_block(&mut self) {
+        debug_assert!(self.is_empty());
+        self.length = self.length.wrapping_add(BLOCK_BYTES as u128 * 8);
+    }
+
+    /// Appends `data` to the message, calling `compress` on every block that
+    /// gets completed
+    pub fn update(&mut self, mut data: &[u8], mut compress: impl FnMut(&[u8; BLOCK_BYTES])) {
+        if !self.length.is_multiple_of(8) {
+            for &byte in data {
+                self.push_bits(byte, 8, &mut compress);
+            }
+            return;
+        }
+        self.length = self.length.wrapping_add((data.len() as u128) << 3);
+        if self.position > 0 {
+            let take = data.len().min(BLOCK_BYTES - self.position);
+            self.block[self.position..self.position + take].copy_from_slice(&data[..take]);
+            self.position += take;
+            data = &data[take..];
+            if self.position < BLOCK_BYTES {
+                return;
+            }
+            compress(&self.block);
+            self.position = 0;
+        }
+        let mut blocks = data.chunks_exact(BLOCK_BYTES);
+        for block in &mut blocks {
+            compress(block.try_into().unwrap());
+        }
+        let rest = blocks.remainder();
+        self.block[..rest.len()].copy_from_slice(rest);
+        self.position = rest.len();
+    }
+
+    /// Appends the first `bits` bits of `data` to the message, most significant
+    /// bit first, calling `compress` on every block that gets completed
+    pub fn update_bits(
+        &mut self,
+        data: &[u8],
+        bits: usize,
+        mut compress: impl FnMut(&[u8; BLOCK_BYTES]),
+    ) {
+        assert!(bits <= data.len() * 8, "Not enough data for the bit length");
+        self.update(&data[..bits / 8], &mut compress);
+        if !bits.is_multiple_of(8) {
+            self.push_bits(data[bits / 8], bits % 8, compress);
+        }
+    }
+
+    /// Appends the `count` most significant bits of `value` to the message
+    fn push_bits(&mut self, value: u8, count: usize, mut compress: impl FnMut(&[u8; BLOCK_BYTES])) {
+        let value = value & (0xff00_u16 >> count) as u8;
+        let used = (self.length % 8) as usize;
+        if used == 0 {
+            self.block[self.position] = 0;
+        }
+        self.block[self.position] |= value >> used;
+        self.length = self.length.wrapping_add(count as u128);
+        if used + count >= 8 {
+            self.position += 1;
+            if self.position == BLOCK_BYTES {
+                compress(&self.block);
+                self.position = 0;
+            }
+            if used + count > 8 {
+                self.block[self.position] = value << (8 - used);
+            }
+        }
+    }
+
+    /// Pads the message and compresses the final block(s). The length is
+    /// encoded in the last `length_bytes` bytes (8 or 16) of the last block.
+    pub fn pad(
+        &mut self,
+        length_bytes: usize,
+        endianness: Endianness,
+        mut compress: impl FnMut(&[u8; BLOCK_BYTES]),
+    ) {
+        let length_start = BLOCK_BYTES - length_bytes;
+        let used = self.length % 8;
+        if used == 0 {
+            self.block[self.position] = 0x80;
+        } else {
+            self.block[self.position] |= 0x80 >> used;
+        }
+        self.position += 1;
+        if self.position > length_start {
+            self.block[self.position..].fill(0);
+            compress(&self.block);
+            self.position = 0;
+        }
+        self.block[self.position..length_start].fill(0);
+        match endianness {
+            Endianness::Big => {
+                let bytes = self.length.to_be_bytes();
+                self.block[length_start..].copy_from_slice(&bytes[16 - length_bytes..]);
+            }
+            Endianness::Little => {
+                let bytes = self.length.to_le_bytes();
+                self.block[length_start..].copy_from_slice(&bytes[..length_bytes]);
+            }
+        }
+        compress(&self.block);
+        self.position = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn padded(message: &[u8], length_bytes: usize, endianness: Endianness) -> Vec<u8> {
+        let mut buffer = BlockBuffer::<64>::new();
+        let mut out = vec![];
+        buffer.update(message, |block| out.extend_from_slice(block));
+        buffer.pad(length_bytes, endianness, |block| out.extend_from_slice(block));
+        out
+    }
+
+    #[test]
+    fn padding_layout() {
+        let out = padded(b"abc", 8, Endianness::Big);
+        assert_eq!(out.len(), 64);
+        assert_eq!(&out[..4], b"abc\x80");
+        assert!(out[4..63].iter().all(|&b| b == 0));
+        assert_eq!(out[63], 24);
+
+        let out = padded(b"abc", 8, Endianness::Little);
+        assert_eq!(out[56], 24);
+        assert!(out[57..].iter().all(|&b| b == 0));
+    }
+
+    #[test]
+    fn padding_spills_into_extra_block() {
+        for len in 0..200 {
+            let message = vec![0xaa; len];
+            for length_bytes in [8, 16] {
+                let out = padded(&message, length_bytes, Endianness::Big);
+                assert_eq!(out.len() % 64, 0);
+                assert!(out.len() >= len + 1 + length_bytes);
+                assert!(out.len() < len + 1 + length_bytes + 64);
+                assert_eq!(out[len], 0x80);
+                let bits = u16::from_be_bytes([out[out.len() - 2], out[out.len() - 1]]);
+                assert_eq!(bits as usize, len * 8);
+            }
+        }
+    }
+
+    #[test]
+    fn split_updates() {
+        let message: Vec<u8> = (0..=255).collect();
+        let whole = padded(&message, 16, Endianness::Big);
+        let mut buffer = BlockBuffer::<64>::new();
+        let mut out = vec![];
+        for chunk in message.chunks(7) {
+            buffer.update(chunk, |block| out.extend_from_slice(block));
+        }
+        assert_eq!(buffer.length(), 256 * 8);
+        buffer.pad(16, Endianness::Big, |block| out.extend_from_slice(block));
+        assert_eq!(out, whole);
+    }
+
+    #[test]
+    fn bit_updates() {
+        let message: Vec<u8> = (0..=255).collect();
+        let whole = padded(&message, 8, Endianness::Big);
+        for head in 0..8 {
+            let mut buffer = BlockBuffer::<64>::new();
+            let mut out = vec![];
+            buffer.update_bits(&message, head, |block| out.extend_from_slice(block));
+            let tail: Vec<u8> = message
+                .windows(2)
+                .map(|w| (u16::from_be_bytes([w[0], w[1]]) << head >> 8) as u8)
+                .chain([message[255] << head])
+                .collect();
+            buffer.update_bits(&tail, 256 * 8 - head, |block| out.extend_from_slice(block));
+            assert_eq!(buffer.length(), 256 * 8);
+            buffer.pad(8, Endianness::Big, |block| out.extend_from_slice(block));
+            assert_eq!(out, whole);
+        }
+    }
+
+    #[test]
+    fn pending_round_trip() {
+        let mut buffer = BlockBuffer::<64>::new();
+        buffer.update(&[0xaa; 70], |_| {});
+        buffer.update_bits(&[0xff], 3, |_| {});
+        assert_eq!(
+            buffer.pending(),
+            &[0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xe0]
+        );
+        let restored = BlockBuffer::<64>::from_parts(buffer.pending(), buffer.length()).unwrap();
+        assert_eq!(restored.pending(), buffer.pending());
+        assert_eq!(restored.position, 6);
+        assert!(BlockBuffer::<64>::from_parts(&[0xaa; 6], 563).is_err());
+        assert!(BlockBuffer::<64>::from_parts(&[0xaa; 7], 563).is_err());
+    }
+}
@@ -0,0 +1,418 @@
+//! Merkle tree as specified by RFC 6962 (Certificate Transparency)
+//!
+//! The leaves of the tree are the hashes of the data entries, and every inner
+//! node is the hash of its two children. Leaves and inner nodes are hashed
+//! with different prefixes, `0x00` and `0x01`, so that an inner node can not
+//! be passed off as a leaf. A tree of `n > 1` leaves is split into a left
+//! subtree holding the largest power of two less than `n` leaves and a right
+//! subtree holding the rest, so trees only grow on the right.
+//!
+//! An inclusion proof shows that an entry is in a tree with a given root, and
+//! a consistency proof shows that a tree is an older version of another, i.e.
+//! the second one only appends entries to the first. Both are made of
+//! `O(log n)` hashes.
+//!
+//! - [`RFC 6962`](https://www.rfc-editor.org/rfc/rfc6962#section-2.1)
+//! - [`RFC 9162`](https://www.rfc-editor.org/rfc/rfc9162#section-2.1), which
+//!   gives the verification algorithms
+
+use std::marker::PhantomData;
+
+use super::Hasher;
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+/// Merkle tree, see the [module documentation](self)
+#[derive(Debug, Clone)]
+pub struct MerkleTree<const DIGEST_BYTES: usize, H: Hasher<DIGEST_BYTES>> {
+    leaves: Vec<[u8; DIGEST_BYTES]>,
+    hasher: PhantomData<H>,
+}
+
+/// The largest power of two less than `n`, which must be at least 2
+fn split(n: usize) -> usize {
+    1 << (usize::BITS - 1 - (n - 1).leading_zeros())
+}
+
+impl<const DIGEST_BYTES: usize, H: Hasher<DIGEST_BYTES>> MerkleTree<DIGEST_BYTES, H> {
+    /// Create a new tree with the given entries. The entries are hashed in
+    /// parallel if the `rayon` feature is enabled.
+    pub fn new<T: AsRef<[u8]> + Sync>(entries: &[T]) -> Self {
+        #[cfg(feature = "rayon")]
+        let leaves = entries
+            .par_iter()
+            .map(|entry| Self::leaf_hash(entry.as_ref()))
+            .collect();
+        #[cfg(not(feature = "rayon"))]
+        let leaves = entries
+            .iter()
+            .map(|entry| Self::leaf_hash(entry.as_ref()))
+            .collect();
+        Self {
+            leaves,
+            hasher: PhantomData,
+        }
+    }
+
+    /// Append an entry to the tree
+    pub fn push(&mut self, entry: &[u8]) {
+        self.leaves.push(Self::leaf_hash(entry));
+    }
+
+    /// Returns the number of entries
+    pub fn len(&self) -> usize {
+        self.leaves.len()
+    }
+
+    /// Returns `true` if the tree has no entries
+    pub fn is_empty(&self) -> bool {
+        self.leaves.is_empty()
+    }
+
+    /// The hash of a leaf holding `entry`
+    pub fn leaf_hash(entry: &[u8]) -> [u8; DIGEST_BYTES] {
+        let mut hasher = H::new_default();
+        hasher.update(&[0]);
+        hasher.update(entry);
+        hasher.get_hash()
+    }
+
+    /// The hash of an inner node with children `left` and `right`
+    pub fn node_hash(left: &[u8; DIGEST_BYTES], right: &[u8; DIGEST_BYTES]) -> [u8; DIGEST_BYTES] {
+        let mut hasher = H::new_default();
+        hasher.update(&[1]);
+        hasher.update(left);
+        hasher.update(right);
+        hasher.get_hash()
+    }
+
+    /// The root of the tree with the given leaves
+    fn subtree_root(leaves: &[[u8; DIGEST_BYTES]]) -> [u8; DIGEST_BYTES] {
+        match leaves.len() {
+            0 => H::new_default().get_hash(),
+            1 => leaves[0],
+            n => {
+                let (left, right) = leaves.split_at(split(n));
+                Self::node_hash(&Self::subtree_root(left), &Self::subtree_root(right))
+            }
+        }
+    }
+
+    /// The root of the tree. The root of the empty tree is the hash of the
+    /// empty string.
+    pub fn root(&self) -> [u8; DIGEST_BYTES] {
+        Self::subtree_root(&self.leaves)
+    }
+
+    /// The root of the tree holding the first `size` entries, or an error if
+    /// there are less than `size` entries
+    pub fn root_at(&self, size: usize) -> Result<[u8; DIGEST_BYTES], &'static str> {
+        if size > self.leaves.len() {
+            return Err("Size is larger than the tree");
+        }
+        Ok(Self::subtree_root(&self.leaves[..size]))
+    }
+
+    /// The hashes which, together with the entry, give the root of the tree
+    /// with the given leaves, from the bottom to the top
+    fn path(index: usize, leaves: &[[u8; DIGEST_BYTES]], proof: &mut Vec<[u8; DIGEST_BYTES]>) {
+        if leaves.len() > 1 {
+            let k = split(leaves.len());
+            let (left, right) = leaves.split_at(k);
+            if index < k {
+                Self::path(index, left, proof);
+                proof.push(Self::subtree_root(right));
+            } else {
+                Self::path(index - k, right, proof);
+                proof.push(Self::subtree_root(left));
+            }
+        }
+    }
+
+    /// The proof that the entry at `index` is in the tree, or an error if
+    /// there is no such entry
+    pub fn inclusion_proof(&self, index: usize) -> Result<Vec<[u8; DIGEST_BYTES]>, &'static str> {
+        if index >= self.leaves.len() {
+            return Err("Index is out of range");
+        }
+        let mut proof = Vec::new();
+        Self::path(index, &self.leaves, &mut proof);
+        Ok(proof)
+    }
+
+    /// Returns `true` if `proof` shows that `entry` is at `index` in the tree
+    /// of `size` entries with root `root`
+    pub fn verify_inclusion(
+        entry: &[u8],
+        index: usize,
+        size: usize,
+        proof: &[[u8; DIGEST_BYTES]],
+        root: &[u8; DIGEST_BYTES],
+    ) -> bool {
+        if index >= size {
+            return false;
+        }
+        let (mut f, mut s) = (index, size - 1);
+        let mut hash = Self::leaf_hash(entry);
+        for sibling in proof {
+            if s == 0 {
+                return false;
+            }
+            if f & 1 == 1 || f == s {
+                hash = Self::node_hash(sibling, &hash);
+                while f & 1 == 0 && f != 0 {
+                    f >>= 1;
+                    s >>= 1;
+                }
+            } else {
+                hash = Self::node_hash(&hash, sibling);
+            }
+            f >>= 1;
+            s >>= 1;
+        }
+        s == 0 && &hash == root
+    }
+
+    /// The hashes needed to prove that the first `old_size` of the given
+    /// leaves are a prefix of them. `complete` is `true` if the old tree is
+    /// this whole subtree, whose root is then known to the verifier.
+    fn subproof(
+        old_size: usize,
+        leaves: &[[u8; DIGEST_BYTES]],
+        complete: bool,
+        proof: &mut Vec<[u8; DIGEST_BYTES]>,
+    ) {
+        let n = leaves.len();
+        if old_size == n {
+            if !complete {
+                proof.push(Self::subtree_root(leaves));
+            }
+            return;
+        }
+        let k = split(n);
+        let (left, right) = leaves.split_at(k);
+        if old_size <= k {
+            Self::subproof(old_size, left, complete, proof);
+            proof.push(Self::subtree_root(right));
+        } else {
+            Self::subproof(old_size - k, right, false, proof);
+            proof.push(Self::subtree_root(left));
+        }
+    }
+
+    /// The proof that the tree of the first `old_size` entries is an older
+    /// version of this tree, or an error if `old_size` is 0 or larger than
+    /// the tree. The proof is empty if `old_size` is the size of the tree.
+    pub fn consistency_proof(
+        &self,
+        old_size: usize,
+    ) -> Result<Vec<[u8; DIGEST_BYTES]>, &'static str> {
+        if old_size == 0 || old_size > self.leaves.len() {
+            return Err("Old size must be positive and at most the size of the tree");
+        }
+        let mut proof = Vec::new();
+        Self::subproof(old_size, &self.leaves, true, &mut proof);
+        Ok(proof)
+    }
+
+    /// Returns `true` if `proof` shows that the tree of `old_size` entries
+    /// with root `old_root` is an older version of the tree of `new_size`
+    /// entries with root `new_root`
+    pub fn verify_consistency(
+        old_size: usize,
+        new_size: usize,
+        old_root: &[u8; DIGEST_BYTES],
+        new_root: &[u8; DIGEST_BYTES],
+        proof: &[[u8; DIGEST_BYTES]],
+    ) -> bool {
+        if old_size == 0 || old_size > new_size {
+            return false;
+        }
+        if old_size == new_size {
+            return proof.is_empty() && old_root == new_root;
+        }
+        let (first, rest) = if old_size.is_power_of_two() {
+            (old_root, proof)
+        } else {
+            match proof.split_first() {
+                Some(split) => split,
+                None => return false,
+            }
+        };
+        let (mut f, mut s) = (old_size - 1, new_size - 1);
+        while f & 1 == 1 {
+            f >>= 1;
+            s >>= 1;
+        }
+        let (mut old_hash, mut new_hash) = (*first, *first);
+        for hash in rest {
+            if s == 0 {
+                return false;
+            }
+            if f & 1 == 1 || f == s {
+                old_hash = Self::node_hash(hash, &old_hash);
+                new_hash = Self::node_hash(hash, &new_hash);
+                while f & 1 == 0 && f != 0 {
+                    f >>= 1;
+                    s >>= 1;
+                }
+            } else {
+                new_hash = Self::node_hash(&new_hash, hash);
+            }
+            f >>= 1;
+            s >>= 1;
+        }
+        s == 0 && &old_hash == old_root && &new_hash == new_root
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_utils::from_hex;
+    use super::super::SHA256;
+    use super::*;
+
+    type Tree = MerkleTree<32, SHA256>;
+
+    /// The entries of the Certificate Transparency test vectors
+    fn entries() -> Vec<Vec<u8>> {
+        vec![
+            vec![],
+            vec![0x00],
+            vec![0x10],
+            vec![0x20, 0x21],
+            vec![0x30, 0x31],
+            vec![0x40, 0x41, 0x42, 0x43],
+            (0x50..0x58).collect(),
+            (0x60..0x70).collect(),
+        ]
+    }
+
+    fn numbered(size: usize) -> Tree {
+        let entries: Vec<Vec<u8>> = (0..size).map(|i| i.to_string().into_bytes()).collect();
+        Tree::new(&entries)
+    }
+
+    #[test]
+    fn roots() {
+        let expected = [
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+            "6e340b9cffb37a989ca544e6bb780a2c78901d3fb33738768511a30617afa01d",
+            "fac54203e7cc696cf0dfcb42c92a1d9dbaf70ad9e621f4bd8d98662f00e3c125",
+            "aeb6bcfe274b70a14fb067a5e5578264db0fa9b51af5e0ba159158f329e06e77",
+            "d37ee418976dd95753c1c73862b9398fa2a2cf9b4ff0fdfe8b30cd95209614b7",
+            "4e3bbb1f7b478dcfe71fb631631519a3bca12c9aefca1612bfce4c13a86264d4",
+            "76e67dadbcdf1e10e1b74ddc608abd2f98dfb16fbce75277b5232a127f2087ef",
+            "ddb89be403809e325750d3d263cd78929c2942b7942a34b77e122c9594a74c8c",
+            "5dc9da79a70659a9ad559cb701ded9a2ab9d823aad2f4960cfe370eff4604328",
+        ];
+        let entries = entries();
+        let mut tree = Tree::new::<&[u8]>(&[]);
+        assert!(tree.is_empty());
+        for (size, root) in expected.iter().enumerate() {
+            assert_eq!(tree.root().to_vec(), from_hex(root));
+            assert_eq!(Tree::new(&entries[..size]).root(), tree.root());
+            if size < entries.len() {
+                tree.push(&entries[size]);
+            }
+        }
+        assert_eq!(tree.len(), 8);
+        assert_eq!(tree.root_at(3).unwrap().to_vec(), from_hex(expected[3]));
+        assert!(tree.root_at(9).is_err());
+    }
+
+    #[test]
+    fn inclusion_proofs() {
+        let entries = entries();
+        let tree = Tree::new(&entries);
+        let proof = tree.inclusion_proof(0).unwrap();
+        let expected = [
+            "96a296d224f285c67bee93c30f8a309157f0daa35dc5b87e410b78630a09cfc7",
+            "5f083f0a1a33ca076a95279832580db3e0ef4584bdff1f54c8a360f50de3031e",
+            "6b47aaf29ee3c2af9af889bc1fb9254dabd31177f16232dd6aab035ca39bf6e4",
+        ];
+        assert_eq!(proof.len(), 3);
+        for (hash, expected) in proof.iter().zip(expected) {
+            assert_eq!(hash.to_vec(), from_hex(expected));
+        }
+        assert!(tree.inclusion_proof(8).is_err());
+
+        for size in 1..=20 {
+            let tree = numbered(size);
+            let root = tree.root();
+            for index in 0..size {
+                let entry = index.to_string().into_bytes();
+                let proof = tree.inclusion_proof(index).unwrap();
+                assert!(Tree::verify_inclusion(&entry, index, size, &proof, &root));
+                assert!(!Tree::verify_inclusion(b"x", index, size, &proof, &root));
+                assert!(!Tree::verify_inclusion(&entry, size, size, &proof, &root));
+                if size > 1 {
+                    let other = (index + 1) % size;
+                    assert!(!Tree::verify_inclusion(&entry, other, size, &proof, &root));
+                    let mut tampered = proof.clone();
+                    tampered[0][0] ^= 1;
+                    assert!(!Tree::verify_inclusion(
+                        &entry, index, size, &tampered, &root
+                    ));
+                    assert!(!Tree::verify_inclusion(
+                        &entry,
+                        index,
+                        size,
+                        &proof[1..],
+                        &root
+                    ));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn consistency_proofs() {
+        let tree = Tree::new(&entries());
+        let proof = tree.consistency_proof(6).unwrap();
+        let expected = [
+            "0ebc5d3437fbe2db158b9f126a1d118e308181031d0a949f8dededebc558ef6a",
+            "ca854ea128ed050b41b35ffc1b87b8eb2bde461e9e3b5596ece6b9d5975a0ae0",
+            "d37ee418976dd95753c1c73862b9398fa2a2cf9b4ff0fdfe8b30cd95209614b7",
+        ];
+        assert_eq!(proof.len(), 3);
+        for (hash, expected) in proof.iter().zip(expected) {
+            assert_eq!(hash.to_vec(), from_hex(expected));
+        }
+        assert!(tree.consistency_proof(0).is_err());
+        assert!(tree.consistency_proof(9).is_err());
+
+        for new_size in 1..=20 {
+            let tree = numbered(new_size);
+            let new_root = tree.root();
+            for old_size in 1..=new_size {
+                let old_root = tree.root_at(old_size).unwrap();
+                let proof = tree.consistency_proof(old_size).unwrap();
+                assert!(Tree::verify_consistency(
+                    old_size, new_size, &old_root, &new_root, &proof
+                ));
+                assert!(
+                    !Tree::verify_consistency(old_size, new_size, &new_root, &old_root, &proof)
+                        || old_root == new_root
+                );
+                if !proof.is_empty() {
+                    let mut tampered = proof.clone();
+                    tampered[proof.len() - 1][0] ^= 1;
+                    assert!(!Tree::verify_consistency(
+                        old_size, new_size, &old_root, &new_root, &tampered
+                    ));
+                }
+                if old_size > 1 {
+                    assert!(!Tree::verify_consistency(
+                        old_size - 1,
+                        new_size,
+                        &old_root,
+                        &new_root,
+                        &proof
+                    ));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,144 @@
+//! PBKDF2 implementation
+//!
+//! This implementation is based on [`RFC8018`], with HMAC as the pseudorandom
+//! function. PBKDF2 slows down password guessing by iterating HMAC, but it
+//! needs almost no memory, so memory-hard functions like
+//! [`scrypt`](super::scrypt) or [`argon2`](super::argon2) are preferable for
+//! new password hashes.
+//!
+//! [`RFC8018`]: https://www.rfc-editor.org/rfc/rfc8018#section-5.2
+
+use super::{Hasher, HMAC};
+
+/// Parameters of [`pbkdf2`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(clippy::upper_case_acronyms)]
+pub struct PBKDF2Params {
+    iterations: u32,
+}
+
+impl PBKDF2Params {
+    /// Create new parameters with the given iteration count, which must be
+    /// positive
+    pub fn new(iterations: u32) -> Result<Self, &'static str> {
+        if iterations == 0 {
+            return Err("Iteration count must be positive");
+        }
+        Ok(Self { iterations })
+    }
+
+    /// Returns the iteration count
+    pub fn iterations(&self) -> u32 {
+        self.iterations
+    }
+}
+
+/// Fill `output` with a key derived from `password` and `salt` using
+/// PBKDF2-HMAC with the hash function `H`. At most `(2^32 - 1)` digests can be
+/// derived.
+pub fn pbkdf2<const KEY_BYTES: usize, const DIGEST_BYTES: usize, H>(
+    password: &[u8],
+    salt: &[u8],
+    params: &PBKDF2Params,
+    output: &mut [u8],
+) -> Result<(), &'static str>
+where
+    H: Hasher<DIGEST_BYTES> + Clone,
+{
+    if output.len() as u64 > u32::MAX as u64 * DIGEST_BYTES as u64 {
+        return Err("Output is longer than 2^32 - 1 digests");
+    }
+    let mut hmac: HMAC<KEY_BYTES, DIGEST_BYTES, H> = HMAC::new_default();
+    hmac.add_key(password)?;
+    for (counter, chunk) in (1..=u32::MAX).zip(output.chunks_mut(DIGEST_BYTES)) {
+        // U_1 = PRF(password, salt | counter), U_i = PRF(password, U_(i - 1))
+        hmac.update(salt);
+        hmac.update(&counter.to_be_bytes());
+        let mut u = hmac.finalize();
+        let mut block = u;
+        for _ in 1..params.iterations {
+            hmac.update(&u);
+            u = hmac.finalize();
+            for (b, u) in block.iter_mut().zip(u) {
+                *b ^= u;
+            }
+        }
+        chunk.copy_from_slice(&block[..chunk.len()]);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_utils::get_hash_string;
+    use super::super::{SHA1, SHA256, SHA512};
+    use super::*;
+
+    fn derive<const KEY_BYTES: usize, const DIGEST_BYTES: usize, H>(
+        password: &[u8],
+        salt: &[u8],
+        iterations: u32,
+        length: usize,
+    ) -> String
+    where
+        H: Hasher<DIGEST_BYTES> + Clone,
+    {
+        let params = PBKDF2Params::new(iterations).unwrap();
+        let mut output = vec![0; length];
+        pbkdf2::<KEY_BYTES, DIGEST_BYTES, H>(password, salt, &params, &mut output).unwrap();
+        get_hash_string(&output)
+    }
+
+    #[test]
+    fn rfc6070_sha1() {
+        for (iterations, expected) in [
+            (1, "0c60c80f961f0e71f3a9b524af6012062fe037a6"),
+            (2, "ea6c014dc72d6f8ccd1ed92ace1d41f0d8de8957"),
+            (4096, "4b007901b765489abead49d926f721d065a429c1"),
+        ] {
+            assert_eq!(
+                derive::<64, 20, SHA1>(b"password", b"salt", iterations, 20),
+                expected
+            );
+        }
+        assert_eq!(
+            derive::<64, 20, SHA1>(
+                b"passwordPASSWORDpassword",
+                b"saltSALTsaltSALTsaltSALTsaltSALTsalt",
+                4096,
+                25
+            ),
+            "3d2eec4fe41c849b80c8d83662c0e44a8b291a964cf2f07038"
+        );
+        assert_eq!(
+            derive::<64, 20, SHA1>(b"pass\0word", b"sa\0lt", 4096, 16),
+            "56fa6aa75548099dcc37d7f03425e0c3"
+        );
+    }
+
+    #[test]
+    fn rfc7914_sha256() {
+        assert_eq!(
+            derive::<64, 32, SHA256>(b"passwd", b"salt", 1, 64),
+            "55ac046e56e3089fec1691c22544b605f94185216dde0465e68b9d57c20dacbc49ca9cccf179b645991664b39d77ef317c71b845b1e30bd509112041d3a19783"
+        );
+        assert_eq!(
+            derive::<64, 32, SHA256>(b"Password", b"NaCl", 80000, 64),
+            "4ddcd8f60b98be21830cee5ef22701f9641a4418d04c0414aeff08876b34ab56a1d425a1225833549adb841b51c9b3176a272bdebba1d078478f62b397f33c8d"
+        );
+    }
+
+    #[test]
+    fn sha512() {
+        assert_eq!(
+            derive::<128, 64, SHA512>(b"password", b"salt", 2, 64),
+            "e1d9c16aa681708a45f5c7c4e215ceb66e011a2e9f0040713f18aefdb866d53cf76cab2868a39b9f7840edce4fef5a82be67335c77a6068e04112754f27ccf4e"
+        );
+    }
+
+    #[test]
+    fn validation() {
+        assert!(PBKDF2Params::new(0).is_err());
+        assert_eq!(PBKDF2Params::new(10).unwrap().iterations(), 10);
+    }
+}
@@ -0,0 +1,138 @@
+//! Playfair cipher
+//!
+//! The Playfair cipher encrypts pairs of letters with a 5x5 square built from
+//! a keyword, see [`Polybius`](super::Polybius). If the two letters are in the
+//! same row, they are replaced by the letters on their right, if they are in
+//! the same column, by the letters below them, and otherwise by the letters in
+//! their own row and the column of the other letter.
+//!
+//! Before encryption, the message is normalized: everything but letters is
+//! dropped, the letters are made uppercase and `J` is replaced by `I`. An `X`
+//! (or a `Q` after an `X`) is inserted between two equal letters of a pair and
+//! added to complete the last pair. Decryption gives the normalized message,
+//! see [`Playfair::normalize`].
+//!
+//! - [`Wikipedia`](https://en.wikipedia.org/wiki/Playfair_cipher)
+
+use super::polybius::{normalize_letter, square};
+use super::ClassicalCipher;
+
+/// Playfair cipher, see the [module documentation](self)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Playfair {
+    square: [u8; 25],
+}
+
+/// The letter used to separate `letter` from an equal one
+fn filler(letter: u8) -> u8 {
+    if letter == b'X' {
+        b'Q'
+    } else {
+        b'X'
+    }
+}
+
+/// The normalized letters of `data`, see the [module documentation](self)
+fn normalize_bytes(data: &[u8]) -> Vec<u8> {
+    let mut result = Vec::with_capacity(data.len() + 1);
+    for letter in data
+        .iter()
+        .filter(|b| b.is_ascii_alphabetic())
+        .map(|&b| normalize_letter(b))
+    {
+        if result.len() % 2 == 1 && result.last() == Some(&letter) {
+            result.push(filler(letter));
+        }
+        result.push(letter);
+    }
+    if let Some(&last) = result.last().filter(|_| result.len() % 2 == 1) {
+        result.push(filler(last));
+    }
+    result
+}
+
+impl Playfair {
+    /// The message as it is decrypted, see the [module documentation](self)
+    pub fn normalize(text: &str) -> String {
+        String::from_utf8(normalize_bytes(text.as_bytes())).unwrap()
+    }
+
+    /// Replace every pair of `data`, which must be normalized, moving `shift`
+    /// places along rows and columns
+    fn apply(&self, data: &[u8], shift: usize) -> Vec<u8> {
+        let position = |letter| self.square.iter().position(|&x| x == letter).unwrap();
+        let mut result = Vec::with_capacity(data.len());
+        for pair in data.chunks_exact(2) {
+            let (a, b) = (position(pair[0]), position(pair[1]));
+            let (row_a, column_a, row_b, column_b) = (a / 5, a % 5, b / 5, b % 5);
+            let (a, b) = if row_a == row_b {
+                (
+                    5 * row_a + (column_a + shift) % 5,
+                    5 * row_b + (column_b + shift) % 5,
+                )
+            } else if column_a == column_b {
+                (
+                    5 * ((row_a + shift) % 5) + column_a,
+                    5 * ((row_b + shift) % 5) + column_b,
+                )
+            } else {
+                (5 * row_a + column_b, 5 * row_b + column_a)
+            };
+            result.extend([self.square[a], self.square[b]]);
+        }
+        result
+    }
+}
+
+impl ClassicalCipher for Playfair {
+    /// The keyword, which may be empty and must only contain ASCII letters
+    type Key = String;
+
+    fn new(keyword: String) -> Result<Self, &'static str> {
+        Ok(Self {
+            square: square(&keyword)?,
+        })
+    }
+
+    fn encrypt_bytes(&self, data: &[u8]) -> Vec<u8> {
+        self.apply(&normalize_bytes(data), 1)
+    }
+
+    fn decrypt_bytes(&self, data: &[u8]) -> Vec<u8> {
+        self.apply(&normalize_bytes(data), 4)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quickcheck_macros::quickcheck;
+
+    #[test]
+    fn wikipedia_example() {
+        let cipher = Playfair::new("playfairexample".to_owned()).unwrap();
+        let message = "Hide the gold in the tree stump";
+        assert_eq!(Playfair::normalize(message), "HIDETHEGOLDINTHETREXESTUMP");
+        assert_eq!(cipher.encrypt(message), "BMODZBXDNABEKUDMUIXMMOUVIF");
+        assert_eq!(
+            cipher.decrypt("BMODZBXDNABEKUDMUIXMMOUVIF"),
+            "HIDETHEGOLDINTHETREXESTUMP"
+        );
+    }
+
+    #[test]
+    fn normalize() {
+        assert_eq!(Playfair::normalize(""), "");
+        assert_eq!(Playfair::normalize("jam"), "IAMX");
+        assert_eq!(Playfair::normalize("xx x"), "XQXQXQ");
+        assert_eq!(Playfair::normalize("balloon"), "BALXLOON");
+    }
+
+    #[quickcheck]
+    fn round_trip(text: String) -> bool {
+        let cipher = Playfair::new("monarchy".to_owned()).unwrap();
+        let normalized = Playfair::normalize(&text);
+        cipher.decrypt(&cipher.encrypt(&text)) == normalized
+            && Playfair::normalize(&normalized) == normalized
+    }
+}
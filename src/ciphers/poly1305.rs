@@ -0,0 +1,253 @@
+//! Poly1305 message authentication code
+//!
+//! This implementation is based on [`RFC8439`]. The message is split into 16
+//! byte blocks, which are the coefficients of a polynomial evaluated at `r`
+//! modulo `2^130 - 5`, and the result is masked with `s`. The key `(r, s)`
+//! must only be used for a single message.
+//!
+//! The accumulator is stored in five 26-bit limbs, so the products fit in 64
+//! bits, and no step branches on secret data. For a reference implementation
+//! on big integers, see [`crate::big_integer::Poly1305`].
+//!
+//! [`RFC8439`]: https://www.rfc-editor.org/rfc/rfc8439#section-2.5
+
+const MASK_26: u32 = 0x3ffffff;
+
+#[inline]
+fn le32(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes(bytes[..4].try_into().unwrap())
+}
+
+/// Poly1305 message authentication code, see the [module documentation](self)
+#[derive(Debug, Clone, Copy)]
+pub struct Poly1305 {
+    r: [u32; 5],
+    s: [u32; 4],
+    /// The accumulator
+    h: [u32; 5],
+    buffer: [u8; 16],
+    /// Number of bytes in `buffer`, which is always less than 16
+    buffered: usize,
+}
+
+impl Poly1305 {
+    /// Create a new instance with the one-time key `key`, which is `r`
+    /// followed by `s`
+    pub fn new(key: &[u8; 32]) -> Self {
+        // clamp r while splitting it into limbs
+        let r = [
+            le32(&key[0..]) & 0x3ffffff,
+            (le32(&key[3..]) >> 2) & 0x3ffff03,
+            (le32(&key[6..]) >> 4) & 0x3ffc0ff,
+            (le32(&key[9..]) >> 6) & 0x3f03fff,
+            (le32(&key[12..]) >> 8) & 0x00fffff,
+        ];
+        let s = std::array::from_fn(|i| le32(&key[16 + 4 * i..]));
+        Self {
+            r,
+            s,
+            h: [0; 5],
+            buffer: [0; 16],
+            buffered: 0,
+        }
+    }
+
+    /// Add `block` to the accumulator and multiply it by `r`. `high_bit` is
+    /// the bit above the 128 bits of the block, which is only clear for the
+    /// padded last block.
+    fn process_block(&mut self, block: &[u8; 16], high_bit: u32) {
+        let [r0, r1, r2, r3, r4] = self.r.map(u64::from);
+        let [s1, s2, s3, s4] = [r1 * 5, r2 * 5, r3 * 5, r4 * 5];
+        let h = &mut self.h;
+        h[0] += le32(&block[0..]) & MASK_26;
+        h[1] += (le32(&block[3..]) >> 2) & MASK_26;
+        h[2] += (le32(&block[6..]) >> 4) & MASK_26;
+        h[3] += (le32(&block[9..]) >> 6) & MASK_26;
+        h[4] += (le32(&block[12..]) >> 8) | high_bit;
+        let [h0, h1, h2, h3, h4] = h.map(u64::from);
+
+        // multiply by r, using 2^130 = 5 to fold the high limbs back
+        let mut d = [
+            h0 * r0 + h1 * s4 + h2 * s3 + h3 * s2 + h4 * s1,
+            h0 * r1 + h1 * r0 + h2 * s4 + h3 * s3 + h4 * s2,
+            h0 * r2 + h1 * r1 + h2 * r0 + h3 * s4 + h4 * s3,
+            h0 * r3 + h1 * r2 + h2 * r1 + h3 * r0 + h4 * s4,
+            h0 * r4 + h1 * r3 + h2 * r2 + h3 * r1 + h4 * r0,
+        ];
+
+        // partial carry propagation, which leaves h[1] slightly above 26 bits
+        for i in 0..4 {
+            d[i + 1] += d[i] >> 26;
+            h[i] = d[i] as u32 & MASK_26;
+        }
+        h[4] = d[4] as u32 & MASK_26;
+        h[0] += (d[4] >> 26) as u32 * 5;
+        h[1] += h[0] >> 26;
+        h[0] &= MASK_26;
+    }
+
+    /// Add `data` to the authenticated message
+    pub fn update(&mut self, mut data: &[u8]) {
+        if self.buffered > 0 {
+            let take = data.len().min(16 - self.buffered);
+            self.buffer[self.buffered..self.buffered + take].copy_from_slice(&data[..take]);
+            self.buffered += take;
+            data = &data[take..];
+            if self.buffered < 16 {
+                return;
+            }
+            let block = self.buffer;
+            self.process_block(&block, 1 << 24);
+            self.buffered = 0;
+        }
+        let mut blocks = data.chunks_exact(16);
+        for block in &mut blocks {
+            self.process_block(block.try_into().unwrap(), 1 << 24);
+        }
+        let rest = blocks.remainder();
+        self.buffer[..rest.len()].copy_from_slice(rest);
+        self.buffered = rest.len();
+    }
+
+    /// Returns the tag of the message
+    pub fn finalize(mut self) -> [u8; 16] {
+        if self.buffered > 0 {
+            // the last block is padded with a one and zeros instead of having
+            // the high bit set
+            let mut block = [0u8; 16];
+            block[..self.buffered].copy_from_slice(&self.buffer[..self.buffered]);
+            block[self.buffered] = 1;
+            self.process_block(&block, 0);
+        }
+
+        // full carry propagation
+        let h = &mut self.h;
+        for i in 1..5 {
+            h[i] += h[i - 1] >> 26;
+            h[i - 1] &= MASK_26;
+        }
+        h[0] += (h[4] >> 26) * 5;
+        h[4] &= MASK_26;
+        h[1] += h[0] >> 26;
+        h[0] &= MASK_26;
+
+        // g = h + 5 - 2^130, which is the reduced value if it is not negative
+        let mut g = [0u32; 5];
+        let mut carry = 5;
+        for (g, h) in g.iter_mut().zip(h.iter()) {
+            let sum = h + carry;
+            *g = sum & MASK_26;
+            carry = sum >> 26;
+        }
+        g[4] = g[4].wrapping_add(carry << 26).wrapping_sub(1 << 26);
+        // all ones if g is not negative, zero otherwise
+        let mask = (g[4] >> 31).wrapping_sub(1);
+        for (h, g) in h.iter_mut().zip(g) {
+            *h = (*h & !mask) | (g & mask);
+        }
+
+        // h mod 2^128 + s
+        let words = [
+            h[0] | (h[1] << 26),
+            (h[1] >> 6) | (h[2] << 20),
+            (h[2] >> 12) | (h[3] << 14),
+            (h[3] >> 18) | (h[4] << 8),
+        ];
+        let mut tag = [0u8; 16];
+        let mut carry = 0;
+        for ((bytes, word), s) in tag.chunks_exact_mut(4).zip(words).zip(self.s) {
+            let sum = u64::from(word) + u64::from(s) + carry;
+            bytes.copy_from_slice(&(sum as u32).to_le_bytes());
+            carry = sum >> 32;
+        }
+        tag
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_utils::get_hash_string;
+    use super::*;
+
+    fn mac(key: &[u8; 32], message: &[u8]) -> String {
+        let mut poly = Poly1305::new(key);
+        poly.update(message);
+        get_hash_string(&poly.finalize())
+    }
+
+    fn key(r: &[u8], s: &[u8]) -> [u8; 32] {
+        let mut key = [0u8; 32];
+        key[..r.len()].copy_from_slice(r);
+        key[16..16 + s.len()].copy_from_slice(s);
+        key
+    }
+
+    #[test]
+    fn rfc8439_vector() {
+        // RFC8439 2.5.2
+        let key = [
+            0x85, 0xd6, 0xbe, 0x78, 0x57, 0x55, 0x6d, 0x33, 0x7f, 0x44, 0x52, 0xfe, 0x42, 0xd5,
+            0x06, 0xa8, 0x01, 0x03, 0x80, 0x8a, 0xfb, 0x0d, 0xb2, 0xfd, 0x4a, 0xbf, 0xf6, 0xaf,
+            0x41, 0x49, 0xf5, 0x1b,
+        ];
+        let message = b"Cryptographic Forum Research Group";
+        assert_eq!(mac(&key, message), "a8061dc1305136c6c22b8baf0c0127a9");
+        // feeding the message in pieces gives the same tag
+        for piece in 1..=17 {
+            let mut poly = Poly1305::new(&key);
+            message.chunks(piece).for_each(|chunk| poly.update(chunk));
+            assert_eq!(
+                get_hash_string(&poly.finalize()),
+                "a8061dc1305136c6c22b8baf0c0127a9"
+            );
+        }
+    }
+
+    #[test]
+    fn rfc8439_edge_cases() {
+        // RFC8439 A.3, test vectors 5 to 9, which exercise the final reduction
+        assert_eq!(
+            mac(&key(&[2], &[]), &[0xff; 16]),
+            "03000000000000000000000000000000"
+        );
+        assert_eq!(
+            mac(&key(&[2], &[0xff; 16]), &key(&[2], &[])[..16]),
+            "03000000000000000000000000000000"
+        );
+        let mut message = [0xff; 48];
+        message[16] = 0xf0;
+        message[32..].copy_from_slice(&key(&[0x11], &[])[..16]);
+        assert_eq!(
+            mac(&key(&[1], &[]), &message),
+            "05000000000000000000000000000000"
+        );
+        let mut message = [0xfe; 48];
+        message[..16].copy_from_slice(&[0xff; 16]);
+        message[16] = 0xfb;
+        message[32..].copy_from_slice(&[0x01; 16]);
+        assert_eq!(
+            mac(&key(&[1], &[]), &message),
+            "00000000000000000000000000000000"
+        );
+        let mut message = [0xff; 16];
+        message[0] = 0xfd;
+        assert_eq!(
+            mac(&key(&[2], &[]), &message),
+            "faffffffffffffffffffffffffffffff"
+        );
+    }
+
+    #[cfg(feature = "big-math")]
+    #[quickcheck_macros::quickcheck]
+    fn matches_big_integer_reference(key: Vec<u8>, message: Vec<u8>) -> bool {
+        let key: [u8; 32] = std::array::from_fn(|i| key.get(i).copied().unwrap_or(0xff));
+        let mut reference = crate::big_integer::Poly1305::new();
+        reference.set_key(&key);
+        for chunk in message.chunks(16) {
+            let mut block = [0u8; 16];
+            block[..chunk.len()].copy_from_slice(chunk);
+            reference.add_msg(&block, chunk.len() as u64);
+        }
+        mac(&key, &message) == get_hash_string(&reference.get_tag())
+    }
+}
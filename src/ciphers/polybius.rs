@@ -0,0 +1,133 @@
+//! Polybius square
+//!
+//! The 25 letters of the alphabet without `J` are written in a 5x5 square,
+//! starting with the letters of an optional keyword. Every letter is then
+//! replaced by the digits of its row and column, from 1 to 5.
+//!
+//! `J` is encrypted as `I` and letters are decrypted as uppercase, so
+//! decryption gives [`Polybius::normalize`] of the message. Digits are dropped
+//! from the message, since they could not be told apart from the encrypted
+//! letters, and the other characters are left as they are.
+//!
+//! - [`Wikipedia`](https://en.wikipedia.org/wiki/Polybius_square)
+
+use super::ClassicalCipher;
+
+/// The 5x5 square, row by row, starting with the letters of `keyword` in the
+/// order they first appear. `J` is merged with `I`.
+pub(crate) fn square(keyword: &str) -> Result<[u8; 25], &'static str> {
+    if !keyword.bytes().all(|b| b.is_ascii_alphabetic()) {
+        return Err("Keyword must only contain ASCII letters");
+    }
+    let mut square = [0u8; 25];
+    let mut len = 0;
+    for letter in keyword.bytes().map(normalize_letter).chain(b'A'..=b'Z') {
+        if letter != b'J' && !square[..len].contains(&letter) {
+            square[len] = letter;
+            len += 1;
+        }
+    }
+    Ok(square)
+}
+
+/// The uppercase version of an ASCII letter, with `J` replaced by `I`
+pub(crate) fn normalize_letter(letter: u8) -> u8 {
+    match letter.to_ascii_uppercase() {
+        b'J' => b'I',
+        letter => letter,
+    }
+}
+
+/// Polybius square cipher, see the [module documentation](self)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Polybius {
+    square: [u8; 25],
+}
+
+impl Polybius {
+    /// The message as it is decrypted: uppercase, with `J` replaced by `I`
+    /// and without digits
+    pub fn normalize(text: &str) -> String {
+        text.chars()
+            .filter(|c| !c.is_ascii_digit())
+            .map(|c| {
+                if c.is_ascii_alphabetic() {
+                    char::from(normalize_letter(c as u8))
+                } else {
+                    c
+                }
+            })
+            .collect()
+    }
+}
+
+impl ClassicalCipher for Polybius {
+    /// The keyword, which may be empty and must only contain ASCII letters
+    type Key = String;
+
+    fn new(keyword: String) -> Result<Self, &'static str> {
+        Ok(Self {
+            square: square(&keyword)?,
+        })
+    }
+
+    fn encrypt_bytes(&self, data: &[u8]) -> Vec<u8> {
+        let mut result = Vec::with_capacity(2 * data.len());
+        for &b in data {
+            if b.is_ascii_alphabetic() {
+                let position = self.square.iter().position(|&x| x == normalize_letter(b));
+                let position = position.unwrap() as u8;
+                result.extend([b'1' + position / 5, b'1' + position % 5]);
+            } else if !b.is_ascii_digit() {
+                result.push(b);
+            }
+        }
+        result
+    }
+
+    fn decrypt_bytes(&self, data: &[u8]) -> Vec<u8> {
+        let mut result = Vec::with_capacity(data.len());
+        let mut i = 0;
+        while i < data.len() {
+            match data[i..] {
+                [row @ b'1'..=b'5', column @ b'1'..=b'5', ..] => {
+                    result.push(self.square[usize::from(5 * (row - b'1') + column - b'1')]);
+                    i += 2;
+                }
+                _ => {
+                    result.push(data[i]);
+                    i += 1;
+                }
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quickcheck_macros::quickcheck;
+
+    #[test]
+    fn standard_square() {
+        let cipher = Polybius::new(String::new()).unwrap();
+        assert_eq!(cipher.encrypt("Hello, world"), "2315313134, 5234423114");
+        assert_eq!(cipher.decrypt("2315313134, 5234423114"), "HELLO, WORLD");
+        assert_eq!(cipher.encrypt("Jig 42"), "242422 ");
+    }
+
+    #[test]
+    fn keyword() {
+        assert_eq!(&square("zebras").unwrap()[..10], b"ZEBRASCDFG");
+        let cipher = Polybius::new("ZEBRAS".to_owned()).unwrap();
+        assert_eq!(cipher.encrypt("ZEBRA"), "1112131415");
+        assert!(Polybius::new("two words".to_owned()).is_err());
+    }
+
+    #[quickcheck]
+    fn round_trip(text: String) -> bool {
+        let cipher = Polybius::new("keyword".to_owned()).unwrap();
+        cipher.decrypt(&cipher.encrypt(&text)) == Polybius::normalize(&text)
+    }
+}
@@ -0,0 +1,182 @@
+//! RIPEMD-160 implementation
+//!
+//! This implementation is based on the [`RIPEMD-160 specification`]. Each
+//! block is compressed by two parallel lines of 80 steps with different
+//! boolean functions, constants and message word orders, which are combined
+//! at the end.
+//!
+//! [`RIPEMD-160 specification`]: https://homes.esat.kuleuven.be/~bosselae/ripemd160.html
+
+use super::merkle_damgard::{BlockBuffer, Endianness};
+use super::Hasher;
+
+const H0: [u32; 5] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+
+const K_LEFT: [u32; 5] = [0x00000000, 0x5a827999, 0x6ed9eba1, 0x8f1bbcdc, 0xa953fd4e];
+const K_RIGHT: [u32; 5] = [0x50a28be6, 0x5c4dd124, 0x6d703ef3, 0x7a6d76e9, 0x00000000];
+
+/// Message word selection of the left line
+const R_LEFT: [usize; 80] = [
+    0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 7, 4, 13, 1, 10, 6, 15, 3, 12, 0, 9, 5,
+    2, 14, 11, 8, 3, 10, 14, 4, 9, 15, 8, 1, 2, 7, 0, 6, 13, 11, 5, 12, 1, 9, 11, 10, 0, 8, 12, 4,
+    13, 3, 7, 15, 14, 5, 6, 2, 4, 0, 5, 9, 7, 12, 2, 10, 14, 1, 3, 8, 11, 6, 15, 13,
+];
+
+/// Message word selection of the right line
+const R_RIGHT: [usize; 80] = [
+    5, 14, 7, 0, 9, 2, 11, 4, 13, 6, 15, 8, 1, 10, 3, 12, 6, 11, 3, 7, 0, 13, 5, 10, 14, 15, 8, 12,
+    4, 9, 1, 2, 15, 5, 1, 3, 7, 14, 6, 9, 11, 8, 12, 2, 10, 0, 4, 13, 8, 6, 4, 1, 3, 11, 15, 0, 5,
+    12, 2, 13, 9, 7, 10, 14, 12, 15, 10, 4, 1, 5, 8, 7, 6, 2, 13, 14, 0, 3, 9, 11,
+];
+
+/// Rotation amounts of the left line
+const S_LEFT: [u32; 80] = [
+    11, 14, 15, 12, 5, 8, 7, 9, 11, 13, 14, 15, 6, 7, 9, 8, 7, 6, 8, 13, 11, 9, 7, 15, 7, 12, 15,
+    9, 11, 7, 13, 12, 11, 13, 6, 7, 14, 9, 13, 15, 14, 8, 13, 6, 5, 12, 7, 5, 11, 12, 14, 15, 14,
+    15, 9, 8, 9, 14, 5, 6, 8, 6, 5, 12, 9, 15, 5, 11, 6, 8, 13, 12, 5, 12, 13, 14, 11, 8, 5, 6,
+];
+
+/// Rotation amounts of the right line
+const S_RIGHT: [u32; 80] = [
+    8, 9, 9, 11, 13, 15, 15, 5, 7, 7, 8, 11, 14, 14, 12, 6, 9, 13, 15, 7, 12, 8, 9, 11, 7, 7, 12,
+    7, 6, 15, 13, 11, 9, 7, 15, 11, 8, 6, 6, 14, 12, 13, 5, 14, 13, 13, 7, 5, 15, 5, 8, 11, 14, 14,
+    6, 14, 6, 9, 12, 9, 12, 5, 15, 8, 8, 5, 12, 9, 12, 5, 14, 6, 8, 13, 6, 5, 15, 13, 11, 11,
+];
+
+#[inline]
+fn f(j: usize, x: u32, y: u32, z: u32) -> u32 {
+    match j / 16 {
+        0 => x ^ y ^ z,
+        1 => (x & y) | (!x & z),
+        2 => (x | !y) ^ z,
+        3 => (x & z) | (y & !z),
+        _ => x ^ (y | !z),
+    }
+}
+
+fn process_block(h: &mut [u32; 5], block: &[u8; 64]) {
+    let mut x = [0u32; 16];
+    for (word, bytes) in x.iter_mut().zip(block.chunks_exact(4)) {
+        *word = u32::from_le_bytes(bytes.try_into().unwrap());
+    }
+    let mut left = *h;
+    let mut right = *h;
+    for j in 0..80 {
+        // the right line uses the boolean functions in reverse order
+        for (line, function, k, r, s) in [
+            (&mut left, j, K_LEFT[j / 16], R_LEFT[j], S_LEFT[j]),
+            (&mut right, 79 - j, K_RIGHT[j / 16], R_RIGHT[j], S_RIGHT[j]),
+        ] {
+            let [a, b, c, d, e] = *line;
+            let t = a
+                .wrapping_add(f(function, b, c, d))
+                .wrapping_add(x[r])
+                .wrapping_add(k)
+                .rotate_left(s)
+                .wrapping_add(e);
+            *line = [e, t, b, c.rotate_left(10), d];
+        }
+    }
+    *h = [
+        h[1].wrapping_add(left[2]).wrapping_add(right[3]),
+        h[2].wrapping_add(left[3]).wrapping_add(right[4]),
+        h[3].wrapping_add(left[4]).wrapping_add(right[0]),
+        h[4].wrapping_add(left[0]).wrapping_add(right[1]),
+        h[0].wrapping_add(left[1]).wrapping_add(right[2]),
+    ];
+}
+
+/// RIPEMD-160 implementation
+///
+/// This implementation is based on the [`RIPEMD-160 specification`].
+///
+/// [`RIPEMD-160 specification`]: https://homes.esat.kuleuven.be/~bosselae/ripemd160.html
+#[derive(Debug, Clone, Copy)]
+pub struct RIPEMD160 {
+    buffer: BlockBuffer<64>,
+    h: [u32; 5],
+}
+
+impl RIPEMD160 {
+    /// Create a new RIPEMD160 instance with the default initial hash value
+    pub fn new_default() -> Self {
+        Self {
+            buffer: BlockBuffer::new(),
+            h: H0,
+        }
+    }
+
+    /// Update the hash with the given data
+    pub fn update(&mut self, data: &[u8]) {
+        let h = &mut self.h;
+        self.buffer.update(data, |block| process_block(h, block));
+    }
+
+    /// Returns the hash of the data so far. This does not consume the state,
+    /// so more data can still be added afterwards.
+    pub fn get_hash(&self) -> [u8; 20] {
+        let mut h = self.h;
+        let mut buffer = self.buffer;
+        buffer.pad(8, Endianness::Little, |block| process_block(&mut h, block));
+        let mut result = [0u8; 20];
+        for (bytes, word) in result.chunks_exact_mut(4).zip(h) {
+            bytes.copy_from_slice(&word.to_le_bytes());
+        }
+        result
+    }
+}
+
+impl Hasher<20> for RIPEMD160 {
+    fn new_default() -> Self {
+        RIPEMD160::new_default()
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.update(data);
+    }
+
+    fn get_hash(&mut self) -> [u8; 20] {
+        RIPEMD160::get_hash(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_utils::{get_hash_string, nist_vectors};
+    use super::*;
+
+    #[test]
+    fn reference_vectors() {
+        for (message, expected) in [
+            ("a", "0bdc9d2d256b3ee9daae347be6f4dc835a467ffe"),
+            ("message digest", "5d0689ef49d2fae572b881b123a85ffa21595f36"),
+            (
+                "abcdefghijklmnopqrstuvwxyz",
+                "f71c27109c692c1b56bbdceb5b9d2865b3708dbc",
+            ),
+            (
+                "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789",
+                "b0e20b6e3116640286ed3a87a5713079b21f5189",
+            ),
+            (
+                "12345678901234567890123456789012345678901234567890123456789012345678901234567890",
+                "9b752e45573d4b39f4dbd3323cab82bf63326bfb",
+            ),
+        ] {
+            let mut ripemd = RIPEMD160::new_default();
+            ripemd.update(message.as_bytes());
+            assert_eq!(get_hash_string(&ripemd.get_hash()), expected);
+        }
+    }
+
+    #[test]
+    fn long_messages() {
+        nist_vectors::<20, RIPEMD160>([
+            "9c1185a5c5e9fc54612808977ee8f548b2258d31",
+            "8eb208f7e05d987a9b044a8e98c6b087f15a0bfc",
+            "12a053384a9c0c88e405a06c27dcf49ada62eb2b",
+            "6f3fa39b6b503c384f919a49a7aa5c2c08bdfb45",
+            "52783243c1697bdbe16d37f97f68f08325dc1528",
+        ]);
+    }
+}
@@ -1,22 +1,38 @@
 //! rot13 or rotate 13 cipher algorithm
 
+use super::caesar::rotate_letter;
+use super::ClassicalCipher;
+
+/// ROT13, the Caesar cipher with a shift of 13, which is its own inverse
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Rot13;
+
+impl ClassicalCipher for Rot13 {
+    type Key = ();
+
+    fn new(_: ()) -> Result<Self, &'static str> {
+        Ok(Self)
+    }
+
+    fn encrypt_bytes(&self, data: &[u8]) -> Vec<u8> {
+        data.iter().map(|&b| rotate_letter(b, 13)).collect()
+    }
+
+    fn decrypt_bytes(&self, data: &[u8]) -> Vec<u8> {
+        self.encrypt_bytes(data)
+    }
+}
+
+/// ROT13 of the uppercase version of `text`
 pub fn rot13<T: AsRef<str>>(text: T) -> String {
-    // let text = text.as_ref();
-    // let to_enc = text.to_uppercase();
-    text.as_ref()
-        .to_uppercase()
-        .chars()
-        .map(|c| match c {
-            'A'..='M' => ((c as u8) + 13) as char,
-            'N'..='Z' => ((c as u8) - 13) as char,
-            _ => c,
-        })
-        .collect()
+    Rot13.encrypt(&text.as_ref().to_uppercase())
 }
 
 #[cfg(test)]
 mod tests {
-    use super::rot13;
+    use super::super::classical_cipher::tests::round_trips;
+    use super::*;
+    use quickcheck_macros::quickcheck;
 
     #[test]
     fn single_character() {
@@ -37,4 +53,14 @@ mod tests {
     fn double_encoded() {
         assert_eq!(rot13(rot13("ABCD")), "ABCD");
     }
+
+    #[test]
+    fn keeps_case() {
+        assert_eq!(Rot13.encrypt("Hello"), "Uryyb");
+    }
+
+    #[quickcheck]
+    fn round_trip(text: String, bytes: Vec<u8>) -> bool {
+        round_trips(&Rot13, &text, &bytes) && Rot13.encrypt(&Rot13.encrypt(&text)) == text
+    }
 }
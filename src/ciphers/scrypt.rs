@@ -0,0 +1,223 @@
+//! scrypt implementation
+//!
+//! This implementation is based on [`RFC7914`]. scrypt is memory-hard: it
+//! fills `128 * r * N` bytes with a chain of Salsa20/8 based mixes, then reads
+//! them back in a data-dependent order, so computing it with less memory costs
+//! a lot more time. The password is stretched with [`pbkdf2`] on SHA-256 before
+//! and after the mixing.
+//!
+//! [`RFC7914`]: https://www.rfc-editor.org/rfc/rfc7914
+
+use super::pbkdf2::{pbkdf2, PBKDF2Params};
+use super::SHA256;
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+/// Parameters of [`scrypt`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScryptParams {
+    log_n: u8,
+    r: u32,
+    p: u32,
+}
+
+impl ScryptParams {
+    /// Create new parameters with the CPU/memory cost `N = 2^log_n`, the
+    /// block size `r` and the parallelization `p`.
+    ///
+    /// As required by the RFC, `N` must be greater than 1 and less than
+    /// `2^(16 * r)`, and `r * p` must be less than `2^30`. The `128 * r * N`
+    /// bytes of memory must also be addressable.
+    pub fn new(log_n: u8, r: u32, p: u32) -> Result<Self, &'static str> {
+        if r == 0 || p == 0 {
+            return Err("Block size and parallelization must be positive");
+        }
+        if log_n == 0 || log_n as u64 >= 16 * r as u64 {
+            return Err("Invalid cost parameter");
+        }
+        if r as u64 * p as u64 >= 1 << 30 {
+            return Err("Block size times parallelization is too large");
+        }
+        1usize
+            .checked_shl(log_n as u32)
+            .and_then(|n| n.checked_mul(128))
+            .and_then(|bytes| bytes.checked_mul(r as usize))
+            .ok_or("Not enough address space")?;
+        Ok(Self { log_n, r, p })
+    }
+
+    /// Returns the base 2 logarithm of the CPU/memory cost `N`
+    pub fn log_n(&self) -> u8 {
+        self.log_n
+    }
+
+    /// Returns the block size `r`
+    pub fn r(&self) -> u32 {
+        self.r
+    }
+
+    /// Returns the parallelization `p`
+    pub fn p(&self) -> u32 {
+        self.p
+    }
+}
+
+/// The Salsa20/8 core, working on the 16 little endian words of a 64 byte
+/// block
+fn salsa20_8(block: &mut [u32; 16]) {
+    let mut x = *block;
+    for _ in 0..4 {
+        for [a, b, c, d] in [
+            // columns
+            [0, 4, 8, 12],
+            [5, 9, 13, 1],
+            [10, 14, 2, 6],
+            [15, 3, 7, 11],
+            // rows
+            [0, 1, 2, 3],
+            [5, 6, 7, 4],
+            [10, 11, 8, 9],
+            [15, 12, 13, 14],
+        ] {
+            x[b] ^= x[a].wrapping_add(x[d]).rotate_left(7);
+            x[c] ^= x[b].wrapping_add(x[a]).rotate_left(9);
+            x[d] ^= x[c].wrapping_add(x[b]).rotate_left(13);
+            x[a] ^= x[d].wrapping_add(x[c]).rotate_left(18);
+        }
+    }
+    for (b, x) in block.iter_mut().zip(x) {
+        *b = b.wrapping_add(x);
+    }
+}
+
+/// scryptBlockMix of the `2 * r` blocks of 16 words in `input` into `output`
+fn block_mix(input: &[u32], output: &mut [u32]) {
+    let blocks = input.len() / 16;
+    let mut x: [u32; 16] = input[input.len() - 16..].try_into().unwrap();
+    for (i, block) in input.chunks_exact(16).enumerate() {
+        for (x, b) in x.iter_mut().zip(block) {
+            *x ^= b;
+        }
+        salsa20_8(&mut x);
+        // even blocks go to the first half of the output, odd ones to the second
+        let position = (i / 2 + (i % 2) * blocks / 2) * 16;
+        output[position..position + 16].copy_from_slice(&x);
+    }
+}
+
+/// scryptROMix of `block`, which holds `32 * r` words, with `n` memory blocks
+fn ro_mix(block: &mut [u32], n: usize) {
+    let len = block.len();
+    let mut v = vec![0u32; len * n];
+    let mut scratch = vec![0u32; len];
+    for chunk in v.chunks_exact_mut(len) {
+        chunk.copy_from_slice(block);
+        block_mix(chunk, block);
+    }
+    for _ in 0..n {
+        // Integerify: the first two words of the last 64 byte block, read as
+        // a little endian integer, since N may not fit in 32 bits
+        let integer = u64::from(block[len - 16]) | u64::from(block[len - 15]) << 32;
+        let j = (integer & (n as u64 - 1)) as usize;
+        scratch.copy_from_slice(&v[j * len..(j + 1) * len]);
+        for (x, b) in scratch.iter_mut().zip(block.iter()) {
+            *x ^= b;
+        }
+        block_mix(&scratch, block);
+    }
+}
+
+/// Fill `output` with a key derived from `password` and `salt` using scrypt
+pub fn scrypt(
+    password: &[u8],
+    salt: &[u8],
+    params: &ScryptParams,
+    output: &mut [u8],
+) -> Result<(), &'static str> {
+    let r = params.r as usize;
+    let n = 1 << params.log_n;
+    let single_iteration = PBKDF2Params::new(1)?;
+    let mut bytes = vec![0u8; params.p as usize * 128 * r];
+    pbkdf2::<64, 32, SHA256>(password, salt, &single_iteration, &mut bytes)?;
+    let mut words: Vec<u32> = bytes
+        .chunks_exact(4)
+        .map(|word| u32::from_le_bytes(word.try_into().unwrap()))
+        .collect();
+    // the p blocks are independent of each other
+    #[cfg(feature = "rayon")]
+    words
+        .par_chunks_mut(32 * r)
+        .for_each(|block| ro_mix(block, n));
+    #[cfg(not(feature = "rayon"))]
+    words.chunks_mut(32 * r).for_each(|block| ro_mix(block, n));
+    for (bytes, word) in bytes.chunks_exact_mut(4).zip(words) {
+        bytes.copy_from_slice(&word.to_le_bytes());
+    }
+    pbkdf2::<64, 32, SHA256>(password, &bytes, &single_iteration, output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_utils::get_hash_string;
+    use super::*;
+
+    #[test]
+    fn salsa20_8_core() {
+        // RFC 7914, section 8
+        let input = [
+            0x7e, 0x87, 0x9a, 0x21, 0x4f, 0x3e, 0xc9, 0x86, 0x7c, 0xa9, 0x40, 0xe6, 0x41, 0x71,
+            0x8f, 0x26, 0xba, 0xee, 0x55, 0x5b, 0x8c, 0x61, 0xc1, 0xb5, 0x0d, 0xf8, 0x46, 0x11,
+            0x6d, 0xcd, 0x3b, 0x1d, 0xee, 0x24, 0xf3, 0x19, 0xdf, 0x9b, 0x3d, 0x85, 0x14, 0x12,
+            0x1e, 0x4b, 0x5a, 0xc5, 0xaa, 0x32, 0x76, 0x02, 0x1d, 0x29, 0x09, 0xc7, 0x48, 0x29,
+            0xed, 0xeb, 0xc6, 0x8d, 0xb8, 0xb8, 0xc2, 0x5e,
+        ];
+        let mut block = [0u32; 16];
+        for (word, bytes) in block.iter_mut().zip(input.chunks_exact(4)) {
+            *word = u32::from_le_bytes(bytes.try_into().unwrap());
+        }
+        salsa20_8(&mut block);
+        let output: Vec<u8> = block.iter().flat_map(|word| word.to_le_bytes()).collect();
+        assert_eq!(
+            get_hash_string(&output),
+            "a41f859c6608cc993b81cacb020cef05044b2181a2fd337dfd7b1c6396682f29b4393168e3c9e6bcfe6bc5b7a06d96bae424cc102c91745c24ad673dc7618f81"
+        );
+    }
+
+    fn derive(password: &[u8], salt: &[u8], log_n: u8, r: u32, p: u32) -> String {
+        let params = ScryptParams::new(log_n, r, p).unwrap();
+        let mut output = [0; 64];
+        scrypt(password, salt, &params, &mut output).unwrap();
+        get_hash_string(&output)
+    }
+
+    #[test]
+    fn rfc7914_vectors() {
+        assert_eq!(
+            derive(b"", b"", 4, 1, 1),
+            "77d6576238657b203b19ca42c18a0497f16b4844e3074ae8dfdffa3fede21442fcd0069ded0948f8326a753a0fc81f17e8d3e0fb2e0d3628cf35e20c38d18906"
+        );
+        assert_eq!(
+            derive(b"password", b"NaCl", 10, 8, 16),
+            "fdbabe1c9d3472007856e7190d01e9fe7c6ad7cbc8237830e77376634b3731622eaf30d92e22a3886ff109279d9830dac727afb94a83ee6d8360cbdfa2cc0640"
+        );
+    }
+
+    #[test]
+    fn rfc7914_16_mib() {
+        assert_eq!(
+            derive(b"pleaseletmein", b"SodiumChloride", 14, 8, 1),
+            "7023bdcb3afd7348461c06cd81fd38ebfda8fbba904f8e3ea9b543f6545da1f2d5432955613f0fcf62d49705242a9af9e61e85dc0d651e40dfcf017b45575887"
+        );
+    }
+
+    #[test]
+    fn validation() {
+        assert!(ScryptParams::new(0, 8, 1).is_err());
+        assert!(ScryptParams::new(16, 1, 1).is_err());
+        assert!(ScryptParams::new(15, 1, 1).is_ok());
+        assert!(ScryptParams::new(10, 0, 1).is_err());
+        assert!(ScryptParams::new(10, 1 << 15, 1 << 15).is_err());
+        assert!(ScryptParams::new(63, 8, 1).is_err());
+    }
+}
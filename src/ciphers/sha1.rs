@@ -0,0 +1,438 @@
+//! SHA-1 implementation, with optional collision detection
+//!
+//! This implementation is based on [`RFC3174`]. SHA-1 is broken as a
+//! cryptographic hash (see [`SHAttered`]), it is only provided for
+//! interoperability, e.g. with git object IDs.
+//!
+//! [`SHA1::new_with_collision_detection`] enables counter-cryptanalysis in the
+//! style of [`SHA-1DC`]: every known practical collision attack goes through a
+//! near-collision block built from one of a small set of disturbance vectors.
+//! For each of those vectors, a compressed block is recompressed with the
+//! corresponding message difference, starting from the intermediate state at
+//! the step where the attack's differential path ends. If the result collides
+//! with the actual output, the block is part of a collision attack. Unlike
+//! SHA-1DC we do not filter the vectors with unavoidable bit conditions, so
+//! each block costs 32 extra partial compressions.
+//!
+//! [`RFC3174`]: https://www.rfc-editor.org/rfc/rfc3174
+//! [`SHAttered`]: https://shattered.io
+//! [`SHA-1DC`]: https://github.com/cr-marcstevens/sha1collisiondetection
+
+use super::merkle_damgard::{BlockBuffer, Endianness};
+use super::Hasher;
+
+const H0: [u32; 5] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+
+const K: [u32; 4] = [0x5a827999, 0x6ed9eba1, 0x8f1bbcdc, 0xca62c1d6];
+
+/// Disturbance vectors checked by SHA-1DC: type (I or II), `K`, `b` and the
+/// step whose intermediate state is used for the recompression
+const DISTURBANCE_VECTORS: [(u8, usize, u32, usize); 32] = [
+    (1, 43, 0, 58),
+    (1, 44, 0, 58),
+    (1, 45, 0, 58),
+    (1, 46, 0, 58),
+    (1, 46, 2, 58),
+    (1, 47, 0, 58),
+    (1, 47, 2, 58),
+    (1, 48, 0, 58),
+    (1, 48, 2, 58),
+    (1, 49, 0, 58),
+    (1, 49, 2, 58),
+    (1, 50, 0, 65),
+    (1, 50, 2, 65),
+    (1, 51, 0, 65),
+    (1, 51, 2, 65),
+    (1, 52, 0, 65),
+    (2, 45, 0, 58),
+    (2, 46, 0, 58),
+    (2, 46, 2, 58),
+    (2, 47, 0, 58),
+    (2, 48, 0, 58),
+    (2, 49, 0, 58),
+    (2, 49, 2, 58),
+    (2, 50, 0, 65),
+    (2, 50, 2, 65),
+    (2, 51, 0, 65),
+    (2, 51, 2, 65),
+    (2, 52, 0, 65),
+    (2, 53, 0, 65),
+    (2, 54, 0, 65),
+    (2, 55, 0, 65),
+    (2, 56, 0, 65),
+];
+
+/// Message difference of the disturbance vector `I(k, b)` or `II(k, b)`.
+///
+/// The disturbance vector is a sequence of 80 words following the SHA-1
+/// message expansion, which is zero on steps `k..k + 16` except for `2^b` on
+/// step `k + 15` (and, for type II, `2^(b + 31)` on steps `k + 1` and `k + 3`).
+/// Every set bit starts a local collision, corrected over the next 5 steps.
+const fn message_difference(kind: u8, k: usize, b: u32) -> [u32; 80] {
+    // the disturbance vector on steps -5..80
+    let mut dv = [0u32; 85];
+    dv[k + 20] = 1 << b;
+    if kind == 2 {
+        dv[k + 6] = 1u32.rotate_left(b + 31);
+        dv[k + 8] = 1u32.rotate_left(b + 31);
+    }
+    let mut i = k + 21;
+    while i < 85 {
+        dv[i] = (dv[i - 3] ^ dv[i - 8] ^ dv[i - 14] ^ dv[i - 16]).rotate_left(1);
+        i += 1;
+    }
+    let mut i = k + 5;
+    while i > 0 {
+        i -= 1;
+        dv[i] = dv[i + 16].rotate_right(1) ^ dv[i + 13] ^ dv[i + 8] ^ dv[i + 2];
+    }
+    let mut result = [0u32; 80];
+    let mut t = 0;
+    while t < 80 {
+        let i = t + 5;
+        result[t] = dv[i]
+            ^ dv[i - 1].rotate_left(5)
+            ^ dv[i - 2]
+            ^ dv[i - 3].rotate_left(30)
+            ^ dv[i - 4].rotate_left(30)
+            ^ dv[i - 5].rotate_left(30);
+        t += 1;
+    }
+    result
+}
+
+const MESSAGE_DIFFERENCES: [[u32; 80]; 32] = {
+    let mut result = [[0u32; 80]; 32];
+    let mut i = 0;
+    while i < 32 {
+        let (kind, k, b, _) = DISTURBANCE_VECTORS[i];
+        result[i] = message_difference(kind, k, b);
+        i += 1;
+    }
+    result
+};
+
+#[inline]
+fn f(t: usize, b: u32, c: u32, d: u32) -> u32 {
+    match t / 20 {
+        0 => (b & c) | (!b & d),
+        2 => (b & c) | (b & d) | (c & d),
+        _ => b ^ c ^ d,
+    }
+}
+
+#[inline]
+fn step(state: &mut [u32; 5], t: usize, w: u32) {
+    let [a, b, c, d, e] = *state;
+    let temp = a
+        .rotate_left(5)
+        .wrapping_add(f(t, b, c, d))
+        .wrapping_add(e)
+        .wrapping_add(K[t / 20])
+        .wrapping_add(w);
+    *state = [temp, a, b.rotate_left(30), c, d];
+}
+
+/// Inverse of [`step`]
+#[inline]
+fn step_back(state: &mut [u32; 5], t: usize, w: u32) {
+    let [temp, a, b, c, d] = *state;
+    let b = b.rotate_right(30);
+    let e = temp
+        .wrapping_sub(a.rotate_left(5))
+        .wrapping_sub(f(t, b, c, d))
+        .wrapping_sub(K[t / 20])
+        .wrapping_sub(w);
+    *state = [a, b, c, d, e];
+}
+
+fn expand(block: &[u8; 64]) -> [u32; 80] {
+    let mut w = [0u32; 80];
+    for (word, bytes) in w.iter_mut().zip(block.chunks_exact(4)) {
+        *word = u32::from_be_bytes(bytes.try_into().unwrap());
+    }
+    for t in 16..80 {
+        w[t] = (w[t - 3] ^ w[t - 8] ^ w[t - 14] ^ w[t - 16]).rotate_left(1);
+    }
+    w
+}
+
+/// Whether the message `w`, whose state before step `test_step` is `state`,
+/// compresses to `ihv_out` from the input hash value that leads to `state`
+fn recompression_collides(
+    ihv_out: &[u32; 5],
+    state: [u32; 5],
+    test_step: usize,
+    w: &[u32; 80],
+) -> bool {
+    let mut ihv_in = state;
+    for t in (0..test_step).rev() {
+        step_back(&mut ihv_in, t, w[t]);
+    }
+    let mut state = state;
+    for (t, &word) in w.iter().enumerate().skip(test_step) {
+        step(&mut state, t, word);
+    }
+    (0..5).all(|i| ihv_in[i].wrapping_add(state[i]) == ihv_out[i])
+}
+
+/// Compresses `block` into `h`, returning whether collision detection is
+/// enabled and found a collision attack
+fn process_block(h: &mut [u32; 5], block: &[u8; 64], detect_collisions: bool) -> bool {
+    let w = expand(block);
+    let mut state = *h;
+    let mut saved = [[0u32; 5]; 2];
+    for (t, &word) in w.iter().enumerate() {
+        match t {
+            58 => saved[0] = state,
+            65 => saved[1] = state,
+            _ => {}
+        }
+        step(&mut state, t, word);
+    }
+    for (h, s) in h.iter_mut().zip(state) {
+        *h = h.wrapping_add(s);
+    }
+    detect_collisions
+        && DISTURBANCE_VECTORS.iter().zip(&MESSAGE_DIFFERENCES).any(
+            |(&(_, _, _, test_step), difference)| {
+                let mut other = w;
+                for (word, diff) in other.iter_mut().zip(difference) {
+                    *word ^= diff;
+                }
+                let state = saved[usize::from(test_step == 65)];
+                // the state of the other message is the same at `test_step`
+                recompression_collides(h, state, test_step, &other)
+            },
+        )
+}
+
+/// SHA-1 implementation
+///
+/// This implementation is based on [`RFC3174`].
+///
+/// [`RFC3174`]: https://www.rfc-editor.org/rfc/rfc3174
+#[derive(Debug, Clone, Copy)]
+pub struct SHA1 {
+    buffer: BlockBuffer<64>,
+    h: [u32; 5],
+    /// Whether blocks are checked for collision attacks
+    detect_collisions: bool,
+    /// Whether a block of a collision attack was found so far
+    collision: bool,
+}
+
+impl SHA1 {
+    /// Create a new SHA1 instance with the default initial hash value
+    pub fn new_default() -> Self {
+        Self {
+            buffer: BlockBuffer::new(),
+            h: H0,
+            detect_collisions: false,
+            collision: false,
+        }
+    }
+
+    /// Create a new SHA1 instance which checks every block for collision
+    /// attacks, see [`SHA1::collision_detected`]
+    pub fn new_with_collision_detection() -> Self {
+        Self {
+            detect_collisions: true,
+            ..Self::new_default()
+        }
+    }
+
+    /// Update the hash with the given data
+    pub fn update(&mut self, data: &[u8]) {
+        let Self {
+            h,
+            detect_collisions,
+            collision,
+            ..
+        } = self;
+        self.buffer.update(data, |block| {
+            *collision |= process_block(h, block, *detect_collisions);
+        });
+    }
+
+    fn finish(&self) -> ([u32; 5], bool) {
+        let mut h = self.h;
+        let mut collision = self.collision;
+        let mut buffer = self.buffer;
+        buffer.pad(8, Endianness::Big, |block| {
+            collision |= process_block(&mut h, block, self.detect_collisions);
+        });
+        (h, collision)
+    }
+
+    /// Returns the hash of the data so far. This does not consume the state,
+    /// so more data can still be added afterwards.
+    pub fn get_hash(&self) -> [u8; 20] {
+        let mut result = [0u8; 20];
+        for (bytes, word) in result.chunks_exact_mut(4).zip(self.finish().0) {
+            bytes.copy_from_slice(&word.to_be_bytes());
+        }
+        result
+    }
+
+    /// Whether collision detection is enabled and the (padded) data so far
+    /// contains a block of a known collision attack. Such data should be
+    /// rejected, as another message with the same hash likely exists.
+    pub fn collision_detected(&self) -> bool {
+        self.finish().1
+    }
+}
+
+impl Hasher<20> for SHA1 {
+    fn new_default() -> Self {
+        SHA1::new_default()
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.update(data);
+    }
+
+    fn get_hash(&mut self) -> [u8; 20] {
+        SHA1::get_hash(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_utils::{cavp_vectors, get_hash_string, nist_vectors};
+    use super::super::HMAC;
+    use super::*;
+    use crate::encoding::{Encoding, Hex};
+
+    #[test]
+    fn fips_vectors() {
+        nist_vectors::<20, SHA1>([
+            "da39a3ee5e6b4b0d3255bfef95601890afd80709",
+            "a9993e364706816aba3e25717850c26c9cd0d89d",
+            "84983e441c3bd26ebaae4aa1f95129e5e54670f1",
+            "a49b2446a02c645bf419f995b67091253a04a259",
+            "34aa973cd4c4daa4f61eeb2bdbad27316534016f",
+        ]);
+        // SHA1ShortMsg.rsp
+        cavp_vectors::<20, SHA1>(&[
+            ("36", "c1dfd96eea8cc2b62785275bca38ac261256e278"),
+            ("195a", "0a1c2d555bbe431ad6288af5a54f93e0449c9232"),
+            ("df4bd2", "bf36ed5d74727dfd5d7854ec6b1d49468d8ee8aa"),
+            ("549e959e", "b78bae6d14338ffccfd5d5b5674a275f6ef9c717"),
+        ]);
+    }
+
+    #[test]
+    fn git_object_id() {
+        // `echo 'hello world' | git hash-object --stdin`
+        let content = b"hello world\n";
+        let mut sha = SHA1::new_default();
+        sha.update(format!("blob {}\0", content.len()).as_bytes());
+        sha.update(content);
+        assert_eq!(
+            get_hash_string(&sha.get_hash()),
+            "3b18e512dba79e4c8300dd08aeb37f8e728b8dad"
+        );
+    }
+
+    #[test]
+    fn collision_detection_keeps_hash() {
+        let message: Vec<u8> = (0..10_000_u32).map(|i| (i * 31 + i / 7) as u8).collect();
+        let mut plain = SHA1::new_default();
+        let mut checked = SHA1::new_with_collision_detection();
+        plain.update(&message);
+        checked.update(&message);
+        assert_eq!(plain.get_hash(), checked.get_hash());
+        assert!(!checked.collision_detected());
+        assert!(!plain.collision_detected());
+    }
+
+    #[test]
+    fn shattered() {
+        // the first 320 bytes of the two SHAttered PDFs, which only differ in
+        // their two near-collision blocks
+        let prefix = concat!(
+            "255044462d312e330a25e2e3cfd30a0a0a312030206f626a0a3c3c2f57696474",
+            "682032203020522f4865696768742033203020522f547970652034203020522f",
+            "537562747970652035203020522f46696c7465722036203020522f436f6c6f72",
+            "53706163652037203020522f4c656e6774682038203020522f42697473506572",
+            "436f6d706f6e656e7420383e3e0a73747265616d0affd8fffe00245348412d31",
+            "20697320646561642121212121852fec092339759c39b1a1c63c4c97e1fffe01",
+        );
+        let shattered_1 = concat!(
+            "7f46dc93a6b67e013b029aaa1db2560b45ca67d688c7f84b8c4c791fe02b3df6",
+            "14f86db1690901c56b45c1530afedfb76038e972722fe7ad728f0e4904e046c2",
+            "30570fe9d41398abe12ef5bc942be33542a4802d98b5d70f2a332ec37fac3514",
+            "e74ddc0f2cc1a874cd0c78305a21566461309789606bd0bf3f98cda8044629a1",
+        );
+        let shattered_2 = concat!(
+            "7346dc9166b67e118f029ab621b2560ff9ca67cca8c7f85ba84c79030c2b3de2",
+            "18f86db3a90901d5df45c14f26fedfb3dc38e96ac22fe7bd728f0e45bce046d2",
+            "3c570feb141398bb552ef5a0a82be331fea48037b8b5d71f0e332edf93ac3500",
+            "eb4ddc0decc1a864790c782c76215660dd309791d06bd0af3f98cda4bc4629b1",
+        );
+        for blocks in [shattered_1, shattered_2] {
+            let message = Hex::LOWER.decode(&format!("{prefix}{blocks}")).unwrap();
+            let mut plain = SHA1::new_default();
+            let mut checked = SHA1::new_with_collision_detection();
+            plain.update(&message);
+            assert!(!plain.collision_detected());
+            // the prefix alone is benign
+            checked.update(&message[..192]);
+            assert!(!checked.collision_detected());
+            checked.update(&message[192..]);
+            assert!(checked.collision_detected());
+            assert_eq!(
+                get_hash_string(&plain.get_hash()),
+                "f92d74e3874587aaf443d1db961d4e26dde13e9c"
+            );
+        }
+    }
+
+    #[test]
+    fn recompression() {
+        let block: [u8; 64] = std::array::from_fn(|i| i as u8);
+        let w = expand(&block);
+        let mut state = H0;
+        for (t, &word) in w.iter().enumerate().take(58) {
+            step(&mut state, t, word);
+        }
+        let mut back = state;
+        for t in (0..58).rev() {
+            step_back(&mut back, t, w[t]);
+        }
+        assert_eq!(back, H0);
+        // without a message difference the recompression reproduces the output
+        let mut h = H0;
+        process_block(&mut h, &block, false);
+        assert!(recompression_collides(&h, state, 58, &w));
+        let mut other = w;
+        other[70] ^= 1;
+        assert!(!recompression_collides(&h, state, 58, &other));
+    }
+
+    #[test]
+    fn disturbance_vectors() {
+        for difference in MESSAGE_DIFFERENCES {
+            // a message difference follows the message expansion
+            for t in 16..80 {
+                let expected = difference[t - 3] ^ difference[t - 8];
+                let expected = (expected ^ difference[t - 14] ^ difference[t - 16]).rotate_left(1);
+                assert_eq!(difference[t], expected);
+            }
+            assert!(difference.iter().any(|&word| word != 0));
+        }
+    }
+
+    #[test]
+    fn hmac() {
+        // RFC 2202 test case 2
+        let mut hmac: HMAC<64, 20, SHA1> = HMAC::new_default();
+        hmac.add_key(b"Jefe").unwrap();
+        hmac.update(b"what do ya want for nothing?");
+        assert_eq!(
+            get_hash_string(&hmac.finalize()),
+            "effcdf6ae5eb2fa2d27416d5f184df9c259a7c79"
+        );
+    }
+}
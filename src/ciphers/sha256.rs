@@ -1,17 +1,22 @@
-//! SHA-2 256 bit implementation
+//! SHA-2 256 and 224 bit implementations
 //!
 //! This implementation is based on [`RFC6234`].
-//! Keep in mind that the amount of data (in bits) processed should always be an
-//! integer multiple of 8.
+//! Messages may have any length in bits, see [`SHA256::update_bits`], and a
+//! partially updated hasher can be saved and resumed as a [`SHA256Midstate`].
 //!
 //! [`RFC6234`]: https://www.rfc-editor.org/rfc/rfc6234
 
+use super::merkle_damgard::{BlockBuffer, Endianness};
 use super::Hasher;
 
 pub const H0: [u32; 8] = [
     0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
 ];
 
+pub const H0_224: [u32; 8] = [
+    0xc1059ed8, 0x367cd507, 0x3070dd17, 0xf70e5939, 0xffc00b31, 0x68581511, 0x64f98fa7, 0xbefa4fa4,
+];
+
 pub const K: [u32; 64] = [
     0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
     0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
@@ -59,16 +64,12 @@ fn ssig1(x: u32) -> u32 {
 /// SHA-2 256 bit implementation
 ///
 /// This implementation is based on [`RFC6234`].
-/// Keep in mind that the amount of data (in bits) processed should always be an
-/// integer multiple of 8.
 ///
 /// [`RFC6234`]: https://www.rfc-editor.org/rfc/rfc6234
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct SHA256 {
-    /// The current block to be processed, 512 bits long
-    buffer: [u32; 16],
-    /// Length (bits) of the message, should always be a multiple of 8
-    length: u64,
+    /// The current block to be processed, 512 bits long, and the message length
+    buffer: BlockBuffer<64>,
     /// The current hash value. Note: this value is invalid unless `finalize` is called
     pub h: [u32; 8],
     /// Message schedule
@@ -79,6 +80,23 @@ pub struct SHA256 {
     round: [u32; 8],
 }
 
+/// The state of a [`SHA256`] hasher which was not finalized, from which the
+/// hashing can be resumed, e.g. in another process
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serialize-num",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct SHA256Midstate {
+    /// The hash value after the last compressed block
+    pub h: [u32; 8],
+    /// Length (bits) of the message so far, modulo 2^64
+    pub length: u64,
+    /// The end of the message which is not part of a compressed block: the
+    /// last `length % 512` bits, with the unused bits of the last byte zero
+    pub pending: Vec<u8>,
+}
+
 fn process_block(h: &mut [u32; 8], w: &mut [u32; 64], round: &mut [u32; 8], buf: &[u32; 16]) {
     // Prepare the message schedule:
     w[..buf.len()].copy_from_slice(&buf[..]);
@@ -110,13 +128,25 @@ fn process_block(h: &mut [u32; 8], w: &mut [u32; 64], round: &mut [u32; 8], buf:
     }
 }
 
+fn process_bytes(h: &mut [u32; 8], w: &mut [u32; 64], round: &mut [u32; 8], block: &[u8; 64]) {
+    let mut buf = [0u32; 16];
+    for (word, bytes) in buf.iter_mut().zip(block.chunks_exact(4)) {
+        *word = u32::from_be_bytes(bytes.try_into().unwrap());
+    }
+    process_block(h, w, round, &buf);
+}
+
 impl SHA256 {
     /// Create a new SHA256 instance with the default initial hash value
     pub fn new_default() -> Self {
+        Self::with_initial_hash(H0)
+    }
+
+    /// Create a new instance starting from the hash value `h`
+    fn with_initial_hash(h: [u32; 8]) -> Self {
         SHA256 {
-            buffer: [0u32; 16],
-            length: 0,
-            h: H0,
+            buffer: BlockBuffer::new(),
+            h,
             w: [0u32; 64],
             round: [0u32; 8],
             finalized: false,
@@ -127,48 +157,47 @@ impl SHA256 {
     /// Note: buffer should be empty before calling this!
     pub fn process_block(&mut self, buf: &[u32; 16]) {
         process_block(&mut self.h, &mut self.w, &mut self.round, buf);
-        self.length += 512;
+        self.buffer.add_block();
     }
 
     /// Update the hash with the given data
     pub fn update(&mut self, data: &[u8]) {
-        if data.is_empty() {
-            return;
-        }
-        let offset = (((32 - (self.length & 31)) & 31) >> 3) as usize;
-        let mut buf_ind = ((self.length & 511) >> 5) as usize;
-        for (i, &byte) in data.iter().enumerate().take(offset) {
-            self.buffer[buf_ind] ^= (byte as u32) << ((offset - i - 1) << 3);
-        }
-        self.length += (data.len() as u64) << 3;
-        if offset > data.len() {
-            return;
-        }
-        if offset > 0 {
-            buf_ind += 1;
-        }
-        if data.len() > 3 {
-            for i in (offset..(data.len() - 3)).step_by(4) {
-                if buf_ind & 16 == 16 {
-                    process_block(&mut self.h, &mut self.w, &mut self.round, &self.buffer);
-                    buf_ind = 0;
-                }
-                self.buffer[buf_ind] = ((data[i] as u32) << 24)
-                    ^ ((data[i + 1] as u32) << 16)
-                    ^ ((data[i + 2] as u32) << 8)
-                    ^ data[i + 3] as u32;
-                buf_ind += 1;
-            }
-        }
-        if buf_ind & 16 == 16 {
-            process_block(&mut self.h, &mut self.w, &mut self.round, &self.buffer);
-            buf_ind = 0;
-        }
-        self.buffer[buf_ind] = 0;
-        let rem_ind = offset + ((data.len() - offset) & !0b11);
-        for (i, &byte) in data[rem_ind..].iter().enumerate() {
-            self.buffer[buf_ind] ^= (byte as u32) << ((3 - i) << 3);
+        let Self { h, w, round, .. } = self;
+        self.buffer
+            .update(data, |block| process_bytes(h, w, round, block));
+    }
+
+    /// Update the hash with the first `bits` bits of `data`, most significant
+    /// bit first. The message can then be continued with any method, so it
+    /// does not have to be a whole number of bytes.
+    ///
+    /// # Panics
+    ///
+    /// If `data` is shorter than `bits` bits
+    pub fn update_bits(&mut self, data: &[u8], bits: usize) {
+        let Self { h, w, round, .. } = self;
+        self.buffer
+            .update_bits(data, bits, |block| process_bytes(h, w, round, block));
+    }
+
+    /// Returns the state of this [`SHA256`], or `None` if it was finalized
+    pub fn midstate(&self) -> Option<SHA256Midstate> {
+        if self.finalized {
+            return None;
         }
+        Some(SHA256Midstate {
+            h: self.h,
+            length: self.buffer.length() as u64,
+            pending: self.buffer.pending().to_vec(),
+        })
+    }
+
+    /// Create a new instance resuming from `midstate`. Fails if its pending
+    /// data does not match its length.
+    pub fn from_midstate(midstate: &SHA256Midstate) -> Result<Self, &'static str> {
+        let mut hasher = Self::with_initial_hash(midstate.h);
+        hasher.buffer = BlockBuffer::from_parts(&midstate.pending, u128::from(midstate.length))?;
+        Ok(hasher)
     }
 
     /// Returns the hash of this [`SHA256`].
@@ -178,31 +207,13 @@ impl SHA256 {
         // will append the binary representation of length to the data
         if !self.finalized {
             self.finalized = true;
-            let clen = (self.length + 8) & 511;
-            let num_0 = match clen.cmp(&448) {
-                std::cmp::Ordering::Greater => (448 + 512 - clen) >> 3,
-                _ => (448 - clen) >> 3,
-            };
-            let mut padding: Vec<u8> = vec![0_u8; (num_0 + 9) as usize];
-            let len = padding.len();
-            padding[0] = 0x80;
-            padding[len - 8] = (self.length >> 56) as u8;
-            padding[len - 7] = (self.length >> 48) as u8;
-            padding[len - 6] = (self.length >> 40) as u8;
-            padding[len - 5] = (self.length >> 32) as u8;
-            padding[len - 4] = (self.length >> 24) as u8;
-            padding[len - 3] = (self.length >> 16) as u8;
-            padding[len - 2] = (self.length >> 8) as u8;
-            padding[len - 1] = self.length as u8;
-            self.update(&padding);
+            let Self { h, w, round, .. } = self;
+            self.buffer
+                .pad(8, Endianness::Big, |block| process_bytes(h, w, round, block));
         }
-        assert_eq!(self.length & 511, 0);
         let mut result = [0u8; 32];
-        for i in (0..32).step_by(4) {
-            result[i] = (self.h[i >> 2] >> 24) as u8;
-            result[i + 1] = (self.h[i >> 2] >> 16) as u8;
-            result[i + 2] = (self.h[i >> 2] >> 8) as u8;
-            result[i + 3] = self.h[i >> 2] as u8;
+        for (bytes, word) in result.chunks_exact_mut(4).zip(self.h) {
+            bytes.copy_from_slice(&word.to_be_bytes());
         }
         result
     }
@@ -222,20 +233,304 @@ impl Hasher<32> for SHA256 {
     }
 }
 
+/// SHA-2 224 bit implementation
+///
+/// SHA-224 is [`SHA256`] started from a different initial hash value, with the
+/// result truncated to its first 224 bits.
+#[derive(Debug, Clone)]
+pub struct SHA224(SHA256);
+
+impl SHA224 {
+    /// Create a new SHA224 instance with the default initial hash value
+    pub fn new_default() -> Self {
+        Self(SHA256::with_initial_hash(H0_224))
+    }
+
+    /// Update the hash with the given data
+    pub fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    /// Update the hash with the first `bits` bits of `data`, see
+    /// [`SHA256::update_bits`]
+    pub fn update_bits(&mut self, data: &[u8], bits: usize) {
+        self.0.update_bits(data, bits);
+    }
+
+    /// Returns the hash of this [`SHA224`].
+    pub fn get_hash(&mut self) -> [u8; 28] {
+        self.0.get_hash()[..28].try_into().unwrap()
+    }
+}
+
+impl Hasher<28> for SHA224 {
+    fn new_default() -> Self {
+        SHA224::new_default()
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.update(data);
+    }
+
+    fn get_hash(&mut self) -> [u8; 28] {
+        self.get_hash()
+    }
+}
+
 #[cfg(test)]
-pub mod tests {
+mod tests {
+    use super::super::test_utils::{cavp_vectors, get_hash_string, nist_vectors};
     use super::*;
-    use std::fmt::Write;
-    // use crate::math::LinearSieve;
-    // TODO implement test `test_constants` after implementing `crate::math::LinearSieve`
-
-    pub fn get_hash_string(hash: &[u8; 32]) -> String {
-        let mut result = String::new();
-        result.reserve(64);
-        for &ch in hash {
-            write!(&mut result, "{ch:02x}").unwrap();
+    use crate::encoding::{Encoding, Hex};
+    use crate::math::LinearSieve;
+
+    fn integer_root(n: u128, k: u32) -> u128 {
+        let (mut lo, mut hi) = (0_u128, 1_u128 << (128 / k + 1));
+        while hi - lo > 1 {
+            let mid = (lo + hi) / 2;
+            match mid.checked_pow(k) {
+                Some(p) if p <= n => lo = mid,
+                _ => hi = mid,
+            }
         }
-        result
+        lo
+    }
+
+    #[test]
+    fn test_constants() {
+        let mut ls = LinearSieve::new();
+        ls.prepare(311).unwrap();
+        for (i, &p) in ls.primes.iter().enumerate().take(64) {
+            // first 32 bits of the fractional part of the cube root of p
+            assert_eq!(integer_root((p as u128) << 96, 3) as u32, K[i]);
+        }
+        for (i, &p) in ls.primes.iter().enumerate().take(8) {
+            // first 32 bits of the fractional part of the square root of p
+            assert_eq!(integer_root((p as u128) << 64, 2) as u32, H0[i]);
+        }
+    }
+
+    #[test]
+    fn sha256_nist() {
+        nist_vectors::<32, SHA256>([
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1",
+            "cf5b16a778af8380036ce59e7b0492370b249b11e8f07a51afac45037afee9d1",
+            "cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0",
+        ]);
+    }
+
+    #[test]
+    fn sha224_nist() {
+        nist_vectors::<28, SHA224>([
+            "d14a028c2a3a2bc9476102bb288234c415a2b01f828ea62ac5b3e42f",
+            "23097d223405d8228642a477bda255b32aadbce4bda0b3f7e36c9da7",
+            "75388b16512776cc5dba5da1fd890150b0c6455cb4f58b1952522525",
+            "c97ca9a559850ce97a04a96def6d99a9e0e0e2ab14e6b8df265fc0b3",
+            "20794655980c91d8bbb4c1ea97618a4bf03f42581948b2ee4ee7ad67",
+        ]);
+    }
+
+    #[test]
+    fn cavp_short_messages() {
+        // SHA256ShortMsg.rsp and SHA224ShortMsg.rsp, the 512 bit message
+        // filling a whole block
+        cavp_vectors::<32, SHA256>(&[
+            ("d3", "28969cdfa74a12c82f3bad960b0b000aca2ac329deea5c2328ebc6f2ba9802c1"),
+            ("11af", "5ca7133fa735326081558ac312c620eeca9970d1e70a4b95533d956f072d1f98"),
+            ("b4190e", "dff2e73091f6c05e528896c4c831b9448653dc2ff043528f6769437bc7b975c2"),
+            ("74ba2521", "b16aa56be3880d18cd41e68384cf1ec8c17680c45a02b1575dc1518923ae8b0e"),
+            ("c299209682", "f0887fe961c9cd3beab957e8222494abb969b1ce4c6557976df8b0f6d20e9166"),
+            ("e1dc724d5621", "eca0a060b489636225b4fa64d267dabbe44273067ac679f20820bddc6b6a90ac"),
+            ("06e076f5a442d5", "3fd877e27450e6bbd5d74bb82f9870c64c66e109418baa8e6bbcff355e287926"),
+            ("5738c929c4f4ccb6", "963bb88f27f512777aab6c8b1a02c70ec0ad651d428f870036e1917120fb48bf"),
+            ("3334c58075d3f4139e", "078da3d77ed43bd3037a433fd0341855023793f9afd08b4b08ea1e5597ceef20"),
+            ("74cb9381d89f5aa73368", "73d6fad1caaa75b43b21733561fd3958bdc555194a037c2addec19dc2d7a52bd"),
+            (
+                "5a86b737eaea8ee976a0a24da63e7ed7eefad18a101c1211e2b3650c5187c2a8a650547208251f6d4237e661c7bf4c77f335390394c37fa1a9f9be836ac28509",
+                "42e61e174fbb3897d6dd6cef3dd2802fe67b331953b06114a65c772859dfc1aa",
+            ),
+        ]);
+        cavp_vectors::<28, SHA224>(&[
+            (
+                "84",
+                "3cd36921df5d6963e73739cf4d20211e2d8877c19cff087ade9d0e3a",
+            ),
+            (
+                "5c7b",
+                "daff9bce685eb831f97fc1225b03c275a6c112e2d6e76f5faf7a36e6",
+            ),
+            (
+                "51ca3d",
+                "2c8959023515476e38388abb43599a29876b4b33d56adc06032de3a2",
+            ),
+            (
+                "a3310ba064be2e14ad32276e18cd0310c933a6e650c3c754d0243c6c61207865b4b65248f66a08edf6e0832689a9dc3a2e5d2095eeea50bd862bac88c8bd318d",
+                "b2a5586d9cbf0baa999157b4af06d88ae08d7c9faab4bc1a96829d65",
+            ),
+        ]);
+    }
+
+    #[test]
+    fn cavp_long_messages() {
+        // SHA256LongMsg.rsp, a 1304 bit message spanning three blocks
+        cavp_vectors::<32, SHA256>(&[(
+            concat!(
+                "451101250ec6f26652249d59dc974b7361d571a8101cdfd36aba3b5854d3ae08",
+                "6b5fdd4597721b66e3c0dc5d8c606d9657d0e323283a5217d1f53f2f284f57b8",
+                "5c8a61ac8924711f895c5ed90ef17745ed2d728abd22a5f7a13479a462d71b56",
+                "c19a74a40b655c58edfe0a188ad2cf46cbf30524f65d423c837dd1ff2bf462ac",
+                "4198007345bb44dbb7b1c861298cdf61982a833afc728fae1eda2f87aa2c9480",
+                "858bec",
+            ),
+            "3c593aa539fdcdae516cdf2f15000f6634185c88f505b39775fb9ab137a10aa2",
+        )]);
+    }
+
+    /// A message of 81 bytes, cut at various bit lengths in the tests
+    fn bit_message() -> Vec<u8> {
+        (0..81_u32).map(|i| (i * 37 + 11) as u8).collect()
+    }
+
+    #[test]
+    fn bit_oriented() {
+        // RFC 6234 test 9, 76 bytes followed by the 3 bits 101
+        let test_9 = Hex::LOWER
+            .decode(concat!(
+                "3e740371c810c2b99fc04e804907ef7cf26be28b57cb58a3e2f3c007166e49c1",
+                "2e9ba34c0104069129ea7615642545703a2bd901e16eb0e05deba014ebff6406",
+                "a07d54364eff742da779b0b3a0",
+            ))
+            .unwrap();
+        for (message, bits, expected) in [
+            // SHAVS SHA256ShortMsg, Len = 1
+            (
+                &[0x00][..],
+                1,
+                "bd4f9e98beb68c6ead3243b1b4c7fed75fa4feaab1f84795cbd8a98676a2a375",
+            ),
+            // RFC 6234 test 5, the 5 bit message 01101
+            (
+                &[0x68],
+                5,
+                "d6d3e02a31a84a8caa9718ed6c2057be09db45e7823eb5079ce7a573a3760f95",
+            ),
+            (
+                &test_9,
+                611,
+                "3e9ad6468bbbad2ac3c2cdc292e018ba5fd70b960cf1679777fce708fdb066e9",
+            ),
+        ] {
+            let mut hasher = SHA256::new_default();
+            hasher.update_bits(message, bits);
+            assert_eq!(get_hash_string(&hasher.get_hash()), expected, "{bits} bits");
+        }
+    }
+
+    #[test]
+    fn one_bit_at_a_time() {
+        // lengths around the padding and block boundaries
+        let message = bit_message();
+        for bits in [1, 7, 8, 9, 447, 448, 449, 511, 512, 513, 647] {
+            let mut expected = SHA256::new_default();
+            expected.update_bits(&message, bits);
+            if bits % 8 == 0 {
+                let mut bytes = SHA256::new_default();
+                bytes.update(&message[..bits / 8]);
+                assert_eq!(bytes.get_hash(), expected.get_hash(), "{bits} bits");
+            }
+            let mut hasher = SHA256::new_default();
+            for i in 0..bits {
+                hasher.update_bits(&[message[i / 8] << (i % 8)], 1);
+            }
+            assert_eq!(hasher.get_hash(), expected.get_hash(), "{bits} bits");
+        }
+    }
+
+    #[test]
+    fn unaligned_updates() {
+        // bytes added after an incomplete byte are shifted into place
+        let message = bit_message();
+        for head in 0..16 {
+            let mut expected = SHA256::new_default();
+            expected.update_bits(&message, head + 8 * 70);
+            let mut hasher = SHA256::new_default();
+            hasher.update_bits(&message, head);
+            let shifted: Vec<u8> = (0..70)
+                .map(|i| {
+                    let bits =
+                        u16::from_be_bytes([message[head / 8 + i], message[head / 8 + i + 1]]);
+                    (bits << (head % 8) >> 8) as u8
+                })
+                .collect();
+            hasher.update(&shifted);
+            assert_eq!(hasher.get_hash(), expected.get_hash());
+        }
+    }
+
+    #[test]
+    fn midstate() {
+        let message = bit_message();
+        for bits in [0, 5, 8, 100, 511, 512, 513, 647] {
+            let mut whole = SHA256::new_default();
+            whole.update_bits(&message, bits);
+            whole.update(b"rest of the message");
+
+            let mut hasher = SHA256::new_default();
+            hasher.update_bits(&message, bits);
+            let state = hasher.midstate().unwrap();
+            assert_eq!(state.length, bits as u64);
+            assert_eq!(state.pending.len(), (bits % 512).div_ceil(8));
+            let mut resumed = SHA256::from_midstate(&state).unwrap();
+            assert_eq!(resumed.midstate(), Some(state));
+            resumed.update(b"rest of the message");
+            assert_eq!(resumed.get_hash(), whole.get_hash());
+            assert_eq!(resumed.midstate(), None);
+        }
+
+        let mut state = SHA256::new_default().midstate().unwrap();
+        assert_eq!(state.h, H0);
+        state.length = 12;
+        assert!(SHA256::from_midstate(&state).is_err());
+        state.pending = vec![0xab, 0xc8];
+        assert!(SHA256::from_midstate(&state).is_err());
+        state.pending = vec![0xab, 0xc0];
+        assert!(SHA256::from_midstate(&state).is_ok());
+    }
+
+    #[cfg(feature = "serialize-num")]
+    #[test]
+    fn midstate_serde() {
+        use serde_test::{assert_tokens, Token};
+
+        let mut hasher = SHA256::new_default();
+        hasher.update(b"abc");
+        hasher.update_bits(&[0xff], 3);
+        let state = hasher.midstate().unwrap();
+        let mut tokens = vec![
+            Token::Struct {
+                name: "SHA256Midstate",
+                len: 3,
+            },
+            Token::Str("h"),
+            Token::Tuple { len: 8 },
+        ];
+        tokens.extend(H0.map(Token::U32));
+        tokens.extend([
+            Token::TupleEnd,
+            Token::Str("length"),
+            Token::U64(27),
+            Token::Str("pending"),
+            Token::Seq { len: Some(4) },
+            Token::U8(b'a'),
+            Token::U8(b'b'),
+            Token::U8(b'c'),
+            Token::U8(0xe0),
+            Token::SeqEnd,
+            Token::StructEnd,
+        ]);
+        assert_tokens(&state, &tokens);
     }
 
     #[test]
@@ -0,0 +1,528 @@
+//! SHA-3 and the SHAKE extendable output functions
+//!
+//! Everything here is built on the [`Keccak-f[1600]`] permutation and the
+//! sponge construction of [`FIPS 202`]: the message is XORed into the first
+//! `rate` bytes of the state, permuting after every block, and the output is
+//! then squeezed out of the same bytes. cSHAKE is defined in
+//! [`NIST SP 800-185`].
+//!
+//! [`Keccak-f[1600]`]: https://keccak.team/keccak_specs_summary.html
+//! [`FIPS 202`]: https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.202.pdf
+//! [`NIST SP 800-185`]: https://nvlpubs.nist.gov/nistpubs/SpecialPublications/NIST.SP.800-185.pdf
+
+use super::Hasher;
+
+#[rustfmt::skip]
+const ROUND_CONSTANTS: [u64; 24] = [
+    0x0000000000000001, 0x0000000000008082, 0x800000000000808a, 0x8000000080008000,
+    0x000000000000808b, 0x0000000080000001, 0x8000000080008081, 0x8000000000008009,
+    0x000000000000008a, 0x0000000000000088, 0x0000000080008009, 0x000000008000000a,
+    0x000000008000808b, 0x800000000000008b, 0x8000000000008089, 0x8000000000008003,
+    0x8000000000008002, 0x8000000000000080, 0x000000000000800a, 0x800000008000000a,
+    0x8000000080008081, 0x8000000000008080, 0x0000000080000001, 0x8000000080008008,
+];
+
+/// Rotation offsets of the ρ step, in the order the π step visits the lanes
+const RHO: [u32; 24] = [
+    1, 3, 6, 10, 15, 21, 28, 36, 45, 55, 2, 14, 27, 41, 56, 8, 25, 43, 62, 18, 39, 61, 20, 44,
+];
+
+/// Lanes visited by the π step, starting after lane 1
+const PI: [usize; 24] = [
+    10, 7, 11, 17, 18, 3, 5, 16, 8, 21, 24, 4, 15, 23, 19, 13, 12, 2, 20, 14, 22, 9, 6, 1,
+];
+
+/// The Keccak-f\[1600\] permutation. Lane `(x, y)` of the state is
+/// `state[x + 5 * y]`.
+pub fn keccak_f1600(state: &mut [u64; 25]) {
+    for rc in ROUND_CONSTANTS {
+        // θ
+        let mut c = [0u64; 5];
+        for (x, column) in c.iter_mut().enumerate() {
+            *column = state[x] ^ state[x + 5] ^ state[x + 10] ^ state[x + 15] ^ state[x + 20];
+        }
+        for x in 0..5 {
+            let d = c[(x + 4) % 5] ^ c[(x + 1) % 5].rotate_left(1);
+            for y in 0..5 {
+                state[x + 5 * y] ^= d;
+            }
+        }
+        // ρ and π
+        let mut last = state[1];
+        for (&lane, &rotation) in PI.iter().zip(&RHO) {
+            let current = state[lane];
+            state[lane] = last.rotate_left(rotation);
+            last = current;
+        }
+        // χ
+        for y in 0..5 {
+            let row: [u64; 5] = state[5 * y..5 * y + 5].try_into().unwrap();
+            for x in 0..5 {
+                state[x + 5 * y] = row[x] ^ (!row[(x + 1) % 5] & row[(x + 2) % 5]);
+            }
+        }
+        // ι
+        state[0] ^= rc;
+    }
+}
+
+/// A Keccak sponge absorbing `rate` bytes per permutation
+#[derive(Debug, Clone, Copy)]
+pub struct KeccakSponge {
+    state: [u64; 25],
+    /// Number of bytes absorbed or squeezed per permutation
+    rate: usize,
+    /// Position inside the current block
+    position: usize,
+}
+
+impl KeccakSponge {
+    /// Create an empty sponge, `rate` must be a multiple of 8 below 200
+    pub fn new(rate: usize) -> Self {
+        assert!(
+            rate > 0 && rate < 200 && rate.is_multiple_of(8),
+            "Invalid sponge rate"
+        );
+        Self {
+            state: [0; 25],
+            rate,
+            position: 0,
+        }
+    }
+
+    /// Number of bytes absorbed or squeezed per permutation
+    pub fn rate(&self) -> usize {
+        self.rate
+    }
+
+    fn xor_byte(&mut self, index: usize, byte: u8) {
+        self.state[index / 8] ^= (byte as u64) << (8 * (index % 8));
+    }
+
+    /// Absorb `data` into the sponge
+    pub fn absorb(&mut self, data: &[u8]) {
+        for &byte in data {
+            self.xor_byte(self.position, byte);
+            self.position += 1;
+            if self.position == self.rate {
+                keccak_f1600(&mut self.state);
+                self.position = 0;
+            }
+        }
+    }
+
+    /// Absorb zeros up to the end of the current block
+    fn zero_pad(&mut self) {
+        if self.position > 0 {
+            keccak_f1600(&mut self.state);
+            self.position = 0;
+        }
+    }
+
+    /// Pads the message with the domain separation bits `domain` (which
+    /// include the first bit of the `10*1` padding) and switches to squeezing
+    pub fn finalize(mut self, domain: u8) -> XofReader {
+        self.xor_byte(self.position, domain);
+        self.xor_byte(self.rate - 1, 0x80);
+        keccak_f1600(&mut self.state);
+        self.position = 0;
+        XofReader { sponge: self }
+    }
+}
+
+/// Reads the output of a finalized sponge, any amount at a time
+#[derive(Debug, Clone, Copy)]
+pub struct XofReader {
+    sponge: KeccakSponge,
+}
+
+impl XofReader {
+    /// Fill `out` with the next `out.len()` bytes of output
+    pub fn read(&mut self, out: &mut [u8]) {
+        let sponge = &mut self.sponge;
+        for byte in out {
+            if sponge.position == sponge.rate {
+                keccak_f1600(&mut sponge.state);
+                sponge.position = 0;
+            }
+            *byte = (sponge.state[sponge.position / 8] >> (8 * (sponge.position % 8))) as u8;
+            sponge.position += 1;
+        }
+    }
+}
+
+/// Domain separation and first padding bits of SHA-3
+const SHA3_DOMAIN: u8 = 0x06;
+/// Domain separation and first padding bits of SHAKE
+const SHAKE_DOMAIN: u8 = 0x1f;
+/// Domain separation and first padding bits of cSHAKE
+const CSHAKE_DOMAIN: u8 = 0x04;
+
+macro_rules! sha3 {
+    ($(#[$meta:meta])* $name:ident, $bytes:literal) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy)]
+        #[allow(non_camel_case_types)]
+        pub struct $name {
+            sponge: KeccakSponge,
+        }
+
+        impl $name {
+            #[doc = concat!("Create a new ", stringify!($name), " instance")]
+            pub fn new_default() -> Self {
+                Self {
+                    sponge: KeccakSponge::new(200 - 2 * $bytes),
+                }
+            }
+
+            /// Update the hash with the given data
+            pub fn update(&mut self, data: &[u8]) {
+                self.sponge.absorb(data);
+            }
+
+            /// Returns the hash of the data so far. This does not consume the
+            /// state, so more data can still be added afterwards.
+            pub fn get_hash(&self) -> [u8; $bytes] {
+                let mut result = [0u8; $bytes];
+                self.sponge.finalize(SHA3_DOMAIN).read(&mut result);
+                result
+            }
+        }
+
+        impl Hasher<$bytes> for $name {
+            fn new_default() -> Self {
+                $name::new_default()
+            }
+
+            fn update(&mut self, data: &[u8]) {
+                self.update(data);
+            }
+
+            fn get_hash(&mut self) -> [u8; $bytes] {
+                $name::get_hash(self)
+            }
+        }
+    };
+}
+
+sha3!(
+    /// SHA3-224, a Keccak sponge with a 144 byte rate
+    SHA3_224,
+    28
+);
+sha3!(
+    /// SHA3-256, a Keccak sponge with a 136 byte rate
+    SHA3_256,
+    32
+);
+sha3!(
+    /// SHA3-384, a Keccak sponge with a 104 byte rate
+    SHA3_384,
+    48
+);
+sha3!(
+    /// SHA3-512, a Keccak sponge with a 72 byte rate
+    SHA3_512,
+    64
+);
+
+/// `left_encode` of SP 800-185: the big endian bytes of `x`, prefixed by
+/// their count
+fn left_encode(x: u64) -> Vec<u8> {
+    let bytes = x.to_be_bytes();
+    let skip = (x.leading_zeros() as usize / 8).min(7);
+    let mut result = vec![(8 - skip) as u8];
+    result.extend_from_slice(&bytes[skip..]);
+    result
+}
+
+macro_rules! shake {
+    ($(#[$shake_meta:meta])* $shake:ident, $(#[$cshake_meta:meta])* $cshake:ident, $rate:literal) => {
+        $(#[$shake_meta])*
+        #[derive(Debug, Clone, Copy)]
+        pub struct $shake {
+            sponge: KeccakSponge,
+        }
+
+        impl $shake {
+            #[doc = concat!("Create a new ", stringify!($shake), " instance")]
+            pub fn new_default() -> Self {
+                Self {
+                    sponge: KeccakSponge::new($rate),
+                }
+            }
+
+            /// Update the state with the given data
+            pub fn update(&mut self, data: &[u8]) {
+                self.sponge.absorb(data);
+            }
+
+            /// Returns a reader for the output on the data so far
+            pub fn finalize_xof(&self) -> XofReader {
+                self.sponge.finalize(SHAKE_DOMAIN)
+            }
+        }
+
+        $(#[$cshake_meta])*
+        #[derive(Debug, Clone, Copy)]
+        pub struct $cshake {
+            sponge: KeccakSponge,
+            domain: u8,
+        }
+
+        impl $cshake {
+            /// Create a new instance for the function name `name` and the
+            /// customization string `customization`. When both are empty this
+            #[doc = concat!("is the same as [`", stringify!($shake), "`].")]
+            pub fn new(name: &[u8], customization: &[u8]) -> Self {
+                let mut sponge = KeccakSponge::new($rate);
+                if name.is_empty() && customization.is_empty() {
+                    return Self {
+                        sponge,
+                        domain: SHAKE_DOMAIN,
+                    };
+                }
+                // bytepad(encode_string(name) || encode_string(customization), rate)
+                sponge.absorb(&left_encode($rate));
+                for string in [name, customization] {
+                    sponge.absorb(&left_encode(8 * string.len() as u64));
+                    sponge.absorb(string);
+                }
+                sponge.zero_pad();
+                Self {
+                    sponge,
+                    domain: CSHAKE_DOMAIN,
+                }
+            }
+
+            /// Update the state with the given data
+            pub fn update(&mut self, data: &[u8]) {
+                self.sponge.absorb(data);
+            }
+
+            /// Returns a reader for the output on the data so far
+            pub fn finalize_xof(&self) -> XofReader {
+                self.sponge.finalize(self.domain)
+            }
+        }
+    };
+}
+
+shake!(
+    /// SHAKE128 extendable output function
+    SHAKE128,
+    /// cSHAKE128, a customizable [`SHAKE128`]
+    CSHAKE128,
+    168
+);
+shake!(
+    /// SHAKE256 extendable output function
+    SHAKE256,
+    /// cSHAKE256, a customizable [`SHAKE256`]
+    CSHAKE256,
+    136
+);
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        super::{
+            test_utils::{cavp_vectors, get_hash_string, nist_vectors},
+            HMAC,
+        },
+        *,
+    };
+
+    fn read_hex(reader: &mut XofReader, len: usize) -> String {
+        let mut out = vec![0; len];
+        reader.read(&mut out);
+        get_hash_string(&out)
+    }
+
+    #[test]
+    fn sha3_224() {
+        nist_vectors::<28, SHA3_224>([
+            "6b4e03423667dbb73b6e15454f0eb1abd4597f9a1b078e3f5b5a6bc7",
+            "e642824c3f8cf24ad09234ee7d3c766fc9a3a5168d0c94ad73b46fdf",
+            "8a24108b154ada21c9fd5574494479ba5c7e7ab76ef264ead0fcce33",
+            "543e6868e1666c1a643630df77367ae5a62a85070a51c14cbf665cbc",
+            "d69335b93325192e516a912e6d19a15cb51c6ed5c15243e7a7fd653c",
+        ]);
+    }
+
+    #[test]
+    fn sha3_256() {
+        nist_vectors::<32, SHA3_256>([
+            "a7ffc6f8bf1ed76651c14756a061d662f580ff4de43b49fa82d80a4b80f8434a",
+            "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532",
+            "41c0dba2a9d6240849100376a8235e2c82e1b9998a999e21db32dd97496d3376",
+            "916f6061fe879741ca6469b43971dfdb28b1a32dc36cb3254e812be27aad1d18",
+            "5c8875ae474a3634ba4fd55ec85bffd661f32aca75c6d699d0cdcb6c115891c1",
+        ]);
+    }
+
+    #[test]
+    fn sha3_384() {
+        nist_vectors::<48, SHA3_384>([
+            "0c63a75b845e4f7d01107d852e4c2485c51a50aaaa94fc61995e71bbee983a2ac3713831264adb47fb6bd1e058d5f004",
+            "ec01498288516fc926459f58e2c6ad8df9b473cb0fc08c2596da7cf0e49be4b298d88cea927ac7f539f1edf228376d25",
+            "991c665755eb3a4b6bbdfb75c78a492e8c56a22c5c4d7e429bfdbc32b9d4ad5aa04a1f076e62fea19eef51acd0657c22",
+            "79407d3b5916b59c3e30b09822974791c313fb9ecc849e406f23592d04f625dc8c709b98b43b3852b337216179aa7fc7",
+            "eee9e24d78c1855337983451df97c8ad9eedf256c6334f8e948d252d5e0e76847aa0774ddb90a842190d2c558b4b8340",
+        ]);
+    }
+
+    #[test]
+    fn sha3_512() {
+        nist_vectors::<64, SHA3_512>([
+            "a69f73cca23a9ac5c8b567dc185a756e97c982164fe25859e0d1dcc1475c80a615b2123af1f5f94c11e3e9402c3ac558f500199d95b6d3e301758586281dcd26",
+            "b751850b1a57168a5693cd924b6b096e08f621827444f70d884f5d0240d2712e10e116e9192af3c91a7ec57647e3934057340b4cf408d5a56592f8274eec53f0",
+            "04a371e84ecfb5b8b77cb48610fca8182dd457ce6f326a0fd3d7ec2f1e91636dee691fbe0c985302ba1b0d8dc78c086346b533b49c030d99a27daf1139d6e75e",
+            "afebb2ef542e6579c50cad06d2e578f9f8dd6881d7dc824d26360feebf18a4fa73e3261122948efcfd492e74e82e2189ed0fb440d187f382270cb455f21dd185",
+            "3c3a876da14034ab60627c077bb98f7e120a2a5370212dffb3385a18d4f38859ed311d0a9d5141ce9cc5c66ee689b266a8aa18ace8282a0e0db596c90b0a7b87",
+        ]);
+    }
+
+    #[test]
+    fn cavp_short_messages() {
+        // SHA3_224ShortMsg.rsp, SHA3_256ShortMsg.rsp, ...
+        cavp_vectors::<28, SHA3_224>(&[(
+            "01",
+            "488286d9d32716e5881ea1ee51f36d3660d70f0db03b3f612ce9eda4",
+        )]);
+        cavp_vectors::<32, SHA3_256>(&[
+            (
+                "e9",
+                "f0d04dd1e6cfc29a4460d521796852f25d9ef8d28b44ee91ff5b759d72c1e6d6",
+            ),
+            (
+                "d477",
+                "94279e8f5ccdf6e17f292b59698ab4e614dfe696a46c46da78305fc6a3146ab7",
+            ),
+        ]);
+        cavp_vectors::<48, SHA3_384>(&[(
+            "80",
+            "7541384852e10ff10d5fb6a7213a4a6c15ccc86d8bc1068ac04f69277142944f4ee50d91fdc56553db06b2f5039c8ab7",
+        )]);
+        cavp_vectors::<64, SHA3_512>(&[(
+            "e5",
+            "150240baf95fb36f8ccb87a19a41767e7aed95125075a2b2dbba6e565e1ce8575f2b042b62e29a04e9440314a821c6224182964d8b557b16a492b3806f4c39c1",
+        )]);
+    }
+
+    #[test]
+    fn get_hash_keeps_state() {
+        let mut sha = SHA3_256::new_default();
+        sha.update(b"a");
+        let first = sha.get_hash();
+        assert_eq!(sha.get_hash(), first);
+        sha.update(b"bc");
+        assert_eq!(
+            get_hash_string(&sha.get_hash()),
+            "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532"
+        );
+    }
+
+    #[test]
+    fn shake() {
+        assert_eq!(
+            read_hex(&mut SHAKE128::new_default().finalize_xof(), 32),
+            "7f9c2ba4e88f827d616045507605853ed73b8093f6efbc88eb1a6eacfa66ef26"
+        );
+        assert_eq!(
+            read_hex(&mut SHAKE256::new_default().finalize_xof(), 64),
+            "46b9dd2b0ba88d13233b3feb743eeb243fcd52ea62b81b82b50c27646ed5762fd75dc4ddd8c0f200cb05019d67b592f6fc821c49479ab48640292eacb3b7c4be"
+        );
+        let mut shake = SHAKE128::new_default();
+        shake.update(b"The quick brown fox jumps over the lazy dog");
+        assert_eq!(
+            read_hex(&mut shake.finalize_xof(), 32),
+            "f4202e3c5852f9182a0430fd8144f0a74b95e7417ecae17db0f8cfeed0e3e66e"
+        );
+    }
+
+    #[test]
+    fn shake_reads_in_pieces() {
+        let message: Vec<u8> = (0..300_u32).map(|i| (i * 7 + 3) as u8).collect();
+        let mut shake = SHAKE256::new_default();
+        shake.update(&message);
+        let mut whole = [0u8; 1000];
+        shake.finalize_xof().read(&mut whole);
+        assert_eq!(
+            get_hash_string(&whole[968..]),
+            "1cb52e82578d4e99147599484cb527a7a768d024aead4a2fa121584f3135e3d9"
+        );
+        let mut reader = shake.finalize_xof();
+        let mut pieces = vec![];
+        let mut len = 1;
+        while pieces.len() < whole.len() {
+            let mut piece = vec![0; len.min(whole.len() - pieces.len())];
+            reader.read(&mut piece);
+            pieces.extend(piece);
+            len += 1;
+        }
+        assert_eq!(pieces, whole);
+    }
+
+    #[test]
+    fn cshake() {
+        // NIST SP 800-185 cSHAKE samples
+        let short = [0, 1, 2, 3];
+        let long: Vec<u8> = (0..200).collect();
+        let mut cshake = CSHAKE128::new(b"", b"Email Signature");
+        cshake.update(&short);
+        assert_eq!(
+            read_hex(&mut cshake.finalize_xof(), 32),
+            "c1c36925b6409a04f1b504fcbca9d82b4017277cb5ed2b2065fc1d3814d5aaf5"
+        );
+        let mut cshake = CSHAKE128::new(b"", b"Email Signature");
+        cshake.update(&long);
+        assert_eq!(
+            read_hex(&mut cshake.finalize_xof(), 32),
+            "c5221d50e4f822d96a2e8881a961420f294b7b24fe3d2094baed2c6524cc166b"
+        );
+        let mut cshake = CSHAKE256::new(b"", b"Email Signature");
+        cshake.update(&short);
+        assert_eq!(
+            read_hex(&mut cshake.finalize_xof(), 64),
+            "d008828e2b80ac9d2218ffee1d070c48b8e4c87bff32c9699d5b6896eee0edd164020e2be0560858d9c00c037e34a96937c561a74c412bb4c746469527281c8c"
+        );
+        let mut cshake = CSHAKE256::new(b"", b"Email Signature");
+        cshake.update(&long);
+        assert_eq!(
+            read_hex(&mut cshake.finalize_xof(), 64),
+            "07dc27b11e51fbac75bc7b3c1d983e8b4b85fb1defaf218912ac86430273091727f42b17ed1df63e8ec118f04b23633c1dfb1574c8fb55cb45da8e25afb092bb"
+        );
+        // without a name and customization cSHAKE is SHAKE
+        let mut cshake = CSHAKE128::new(b"", b"");
+        cshake.update(b"The quick brown fox jumps over the lazy dog");
+        assert_eq!(
+            read_hex(&mut cshake.finalize_xof(), 32),
+            "f4202e3c5852f9182a0430fd8144f0a74b95e7417ecae17db0f8cfeed0e3e66e"
+        );
+    }
+
+    #[test]
+    fn encodings() {
+        assert_eq!(left_encode(0), [1, 0]);
+        assert_eq!(left_encode(168), [1, 168]);
+        assert_eq!(left_encode(256), [2, 1, 0]);
+        assert_eq!(
+            left_encode(u64::MAX),
+            [8, 255, 255, 255, 255, 255, 255, 255, 255]
+        );
+    }
+
+    #[test]
+    fn hmac() {
+        // NIST HMAC-SHA3-256 example, the HMAC block size of SHA3-256 is its rate
+        let key: Vec<u8> = (0..32).collect();
+        let mut hmac: HMAC<136, 32, SHA3_256> = HMAC::new_default();
+        hmac.add_key(&key).unwrap();
+        hmac.update(b"Sample message for keylen<blocklen");
+        assert_eq!(
+            get_hash_string(&hmac.finalize()),
+            "4fe8e202c4f058e8dddc23d8c34e467343e23555e24fc2f025d598f558f67205"
+        );
+    }
+}
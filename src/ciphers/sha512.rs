@@ -0,0 +1,393 @@
+//! SHA-2 512 bit implementation and its truncated variants
+//!
+//! This implementation is based on [`RFC6234`] and [`FIPS 180-4`]. SHA-384,
+//! SHA-512/224 and SHA-512/256 only differ from SHA-512 in their initial hash
+//! value and in the number of output bytes.
+//! Keep in mind that the amount of data (in bits) processed should always be an
+//! integer multiple of 8.
+//!
+//! [`RFC6234`]: https://www.rfc-editor.org/rfc/rfc6234
+//! [`FIPS 180-4`]: https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.180-4.pdf
+
+use super::merkle_damgard::{BlockBuffer, Endianness};
+use super::Hasher;
+
+pub const H0_512: [u64; 8] = [
+    0x6a09e667f3bcc908, 0xbb67ae8584caa73b, 0x3c6ef372fe94f82b, 0xa54ff53a5f1d36f1,
+    0x510e527fade682d1, 0x9b05688c2b3e6c1f, 0x1f83d9abfb41bd6b, 0x5be0cd19137e2179,
+];
+
+pub const H0_384: [u64; 8] = [
+    0xcbbb9d5dc1059ed8, 0x629a292a367cd507, 0x9159015a3070dd17, 0x152fecd8f70e5939,
+    0x67332667ffc00b31, 0x8eb44a8768581511, 0xdb0c2e0d64f98fa7, 0x47b5481dbefa4fa4,
+];
+
+pub const H0_512_224: [u64; 8] = [
+    0x8c3d37c819544da2, 0x73e1996689dcd4d6, 0x1dfab7ae32ff9c82, 0x679dd514582f9fcf,
+    0x0f6d2b697bd44da8, 0x77e36f7304c48942, 0x3f9d85a86a1d36c8, 0x1112e6ad91d692a1,
+];
+
+pub const H0_512_256: [u64; 8] = [
+    0x22312194fc2bf72c, 0x9f555fa3c84c64c2, 0x2393b86b6f53b151, 0x963877195940eabd,
+    0x96283ee2a88effe3, 0xbe5e1e2553863992, 0x2b0199fc2c85b8aa, 0x0eb72ddc81c52ca2,
+];
+
+pub const K: [u64; 80] = [
+    0x428a2f98d728ae22, 0x7137449123ef65cd, 0xb5c0fbcfec4d3b2f, 0xe9b5dba58189dbbc,
+    0x3956c25bf348b538, 0x59f111f1b605d019, 0x923f82a4af194f9b, 0xab1c5ed5da6d8118,
+    0xd807aa98a3030242, 0x12835b0145706fbe, 0x243185be4ee4b28c, 0x550c7dc3d5ffb4e2,
+    0x72be5d74f27b896f, 0x80deb1fe3b1696b1, 0x9bdc06a725c71235, 0xc19bf174cf692694,
+    0xe49b69c19ef14ad2, 0xefbe4786384f25e3, 0x0fc19dc68b8cd5b5, 0x240ca1cc77ac9c65,
+    0x2de92c6f592b0275, 0x4a7484aa6ea6e483, 0x5cb0a9dcbd41fbd4, 0x76f988da831153b5,
+    0x983e5152ee66dfab, 0xa831c66d2db43210, 0xb00327c898fb213f, 0xbf597fc7beef0ee4,
+    0xc6e00bf33da88fc2, 0xd5a79147930aa725, 0x06ca6351e003826f, 0x142929670a0e6e70,
+    0x27b70a8546d22ffc, 0x2e1b21385c26c926, 0x4d2c6dfc5ac42aed, 0x53380d139d95b3df,
+    0x650a73548baf63de, 0x766a0abb3c77b2a8, 0x81c2c92e47edaee6, 0x92722c851482353b,
+    0xa2bfe8a14cf10364, 0xa81a664bbc423001, 0xc24b8b70d0f89791, 0xc76c51a30654be30,
+    0xd192e819d6ef5218, 0xd69906245565a910, 0xf40e35855771202a, 0x106aa07032bbd1b8,
+    0x19a4c116b8d2d0c8, 0x1e376c085141ab53, 0x2748774cdf8eeb99, 0x34b0bcb5e19b48a8,
+    0x391c0cb3c5c95a63, 0x4ed8aa4ae3418acb, 0x5b9cca4f7763e373, 0x682e6ff3d6b2b8a3,
+    0x748f82ee5defb2fc, 0x78a5636f43172f60, 0x84c87814a1f0ab72, 0x8cc702081a6439ec,
+    0x90befffa23631e28, 0xa4506cebde82bde9, 0xbef9a3f7b2c67915, 0xc67178f2e372532b,
+    0xca273eceea26619c, 0xd186b8c721c0c207, 0xeada7dd6cde0eb1e, 0xf57d4f7fee6ed178,
+    0x06f067aa72176fba, 0x0a637dc5a2c898a6, 0x113f9804bef90dae, 0x1b710b35131c471b,
+    0x28db77f523047d84, 0x32caab7b40c72493, 0x3c9ebe0a15c9bebc, 0x431d67c49c100d4c,
+    0x4cc5d4becb3e42b6, 0x597f299cfc657e2a, 0x5fcb6fab3ad6faec, 0x6c44198c4a475817,
+];
+
+// The following functions are implemented according to
+// page 11 of RFC6234 - https://www.rfc-editor.org/rfc/rfc6234#page-11
+
+#[inline]
+fn ch(x: u64, y: u64, z: u64) -> u64 {
+    (x & y) ^ ((!x) & z)
+}
+
+#[inline]
+fn maj(x: u64, y: u64, z: u64) -> u64 {
+    (x & y) ^ (x & z) ^ (y & z)
+}
+
+#[inline]
+fn bsig0(x: u64) -> u64 {
+    x.rotate_right(28) ^ x.rotate_right(34) ^ x.rotate_right(39)
+}
+
+#[inline]
+fn bsig1(x: u64) -> u64 {
+    x.rotate_right(14) ^ x.rotate_right(18) ^ x.rotate_right(41)
+}
+
+#[inline]
+fn ssig0(x: u64) -> u64 {
+    x.rotate_right(1) ^ x.rotate_right(8) ^ (x >> 7)
+}
+
+#[inline]
+fn ssig1(x: u64) -> u64 {
+    x.rotate_right(19) ^ x.rotate_right(61) ^ (x >> 6)
+}
+
+fn process_block(h: &mut [u64; 8], w: &mut [u64; 80], block: &[u8; 128]) {
+    // Prepare the message schedule:
+    for (word, bytes) in w.iter_mut().zip(block.chunks_exact(8)) {
+        *word = u64::from_be_bytes(bytes.try_into().unwrap());
+    }
+    for i in 16..w.len() {
+        w[i] = ssig1(w[i - 2])
+            .wrapping_add(w[i - 7])
+            .wrapping_add(ssig0(w[i - 15]))
+            .wrapping_add(w[i - 16]);
+    }
+    let mut round = *h;
+    for i in 0..w.len() {
+        let t1 = round[7]
+            .wrapping_add(bsig1(round[4]))
+            .wrapping_add(ch(round[4], round[5], round[6]))
+            .wrapping_add(K[i])
+            .wrapping_add(w[i]);
+        let t2 = bsig0(round[0]).wrapping_add(maj(round[0], round[1], round[2]));
+        round.copy_within(0..7, 1);
+        round[4] = round[4].wrapping_add(t1);
+        round[0] = t1.wrapping_add(t2);
+    }
+    for (h, r) in h.iter_mut().zip(round) {
+        *h = h.wrapping_add(r);
+    }
+}
+
+/// SHA-2 512 bit implementation
+///
+/// This implementation is based on [`RFC6234`]. Messages are padded with a 128
+/// bit length, as opposed to the 64 bit one of [`SHA256`](super::SHA256).
+///
+/// [`RFC6234`]: https://www.rfc-editor.org/rfc/rfc6234
+#[derive(Debug, Clone)]
+pub struct SHA512 {
+    /// The current block to be processed, 1024 bits long, and the message length
+    buffer: BlockBuffer<128>,
+    /// The current hash value. Note: this value is invalid unless `finalize` is called
+    pub h: [u64; 8],
+    /// Message schedule
+    w: [u64; 80],
+    /// Whether the hash has been finalized or not
+    pub finalized: bool,
+}
+
+impl SHA512 {
+    /// Create a new SHA512 instance with the default initial hash value
+    pub fn new_default() -> Self {
+        Self::with_initial_hash(H0_512)
+    }
+
+    /// Create a new instance starting from the hash value `h`
+    fn with_initial_hash(h: [u64; 8]) -> Self {
+        SHA512 {
+            buffer: BlockBuffer::new(),
+            h,
+            w: [0u64; 80],
+            finalized: false,
+        }
+    }
+
+    /// Update the hash with the given data
+    pub fn update(&mut self, data: &[u8]) {
+        let Self { h, w, .. } = self;
+        self.buffer.update(data, |block| process_block(h, w, block));
+    }
+
+    /// Returns the hash of this [`SHA512`].
+    pub fn get_hash(&mut self) -> [u8; 64] {
+        if !self.finalized {
+            self.finalized = true;
+            let Self { h, w, .. } = self;
+            self.buffer
+                .pad(16, Endianness::Big, |block| process_block(h, w, block));
+        }
+        let mut result = [0u8; 64];
+        for (bytes, word) in result.chunks_exact_mut(8).zip(self.h) {
+            bytes.copy_from_slice(&word.to_be_bytes());
+        }
+        result
+    }
+}
+
+impl Hasher<64> for SHA512 {
+    fn new_default() -> Self {
+        SHA512::new_default()
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.update(data);
+    }
+
+    fn get_hash(&mut self) -> [u8; 64] {
+        self.get_hash()
+    }
+}
+
+/// Defines a truncated [`SHA512`] variant with its own initial hash value
+macro_rules! truncated_sha512 {
+    ($(#[$meta:meta])* $name:ident, $h0:expr, $bytes:literal) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone)]
+        pub struct $name(SHA512);
+
+        impl $name {
+            #[doc = concat!("Create a new ", stringify!($name), " instance with the default initial hash value")]
+            pub fn new_default() -> Self {
+                Self(SHA512::with_initial_hash($h0))
+            }
+
+            /// Update the hash with the given data
+            pub fn update(&mut self, data: &[u8]) {
+                self.0.update(data);
+            }
+
+            #[doc = concat!("Returns the hash of this [`", stringify!($name), "`].")]
+            pub fn get_hash(&mut self) -> [u8; $bytes] {
+                self.0.get_hash()[..$bytes].try_into().unwrap()
+            }
+        }
+
+        impl Hasher<$bytes> for $name {
+            fn new_default() -> Self {
+                $name::new_default()
+            }
+
+            fn update(&mut self, data: &[u8]) {
+                self.update(data);
+            }
+
+            fn get_hash(&mut self) -> [u8; $bytes] {
+                self.get_hash()
+            }
+        }
+    };
+}
+
+truncated_sha512!(
+    /// SHA-2 384 bit implementation, a truncated [`SHA512`]
+    SHA384,
+    H0_384,
+    48
+);
+
+truncated_sha512!(
+    /// SHA-512/224 implementation, a truncated [`SHA512`]
+    #[allow(non_camel_case_types)]
+    SHA512_224,
+    H0_512_224,
+    28
+);
+
+truncated_sha512!(
+    /// SHA-512/256 implementation, a truncated [`SHA512`]
+    #[allow(non_camel_case_types)]
+    SHA512_256,
+    H0_512_256,
+    32
+);
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_utils::{cavp_vectors, nist_vectors};
+    use super::*;
+
+    #[test]
+    fn sha512_nist() {
+        nist_vectors::<64, SHA512>([
+            "cf83e1357eefb8bdf1542850d66d8007d620e4050b5715dc83f4a921d36ce9ce47d0d13c5d85f2b0ff8318d2877eec2f63b931bd47417a81a538327af927da3e",
+            "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f",
+            "204a8fc6dda82f0a0ced7beb8e08a41657c16ef468b228a8279be331a703c33596fd15c13b1b07f9aa1d3bea57789ca031ad85c7a71dd70354ec631238ca3445",
+            "8e959b75dae313da8cf4f72814fc143f8f7779c6eb9f7fa17299aeadb6889018501d289e4900f7e4331b99dec4b5433ac7d329eeb6dd26545e96e55b874be909",
+            "e718483d0ce769644e2e42c7bc15b4638e1f98b13b2044285632a803afa973ebde0ff244877ea60a4cb0432ce577c31beb009c5c2c49aa2e4eadb217ad8cc09b",
+        ]);
+    }
+
+    #[test]
+    fn sha384_nist() {
+        nist_vectors::<48, SHA384>([
+            "38b060a751ac96384cd9327eb1b1e36a21fdb71114be07434c0cc7bf63f6e1da274edebfe76f65fbd51ad2f14898b95b",
+            "cb00753f45a35e8bb5a03d699ac65007272c32ab0eded1631a8b605a43ff5bed8086072ba1e7cc2358baeca134c825a7",
+            "3391fdddfc8dc7393707a65b1b4709397cf8b1d162af05abfe8f450de5f36bc6b0455a8520bc4e6f5fe95b1fe3c8452b",
+            "09330c33f71147e83d192fc782cd1b4753111b173b3b05d22fa08086e3b0f712fcc7c71a557e2db966c3e9fa91746039",
+            "9d0e1809716474cb086e834e310a4a1ced149e9c00f248527972cec5704c2a5b07b8b3dc38ecc4ebae97ddd87f3d8985",
+        ]);
+    }
+
+    #[test]
+    fn sha512_224_nist() {
+        nist_vectors::<28, SHA512_224>([
+            "6ed0dd02806fa89e25de060c19d3ac86cabb87d6a0ddd05c333b84f4",
+            "4634270f707b6a54daae7530460842e20e37ed265ceee9a43e8924aa",
+            "e5302d6d54bb242275d1e7622d68df6eb02dedd13f564c13dbda2174",
+            "23fec5bb94d60b23308192640b0c453335d664734fe40e7268674af9",
+            "37ab331d76f0d36de422bd0edeb22a28accd487b7a8453ae965dd287",
+        ]);
+    }
+
+    #[test]
+    fn sha512_256_nist() {
+        nist_vectors::<32, SHA512_256>([
+            "c672b8d1ef56ed28ab87c3622c5114069bdd3ad7b8f9737498d0c01ecef0967a",
+            "53048e2681941ef99b2e29b76b4c7dabe4c2d0c634fc6d46e0e2f13107e7af23",
+            "bde8e1f9f19bb9fd3406c90ec6bc47bd36d8ada9f11880dbc8a22a7078b6a461",
+            "3928e184fb8690f840da3988121d31be65cb9d3ef83ee6146feac861e19b563a",
+            "9a59a052930187a97038cae692f30708aa6491923ef5194394dc68d56c74fb21",
+        ]);
+    }
+
+    #[test]
+    fn cavp_short_messages() {
+        // SHA512ShortMsg.rsp, SHA384ShortMsg.rsp, SHA512_224ShortMsg.rsp and
+        // SHA512_256ShortMsg.rsp, the 1024 bit message filling a whole block
+        cavp_vectors::<64, SHA512>(&[
+            (
+                "21",
+                "3831a6a6155e509dee59a7f451eb35324d8f8f2df6e3708894740f98fdee23889f4de5adb0c5010dfb555cda77c8ab5dc902094c52de3278f35a75ebc25f093a",
+            ),
+            (
+                "9083",
+                "55586ebba48768aeb323655ab6f4298fc9f670964fc2e5f2731e34dfa4b0c09e6e1e12e3d7286b3145c61c2047fb1a2a1297f36da64160b31fa4c8c2cddd2fb4",
+            ),
+            (
+                "0a55db",
+                "7952585e5330cb247d72bae696fc8a6b0f7d0804577e347d99bc1b11e52f384985a428449382306a89261ae143c2f3fb613804ab20b42dc097e5bf4a96ef919b",
+            ),
+            (
+                "23be86d5",
+                "76d42c8eadea35a69990c63a762f330614a4699977f058adb988f406fb0be8f2ea3dce3a2bbd1d827b70b9b299ae6f9e5058ee97b50bd4922d6d37ddc761f8eb",
+            ),
+            (
+                concat!(
+                    "fd2203e467574e834ab07c9097ae164532f24be1eb5d88f1af7748ceff0d2c67",
+                    "a21f4e4097f9d3bb4e9fbf97186e0db6db0100230a52b453d421f8ab9c9a6043",
+                    "aa3295ea20d2f06a2f37470d8a99075f1b8a8336f6228cf08b5942fc1fb4299c",
+                    "7d2480e8e82bce175540bdfad7752bc95b577f229515394f3ae5cec870a4b2f8",
+                ),
+                "a21b1077d52b27ac545af63b32746c6e3c51cb0cb9f281eb9f3580a6d4996d5c9917d2a6e484627a9d5a06fa1b25327a9d710e027387fc3e07d7c4d14c6086cc",
+            ),
+        ]);
+        cavp_vectors::<48, SHA384>(&[
+            (
+                "c5",
+                "b52b72da75d0666379e20f9b4a79c33a329a01f06a2fb7865c9062a28c1de860ba432edfd86b4cb1cb8a75b46076e3b1",
+            ),
+            (
+                "6ece",
+                "53d4773da50d8be4145d8f3a7098ff3691a554a29ae6f652cc7121eb8bc96fd2210e06ae2fa2a36c4b3b3497341e70f0",
+            ),
+        ]);
+        cavp_vectors::<28, SHA512_224>(&[(
+            "cf",
+            "4199239e87d47b6feda016802bf367fb6e8b5655eff6225cb2668f4a",
+        )]);
+        cavp_vectors::<32, SHA512_256>(&[
+            (
+                "fa",
+                "c4ef36923c64e51e875720e550298a5ab8a3f2f875b1e1a4c9b95babf7344fef",
+            ),
+            (
+                "74e4",
+                "0c994228b8d3bd5ea5b5259157a9bba7a193118ad22817e6fbed2df1a32a4148",
+            ),
+        ]);
+    }
+
+    #[test]
+    fn truncated_initial_hash_values() {
+        // FIPS 180-4 5.3.6: the initial hash value of SHA-512/t is the
+        // SHA-512 hash of "SHA-512/t", computed from H0 ^ 0xa5a5a5...
+        for (name, h0) in [("SHA-512/224", H0_512_224), ("SHA-512/256", H0_512_256)] {
+            let mut sha = SHA512::with_initial_hash(H0_512.map(|h| h ^ 0xa5a5a5a5a5a5a5a5));
+            sha.update(name.as_bytes());
+            sha.get_hash();
+            assert_eq!(sha.h, h0);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "big-math")]
+    fn test_constants() {
+        use crate::math::LinearSieve;
+        use num_bigint::BigUint;
+
+        let mut ls = LinearSieve::new();
+        ls.prepare(409).unwrap();
+        let frac = |root: BigUint| -> u64 { (root & BigUint::from(u64::MAX)).try_into().unwrap() };
+        for (i, &p) in ls.primes.iter().enumerate().take(80) {
+            // first 64 bits of the fractional part of the cube root of p
+            assert_eq!(frac((BigUint::from(p) << 192_u32).cbrt()), K[i]);
+        }
+        for (i, &p) in ls.primes.iter().enumerate().take(8) {
+            // first 64 bits of the fractional part of the square root of p
+            assert_eq!(frac((BigUint::from(p) << 128_u32).sqrt()), H0_512[i]);
+        }
+        for (i, &p) in ls.primes[8..].iter().enumerate().take(8) {
+            assert_eq!(frac((BigUint::from(p) << 128_u32).sqrt()), H0_384[i]);
+        }
+        // SHA-224 uses the second 32 bits of the same fractional parts
+        assert_eq!(H0_384.map(|h| h as u32), super::super::sha256::H0_224);
+    }
+}
@@ -0,0 +1,56 @@
+//! Helpers shared by the tests of the hash functions and block ciphers
+
+use super::Hasher;
+use crate::encoding::{Encoding, Hex};
+
+/// Lowercase hexadecimal representation of `hash`
+pub fn get_hash_string(hash: &[u8]) -> String {
+    Hex::LOWER.encode(hash)
+}
+
+/// Bytes of the lowercase hexadecimal string `hex`
+pub fn from_hex(hex: &str) -> Vec<u8> {
+    Hex::LOWER.decode(hex).unwrap()
+}
+
+/// Checks the FIPS 180-2 example messages: the empty message, "abc", the
+/// 448 and 896 bit messages and one million repetitions of "a". The 448 and
+/// 896 bit messages leave no room for the length in the last block of 64 and
+/// 128 byte block functions, so their padding takes a block of its own.
+pub fn nist_vectors<const N: usize, H: Hasher<N>>(expected: [&str; 5]) {
+    let messages: [&[u8]; 4] = [
+        b"",
+        b"abc",
+        b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+        b"abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmnhijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu",
+    ];
+    for (message, expected) in messages.iter().zip(expected) {
+        let mut hasher = H::new_default();
+        hasher.update(message);
+        assert_eq!(get_hash_string(&hasher.get_hash()), expected);
+    }
+    let mut hasher = H::new_default();
+    let block = [b'a'; 1000];
+    for _ in 0..1000 {
+        hasher.update(&block);
+    }
+    assert_eq!(get_hash_string(&hasher.get_hash()), expected[4]);
+}
+
+/// Checks pairs of a message and its expected hash
+pub fn known_answers<const N: usize, H: Hasher<N>>(vectors: &[(&[u8], &str)]) {
+    for (message, expected) in vectors {
+        let mut hasher = H::new_default();
+        hasher.update(message);
+        assert_eq!(get_hash_string(&hasher.get_hash()), *expected);
+    }
+}
+
+/// Checks byte-oriented vectors of the NIST CAVP `ShortMsg` files, given as
+/// the hexadecimal message and its expected hash
+pub fn cavp_vectors<const N: usize, H: Hasher<N>>(vectors: &[(&str, &str)]) {
+    for (message, expected) in vectors {
+        let message = Hex::LOWER.decode(message).unwrap();
+        known_answers::<N, H>(&[(&message, expected)]);
+    }
+}
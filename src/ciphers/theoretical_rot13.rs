@@ -1,21 +1,46 @@
 //! In theory, rot13 only encodes lowercase characters.
 
+use super::caesar::rotate_letter;
+use super::ClassicalCipher;
+
+/// ROT13 of the lowercase letters only, which is its own inverse
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TheoreticalRot13;
+
+impl ClassicalCipher for TheoreticalRot13 {
+    type Key = ();
+
+    fn new(_: ()) -> Result<Self, &'static str> {
+        Ok(Self)
+    }
+
+    fn encrypt_bytes(&self, data: &[u8]) -> Vec<u8> {
+        data.iter()
+            .map(|&b| {
+                if b.is_ascii_lowercase() {
+                    rotate_letter(b, 13)
+                } else {
+                    b
+                }
+            })
+            .collect()
+    }
+
+    fn decrypt_bytes(&self, data: &[u8]) -> Vec<u8> {
+        self.encrypt_bytes(data)
+    }
+}
+
+/// ROT13 of the lowercase letters of `text`, leaving the others as they are
 pub fn theoretical_rot13(text: impl AsRef<str>) -> String {
-    text.as_ref()
-        .chars()
-        .map(|c| {
-            if c.is_ascii_lowercase() {
-                char::from(((c as u8 - b'a' + 13) % 26) + b'a')
-            } else {
-                c
-            }
-        })
-        .collect()
+    TheoreticalRot13.encrypt(text.as_ref())
 }
 
 #[cfg(test)]
 mod tests {
+    use super::super::classical_cipher::tests::round_trips;
     use super::*;
+    use quickcheck_macros::quickcheck;
 
     #[test]
     fn single_character() {
@@ -36,4 +61,14 @@ mod tests {
     fn encode_twice_decodes() {
         assert_eq!("abcd", theoretical_rot13(theoretical_rot13("abcd")));
     }
+
+    #[test]
+    fn uppercase() {
+        assert_eq!("Aop", theoretical_rot13("Abc"));
+    }
+
+    #[quickcheck]
+    fn round_trip(text: String, bytes: Vec<u8>) -> bool {
+        round_trips(&TheoreticalRot13, &text, &bytes)
+    }
 }
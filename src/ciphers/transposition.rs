@@ -0,0 +1,178 @@
+//! Rail fence and columnar transposition ciphers
+//!
+//! Transposition ciphers keep the characters of the message, but change their
+//! order. The rail fence cipher writes the message in a zigzag over a number of
+//! rails and reads it off rail by rail. The columnar transposition writes the
+//! message in rows under a keyword and reads it off column by column, in the
+//! alphabetical order of the letters of the keyword. The last row may be
+//! incomplete, so there is no padding.
+//!
+//! Every character, including spaces and punctuation, is moved. The text
+//! methods move whole characters rather than bytes.
+//!
+//! - [`Wikipedia`](https://en.wikipedia.org/wiki/Transposition_cipher)
+
+use super::classical_cipher::letter_key;
+use super::ClassicalCipher;
+
+/// Returns `data[order[0]], data[order[1]], ...`
+fn gather<T: Copy>(data: &[T], order: &[usize]) -> Vec<T> {
+    order.iter().map(|&i| data[i]).collect()
+}
+
+/// The inverse of [`gather`]
+fn scatter<T: Copy>(data: &[T], order: &[usize]) -> Vec<T> {
+    let mut result = data.to_vec();
+    for (&i, &x) in order.iter().zip(data) {
+        result[i] = x;
+    }
+    result
+}
+
+/// The methods of [`ClassicalCipher`] for a cipher with a method `order`,
+/// which returns the positions of the message in the order they are read off
+macro_rules! transposition_methods {
+    () => {
+        fn encrypt_bytes(&self, data: &[u8]) -> Vec<u8> {
+            gather(data, &self.order(data.len()))
+        }
+
+        fn decrypt_bytes(&self, data: &[u8]) -> Vec<u8> {
+            scatter(data, &self.order(data.len()))
+        }
+
+        fn encrypt(&self, text: &str) -> String {
+            let chars: Vec<char> = text.chars().collect();
+            gather(&chars, &self.order(chars.len()))
+                .into_iter()
+                .collect()
+        }
+
+        fn decrypt(&self, text: &str) -> String {
+            let chars: Vec<char> = text.chars().collect();
+            scatter(&chars, &self.order(chars.len()))
+                .into_iter()
+                .collect()
+        }
+    };
+}
+
+/// Rail fence cipher, see the [module documentation](self)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RailFence {
+    rails: usize,
+}
+
+impl RailFence {
+    /// The positions of the message in the order they are read off
+    fn order(&self, len: usize) -> Vec<usize> {
+        let period = (self.rails - 1).saturating_mul(2);
+        let rail = |i: usize| {
+            if period == 0 {
+                return 0;
+            }
+            let i = i % period;
+            i.min(period - i)
+        };
+        let mut order: Vec<usize> = (0..len).collect();
+        order.sort_by_key(|&i| rail(i));
+        order
+    }
+}
+
+impl ClassicalCipher for RailFence {
+    /// The number of rails, which must be positive
+    type Key = usize;
+
+    fn new(rails: usize) -> Result<Self, &'static str> {
+        if rails == 0 {
+            return Err("Number of rails must be positive");
+        }
+        Ok(Self { rails })
+    }
+
+    transposition_methods!();
+}
+
+/// Columnar transposition cipher, see the [module documentation](self)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Columnar {
+    /// The columns in the order they are read off
+    columns: Vec<usize>,
+}
+
+impl Columnar {
+    /// The positions of the message in the order they are read off
+    fn order(&self, len: usize) -> Vec<usize> {
+        let width = self.columns.len();
+        self.columns
+            .iter()
+            .flat_map(|&column| (column..len).step_by(width))
+            .collect()
+    }
+}
+
+impl ClassicalCipher for Columnar {
+    /// The keyword, which must only contain ASCII letters. Columns under the
+    /// same letter are read from left to right.
+    type Key = String;
+
+    fn new(key: String) -> Result<Self, &'static str> {
+        let key = letter_key(&key)?;
+        let mut columns: Vec<usize> = (0..key.len()).collect();
+        columns.sort_by_key(|&i| key[i]);
+        Ok(Self { columns })
+    }
+
+    transposition_methods!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::classical_cipher::tests::round_trips;
+    use super::*;
+    use quickcheck_macros::quickcheck;
+
+    #[test]
+    fn rail_fence() {
+        let cipher = RailFence::new(3).unwrap();
+        assert_eq!(
+            cipher.encrypt("WEAREDISCOVEREDRUNATONCE"),
+            "WECRUOERDSOEERNTNEAIVDAC"
+        );
+        assert_eq!(
+            cipher.decrypt("WECRUOERDSOEERNTNEAIVDAC"),
+            "WEAREDISCOVEREDRUNATONCE"
+        );
+        assert_eq!(RailFence::new(1).unwrap().encrypt("abc"), "abc");
+        assert_eq!(RailFence::new(2).unwrap().encrypt("añb€"), "abñ€");
+        assert!(RailFence::new(0).is_err());
+    }
+
+    #[test]
+    fn columnar() {
+        let cipher = Columnar::new("ZEBRAS".to_owned()).unwrap();
+        assert_eq!(
+            cipher.encrypt("WEAREDISCOVEREDFLEEATONCE"),
+            "EVLNACDTESEAROFODEECWIREE"
+        );
+        assert_eq!(
+            cipher.decrypt("EVLNACDTESEAROFODEECWIREE"),
+            "WEAREDISCOVEREDFLEEATONCE"
+        );
+        assert!(Columnar::new(String::new()).is_err());
+    }
+
+    #[quickcheck]
+    fn round_trip(rails: u8, key: Vec<u8>, text: String, bytes: Vec<u8>) -> bool {
+        let key: String = std::iter::once(0)
+            .chain(key)
+            .map(|b| char::from(b'a' + b % 26))
+            .collect();
+        round_trips(
+            &RailFence::new(usize::from(rails) + 1).unwrap(),
+            &text,
+            &bytes,
+        ) && round_trips(&Columnar::new(key).unwrap(), &text, &bytes)
+    }
+}
@@ -0,0 +1,161 @@
+//! Vigenère cipher and its variants
+//!
+//! The Vigenère cipher shifts every letter by the corresponding letter of a
+//! repeated keyword, i.e. it uses a different Caesar cipher for every
+//! position. The Beaufort cipher subtracts the plaintext from the key instead,
+//! which makes it its own inverse, and the autokey cipher extends the keyword
+//! with the plaintext itself instead of repeating it.
+//!
+//! Only ASCII letters are encrypted and they keep their case. The other
+//! characters are left as they are and do not use up a letter of the key.
+//!
+//! - [`Wikipedia`](https://en.wikipedia.org/wiki/Vigen%C3%A8re_cipher)
+
+use super::classical_cipher::{letter_key, map_letters};
+use super::ClassicalCipher;
+
+/// Vigenère cipher, see the [module documentation](self)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Vigenere {
+    key: Vec<u8>,
+}
+
+impl ClassicalCipher for Vigenere {
+    /// The keyword, which must only contain ASCII letters
+    type Key = String;
+
+    fn new(key: String) -> Result<Self, &'static str> {
+        Ok(Self {
+            key: letter_key(&key)?,
+        })
+    }
+
+    fn encrypt_bytes(&self, data: &[u8]) -> Vec<u8> {
+        let mut key = self.key.iter().cycle();
+        map_letters(data, |p| (p + key.next().unwrap()) % 26)
+    }
+
+    fn decrypt_bytes(&self, data: &[u8]) -> Vec<u8> {
+        let mut key = self.key.iter().cycle();
+        map_letters(data, |c| (c + 26 - key.next().unwrap()) % 26)
+    }
+}
+
+/// Beaufort cipher, see the [module documentation](self)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Beaufort {
+    key: Vec<u8>,
+}
+
+impl ClassicalCipher for Beaufort {
+    /// The keyword, which must only contain ASCII letters
+    type Key = String;
+
+    fn new(key: String) -> Result<Self, &'static str> {
+        Ok(Self {
+            key: letter_key(&key)?,
+        })
+    }
+
+    fn encrypt_bytes(&self, data: &[u8]) -> Vec<u8> {
+        let mut key = self.key.iter().cycle();
+        map_letters(data, |p| (key.next().unwrap() + 26 - p) % 26)
+    }
+
+    fn decrypt_bytes(&self, data: &[u8]) -> Vec<u8> {
+        self.encrypt_bytes(data)
+    }
+}
+
+/// Autokey cipher, see the [module documentation](self)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Autokey {
+    key: Vec<u8>,
+}
+
+impl ClassicalCipher for Autokey {
+    /// The primer, which must only contain ASCII letters
+    type Key = String;
+
+    fn new(key: String) -> Result<Self, &'static str> {
+        Ok(Self {
+            key: letter_key(&key)?,
+        })
+    }
+
+    fn encrypt_bytes(&self, data: &[u8]) -> Vec<u8> {
+        let mut key = self.key.clone();
+        let mut position = 0;
+        map_letters(data, |p| {
+            key.push(p);
+            position += 1;
+            (p + key[position - 1]) % 26
+        })
+    }
+
+    fn decrypt_bytes(&self, data: &[u8]) -> Vec<u8> {
+        let mut key = self.key.clone();
+        let mut position = 0;
+        map_letters(data, |c| {
+            let p = (c + 26 - key[position]) % 26;
+            key.push(p);
+            position += 1;
+            p
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::classical_cipher::tests::round_trips;
+    use super::*;
+    use quickcheck_macros::quickcheck;
+
+    #[test]
+    fn vigenere() {
+        let cipher = Vigenere::new("LEMON".to_owned()).unwrap();
+        assert_eq!(cipher.encrypt("ATTACKATDAWN"), "LXFOPVEFRNHR");
+        assert_eq!(cipher.encrypt("Attack at dawn!"), "Lxfopv ef rnhr!");
+        assert_eq!(cipher.decrypt("Lxfopv ef rnhr!"), "Attack at dawn!");
+        // the case of the key does not matter
+        assert_eq!(Vigenere::new("lemon".to_owned()).unwrap(), cipher);
+    }
+
+    #[test]
+    fn beaufort() {
+        let cipher = Beaufort::new("FORTIFICATION".to_owned()).unwrap();
+        assert_eq!(
+            cipher.encrypt("DEFENDTHEEASTWALLOFTHECASTLE"),
+            "CKMPVCPVWPIWUJOGIUAPVWRIWUUK"
+        );
+        assert_eq!(
+            cipher.decrypt("CKMPVCPVWPIWUJOGIUAPVWRIWUUK"),
+            "DEFENDTHEEASTWALLOFTHECASTLE"
+        );
+    }
+
+    #[test]
+    fn autokey() {
+        let cipher = Autokey::new("QUEENLY".to_owned()).unwrap();
+        assert_eq!(cipher.encrypt("attack at dawn"), "qnxepv yt wtwp");
+        assert_eq!(cipher.decrypt("qnxepv yt wtwp"), "attack at dawn");
+    }
+
+    #[test]
+    fn invalid_keys() {
+        assert!(Vigenere::new(String::new()).is_err());
+        assert!(Beaufort::new("two words".to_owned()).is_err());
+        assert!(Autokey::new("Müller".to_owned()).is_err());
+    }
+
+    #[quickcheck]
+    fn round_trip(key: Vec<u8>, text: String, bytes: Vec<u8>) -> bool {
+        let key: String = std::iter::once(0)
+            .chain(key)
+            .map(|b| char::from(b'a' + b % 26))
+            .collect();
+        round_trips(&Vigenere::new(key.clone()).unwrap(), &text, &bytes)
+            && round_trips(&Beaufort::new(key.clone()).unwrap(), &text, &bytes)
+            && round_trips(&Autokey::new(key).unwrap(), &text, &bytes)
+    }
+}
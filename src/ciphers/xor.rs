@@ -1,18 +1,61 @@
 //! Simple XOR cipher.
 
+use super::ClassicalCipher;
+
+/// XOR cipher, which XORs every byte with a single byte key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Xor {
+    key: u8,
+}
+
+impl ClassicalCipher for Xor {
+    type Key = u8;
+
+    fn new(key: u8) -> Result<Self, &'static str> {
+        Ok(Self { key })
+    }
+
+    fn encrypt_bytes(&self, data: &[u8]) -> Vec<u8> {
+        data.iter().map(|b| b ^ self.key).collect()
+    }
+
+    fn decrypt_bytes(&self, data: &[u8]) -> Vec<u8> {
+        self.encrypt_bytes(data)
+    }
+
+    /// XOR the code point of every character with the key. This only changes
+    /// the lowest 8 bits, so it never produces a surrogate from a valid
+    /// character.
+    fn encrypt(&self, text: &str) -> String {
+        text.chars()
+            .map(|c| char::from_u32(u32::from(c) ^ u32::from(self.key)).unwrap())
+            .collect()
+    }
+
+    fn decrypt(&self, text: &str) -> String {
+        self.encrypt(text)
+    }
+}
+
+/// XOR every byte of `text` with `key`
 #[inline]
 pub fn xor_bytes(text: &[u8], key: u8) -> Vec<u8> {
-    text.iter().map(|b| b ^ key).collect()
+    Xor { key }.encrypt_bytes(text)
 }
 
+/// UTF-8 encoding of `text` with the code point of every character XORed
+/// with `key`, see [`Xor::encrypt`]. For ASCII text and a key less than 128,
+/// this is the same as XORing the bytes of `text`.
 #[inline]
 pub fn xor(text: impl AsRef<str>, key: u8) -> Vec<u8> {
-    xor_bytes(text.as_ref().as_bytes(), key)
+    Xor { key }.encrypt(text.as_ref()).into_bytes()
 }
 
 #[cfg(test)]
 mod tests {
+    use super::super::classical_cipher::tests::round_trips;
     use super::*;
+    use quickcheck_macros::quickcheck;
 
     #[test]
     fn simple() {
@@ -29,4 +72,17 @@ mod tests {
         let chiphered = xor(text, key);
         assert_eq!(text.as_bytes(), xor_bytes(&chiphered, key));
     }
+
+    #[test]
+    fn text() {
+        let cipher = Xor::new(0xff).unwrap();
+        assert_eq!(cipher.encrypt("a\u{d7ff}"), "\u{9e}\u{d700}");
+        assert_eq!(cipher.decrypt("\u{9e}\u{d700}"), "a\u{d7ff}");
+        assert_eq!(xor("a\u{d7ff}", 0xff), "\u{9e}\u{d700}".as_bytes());
+    }
+
+    #[quickcheck]
+    fn round_trip(key: u8, text: String, bytes: Vec<u8>) -> bool {
+        round_trips(&Xor::new(key).unwrap(), &text, &bytes)
+    }
 }
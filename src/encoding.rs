@@ -0,0 +1,321 @@
+//! # Binary-to-text encodings
+//!
+//! The encodings implementing [`Encoding`] work on small groups of bytes, so
+//! they can be streamed with [`Encoder`] and [`Decoder`]:
+//!
+//! - [`Hex`], in lowercase or uppercase
+//! - [`Base64`], with the standard or the URL-safe alphabet, padded or not
+//! - [`Base32`], with the RFC 4648 alphabet, padded or not, or Crockford's
+//! - [`Ascii85`]
+//!
+//! Base58 and Base58Check, see [`base58_encode`] and [`base58check_encode`],
+//! treat the whole input as one number, so they can not be streamed.
+//!
+//! Decoding is strict: characters outside of the alphabet, wrong padding and
+//! non-zero unused bits are errors, reported with their position in the input
+//! as a [`DecodeError`].
+
+mod ascii85;
+mod base32;
+mod base58;
+mod base64;
+mod bits;
+mod hex;
+
+pub use self::ascii85::Ascii85;
+pub use self::base32::Base32;
+pub use self::base58::{base58_decode, base58_encode, base58check_decode, base58check_encode};
+pub use self::base64::Base64;
+pub use self::hex::Hex;
+
+use std::fmt;
+use std::io::{self, Write};
+
+/// An error found while decoding. Positions are byte indices in the input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+    /// A byte which is not part of the alphabet, or not allowed where it is
+    InvalidCharacter {
+        /// Position of the byte
+        index: usize,
+        /// The byte
+        byte: u8,
+    },
+    /// The last group of characters is too short to hold a whole byte, or
+    /// has a length which no input encodes to
+    IncompleteGroup {
+        /// Position of the first character of the group
+        index: usize,
+    },
+    /// Padding is missing, misplaced, or has the wrong length
+    InvalidPadding {
+        /// Position of the first wrong character, or the length of the input
+        /// if padding is missing
+        index: usize,
+    },
+    /// The last character has bits set which are not part of the data, so
+    /// the input is not the encoding of any data
+    InvalidTrailingBits {
+        /// Position of the character
+        index: usize,
+    },
+    /// A group of characters encodes a value which does not fit in the bytes
+    /// of the group
+    Overflow {
+        /// Position of the first character of the group
+        index: usize,
+    },
+    /// The checksum does not match the data, or the input is too short to
+    /// hold a checksum
+    InvalidChecksum,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::InvalidCharacter { index, byte } => {
+                write!(
+                    f,
+                    "invalid character {:?} at index {index}",
+                    char::from(*byte)
+                )
+            }
+            DecodeError::IncompleteGroup { index } => {
+                write!(f, "incomplete group starting at index {index}")
+            }
+            DecodeError::InvalidPadding { index } => write!(f, "invalid padding at index {index}"),
+            DecodeError::InvalidTrailingBits { index } => {
+                write!(f, "invalid trailing bits at index {index}")
+            }
+            DecodeError::Overflow { index } => {
+                write!(f, "group starting at index {index} overflows")
+            }
+            DecodeError::InvalidChecksum => write!(f, "invalid checksum"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// [`Encoding`] is a trait that defines the interface of the binary-to-text
+/// encodings which work on groups of bytes.
+///
+/// The `_chunk` methods do the work and support streaming: they are given the
+/// input seen so far and not yet consumed, and return how much of it they
+/// consumed. Unless `last` is `true`, they may leave an incomplete group for
+/// the next call.
+pub trait Encoding {
+    /// Encode as many whole groups of `data` as possible, or all of it if
+    /// `last`, appending to `out`. Returns the number of bytes consumed.
+    fn encode_chunk(&self, data: &[u8], last: bool, out: &mut String) -> usize;
+
+    /// Decode as many whole groups of `text` as possible, or all of it if
+    /// `last`, appending to `out`. `offset` is the position of `text` in the
+    /// whole input, which is added to the positions in errors. Returns the
+    /// number of bytes consumed.
+    fn decode_chunk(
+        &self,
+        text: &[u8],
+        offset: usize,
+        last: bool,
+        out: &mut Vec<u8>,
+    ) -> Result<usize, DecodeError>;
+
+    /// Encode `data`
+    fn encode(&self, data: &[u8]) -> String {
+        let mut out = String::new();
+        self.encode_chunk(data, true, &mut out);
+        out
+    }
+
+    /// Decode `text`
+    fn decode(&self, text: &str) -> Result<Vec<u8>, DecodeError> {
+        let mut out = Vec::new();
+        self.decode_chunk(text.as_bytes(), 0, true, &mut out)?;
+        Ok(out)
+    }
+}
+
+/// A writer which encodes the bytes written to it, and writes the encoding to
+/// an inner writer. [`finish`](Self::finish) must be called to encode the last
+/// group.
+#[derive(Debug, Clone)]
+pub struct Encoder<E: Encoding, W: Write> {
+    encoding: E,
+    inner: W,
+    pending: Vec<u8>,
+}
+
+impl<E: Encoding, W: Write> Encoder<E, W> {
+    /// Create a new instance encoding with `encoding` to `inner`
+    pub fn new(encoding: E, inner: W) -> Self {
+        Self {
+            encoding,
+            inner,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Encode the last group, and return the inner writer
+    pub fn finish(mut self) -> io::Result<W> {
+        let mut out = String::new();
+        self.encoding.encode_chunk(&self.pending, true, &mut out);
+        self.inner.write_all(out.as_bytes())?;
+        Ok(self.inner)
+    }
+}
+
+impl<E: Encoding, W: Write> Write for Encoder<E, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pending.extend_from_slice(buf);
+        let mut out = String::new();
+        let consumed = self.encoding.encode_chunk(&self.pending, false, &mut out);
+        self.pending.drain(..consumed);
+        self.inner.write_all(out.as_bytes())?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// A writer which decodes the text written to it, and writes the decoded
+/// bytes to an inner writer. [`finish`](Self::finish) must be called to decode
+/// the last group. Decoding errors are returned as [`io::Error`]s of kind
+/// [`InvalidData`](io::ErrorKind::InvalidData) wrapping a [`DecodeError`].
+#[derive(Debug, Clone)]
+pub struct Decoder<E: Encoding, W: Write> {
+    encoding: E,
+    inner: W,
+    pending: Vec<u8>,
+    /// Position of `pending` in the whole input
+    offset: usize,
+}
+
+impl<E: Encoding, W: Write> Decoder<E, W> {
+    /// Create a new instance decoding with `encoding` to `inner`
+    pub fn new(encoding: E, inner: W) -> Self {
+        Self {
+            encoding,
+            inner,
+            pending: Vec::new(),
+            offset: 0,
+        }
+    }
+
+    fn decode(&mut self, last: bool) -> io::Result<()> {
+        let mut out = Vec::new();
+        let consumed = self
+            .encoding
+            .decode_chunk(&self.pending, self.offset, last, &mut out)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        self.pending.drain(..consumed);
+        self.offset += consumed;
+        self.inner.write_all(&out)
+    }
+
+    /// Decode the last group, and return the inner writer
+    pub fn finish(mut self) -> io::Result<W> {
+        self.decode(true)?;
+        Ok(self.inner)
+    }
+}
+
+impl<E: Encoding, W: Write> Write for Decoder<E, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pending.extend_from_slice(buf);
+        self.decode(false)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use quickcheck_macros::quickcheck;
+
+    /// Encode `data` with an [`Encoder`], writing it in pieces of the given
+    /// sizes
+    fn stream_encode(encoding: impl Encoding, data: &[u8], sizes: &[u8]) -> String {
+        let mut encoder = Encoder::new(encoding, Vec::new());
+        let mut rest = data;
+        for &size in sizes.iter().cycle().take(data.len()) {
+            let (piece, tail) = rest.split_at(usize::from(size).min(rest.len()));
+            encoder.write_all(piece).unwrap();
+            rest = tail;
+        }
+        encoder.write_all(rest).unwrap();
+        String::from_utf8(encoder.finish().unwrap()).unwrap()
+    }
+
+    /// Decode `text` with a [`Decoder`], writing it in pieces of the given
+    /// sizes
+    fn stream_decode(
+        encoding: impl Encoding,
+        text: &str,
+        sizes: &[u8],
+    ) -> Result<Vec<u8>, DecodeError> {
+        let mut decoder = Decoder::new(encoding, Vec::new());
+        let mut rest = text.as_bytes();
+        let unwrap = |error: io::Error| *error.into_inner().unwrap().downcast().unwrap();
+        for &size in sizes.iter().cycle().take(text.len()) {
+            let (piece, tail) = rest.split_at(usize::from(size).min(rest.len()));
+            decoder.write_all(piece).map_err(unwrap)?;
+            rest = tail;
+        }
+        decoder.write_all(rest).map_err(unwrap)?;
+        decoder.finish().map_err(unwrap)
+    }
+
+    /// Whether `encoding` gives the same results streamed and in one go,
+    /// and decodes its encoding of `data`
+    pub(crate) fn streams(encoding: impl Encoding + Copy, data: &[u8], sizes: &[u8]) -> bool {
+        let text = encoding.encode(data);
+        stream_encode(encoding, data, sizes) == text
+            && stream_decode(encoding, &text, sizes).as_deref() == Ok(data)
+            && encoding.decode(&text).as_deref() == Ok(data)
+    }
+
+    #[test]
+    fn streaming_errors() {
+        // positions in errors are relative to the whole input
+        assert_eq!(
+            stream_decode(Base64::STANDARD, "Zm9vYmFy Zm9v", &[3]),
+            Err(DecodeError::InvalidCharacter {
+                index: 8,
+                byte: b' '
+            })
+        );
+        assert_eq!(
+            stream_decode(Base64::STANDARD, "Zm9vYg==Zm9v", &[1]),
+            Err(DecodeError::InvalidPadding { index: 8 })
+        );
+        assert_eq!(
+            stream_decode(Hex::LOWER, "abcde", &[2]),
+            Err(DecodeError::IncompleteGroup { index: 4 })
+        );
+        assert_eq!(
+            DecodeError::InvalidCharacter {
+                index: 3,
+                byte: b'!'
+            }
+            .to_string(),
+            "invalid character '!' at index 3"
+        );
+    }
+
+    #[quickcheck]
+    fn all_stream(data: Vec<u8>, sizes: Vec<u8>) -> bool {
+        let sizes: Vec<u8> = sizes.into_iter().map(|s| s % 9 + 1).collect();
+        streams(Hex::UPPER, &data, &sizes)
+            && streams(Base64::URL_SAFE_NO_PAD, &data, &sizes)
+            && streams(Base32::RFC4648, &data, &sizes)
+            && streams(Base32::CROCKFORD, &data, &sizes)
+            && streams(Ascii85, &data, &sizes)
+    }
+}
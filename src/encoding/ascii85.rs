@@ -0,0 +1,171 @@
+//! Ascii85 encoding
+//!
+//! Every group of 4 bytes, as a big-endian number, is written as 5 digits in
+//! base 85, with the characters `!` to `u`. A group of 4 zero bytes is written
+//! as `z` instead. The last group of `n < 4` bytes is padded with zero bytes,
+//! encoded, and only its first `n + 1` characters are kept. Decoding ignores
+//! whitespace.
+//!
+//! The `<~` and `~>` delimiters used by Adobe are not part of the encoding.
+//!
+//! - [`Wikipedia`](https://en.wikipedia.org/wiki/Ascii85)
+
+use super::{DecodeError, Encoding};
+
+/// Ascii85 encoding, see the [module documentation](self)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ascii85;
+
+impl Ascii85 {
+    /// Decode a group of 5 digits, the first one at position `index`
+    fn decode_group(digits: &[u8; 5], index: usize) -> Result<[u8; 4], DecodeError> {
+        let value = digits
+            .iter()
+            .fold(0u64, |value, &digit| 85 * value + u64::from(digit));
+        u32::try_from(value)
+            .map(u32::to_be_bytes)
+            .map_err(|_| DecodeError::Overflow { index })
+    }
+}
+
+impl Encoding for Ascii85 {
+    fn encode_chunk(&self, data: &[u8], last: bool, out: &mut String) -> usize {
+        let len = if last {
+            data.len()
+        } else {
+            data.len() - data.len() % 4
+        };
+        for group in data[..len].chunks(4) {
+            let mut bytes = [0; 4];
+            bytes[..group.len()].copy_from_slice(group);
+            let mut value = u32::from_be_bytes(bytes);
+            if value == 0 && group.len() == 4 {
+                out.push('z');
+                continue;
+            }
+            let mut digits = [0; 5];
+            for digit in digits.iter_mut().rev() {
+                *digit = b'!' + (value % 85) as u8;
+                value /= 85;
+            }
+            out.extend(digits[..=group.len()].iter().map(|&b| char::from(b)));
+        }
+        len
+    }
+
+    fn decode_chunk(
+        &self,
+        text: &[u8],
+        offset: usize,
+        last: bool,
+        out: &mut Vec<u8>,
+    ) -> Result<usize, DecodeError> {
+        let mut digits = [0; 5];
+        let mut count = 0;
+        let mut start = 0;
+        let mut consumed = 0;
+        for (i, &b) in text.iter().enumerate() {
+            match b {
+                b'!'..=b'u' => {
+                    if count == 0 {
+                        start = i;
+                    }
+                    digits[count] = b - b'!';
+                    count += 1;
+                    if count == 5 {
+                        out.extend(Self::decode_group(&digits, offset + start)?);
+                        count = 0;
+                        consumed = i + 1;
+                    }
+                }
+                b'z' if count == 0 => {
+                    out.extend([0; 4]);
+                    consumed = i + 1;
+                }
+                b if b.is_ascii_whitespace() => {}
+                _ => {
+                    return Err(DecodeError::InvalidCharacter {
+                        index: offset + i,
+                        byte: b,
+                    })
+                }
+            }
+        }
+        if !last {
+            return Ok(consumed);
+        }
+        match count {
+            0 => {}
+            1 => {
+                return Err(DecodeError::IncompleteGroup {
+                    index: offset + start,
+                })
+            }
+            _ => {
+                digits[count..].fill(84);
+                let bytes = Self::decode_group(&digits, offset + start)?;
+                out.extend(&bytes[..count - 1]);
+            }
+        }
+        Ok(text.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::streams;
+    use super::*;
+    use quickcheck_macros::quickcheck;
+
+    #[test]
+    fn wikipedia_examples() {
+        let vectors: [(&[u8], &str); 6] = [
+            (b"Man ", "9jqo^"),
+            (b"sure.", "F*2M7/c"),
+            (b"", ""),
+            (&[0; 4], "z"),
+            (&[0; 3], "!!!!"),
+            (b"Man is distinguished", "9jqo^BlbD-BleB1DJ+*+F(f,q"),
+        ];
+        for (data, text) in vectors {
+            assert_eq!(Ascii85.encode(data), text);
+            assert_eq!(Ascii85.decode(text).unwrap(), data);
+        }
+        assert_eq!(Ascii85.decode(" 9jqo\n^ z ").unwrap(), b"Man \0\0\0\0");
+    }
+
+    #[test]
+    fn errors() {
+        use DecodeError::*;
+        let cases = [
+            ("9jqo^F", IncompleteGroup { index: 5 }),
+            (
+                "9jzqo",
+                InvalidCharacter {
+                    index: 2,
+                    byte: b'z',
+                },
+            ),
+            (
+                "9jqo~",
+                InvalidCharacter {
+                    index: 4,
+                    byte: b'~',
+                },
+            ),
+            ("9jqo^s8W-\"", Overflow { index: 5 }),
+            ("s8W-\"", Overflow { index: 0 }),
+            ("uu", Overflow { index: 0 }),
+        ];
+        for (text, error) in cases {
+            assert_eq!(Ascii85.decode(text), Err(error), "{text}");
+        }
+        assert_eq!(Ascii85.decode("s8W-!").unwrap(), [0xff; 4]);
+    }
+
+    #[quickcheck]
+    fn round_trip(data: Vec<u8>, sizes: Vec<u8>) -> bool {
+        let sizes: Vec<u8> = sizes.into_iter().map(|s| s % 9 + 1).collect();
+        streams(Ascii85, &data, &sizes)
+    }
+}
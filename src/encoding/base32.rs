@@ -0,0 +1,157 @@
+//! Base32 encoding
+//!
+//! Every group of 5 bytes is written as 8 characters of 5 bits. The RFC 4648
+//! alphabet is `A` to `Z` followed by `2` to `7`, and the last group is padded
+//! with `=` to 8 characters, unless padding is disabled, in which case it is
+//! not accepted either.
+//!
+//! Crockford's alphabet is `0` to `9` followed by the letters without `I`,
+//! `L`, `O` and `U`, and is never padded. It is meant to be read and typed by
+//! people: decoding ignores case and hyphens, and reads `I` and `L` as `1` and
+//! `O` as `0`. Its optional check symbol is not supported.
+//!
+//! - [`RFC 4648`](https://www.rfc-editor.org/rfc/rfc4648#section-6)
+//! - [`Crockford's Base32`](https://www.crockford.com/base32.html)
+
+use super::bits::{decode_table, with_lowercase, Bits, IGNORED};
+use super::{DecodeError, Encoding};
+
+const RFC4648: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+const CROCKFORD: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+/// The decoding table of Crockford's alphabet, with its aliases
+const fn crockford_table() -> [u8; 256] {
+    let mut table = with_lowercase(decode_table(CROCKFORD));
+    table[b'I' as usize] = 1;
+    table[b'i' as usize] = 1;
+    table[b'L' as usize] = 1;
+    table[b'l' as usize] = 1;
+    table[b'O' as usize] = 0;
+    table[b'o' as usize] = 0;
+    table[b'-' as usize] = IGNORED;
+    table
+}
+
+/// Base32 encoding, see the [module documentation](self)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Base32(Bits);
+
+impl Base32 {
+    /// RFC 4648 alphabet, with padding
+    pub const RFC4648: Self = Self(Bits {
+        alphabet: RFC4648,
+        table: decode_table(RFC4648),
+        bits: 5,
+        padding: true,
+    });
+
+    /// RFC 4648 alphabet, without padding
+    pub const RFC4648_NO_PAD: Self = Self(Bits {
+        alphabet: RFC4648,
+        table: decode_table(RFC4648),
+        bits: 5,
+        padding: false,
+    });
+
+    /// Crockford's alphabet
+    pub const CROCKFORD: Self = Self(Bits {
+        alphabet: CROCKFORD,
+        table: crockford_table(),
+        bits: 5,
+        padding: false,
+    });
+}
+
+impl Encoding for Base32 {
+    fn encode_chunk(&self, data: &[u8], last: bool, out: &mut String) -> usize {
+        self.0.encode_chunk(data, last, out)
+    }
+
+    fn decode_chunk(
+        &self,
+        text: &[u8],
+        offset: usize,
+        last: bool,
+        out: &mut Vec<u8>,
+    ) -> Result<usize, DecodeError> {
+        self.0.decode_chunk(text, offset, last, out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::streams;
+    use super::*;
+    use quickcheck_macros::quickcheck;
+
+    #[test]
+    fn rfc4648() {
+        let vectors = [
+            ("", ""),
+            ("f", "MY======"),
+            ("fo", "MZXQ===="),
+            ("foo", "MZXW6==="),
+            ("foob", "MZXW6YQ="),
+            ("fooba", "MZXW6YTB"),
+            ("foobar", "MZXW6YTBOI======"),
+        ];
+        for (data, text) in vectors {
+            assert_eq!(Base32::RFC4648.encode(data.as_bytes()), text);
+            assert_eq!(Base32::RFC4648.decode(text).unwrap(), data.as_bytes());
+            let unpadded = text.trim_end_matches('=');
+            assert_eq!(Base32::RFC4648_NO_PAD.encode(data.as_bytes()), unpadded);
+            assert_eq!(
+                Base32::RFC4648_NO_PAD.decode(unpadded).unwrap(),
+                data.as_bytes()
+            );
+        }
+    }
+
+    #[test]
+    fn crockford() {
+        assert_eq!(Base32::CROCKFORD.encode(b"foobar"), "CSQPYRK1E8");
+        assert_eq!(Base32::CROCKFORD.encode(&[0xff; 5]), "ZZZZZZZZ");
+        assert_eq!(Base32::CROCKFORD.decode("csqp-yrkl-e8").unwrap(), b"foobar");
+        assert_eq!(Base32::CROCKFORD.decode("oo").unwrap(), [0]);
+        assert_eq!(
+            Base32::CROCKFORD.decode("CSQPU"),
+            Err(DecodeError::InvalidCharacter {
+                index: 4,
+                byte: b'U'
+            })
+        );
+    }
+
+    #[test]
+    fn errors() {
+        use DecodeError::*;
+        let cases = [
+            ("MZXW6YQ", InvalidPadding { index: 7 }),
+            ("M=======", IncompleteGroup { index: 0 }),
+            ("MZX=====", IncompleteGroup { index: 0 }),
+            ("MZXW6Y==", IncompleteGroup { index: 0 }),
+            ("MZ======", InvalidTrailingBits { index: 1 }),
+            ("MZXW6YTBM", IncompleteGroup { index: 8 }),
+            ("MY=====", InvalidPadding { index: 7 }),
+            ("MY=======", InvalidPadding { index: 8 }),
+            (
+                "my======",
+                InvalidCharacter {
+                    index: 0,
+                    byte: b'm',
+                },
+            ),
+        ];
+        for (text, error) in cases {
+            assert_eq!(Base32::RFC4648.decode(text), Err(error), "{text}");
+        }
+    }
+
+    #[quickcheck]
+    fn round_trip(data: Vec<u8>, sizes: Vec<u8>) -> bool {
+        let sizes: Vec<u8> = sizes.into_iter().map(|s| s % 11 + 1).collect();
+        streams(Base32::RFC4648, &data, &sizes)
+            && streams(Base32::RFC4648_NO_PAD, &data, &sizes)
+            && streams(Base32::CROCKFORD, &data, &sizes)
+    }
+}
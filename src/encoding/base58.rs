@@ -0,0 +1,150 @@
+//! Base58 and Base58Check encodings
+//!
+//! Base58 writes the data, as a big-endian number, in base 58 with an
+//! alphabet without `0`, `O`, `I` and `l`, which are easily confused. Every
+//! leading zero byte is written as a leading `1`. Since the whole input is one
+//! number, encoding and decoding take quadratic time and can not be streamed.
+//!
+//! Base58Check, used by Bitcoin addresses, appends the first 4 bytes of the
+//! double SHA-256 of the data to it before encoding, and checks them when
+//! decoding.
+//!
+//! - [`Bitcoin wiki`](https://en.bitcoin.it/wiki/Base58Check_encoding)
+
+use super::DecodeError;
+use crate::ciphers::SHA256;
+
+const ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Encode `data` in Base58
+pub fn base58_encode(data: &[u8]) -> String {
+    let zeros = data.iter().take_while(|&&b| b == 0).count();
+    // base 58 digits, least significant first
+    let mut digits: Vec<u8> = Vec::with_capacity(data.len() * 138 / 100 + 1);
+    for &b in &data[zeros..] {
+        let mut carry = u32::from(b);
+        for digit in digits.iter_mut() {
+            carry += u32::from(*digit) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+    std::iter::repeat_n('1', zeros)
+        .chain(
+            digits
+                .iter()
+                .rev()
+                .map(|&digit| char::from(ALPHABET[usize::from(digit)])),
+        )
+        .collect()
+}
+
+/// Decode Base58 `text`
+pub fn base58_decode(text: &str) -> Result<Vec<u8>, DecodeError> {
+    let zeros = text.bytes().take_while(|&b| b == b'1').count();
+    // bytes, least significant first
+    let mut bytes: Vec<u8> = Vec::with_capacity(text.len() * 733 / 1000 + 1);
+    for (index, b) in text.bytes().enumerate().skip(zeros) {
+        let mut carry = ALPHABET
+            .iter()
+            .position(|&x| x == b)
+            .ok_or(DecodeError::InvalidCharacter { index, byte: b })?
+            as u32;
+        for byte in bytes.iter_mut() {
+            carry += u32::from(*byte) * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+    bytes.extend(std::iter::repeat_n(0, zeros));
+    bytes.reverse();
+    Ok(bytes)
+}
+
+/// The first 4 bytes of the double SHA-256 of `data`
+fn checksum(data: &[u8]) -> [u8; 4] {
+    let mut hasher = SHA256::new_default();
+    hasher.update(data);
+    let hash = hasher.get_hash();
+    let mut hasher = SHA256::new_default();
+    hasher.update(&hash);
+    hasher.get_hash()[..4].try_into().unwrap()
+}
+
+/// Encode `data` in Base58Check
+pub fn base58check_encode(data: &[u8]) -> String {
+    let mut data = data.to_vec();
+    data.extend(checksum(&data));
+    base58_encode(&data)
+}
+
+/// Decode Base58Check `text`, and check its checksum
+pub fn base58check_decode(text: &str) -> Result<Vec<u8>, DecodeError> {
+    let mut data = base58_decode(text)?;
+    if data.len() < 4 {
+        return Err(DecodeError::InvalidChecksum);
+    }
+    let expected = data.split_off(data.len() - 4);
+    if checksum(&data)[..] != expected[..] {
+        return Err(DecodeError::InvalidChecksum);
+    }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{Encoding, Hex};
+    use super::*;
+    use quickcheck_macros::quickcheck;
+
+    #[test]
+    fn base58() {
+        let vectors: [(&[u8], &str); 5] = [
+            (b"", ""),
+            (b"Hello World!", "2NEpo7TZRRrLZSi2U"),
+            (&[0, 0, 0x28, 0x7f, 0xb4, 0xcd], "11233QC4"),
+            (&[0], "1"),
+            (&[0xff; 4], "7YXq9G"),
+        ];
+        for (data, text) in vectors {
+            assert_eq!(base58_encode(data), text);
+            assert_eq!(base58_decode(text).unwrap(), data);
+        }
+        assert_eq!(
+            base58_decode("12I"),
+            Err(DecodeError::InvalidCharacter {
+                index: 2,
+                byte: b'I'
+            })
+        );
+    }
+
+    #[test]
+    fn bitcoin_address() {
+        let payload = Hex::LOWER
+            .decode("00010966776006953d5567439e5e39f86a0d273bee")
+            .unwrap();
+        let address = "16UwLL9Risc3QfPqBUvKofHmBQ7wMtjvM";
+        assert_eq!(base58check_encode(&payload), address);
+        assert_eq!(base58check_decode(address).unwrap(), payload);
+        assert_eq!(
+            base58check_decode("16UwLL9Risc3QfPqBUvKofHmBQ7wMtjvN"),
+            Err(DecodeError::InvalidChecksum)
+        );
+        assert_eq!(base58check_decode("1"), Err(DecodeError::InvalidChecksum));
+    }
+
+    #[quickcheck]
+    fn round_trip(data: Vec<u8>) -> bool {
+        base58_decode(&base58_encode(&data)).as_deref() == Ok(&data[..])
+            && base58check_decode(&base58check_encode(&data)).as_deref() == Ok(&data[..])
+    }
+}
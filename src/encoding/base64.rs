@@ -0,0 +1,147 @@
+//! Base64 encoding
+//!
+//! Every group of 3 bytes is written as 4 characters of 6 bits. The standard
+//! alphabet uses `+` and `/` for the last two symbols, and the URL-safe one
+//! `-` and `_`. The last group is padded with `=` to 4 characters, unless
+//! padding is disabled, in which case it is not accepted either.
+//!
+//! - [`RFC 4648`](https://www.rfc-editor.org/rfc/rfc4648#section-4)
+
+use super::bits::{decode_table, Bits};
+use super::{DecodeError, Encoding};
+
+const STANDARD: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const URL_SAFE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// Base64 encoding, see the [module documentation](self)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Base64(Bits);
+
+impl Base64 {
+    /// Standard alphabet, with padding
+    pub const STANDARD: Self = Self::new(STANDARD, true);
+    /// Standard alphabet, without padding
+    pub const STANDARD_NO_PAD: Self = Self::new(STANDARD, false);
+    /// URL-safe alphabet, with padding
+    pub const URL_SAFE: Self = Self::new(URL_SAFE, true);
+    /// URL-safe alphabet, without padding
+    pub const URL_SAFE_NO_PAD: Self = Self::new(URL_SAFE, false);
+
+    const fn new(alphabet: &'static [u8; 64], padding: bool) -> Self {
+        Self(Bits {
+            alphabet,
+            table: decode_table(alphabet),
+            bits: 6,
+            padding,
+        })
+    }
+}
+
+impl Encoding for Base64 {
+    fn encode_chunk(&self, data: &[u8], last: bool, out: &mut String) -> usize {
+        self.0.encode_chunk(data, last, out)
+    }
+
+    fn decode_chunk(
+        &self,
+        text: &[u8],
+        offset: usize,
+        last: bool,
+        out: &mut Vec<u8>,
+    ) -> Result<usize, DecodeError> {
+        self.0.decode_chunk(text, offset, last, out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::streams;
+    use super::*;
+    use quickcheck_macros::quickcheck;
+
+    #[test]
+    fn rfc4648() {
+        let vectors = [
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg=="),
+            ("fooba", "Zm9vYmE="),
+            ("foobar", "Zm9vYmFy"),
+        ];
+        for (data, text) in vectors {
+            assert_eq!(Base64::STANDARD.encode(data.as_bytes()), text);
+            assert_eq!(Base64::STANDARD.decode(text).unwrap(), data.as_bytes());
+            let unpadded = text.trim_end_matches('=');
+            assert_eq!(Base64::STANDARD_NO_PAD.encode(data.as_bytes()), unpadded);
+            assert_eq!(
+                Base64::STANDARD_NO_PAD.decode(unpadded).unwrap(),
+                data.as_bytes()
+            );
+        }
+    }
+
+    #[test]
+    fn alphabets() {
+        let data = [0xfb, 0xff, 0xbf];
+        assert_eq!(Base64::STANDARD.encode(&data), "+/+/");
+        assert_eq!(Base64::URL_SAFE.encode(&data), "-_-_");
+        assert_eq!(Base64::URL_SAFE.encode(&data[..1]), "-w==");
+        assert_eq!(Base64::URL_SAFE_NO_PAD.encode(&data[..1]), "-w");
+        assert_eq!(Base64::URL_SAFE.decode("-_-_").unwrap(), data);
+        assert_eq!(
+            Base64::STANDARD.decode("-_-_"),
+            Err(DecodeError::InvalidCharacter {
+                index: 0,
+                byte: b'-'
+            })
+        );
+    }
+
+    #[test]
+    fn errors() {
+        use DecodeError::*;
+        let cases = [
+            ("Zm8", InvalidPadding { index: 3 }),
+            (
+                "Zm9v\n",
+                InvalidCharacter {
+                    index: 4,
+                    byte: b'\n',
+                },
+            ),
+            ("Z===", IncompleteGroup { index: 0 }),
+            ("Zm9vZ", IncompleteGroup { index: 4 }),
+            ("=Zm9", InvalidPadding { index: 0 }),
+            ("Zg=", InvalidPadding { index: 3 }),
+            ("Zg=a", InvalidPadding { index: 3 }),
+            ("Zg===", InvalidPadding { index: 4 }),
+            ("Zg==Zg==", InvalidPadding { index: 4 }),
+            ("Zh==", InvalidTrailingBits { index: 1 }),
+            ("Zm9=", InvalidTrailingBits { index: 2 }),
+        ];
+        for (text, error) in cases {
+            assert_eq!(Base64::STANDARD.decode(text), Err(error), "{text}");
+        }
+        assert_eq!(
+            Base64::STANDARD_NO_PAD.decode("Zg=="),
+            Err(InvalidCharacter {
+                index: 2,
+                byte: b'='
+            })
+        );
+        assert_eq!(
+            Base64::STANDARD_NO_PAD.decode("Zm9vZ"),
+            Err(IncompleteGroup { index: 4 })
+        );
+    }
+
+    #[quickcheck]
+    fn round_trip(data: Vec<u8>, sizes: Vec<u8>) -> bool {
+        let sizes: Vec<u8> = sizes.into_iter().map(|s| s % 7 + 1).collect();
+        streams(Base64::STANDARD, &data, &sizes)
+            && streams(Base64::STANDARD_NO_PAD, &data, &sizes)
+            && streams(Base64::URL_SAFE, &data, &sizes)
+    }
+}
@@ -0,0 +1,191 @@
+//! Encodings which write every group of bits as a symbol of an alphabet
+//!
+//! A group of `BYTES` bytes is encoded as `CHARS` symbols of `bits` bits,
+//! where `8 * BYTES = bits * CHARS` is the least common multiple of 8 and
+//! `bits`. The last group may be incomplete: it is then encoded with as few
+//! symbols as possible, the unused bits of the last one being zero, followed
+//! by `=` up to `CHARS` characters if padding is used.
+
+use super::DecodeError;
+
+/// Entry of a decoding table for bytes which are not in the alphabet
+pub(crate) const INVALID: u8 = 0xff;
+/// Entry of a decoding table for bytes which are skipped
+pub(crate) const IGNORED: u8 = 0xfe;
+
+/// The decoding table of `alphabet`: the index of every byte in the alphabet,
+/// or [`INVALID`]
+pub(crate) const fn decode_table(alphabet: &[u8]) -> [u8; 256] {
+    let mut table = [INVALID; 256];
+    let mut i = 0;
+    while i < alphabet.len() {
+        table[alphabet[i] as usize] = i as u8;
+        i += 1;
+    }
+    table
+}
+
+/// `table` where the lowercase versions of the uppercase letters of the
+/// alphabet decode like them
+pub(crate) const fn with_lowercase(mut table: [u8; 256]) -> [u8; 256] {
+    let mut b = b'A';
+    while b <= b'Z' {
+        if table[b as usize] != INVALID {
+            table[b.to_ascii_lowercase() as usize] = table[b as usize];
+        }
+        b += 1;
+    }
+    table
+}
+
+/// An encoding with symbols of `bits` bits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Bits {
+    pub(crate) alphabet: &'static [u8],
+    pub(crate) table: [u8; 256],
+    pub(crate) bits: usize,
+    pub(crate) padding: bool,
+}
+
+impl Bits {
+    /// The number of bytes and of symbols of a group
+    fn group(&self) -> (usize, usize) {
+        let mut group_bits = self.bits;
+        while !group_bits.is_multiple_of(8) {
+            group_bits += self.bits;
+        }
+        (group_bits / 8, group_bits / self.bits)
+    }
+
+    pub(crate) fn encode_chunk(&self, data: &[u8], last: bool, out: &mut String) -> usize {
+        let (group_bytes, group_chars) = self.group();
+        let len = if last {
+            data.len()
+        } else {
+            data.len() - data.len() % group_bytes
+        };
+        let mask = (1 << self.bits) - 1;
+        for group in data[..len].chunks(group_bytes) {
+            let value = group.iter().enumerate().fold(0u64, |value, (i, &b)| {
+                value | u64::from(b) << (8 * (group_bytes - 1 - i))
+            });
+            let chars = (8 * group.len()).div_ceil(self.bits);
+            for i in 1..=chars {
+                let symbol = (value >> (8 * group_bytes - i * self.bits)) & mask;
+                out.push(char::from(self.alphabet[symbol as usize]));
+            }
+            if self.padding {
+                out.extend(std::iter::repeat_n('=', group_chars - chars));
+            }
+        }
+        len
+    }
+
+    /// Append the first `bytes` bytes encoded by `symbols`, the start of a
+    /// group
+    fn output(&self, symbols: &[u8], bytes: usize, out: &mut Vec<u8>) {
+        let (group_bytes, _) = self.group();
+        let value = symbols.iter().fold(0u64, |value, &symbol| {
+            value << self.bits | u64::from(symbol)
+        });
+        let value = value << (8 * group_bytes - symbols.len() * self.bits);
+        out.extend((0..bytes).map(|i| (value >> (8 * (group_bytes - 1 - i))) as u8));
+    }
+
+    /// Decode the incomplete last group, whose symbols are `symbols`, the
+    /// first one at position `start` and the last one at position `end`
+    fn output_last(
+        &self,
+        symbols: &[u8],
+        start: usize,
+        end: usize,
+        out: &mut Vec<u8>,
+    ) -> Result<(), DecodeError> {
+        let bytes = symbols.len() * self.bits / 8;
+        if bytes == 0 || (8 * bytes).div_ceil(self.bits) != symbols.len() {
+            return Err(DecodeError::IncompleteGroup { index: start });
+        }
+        let unused = symbols.len() * self.bits - 8 * bytes;
+        if symbols[symbols.len() - 1] & ((1 << unused) - 1) != 0 {
+            return Err(DecodeError::InvalidTrailingBits { index: end });
+        }
+        self.output(symbols, bytes, out);
+        Ok(())
+    }
+
+    pub(crate) fn decode_chunk(
+        &self,
+        text: &[u8],
+        offset: usize,
+        last: bool,
+        out: &mut Vec<u8>,
+    ) -> Result<usize, DecodeError> {
+        let (group_bytes, group_chars) = self.group();
+        let mut symbols = [0; 8];
+        let mut count = 0;
+        let (mut start, mut end) = (0, 0);
+        let mut consumed = 0;
+        for (i, &b) in text.iter().enumerate() {
+            if b == b'=' && self.padding {
+                if !last {
+                    return Ok(consumed);
+                }
+                if count == 0 {
+                    return Err(DecodeError::InvalidPadding { index: offset + i });
+                }
+                let padding = group_chars - count;
+                if let Some(j) = (i..text.len()).find(|&j| j >= i + padding || text[j] != b'=') {
+                    return Err(DecodeError::InvalidPadding { index: offset + j });
+                }
+                if text.len() < i + padding {
+                    return Err(DecodeError::InvalidPadding {
+                        index: offset + text.len(),
+                    });
+                }
+                self.output_last(&symbols[..count], offset + start, offset + end, out)?;
+                return Ok(text.len());
+            }
+            match self.table[usize::from(b)] {
+                IGNORED => {}
+                INVALID => {
+                    return Err(DecodeError::InvalidCharacter {
+                        index: offset + i,
+                        byte: b,
+                    })
+                }
+                symbol => {
+                    if count == 0 {
+                        start = i;
+                    }
+                    symbols[count] = symbol;
+                    count += 1;
+                    end = i;
+                    if count == group_chars {
+                        self.output(&symbols[..count], group_bytes, out);
+                        count = 0;
+                        consumed = i + 1;
+                    }
+                }
+            }
+        }
+        if !last {
+            return Ok(consumed);
+        }
+        if count > 0 {
+            let mut decoded = Vec::new();
+            self.output_last(
+                &symbols[..count],
+                offset + start,
+                offset + end,
+                &mut decoded,
+            )?;
+            if self.padding {
+                return Err(DecodeError::InvalidPadding {
+                    index: offset + text.len(),
+                });
+            }
+            out.extend(decoded);
+        }
+        Ok(text.len())
+    }
+}
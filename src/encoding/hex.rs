@@ -0,0 +1,94 @@
+//! Hexadecimal encoding
+//!
+//! Every byte is written as two hexadecimal digits, the most significant
+//! first. Both cases are accepted when decoding.
+//!
+//! - [`RFC 4648`](https://www.rfc-editor.org/rfc/rfc4648#section-8)
+
+use super::bits::{decode_table, with_lowercase, Bits};
+use super::{DecodeError, Encoding};
+
+const LOWER: &[u8; 16] = b"0123456789abcdef";
+const UPPER: &[u8; 16] = b"0123456789ABCDEF";
+const TABLE: [u8; 256] = with_lowercase(decode_table(UPPER));
+
+/// Hexadecimal encoding, see the [module documentation](self)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hex(Bits);
+
+impl Hex {
+    /// Encoding with lowercase digits
+    pub const LOWER: Self = Self(Bits {
+        alphabet: LOWER,
+        table: TABLE,
+        bits: 4,
+        padding: false,
+    });
+
+    /// Encoding with uppercase digits
+    pub const UPPER: Self = Self(Bits {
+        alphabet: UPPER,
+        table: TABLE,
+        bits: 4,
+        padding: false,
+    });
+}
+
+impl Encoding for Hex {
+    fn encode_chunk(&self, data: &[u8], last: bool, out: &mut String) -> usize {
+        self.0.encode_chunk(data, last, out)
+    }
+
+    fn decode_chunk(
+        &self,
+        text: &[u8],
+        offset: usize,
+        last: bool,
+        out: &mut Vec<u8>,
+    ) -> Result<usize, DecodeError> {
+        self.0.decode_chunk(text, offset, last, out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::streams;
+    use super::*;
+    use quickcheck_macros::quickcheck;
+
+    #[test]
+    fn rfc4648() {
+        assert_eq!(Hex::UPPER.encode(b""), "");
+        assert_eq!(Hex::UPPER.encode(b"foobar"), "666F6F626172");
+        assert_eq!(Hex::LOWER.encode(&[0, 0xab, 0xff]), "00abff");
+        assert_eq!(Hex::LOWER.decode("666F6f626172").unwrap(), b"foobar");
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            Hex::LOWER.decode("abc"),
+            Err(DecodeError::IncompleteGroup { index: 2 })
+        );
+        assert_eq!(
+            Hex::LOWER.decode("0g"),
+            Err(DecodeError::InvalidCharacter {
+                index: 1,
+                byte: b'g'
+            })
+        );
+        assert_eq!(
+            Hex::LOWER.decode("00="),
+            Err(DecodeError::InvalidCharacter {
+                index: 2,
+                byte: b'='
+            })
+        );
+    }
+
+    #[quickcheck]
+    fn round_trip(data: Vec<u8>, sizes: Vec<u8>) -> bool {
+        let sizes: Vec<u8> = sizes.into_iter().map(|s| s % 5 + 1).collect();
+        streams(Hex::LOWER, &data, &sizes) && streams(Hex::UPPER, &data, &sizes)
+    }
+}
@@ -27,5 +27,6 @@ pub mod big_integer;
 pub mod ciphers;
 pub mod compression;
 pub mod data_structures;
+pub mod encoding;
 pub mod math;
 pub mod sorting;
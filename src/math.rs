@@ -11,8 +11,9 @@ mod lcm_of_n_number;
 mod linear_sieve;
 mod matrix_ops;
 mod mersenne_primes;
+pub mod modular;
 mod prime_numbers;
-mod random;
+pub mod random;
 
 pub use self::{
     abs::abs,
@@ -27,5 +28,12 @@ pub use self::{
     matrix_ops::*,
     mersenne_primes::{get_mersenne_primes, is_mersenne_prime},
     prime_numbers::prime_numbers,
-    random::PCG32,
+    random::{
+        ChaCha20Rng, Generator, SplitMix64, Xoshiro256PlusPlus, Xoshiro256StarStar, PCG32, PCG64,
+    },
+};
+
+#[cfg(feature = "big-math")]
+pub use self::{
+    faster_perfect_numbers::generate_big_perfect_numbers, mersenne_primes::lucas_lehmer,
 };
@@ -1,22 +1,44 @@
+#[cfg(feature = "big-math")]
+use num_bigint::BigUint;
+#[cfg(feature = "big-math")]
+use num_traits::One;
+
+#[cfg(feature = "big-math")]
+use super::lucas_lehmer;
 use super::{is_mersenne_prime, prime_numbers};
-use std::convert::TryInto;
 
 /// Generates a list of perfect numbers till `num` using the
 /// [`Lucas Lehmer test`](https://en.wikipedia.org/wiki/Lucas%E2%80%93Lehmer_primality_test) algorithm.
+///
+/// By the [`Euclid–Euler theorem`](https://en.wikipedia.org/wiki/Euclid%E2%80%93Euler_theorem)
+/// the even perfect numbers are exactly `2^(p-1) * (2^p - 1)` for the
+/// Mersenne primes `2^p - 1`.
 pub fn generate_perfect_numbers(num: usize) -> Vec<usize> {
-    prime_numbers((((num * 8 + 1) as f64).log2() as usize) / 2)
+    // 2^(p-1) * (2^p - 1) <= num implies 2p - 2 < log2(num)
+    let max_exponent = (usize::BITS - num.leading_zeros()) as usize / 2 + 1;
+    prime_numbers(max_exponent)
         .into_iter()
-        .filter_map(|n| {
-            if is_mersenne_prime(n) {
-                Some(
-                    (2usize.pow(n.try_into().unwrap_or_default()) - 1)
-                        * (2usize.pow((n - 1).try_into().unwrap_or_default())),
-                )
-            } else {
-                None
-            }
-        })
-        .filter(|&n| n <= num)
+        .filter(|&p| is_mersenne_prime(p))
+        .map(|p| (1_usize << (p - 1)) * ((1_usize << p) - 1))
+        .take_while(|&n| n <= num)
+        .collect()
+}
+
+/// Generates the first `count` perfect numbers using the
+/// [`Lucas Lehmer test`](https://en.wikipedia.org/wiki/Lucas%E2%80%93Lehmer_primality_test)
+/// and the [`Euclid–Euler theorem`](https://en.wikipedia.org/wiki/Euclid%E2%80%93Euler_theorem).
+///
+/// ```
+/// use algorithms::math::generate_big_perfect_numbers;
+/// let perfect = generate_big_perfect_numbers(5);
+/// assert_eq!(perfect.last().unwrap().to_string(), "33550336");
+/// ```
+#[cfg(feature = "big-math")]
+pub fn generate_big_perfect_numbers(count: usize) -> Vec<BigUint> {
+    (2..)
+        .filter(|&p| lucas_lehmer(p))
+        .map(|p| (BigUint::one() << (p - 1)) * ((BigUint::one() << p) - 1_u32))
+        .take(count)
         .collect()
 }
 
@@ -30,5 +52,56 @@ mod tests {
         assert_eq!(generate_perfect_numbers(n), [6, 28, 496, 8128, 33550336]);
         assert_eq!(generate_perfect_numbers(40), [6, 28]);
         assert_eq!(generate_perfect_numbers(0), []);
+        assert_eq!(generate_perfect_numbers(6), [6]);
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn perfect_numbers_without_overflow() {
+        assert_eq!(
+            generate_perfect_numbers(usize::MAX),
+            [6, 28, 496, 8128, 33550336, 8589869056, 137438691328, 2305843008139952128]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "big-math")]
+    fn first_fifteen_perfect_numbers() {
+        let perfect = generate_big_perfect_numbers(15);
+        let small: Vec<_> = generate_perfect_numbers(usize::MAX)
+            .into_iter()
+            .map(BigUint::from)
+            .collect();
+        assert_eq!(perfect[..small.len()], small);
+        assert_eq!(
+            perfect[8].to_string(),
+            "2658455991569831744654692615953842176"
+        );
+        // the 15th perfect number is 2^1278 * (2^1279 - 1)
+        assert_eq!(perfect[14].bits(), 2 * 1279 - 1);
+        assert_eq!(perfect[14].trailing_zeros(), Some(1278));
+        for n in &perfect {
+            // even perfect numbers are triangular: n = m (m + 1) / 2
+            let m = (n * 8_u32 + 1_u32).sqrt();
+            assert_eq!(&m * &m, n * 8_u32 + 1_u32);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "big-math")]
+    #[ignore]
+    fn first_twenty_perfect_numbers() {
+        // This test case takes ~35 seconds in unoptimized mode, most of it in
+        // the Lucas-Lehmer tests of the exponents above 3000
+        let perfect = generate_big_perfect_numbers(20);
+        let exponents = [
+            2, 3, 5, 7, 13, 17, 19, 31, 61, 89, 107, 127, 521, 607, 1279, 2203, 2281, 3217, 4253,
+            4423,
+        ];
+        assert_eq!(perfect.len(), exponents.len());
+        for (n, p) in perfect.iter().zip(exponents) {
+            assert_eq!(n.trailing_zeros(), Some(p - 1));
+            assert_eq!(n >> (p - 1), (BigUint::one() << p) - 1_u32);
+        }
     }
 }
@@ -4,6 +4,7 @@
 //! the [`matrix_element_type_def`] macro.
 //!
 //! Wikipedia reference: https://www.wikiwand.com/en/Matrix_(mathematics)
+use super::modular::mod_inv;
 use itertools::assert_equal;
 use std::ops::{Add, AddAssign, Index, IndexMut, Mul, Sub};
 
@@ -44,7 +45,7 @@ macro_rules! matrix_element_type_def {
 matrix_element_type_def!(i16, i32, i64, i128, u8, u16, u32, u128, f32, f64);
 
 /// A matrix data structure.
-#[derive(PartialEq, Eq, Debug)]
+#[derive(PartialEq, Eq, Debug, Clone)]
 pub struct Matrix<T: MatrixElement> {
     data: Vec<T>,
     rows: usize,
@@ -81,6 +82,16 @@ impl<T: MatrixElement> Matrix<T> {
         identity
     }
 
+    /// Returns the number of rows
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Returns the number of columns
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
     pub fn transpose(&self) -> Self {
         let mut result = Matrix::zero(self.cols, self.rows);
         for i in 0..self.rows {
@@ -92,6 +103,63 @@ impl<T: MatrixElement> Matrix<T> {
     }
 }
 
+impl Matrix<i64> {
+    /// Returns the inverse of a square matrix modulo `modulus`, or `None` if
+    /// its determinant is not coprime with `modulus`.
+    ///
+    /// The pivots are found by running the Euclidean algorithm on the rows, so
+    /// the modulus does not have to be prime. It must be less than `2^31` so
+    /// the products do not overflow.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the matrix is not square or `modulus` is not positive.
+    pub fn inverse_mod(&self, modulus: i64) -> Option<Self> {
+        assert!(self.rows == self.cols, "Matrix must be square");
+        assert!(modulus > 0, "Modulus must be positive");
+        let n = self.rows;
+        // the matrix augmented with the identity
+        let mut rows: Vec<Vec<i64>> = (0..n)
+            .map(|i| {
+                let mut row: Vec<i64> = (0..n).map(|j| self[[i, j]].rem_euclid(modulus)).collect();
+                row.extend((0..n).map(|j| i64::from(i == j)));
+                row
+            })
+            .collect();
+        for col in 0..n {
+            // leave the gcd of the column below the diagonal in the pivot
+            for row in col + 1..n {
+                while rows[row][col] != 0 {
+                    let q = rows[col][col] / rows[row][col];
+                    let (upper, lower) = rows.split_at_mut(row);
+                    for (value, other) in upper[col].iter_mut().zip(&lower[0]) {
+                        *value = (*value - q * other).rem_euclid(modulus);
+                    }
+                    rows.swap(col, row);
+                }
+            }
+            let inverse = mod_inv(rows[col][col], modulus)?;
+            for value in rows[col].iter_mut() {
+                *value = *value * inverse % modulus;
+            }
+            let pivot_row = rows[col].clone();
+            for (i, row) in rows.iter_mut().enumerate() {
+                if i != col {
+                    let factor = row[col];
+                    for (value, pivot) in row.iter_mut().zip(&pivot_row) {
+                        *value = (*value - factor * pivot).rem_euclid(modulus);
+                    }
+                }
+            }
+        }
+        Some(Self::new(
+            rows.into_iter().flat_map(|row| row[n..].to_vec()).collect(),
+            n,
+            n,
+        ))
+    }
+}
+
 impl<T: MatrixElement> Iterator for Matrix<T> {
     type Item = T;
 
@@ -523,4 +591,32 @@ mod tests {
         ];
         assert_f64_eq!(scalar * &a, scalar_mul);
     }
+
+    #[test]
+    fn inverse_mod() {
+        let a = matrix![
+            [6, 24, 1],
+            [13, 16, 10],
+            [20, 17, 15],
+        ];
+        let inverse = a.inverse_mod(26).unwrap();
+        assert_eq!(inverse, matrix![
+            [8, 5, 10],
+            [21, 8, 21],
+            [21, 12, 8],
+        ]);
+        let product = &a * &inverse;
+        for i in 0..3 {
+            for j in 0..3 {
+                assert_eq!(product[[i, j]] % 26, i64::from(i == j));
+            }
+        }
+        // the determinant 2 is not a unit modulo 26, but it is modulo 7
+        let b = matrix![
+            [2, 0],
+            [0, 1],
+        ];
+        assert_eq!(b.inverse_mod(26), None);
+        assert_eq!(b.inverse_mod(7), Some(matrix![[4, 0], [0, 1]]));
+    }
 }
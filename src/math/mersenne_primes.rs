@@ -1,14 +1,86 @@
+#[cfg(feature = "big-math")]
+use num_bigint::BigUint;
+#[cfg(feature = "big-math")]
+use num_traits::{One, Zero};
+
+#[cfg(not(feature = "big-math"))]
+use super::modular::mod_mul;
+use super::modular::mod_pow;
+
+/// Trial division primality test, only meant for the (small) exponents.
+fn is_prime_exponent(p: u64) -> bool {
+    p >= 2 && (2..).take_while(|d| d * d <= p).all(|d| !p.is_multiple_of(d))
+}
+
+/// Looks for a small factor of `2^p - 1`. Any factor `q` of a Mersenne number
+/// with prime exponent `p` is of the form `2kp + 1` with `q ≡ ±1 (mod 8)`.
+fn has_small_factor(p: u64) -> bool {
+    const MAX_K: u64 = 1 << 12;
+    (1..=MAX_K)
+        .map(|k| 2 * k * p + 1)
+        .filter(|q| q & 7 == 1 || q & 7 == 7)
+        .any(|q| q < (1 << p.min(63)) - 1 && mod_pow(2, p, q) == 1)
+}
+
+/// [`Lucas-Lehmer`](https://en.wikipedia.org/wiki/Lucas%E2%80%93Lehmer_primality_test)
+/// test for the Mersenne number `2^p - 1`.
+///
+/// Works for any exponent, squaring `s` as a [`BigUint`] and reducing it
+/// modulo `2^p - 1` with shifts and additions only, using
+/// `n ≡ (n mod 2^p) + (n >> p) (mod 2^p - 1)`.
+///
+/// ```
+/// use algorithms::math::lucas_lehmer;
+/// assert!(lucas_lehmer(127));
+/// assert!(!lucas_lehmer(67));
+/// ```
+#[cfg(feature = "big-math")]
+pub fn lucas_lehmer(p: u64) -> bool {
+    if p == 2 {
+        return true;
+    }
+    if !is_prime_exponent(p) || has_small_factor(p) {
+        return false;
+    }
+    let m = (BigUint::one() << p) - 1_u32;
+    let mut s = BigUint::from(4_u32);
+    for _ in 0..p - 2 {
+        // add `m` first so that the subtraction can not underflow
+        s = &s * &s + &m - 2_u32;
+        while s.bits() > p {
+            s = (&s & &m) + (&s >> p);
+        }
+    }
+    s.is_zero() || s == m
+}
+
 /// [`mersenne_prime`](https://en.wikipedia.org/wiki/Mersenne_prime)
+///
+/// Returns whether `2^n - 1` is prime. Uses [`lucas_lehmer`] when the
+/// `big-math` feature is enabled, otherwise `n` must be at most 64.
 pub fn is_mersenne_prime(n: usize) -> bool {
-    if n == 2 {
-        return true;
+    #[cfg(feature = "big-math")]
+    {
+        lucas_lehmer(n as u64)
     }
-    let mut s = 4;
-    let m = 2usize.pow(TryInto::try_into(n).unwrap()) - 1;
-    for _ in 0..n - 2 {
-        s = ((s * s) - 2) % m;
+    #[cfg(not(feature = "big-math"))]
+    {
+        assert!(n <= 64, "Exponent is too large without the `big-math` feature");
+        let p = n as u64;
+        if p == 2 {
+            return true;
+        }
+        if !is_prime_exponent(p) || has_small_factor(p) {
+            return false;
+        }
+        let m = u64::MAX >> (64 - p);
+        let mut s = 4;
+        for _ in 0..p - 2 {
+            let sq = mod_mul(s, s, m);
+            s = if sq >= 2 { sq - 2 } else { sq + m - 2 };
+        }
+        s == 0
     }
-    s == 0
 }
 
 /// [`mersenne_prime`](https://en.wikipedia.org/wiki/Mersenne_prime)
@@ -25,10 +97,40 @@ mod tests {
         assert!(is_mersenne_prime(3));
         assert!(is_mersenne_prime(13));
         assert!(!is_mersenne_prime(32));
+        assert!(!is_mersenne_prime(0));
+        assert!(!is_mersenne_prime(1));
+        assert!(!is_mersenne_prime(11));
+        assert!(is_mersenne_prime(61));
     }
 
-    #[allow(dead_code)]
+    #[test]
     fn generation_check() {
         assert_eq!(get_mersenne_primes(30), [2, 3, 5, 7, 13, 17, 19]);
+        assert_eq!(
+            get_mersenne_primes(64),
+            [2, 3, 5, 7, 13, 17, 19, 31, 61]
+        );
+    }
+
+    #[test]
+    fn small_factors() {
+        // 2^11 - 1 = 23 * 89, 2^29 - 1 = 233 * 1103 * 2089
+        assert!(has_small_factor(11));
+        assert!(has_small_factor(29));
+        assert!(!has_small_factor(31));
+        assert!(!has_small_factor(127));
+    }
+
+    #[test]
+    #[cfg(feature = "big-math")]
+    fn large_exponents() {
+        assert_eq!(
+            get_mersenne_primes(1300),
+            [2, 3, 5, 7, 13, 17, 19, 31, 61, 89, 107, 127, 521, 607, 1279]
+        );
+        // 2^67 - 1 = 193707721 * 761838257287 has no small factor
+        assert!(!has_small_factor(67));
+        assert!(!lucas_lehmer(67));
+        assert!(lucas_lehmer(2203));
     }
 }
@@ -0,0 +1,520 @@
+//! Modular arithmetic
+//!
+//! Number theoretic helpers working modulo `m`: greatest common divisors and
+//! Bézout coefficients, modular inverses and exponentiation, the Chinese
+//! Remainder Theorem, square roots modulo a prime, discrete logarithms and
+//! primitive roots.
+//!
+//! The functions which do not need wide intermediate products are generic over
+//! [`num_integer::Integer`] types, the others work on `u64` and use `u128`
+//! internally so they never overflow.
+//!
+//! - [`Wikipedia`](https://en.wikipedia.org/wiki/Modular_arithmetic)
+
+use std::collections::HashMap;
+
+use num_integer::Integer;
+use num_traits::Signed;
+
+/// Greatest common divisor of `a` and `b` using the Euclidean algorithm.
+///
+/// The result is always non-negative and `gcd(0, 0) == 0`.
+///
+/// ```
+/// use algorithms::math::modular::gcd;
+/// assert_eq!(gcd(48, 18), 6);
+/// assert_eq!(gcd(-48_i32, 18), 6);
+/// ```
+pub fn gcd<T: Integer + Copy>(mut a: T, mut b: T) -> T {
+    while !b.is_zero() {
+        let r = a % b;
+        a = b;
+        b = r;
+    }
+    if a < T::zero() {
+        T::zero() - a
+    } else {
+        a
+    }
+}
+
+/// Extended Euclidean algorithm.
+///
+/// Returns `(g, x, y)` such that `a * x + b * y == g` where `g` is the
+/// (non-negative) greatest common divisor of `a` and `b`.
+///
+/// ```
+/// use algorithms::math::modular::xgcd;
+/// let (g, x, y) = xgcd(240, 46);
+/// assert_eq!(g, 2);
+/// assert_eq!(240 * x + 46 * y, 2);
+/// ```
+pub fn xgcd<T: Integer + Signed + Copy>(a: T, b: T) -> (T, T, T) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_s, mut s) = (T::one(), T::zero());
+    let (mut old_t, mut t) = (T::zero(), T::one());
+    while !r.is_zero() {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_s, s) = (s, old_s - q * s);
+        (old_t, t) = (t, old_t - q * t);
+    }
+    if old_r.is_negative() {
+        (-old_r, -old_s, -old_t)
+    } else {
+        (old_r, old_s, old_t)
+    }
+}
+
+/// Modular multiplicative inverse of `a` modulo `m`.
+///
+/// Returns `None` if `a` and `m` are not coprime. The result is in `[0, m)`.
+///
+/// ```
+/// use algorithms::math::modular::mod_inv;
+/// assert_eq!(mod_inv(3, 11), Some(4));
+/// assert_eq!(mod_inv(-3, 11), Some(7));
+/// assert_eq!(mod_inv(6, 9), None);
+/// ```
+///
+/// # Panics
+///
+/// Panics if `m` is not positive.
+pub fn mod_inv<T: Integer + Signed + Copy>(a: T, m: T) -> Option<T> {
+    assert!(m.is_positive(), "Modulus must be positive");
+    let (g, x, _) = xgcd(a.mod_floor(&m), m);
+    if g.is_one() {
+        Some(x.mod_floor(&m))
+    } else {
+        None
+    }
+}
+
+/// Computes `a * b mod m` by doubling, so that only `2 * m` has to fit in `T`.
+fn mul_mod<T: Integer + Copy>(a: T, b: T, m: T) -> T {
+    let two = T::one() + T::one();
+    let (mut a, mut b) = (a.mod_floor(&m), b.mod_floor(&m));
+    let mut result = T::zero();
+    while !b.is_zero() {
+        if b.is_odd() {
+            result = (result + a) % m;
+        }
+        a = (a + a) % m;
+        b = b / two;
+    }
+    result
+}
+
+/// Chinese Remainder Theorem for a system of congruences `x ≡ a_i (mod m_i)`
+/// given as `(a_i, m_i)` pairs.
+///
+/// The moduli do not need to be pairwise coprime. Returns `Some((x, l))` where
+/// `l` is the least common multiple of the moduli and `x` is the unique
+/// solution in `[0, l)`, or `None` if the system is inconsistent. An empty
+/// system is solved by `(0, 1)`.
+///
+/// ```
+/// use algorithms::math::modular::crt;
+/// assert_eq!(crt(&[(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+/// // non-coprime moduli
+/// assert_eq!(crt(&[(2, 4), (4, 6)]), Some((10, 12)));
+/// assert_eq!(crt(&[(1, 4), (2, 6)]), None);
+/// ```
+///
+/// # Panics
+///
+/// Panics if a modulus is not positive. The least common multiple of the
+/// moduli must fit in `T`.
+pub fn crt<T: Integer + Signed + Copy>(congruences: &[(T, T)]) -> Option<(T, T)> {
+    congruences
+        .iter()
+        .try_fold((T::zero(), T::one()), |(a1, m1), &(a2, m2)| {
+            assert!(m2.is_positive(), "Modulus must be positive");
+            let a2 = a2.mod_floor(&m2);
+            let (g, p, _) = xgcd(m1, m2);
+            let diff = a2 - a1;
+            if !diff.is_multiple_of(&g) {
+                return None;
+            }
+            let step = m2 / g;
+            // m1 * k ≡ a2 - a1 (mod m2), with k = (diff / g) * p (mod m2 / g)
+            let k = mul_mod(diff / g, p, step);
+            let l = m1 * step;
+            Some(((a1 + m1 * k).mod_floor(&l), l))
+        })
+}
+
+/// Computes `a * b mod m` without overflowing.
+///
+/// ```
+/// use algorithms::math::modular::mod_mul;
+/// assert_eq!(mod_mul(u64::MAX - 1, u64::MAX - 1, u64::MAX), 1);
+/// ```
+///
+/// # Panics
+///
+/// Panics if `m` is zero.
+#[inline]
+pub fn mod_mul(a: u64, b: u64, m: u64) -> u64 {
+    ((a as u128 * b as u128) % m as u128) as u64
+}
+
+/// Computes `base ^ exp mod m` by binary exponentiation, without overflowing.
+///
+/// ```
+/// use algorithms::math::modular::mod_pow;
+/// assert_eq!(mod_pow(4, 13, 497), 445);
+/// assert_eq!(mod_pow(2, 0, 1), 0);
+/// ```
+///
+/// # Panics
+///
+/// Panics if `m` is zero.
+pub fn mod_pow(base: u64, mut exp: u64, m: u64) -> u64 {
+    assert_ne!(m, 0, "Modulus must be non-zero");
+    let mut base = base % m;
+    let mut result = 1 % m;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = mod_mul(result, base, m);
+        }
+        base = mod_mul(base, base, m);
+        exp >>= 1;
+    }
+    result
+}
+
+/// Square root of `n` modulo an odd prime `p` using the Tonelli–Shanks
+/// algorithm.
+///
+/// Returns the smaller of the two roots `r` and `p - r`, or `None` if `n` is
+/// a quadratic non-residue. `p = 2` is accepted as well.
+///
+/// `p` must be prime. Otherwise `None` is returned, unless the algorithm
+/// happens to find a root anyway: a returned value is always a square root of
+/// `n` modulo `p`. In particular, `p = 0` gives `None`.
+///
+/// ```
+/// use algorithms::math::modular::sqrt_mod;
+/// assert_eq!(sqrt_mod(10, 13), Some(6));
+/// assert_eq!(sqrt_mod(5, 13), None);
+/// ```
+///
+/// - [`Wikipedia`](https://en.wikipedia.org/wiki/Tonelli%E2%80%93Shanks_algorithm)
+pub fn sqrt_mod(n: u64, p: u64) -> Option<u64> {
+    let n = n.checked_rem(p)?;
+    if p == 2 || n == 0 {
+        return Some(n);
+    }
+    // Euler's criterion
+    if mod_pow(n, (p - 1) / 2, p) != 1 {
+        return None;
+    }
+    // p - 1 = q * 2^s with q odd
+    let s = (p - 1).trailing_zeros();
+    let q = (p - 1) >> s;
+    if s == 1 {
+        let r = mod_pow(n, (p + 1) / 4, p);
+        return Some(r.min(p - r));
+    }
+    // a non-residue, or a witness that p is not prime: by Euler's criterion
+    // z^((p - 1) / 2) is 1 or -1 modulo a prime
+    let z = (2..p).find(|&z| mod_pow(z, (p - 1) / 2, p) != 1)?;
+    if mod_pow(z, (p - 1) / 2, p) != p - 1 {
+        return None;
+    }
+
+    let mut m = s;
+    let mut c = mod_pow(z, q, p);
+    let mut t = mod_pow(n, q, p);
+    let mut r = mod_pow(n, q.div_ceil(2), p);
+    while t != 1 {
+        // find the least i such that t^(2^i) = 1
+        let mut i = 0;
+        let mut t2 = t;
+        while t2 != 1 {
+            t2 = mod_mul(t2, t2, p);
+            i += 1;
+            if i == m {
+                // only possible if p is not prime
+                return None;
+            }
+        }
+        let b = mod_pow(c, 1 << (m - i - 1), p);
+        m = i;
+        c = mod_mul(b, b, p);
+        t = mod_mul(t, c, p);
+        r = mod_mul(r, b, p);
+    }
+    (mod_mul(r, r, p) == n).then_some(r.min(p - r))
+}
+
+/// Discrete logarithm using the baby-step giant-step algorithm.
+///
+/// Returns the smallest `x >= 0` such that `g ^ x ≡ h (mod m)`, or `None` if
+/// there is no such `x`. `g` must be coprime to `m`. Runs in O(√m) time and
+/// memory.
+///
+/// ```
+/// use algorithms::math::modular::discrete_log;
+/// assert_eq!(discrete_log(2, 22, 29), Some(26));
+/// assert_eq!(discrete_log(2, 3, 7), None);
+/// ```
+///
+/// - [`Wikipedia`](https://en.wikipedia.org/wiki/Baby-step_giant-step)
+pub fn discrete_log(g: u64, h: u64, m: u64) -> Option<u64> {
+    assert_ne!(m, 0, "Modulus must be non-zero");
+    let h = h % m;
+    if m == 1 {
+        return Some(0);
+    }
+    let n = (m as f64).sqrt().ceil() as u64;
+
+    // baby steps: g^j for j in [0, n), keeping the smallest j for each value
+    let mut table = HashMap::with_capacity(n as usize);
+    let mut e = 1 % m;
+    for j in 0..n {
+        table.entry(e).or_insert(j);
+        e = mod_mul(e, g, m);
+    }
+
+    // giant steps: h * g^(-n*i)
+    let factor = mod_inv(mod_pow(g, n, m) as i128, m as i128)? as u64;
+    let mut gamma = h;
+    for i in 0..=n {
+        if let Some(&j) = table.get(&gamma) {
+            return Some(i * n + j);
+        }
+        gamma = mod_mul(gamma, factor, m);
+    }
+    None
+}
+
+/// Distinct prime factors of `n` by trial division.
+fn distinct_prime_factors(mut n: u64) -> Vec<u64> {
+    let mut factors = Vec::new();
+    let mut d = 2;
+    while d <= n / d {
+        if n.is_multiple_of(d) {
+            factors.push(d);
+            while n.is_multiple_of(d) {
+                n /= d;
+            }
+        }
+        d += if d == 2 { 1 } else { 2 };
+    }
+    if n > 1 {
+        factors.push(n);
+    }
+    factors
+}
+
+/// Euler's totient function, from the distinct prime factors of `n`.
+fn totient(n: u64, factors: &[u64]) -> u64 {
+    factors.iter().fold(n, |phi, &p| phi / p * (p - 1))
+}
+
+/// Smallest primitive root modulo `n`.
+///
+/// A primitive root exists only if `n` is 1, 2, 4, `p^k` or `2 p^k` for an
+/// odd prime `p`; `None` is returned otherwise.
+///
+/// ```
+/// use algorithms::math::modular::primitive_root;
+/// assert_eq!(primitive_root(7), Some(3));
+/// assert_eq!(primitive_root(18), Some(5));
+/// assert_eq!(primitive_root(8), None);
+/// ```
+///
+/// - [`Wikipedia`](https://en.wikipedia.org/wiki/Primitive_root_modulo_n)
+pub fn primitive_root(n: u64) -> Option<u64> {
+    match n {
+        0 => return None,
+        1 | 2 => return Some(n - 1),
+        4 => return Some(3),
+        _ => {}
+    }
+    let factors = distinct_prime_factors(n);
+    let odd_factors = factors.iter().filter(|&&p| p != 2).count();
+    if odd_factors != 1 || n.is_multiple_of(4) {
+        return None;
+    }
+    let phi = totient(n, &factors);
+    let phi_factors = distinct_prime_factors(phi);
+    (2..n).find(|&g| gcd(g, n) == 1 && phi_factors.iter().all(|&q| mod_pow(g, phi / q, n) != 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gcd_basic() {
+        assert_eq!(gcd(0, 0), 0);
+        assert_eq!(gcd(0, 7), 7);
+        assert_eq!(gcd(7_u8, 0), 7);
+        assert_eq!(gcd(54_u64, 24), 6);
+        assert_eq!(gcd(-54_i64, -24), 6);
+        assert_eq!(gcd(17_i16, 5), 1);
+    }
+
+    #[test]
+    fn xgcd_bezout() {
+        for a in -50_i32..50 {
+            for b in -50_i32..50 {
+                let (g, x, y) = xgcd(a, b);
+                assert_eq!(g, gcd(a, b));
+                assert_eq!(a * x + b * y, g);
+            }
+        }
+    }
+
+    #[test]
+    fn mod_inv_all() {
+        let p = 101_i64;
+        for a in 1..p {
+            let inv = mod_inv(a, p).unwrap();
+            assert_eq!(a * inv % p, 1);
+        }
+        assert_eq!(mod_inv(0, 101), None);
+        assert_eq!(mod_inv(4, 1), Some(0));
+        assert_eq!(mod_inv(10_i8, 15), None);
+    }
+
+    #[test]
+    fn crt_coprime() {
+        assert_eq!(crt(&[(0, 3), (3, 4), (4, 5)]), Some((39, 60)));
+        assert_eq!(crt(&[(-1_i64, 7), (12, 11)]), Some((34, 77)));
+        assert_eq!(crt::<i32>(&[]), Some((0, 1)));
+    }
+
+    #[test]
+    fn crt_non_coprime() {
+        assert_eq!(crt(&[(3, 10), (5, 12), (8, 15)]), Some((53, 60)));
+        assert_eq!(crt(&[(3, 10), (4, 12)]), None);
+        // every solution must satisfy all the congruences
+        for a in 0..12 {
+            for b in 0..18 {
+                match crt(&[(a, 12), (b, 18)]) {
+                    Some((x, l)) => {
+                        assert_eq!(l, 36);
+                        assert_eq!(x % 12, a);
+                        assert_eq!(x % 18, b);
+                    }
+                    None => assert_ne!(a % 6, b % 6),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn crt_large_moduli() {
+        let m1 = 1_000_000_007_i64;
+        let m2 = 998_244_353_i64;
+        let (x, l) = crt(&[(123_456_789, m1), (987_654_321, m2)]).unwrap();
+        assert_eq!(l, m1 * m2);
+        assert_eq!(x % m1, 123_456_789);
+        assert_eq!(x % m2, 987_654_321);
+    }
+
+    #[test]
+    fn mod_pow_no_overflow() {
+        // Fermat's little theorem, modulo the largest 64-bit prime
+        let m = 0xffff_ffff_ffff_ffc5;
+        assert_eq!(mod_pow(123_456_789, m - 1, m), 1);
+        assert_eq!(mod_pow(0, 0, 7), 1);
+        assert_eq!(mod_pow(3, 200, 1_000_000_007), 136_318_165);
+        assert_eq!(mod_mul(1 << 63, 4, (1 << 63) + 1), (1 << 63) - 3);
+    }
+
+    #[test]
+    fn tonelli_shanks() {
+        for p in [3_u64, 5, 7, 13, 17, 97, 257, 65537, 1_000_000_007] {
+            for n in (0..200).map(|n| n % p) {
+                match sqrt_mod(n, p) {
+                    Some(r) => {
+                        assert!(r <= p - r || r == 0);
+                        assert_eq!(mod_mul(r, r, p), n);
+                    }
+                    None => assert_eq!(mod_pow(n, (p - 1) / 2, p), p - 1),
+                }
+            }
+        }
+        // p - 1 has a large power of two
+        let p = 0xffff_ffff_0000_0001;
+        let r = sqrt_mod(5, p).unwrap();
+        assert_eq!(mod_mul(r, r, p), 5);
+    }
+
+    #[test]
+    fn tonelli_shanks_composite() {
+        // no panic nor endless loop, and any root returned is a root
+        for p in [9_u64, 15, 21, 33, 45, 65, 105, 561, 1105, 4097, 1 << 20 | 1] {
+            for n in 0..100 {
+                if let Some(r) = sqrt_mod(n, p) {
+                    assert_eq!(mod_mul(r, r, p), n % p, "n = {n}, p = {p}");
+                }
+            }
+        }
+        assert_eq!(sqrt_mod(2, 9), None);
+        assert_eq!(sqrt_mod(4, 15), None);
+        // 2^64 - 1 is composite, and 7 is not a square modulo its factor 5
+        assert_eq!(sqrt_mod(7, u64::MAX), None);
+        assert_eq!(sqrt_mod(7, 0), None);
+    }
+
+    #[test]
+    fn factors_near_u64_max() {
+        // 2^64 - 1 = 3 5 17 257 641 65537 6700417
+        assert_eq!(
+            distinct_prime_factors(u64::MAX),
+            [3, 5, 17, 257, 641, 65537, 6700417]
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn large_prime_factor() {
+        // This test case takes ~20 seconds in unoptimized mode: trial
+        // division goes up to 2^32, where d * d would overflow
+        // 2^64 - 59 is prime
+        assert_eq!(distinct_prime_factors(u64::MAX - 58), [u64::MAX - 58]);
+    }
+
+    #[test]
+    fn baby_step_giant_step() {
+        let p = 1_000_003;
+        let g = primitive_root(p).unwrap();
+        for x in [0, 1, 2, 999, 123_456, p - 2] {
+            let h = mod_pow(g, x, p);
+            assert_eq!(discrete_log(g, h, p), Some(x));
+        }
+        // composite modulus: 3 has order 16 modulo 34
+        assert_eq!(discrete_log(3, 13, 34), Some(4));
+        assert_eq!(discrete_log(3, 2, 34), None);
+        assert_eq!(discrete_log(5, 1, 1), Some(0));
+    }
+
+    #[test]
+    fn primitive_roots() {
+        let expected = [
+            (1, Some(0)),
+            (2, Some(1)),
+            (3, Some(2)),
+            (4, Some(3)),
+            (5, Some(2)),
+            (6, Some(5)),
+            (9, Some(2)),
+            (12, None),
+            (15, None),
+            (23, Some(5)),
+            (25, Some(2)),
+            (41, Some(6)),
+            (50, Some(3)),
+        ];
+        for (n, root) in expected {
+            assert_eq!(primitive_root(n), root, "n = {n}");
+        }
+        assert_eq!(primitive_root(1_000_000_007), Some(5));
+    }
+}
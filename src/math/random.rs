@@ -1,3 +1,13 @@
+//! Pseudo random number generators
+//!
+//! Note that none of these generators, not even [`ChaCha20Rng`], have been
+//! hardened against side channels. They are meant for simulations and tests.
+
+use std::ops::Range;
+
+#[cfg(feature = "rand-num")]
+use rand::SeedableRng;
+
 /// Permuted Congruential Generator
 /// https://en.wikipedia.org/wiki/Permuted_congruential_generator
 ///
@@ -11,9 +21,12 @@ pub struct PCG32 {
     increment:  u64,
 }
 
+/// The default multiplier of [`PCG32`]
 pub const PCG32_MULTIPLIER: u64 = 6364136223846793005_u64;
+/// The default increment of [`PCG32`]
 pub const PCG32_INCREMENT: u64 = 1442695040888963407_u64;
 
+/// Infinite iterator over the `u32` outputs of a [`PCG32`]
 #[derive(Debug)]
 pub struct IterMut<'a> {
     pcg: &'a mut PCG32,
@@ -111,11 +124,326 @@ impl PCG32 {
         self.state
     }
 
-    pub fn iter_mut(&mut self) -> IterMut {
+    pub fn iter_mut(&mut self) -> IterMut<'_> {
         IterMut { pcg: self }
     }
 }
 
+/// Implements [`rand::RngCore`] on top of [`Generator`].
+#[cfg(feature = "rand-num")]
+macro_rules! impl_rng_core {
+    ($T:ty) => {
+        impl rand::RngCore for $T {
+            fn next_u32(&mut self) -> u32 {
+                Generator::get_u32(self)
+            }
+
+            fn next_u64(&mut self) -> u64 {
+                Generator::get_u64(self)
+            }
+
+            fn fill_bytes(&mut self, dest: &mut [u8]) {
+                Generator::fill_bytes(self, dest);
+            }
+
+            fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+                Generator::fill_bytes(self, dest);
+                Ok(())
+            }
+        }
+    };
+}
+
+mod chacha;
+mod pcg64;
+mod splitmix;
+mod xoshiro;
+
+pub mod tests_suite;
+
+pub use self::{
+    chacha::ChaCha20Rng,
+    pcg64::PCG64,
+    splitmix::SplitMix64,
+    xoshiro::{Xoshiro256PlusPlus, Xoshiro256StarStar},
+};
+
+/// Common interface of the pseudo random number generators.
+///
+/// Besides producing random words, every generator can be advanced by an
+/// arbitrary number of steps in logarithmic time, jumped ahead to a
+/// non-overlapping sub-sequence and split into independent sub-streams, e.g.
+/// to give each worker of a parallel simulation its own generator.
+///
+/// Sampling helpers are provided on top of the raw output.
+pub trait Generator {
+    /// Returns the next random `u32`.
+    fn get_u32(&mut self) -> u32;
+
+    /// Returns the next random `u64`.
+    fn get_u64(&mut self) -> u64;
+
+    /// Advances the generator by `delta` steps, a step being one output of
+    /// its native word size.
+    fn advance(&mut self, delta: u64);
+
+    /// Jumps ahead by a large, generator specific, number of steps. Taking a
+    /// copy before each jump partitions the sequence into non-overlapping
+    /// sub-sequences.
+    fn jump(&mut self);
+
+    /// Derives a generator for an independent sub-stream, advancing `self`.
+    fn split(&mut self) -> Self
+    where
+        Self: Sized;
+
+    /// Fills `dest` with random bytes.
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        let mut chunks = dest.chunks_exact_mut(4);
+        for chunk in &mut chunks {
+            chunk.copy_from_slice(&self.get_u32().to_le_bytes());
+        }
+        let rem = chunks.into_remainder();
+        if !rem.is_empty() {
+            let bytes = self.get_u32().to_le_bytes();
+            rem.copy_from_slice(&bytes[..rem.len()]);
+        }
+    }
+
+    /// Returns a uniformly distributed integer in `[0, bound)`, without the
+    /// modulo bias, using Lemire's multiply-and-reject method.
+    ///
+    /// - [`Fast Random Integer Generation in an Interval`](https://arxiv.org/abs/1805.10941)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bound` is zero.
+    fn gen_below(&mut self, bound: u32) -> u32 {
+        assert_ne!(bound, 0, "Bound must be non-zero");
+        let mut m = self.get_u32() as u64 * bound as u64;
+        if (m as u32) < bound {
+            let threshold = bound.wrapping_neg() % bound;
+            while (m as u32) < threshold {
+                m = self.get_u32() as u64 * bound as u64;
+            }
+        }
+        (m >> 32) as u32
+    }
+
+    /// Returns a uniformly distributed integer in `[0, bound)`, the 64-bit
+    /// version of [`Generator::gen_below`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bound` is zero.
+    fn gen_below_u64(&mut self, bound: u64) -> u64 {
+        assert_ne!(bound, 0, "Bound must be non-zero");
+        let mut m = self.get_u64() as u128 * bound as u128;
+        if (m as u64) < bound {
+            let threshold = bound.wrapping_neg() % bound;
+            while (m as u64) < threshold {
+                m = self.get_u64() as u128 * bound as u128;
+            }
+        }
+        (m >> 64) as u64
+    }
+
+    /// Returns a uniformly distributed integer in `range`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is empty.
+    fn gen_range(&mut self, range: Range<u64>) -> u64 {
+        assert!(range.start < range.end, "Range must not be empty");
+        let span = range.end - range.start;
+        range.start
+            + if span <= u32::MAX as u64 {
+                self.gen_below(span as u32) as u64
+            } else {
+                self.gen_below_u64(span)
+            }
+    }
+
+    /// Returns a uniformly distributed float in `[0, 1)`, with 53 bits of
+    /// precision.
+    fn gen_f64(&mut self) -> f64 {
+        (self.get_u64() >> 11) as f64 * (1.0 / (1_u64 << 53) as f64)
+    }
+
+    /// Returns a uniformly distributed float in `[0, 1)`, with 24 bits of
+    /// precision.
+    fn gen_f32(&mut self) -> f32 {
+        (self.get_u32() >> 8) as f32 * (1.0 / (1_u32 << 24) as f32)
+    }
+
+    /// Shuffles `slice` in place with the Fisher–Yates algorithm.
+    fn shuffle<T>(&mut self, slice: &mut [T]) {
+        for i in (1..slice.len()).rev() {
+            let j = self.gen_range(0..i as u64 + 1) as usize;
+            slice.swap(i, j);
+        }
+    }
+
+    /// Returns a uniformly chosen element of `slice`, or `None` if it is empty.
+    fn choose<'a, T>(&mut self, slice: &'a [T]) -> Option<&'a T> {
+        if slice.is_empty() {
+            None
+        } else {
+            slice.get(self.gen_range(0..slice.len() as u64) as usize)
+        }
+    }
+
+    /// Samples the normal distribution `N(mean, std_dev^2)` using the
+    /// Box–Muller transform.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `std_dev` is negative or not finite.
+    fn sample_normal(&mut self, mean: f64, std_dev: f64) -> f64 {
+        assert!(
+            std_dev >= 0.0 && std_dev.is_finite(),
+            "Standard deviation must be non-negative and finite"
+        );
+        // 1 - u is in (0, 1], so the logarithm is finite
+        let u1 = 1.0 - self.gen_f64();
+        let u2 = self.gen_f64();
+        let z = (-2.0 * u1.ln()).sqrt() * (std::f64::consts::TAU * u2).cos();
+        mean + std_dev * z
+    }
+
+    /// Samples the exponential distribution with rate `lambda` by inversion.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `lambda` is not positive.
+    fn sample_exponential(&mut self, lambda: f64) -> f64 {
+        assert!(lambda > 0.0, "Rate must be positive");
+        -(1.0 - self.gen_f64()).ln() / lambda
+    }
+
+    /// Samples the Poisson distribution with mean `lambda`.
+    ///
+    /// Uses Knuth's multiplication method for small means and Hörmann's
+    /// transformed rejection with squeeze (PTRS) otherwise, so the expected
+    /// running time is bounded.
+    ///
+    /// - [`The transformed rejection method for generating Poisson random variables`](https://doi.org/10.1016/0167-6687(93)90997-4)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `lambda` is negative or not finite.
+    fn sample_poisson(&mut self, lambda: f64) -> u64 {
+        assert!(
+            lambda >= 0.0 && lambda.is_finite(),
+            "Mean must be non-negative and finite"
+        );
+        if lambda < 10.0 {
+            let limit = (-lambda).exp();
+            let mut k = 0;
+            let mut p = self.gen_f64();
+            while p > limit {
+                k += 1;
+                p *= self.gen_f64();
+            }
+            return k;
+        }
+
+        let log_lambda = lambda.ln();
+        let b = 0.931 + 2.53 * lambda.sqrt();
+        let a = -0.059 + 0.02483 * b;
+        let inv_alpha = 1.1239 + 1.1328 / (b - 3.4);
+        let v_r = 0.9277 - 3.6224 / (b - 2.0);
+        loop {
+            let u = self.gen_f64() - 0.5;
+            let v = 1.0 - self.gen_f64();
+            let us = 0.5 - u.abs();
+            let k = ((2.0 * a / us + b) * u + lambda + 0.43).floor();
+            if us >= 0.07 && v <= v_r {
+                return k as u64;
+            }
+            if k < 0.0 || (us < 0.013 && v > us) {
+                continue;
+            }
+            if (v * inv_alpha / (a / (us * us) + b)).ln()
+                <= -lambda + k * log_lambda - ln_gamma(k + 1.0)
+            {
+                return k as u64;
+            }
+        }
+    }
+}
+
+/// Natural logarithm of the gamma function, using the Lanczos approximation.
+fn ln_gamma(x: f64) -> f64 {
+    const G: f64 = 7.0;
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+    if x < 0.5 {
+        // reflection formula
+        let pi = std::f64::consts::PI;
+        return (pi / (pi * x).sin()).ln() - ln_gamma(1.0 - x);
+    }
+    let x = x - 1.0;
+    let t = x + G + 0.5;
+    let sum = COEFFICIENTS[1..]
+        .iter()
+        .enumerate()
+        .fold(COEFFICIENTS[0], |acc, (i, &c)| acc + c / (x + i as f64 + 1.0));
+    0.5 * std::f64::consts::TAU.ln() + (x + 0.5) * t.ln() - t + sum.ln()
+}
+
+impl Generator for PCG32 {
+    fn get_u32(&mut self) -> u32 {
+        self.get_u32()
+    }
+
+    fn get_u64(&mut self) -> u64 {
+        self.get_u64()
+    }
+
+    fn advance(&mut self, delta: u64) {
+        self.advance(delta);
+    }
+
+    /// Jumps ahead by `2^32` steps.
+    fn jump(&mut self) {
+        self.advance(1 << 32);
+    }
+
+    /// The new generator uses a different increment, i.e. another stream.
+    fn split(&mut self) -> Self {
+        PCG32::new(self.get_u64(), self.multiplier, self.get_u64())
+    }
+}
+
+#[cfg(feature = "rand-num")]
+impl_rng_core!(PCG32);
+
+/// The seed is the initial state followed by the stream, both little endian.
+/// The default multiplier is used.
+#[cfg(feature = "rand-num")]
+impl SeedableRng for PCG32 {
+    type Seed = [u8; 16];
+
+    fn from_seed(seed: Self::Seed) -> Self {
+        let (state, stream) = seed.split_at(8);
+        PCG32::new(
+            u64::from_le_bytes(state.try_into().unwrap()),
+            PCG32_MULTIPLIER,
+            u64::from_le_bytes(stream.try_into().unwrap()),
+        )
+    }
+}
+
 impl<'a> Iterator for IterMut<'a> {
     type Item = u32;
 
@@ -150,4 +478,141 @@ mod tests {
         randoms.dedup();
         assert_eq!(randoms.len(), numbers);
     }
+
+    #[test]
+    fn fill_bytes_matches_words() {
+        let mut pcg = PCG32::new_default(42);
+        let mut pcg2 = pcg;
+        let mut bytes = [0u8; 11];
+        pcg.fill_bytes(&mut bytes);
+        let words: Vec<u8> = (0..3).flat_map(|_| pcg2.get_u32().to_le_bytes()).collect();
+        assert_eq!(bytes, words[..11]);
+    }
+
+    #[test]
+    fn gen_below_is_uniform() {
+        let mut pcg = PCG32::new_default(2718);
+        let mut counts = [0usize; 6];
+        for _ in 0..60_000 {
+            counts[pcg.gen_below(6) as usize] += 1;
+        }
+        // each count is ~ N(10000, 91^2)
+        assert!(counts.iter().all(|&c| (9_500..10_500).contains(&c)));
+        assert_eq!(pcg.gen_below(1), 0);
+        assert!((0..1000).all(|_| pcg.gen_below_u64(u64::MAX - 1) < u64::MAX - 1));
+    }
+
+    #[test]
+    fn gen_range_bounds() {
+        let mut pcg = PCG32::new_default(1);
+        for _ in 0..1000 {
+            assert!((10..20).contains(&pcg.gen_range(10..20)));
+            assert!(pcg.gen_range(u64::MAX - 3..u64::MAX) >= u64::MAX - 3);
+            assert!(pcg.gen_range(0..1 << 40) < 1 << 40);
+        }
+        let result = std::panic::catch_unwind(|| PCG32::new_default(1).gen_range(5..5));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn floats_in_unit_interval() {
+        let mut pcg = PCG32::new_default(7);
+        let xs: Vec<f64> = (0..10_000).map(|_| pcg.gen_f64()).collect();
+        assert!(xs.iter().all(|x| (0.0..1.0).contains(x)));
+        let mean = xs.iter().sum::<f64>() / xs.len() as f64;
+        assert!((mean - 0.5).abs() < 0.01);
+        assert!((0..10_000).all(|_| (0.0..1.0).contains(&pcg.gen_f32())));
+    }
+
+    #[test]
+    fn shuffle_and_choose() {
+        let mut pcg = PCG32::new_default(99);
+        let mut arr: Vec<u32> = (0..100).collect();
+        pcg.shuffle(&mut arr);
+        assert_ne!(arr, (0..100).collect::<Vec<_>>());
+        arr.sort_unstable();
+        assert_eq!(arr, (0..100).collect::<Vec<_>>());
+
+        assert_eq!(pcg.choose::<u32>(&[]), None);
+        assert_eq!(pcg.choose(&[5]), Some(&5));
+        let mut seen = [false; 4];
+        for _ in 0..100 {
+            seen[*pcg.choose(&[0, 1, 2, 3]).unwrap()] = true;
+        }
+        assert_eq!(seen, [true; 4]);
+    }
+
+    fn mean_and_variance(xs: &[f64]) -> (f64, f64) {
+        let n = xs.len() as f64;
+        let mean = xs.iter().sum::<f64>() / n;
+        let var = xs.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / (n - 1.0);
+        (mean, var)
+    }
+
+    #[test]
+    fn normal_distribution() {
+        let mut pcg = PCG32::new_default(11);
+        let xs: Vec<f64> = (0..50_000).map(|_| pcg.sample_normal(3.0, 2.0)).collect();
+        let (mean, var) = mean_and_variance(&xs);
+        assert!((mean - 3.0).abs() < 0.05, "mean = {mean}");
+        assert!((var - 4.0).abs() < 0.1, "var = {var}");
+        assert_eq!(pcg.sample_normal(1.5, 0.0), 1.5);
+    }
+
+    #[test]
+    fn exponential_distribution() {
+        let mut pcg = PCG32::new_default(12);
+        let xs: Vec<f64> = (0..50_000).map(|_| pcg.sample_exponential(4.0)).collect();
+        assert!(xs.iter().all(|&x| x >= 0.0 && x.is_finite()));
+        let (mean, var) = mean_and_variance(&xs);
+        assert!((mean - 0.25).abs() < 0.005, "mean = {mean}");
+        assert!((var - 0.0625).abs() < 0.003, "var = {var}");
+    }
+
+    #[test]
+    fn poisson_distribution() {
+        let mut pcg = PCG32::new_default(13);
+        for lambda in [0.5, 4.0, 10.0, 30.0, 1000.0] {
+            let xs: Vec<f64> = (0..50_000)
+                .map(|_| pcg.sample_poisson(lambda) as f64)
+                .collect();
+            let (mean, var) = mean_and_variance(&xs);
+            // the mean and the variance are both lambda
+            assert!((mean / lambda - 1.0).abs() < 0.02, "mean = {mean}");
+            assert!((var / lambda - 1.0).abs() < 0.05, "var = {var}");
+        }
+        assert_eq!(pcg.sample_poisson(0.0), 0);
+    }
+
+    #[test]
+    fn ln_gamma_factorials() {
+        let mut factorial = 1.0_f64;
+        for n in 1..30 {
+            assert!((ln_gamma(n as f64) - factorial.ln()).abs() < 1e-10);
+            factorial *= n as f64;
+        }
+        assert!((ln_gamma(0.5) - std::f64::consts::PI.sqrt().ln()).abs() < 1e-10);
+    }
+
+    #[test]
+    #[cfg(feature = "rand-num")]
+    fn rand_integration() {
+        use rand::{seq::SliceRandom, Rng, RngCore};
+
+        let mut pcg = PCG32::seed_from_u64(5);
+        let mut pcg2 = PCG32::seed_from_u64(5);
+        assert_eq!(pcg.next_u64(), pcg2.get_u64());
+
+        let x: f64 = pcg.gen();
+        assert!((0.0..1.0).contains(&x));
+        assert!((3..7).contains(&Rng::gen_range(&mut pcg, 3..7)));
+        let mut arr = [1, 2, 3, 4, 5];
+        arr.shuffle(&mut pcg);
+        arr.sort_unstable();
+        assert_eq!(arr, [1, 2, 3, 4, 5]);
+
+        let seed = [1, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0];
+        let mut seeded = PCG32::from_seed(seed);
+        assert_eq!(seeded.get_u32(), PCG32::new(1, PCG32_MULTIPLIER, 2).get_u32());
+    }
 }
@@ -0,0 +1,176 @@
+//! ChaCha20 based cryptographically secure pseudo random number generator
+//!
+//! The output is the ChaCha20 keystream. Like `rand_chacha`, the last four
+//! words of the input block hold a 64-bit block counter and a 64-bit stream
+//! id, so a key gives `2^64` streams of `2^68` words each.
+//!
+//! - [`RFC8439`](https://www.rfc-editor.org/rfc/rfc8439)
+
+#[cfg(feature = "rand-num")]
+use super::SeedableRng;
+use super::{Generator, SplitMix64};
+use crate::ciphers::chacha20_block;
+
+/// ChaCha20 generator, see the [module documentation](self).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChaCha20Rng {
+    key:      [u32; 8],
+    stream:   u64,
+    /// Position in the keystream, in words
+    position: u128,
+    /// The block the buffer holds
+    block:    Option<u64>,
+    buffer:   [u32; 16],
+}
+
+impl ChaCha20Rng {
+    /// Creates a new [`ChaCha20Rng`] with the given key and stream id.
+    pub fn new(key: [u8; 32], stream: u64) -> Self {
+        let mut k = [0u32; 8];
+        for (word, chunk) in k.iter_mut().zip(key.chunks_exact(4)) {
+            *word = u32::from_le_bytes(chunk.try_into().unwrap());
+        }
+        Self {
+            key: k,
+            stream,
+            position: 0,
+            block: None,
+            buffer: [0; 16],
+        }
+    }
+
+    /// Creates a new [`ChaCha20Rng`] on stream 0, with a key expanded from
+    /// `seed` by [`SplitMix64`]. The key has only 64 bits of entropy.
+    pub fn new_default(seed: u64) -> Self {
+        let mut sm = SplitMix64::new_default(seed);
+        let mut key = [0u8; 32];
+        sm.fill_bytes(&mut key);
+        Self::new(key, 0)
+    }
+
+    /// Returns the current position in the keystream, in words.
+    pub fn word_pos(&self) -> u128 {
+        self.position
+    }
+
+    /// Returns the stream id.
+    pub fn stream(&self) -> u64 {
+        self.stream
+    }
+
+    /// Returns the next random `u32`.
+    #[inline]
+    pub fn get_u32(&mut self) -> u32 {
+        let block = (self.position >> 4) as u64;
+        if self.block != Some(block) {
+            let tail = [
+                block as u32,
+                (block >> 32) as u32,
+                self.stream as u32,
+                (self.stream >> 32) as u32,
+            ];
+            self.buffer = chacha20_block(&self.key, &tail);
+            self.block = Some(block);
+        }
+        let result = self.buffer[(self.position & 15) as usize];
+        self.position = (self.position + 1) & ((1 << 68) - 1);
+        result
+    }
+}
+
+impl Generator for ChaCha20Rng {
+    fn get_u32(&mut self) -> u32 {
+        self.get_u32()
+    }
+
+    fn get_u64(&mut self) -> u64 {
+        let lo = self.get_u32() as u64;
+        let hi = self.get_u32() as u64;
+        (hi << 32) | lo
+    }
+
+    fn advance(&mut self, delta: u64) {
+        self.position = (self.position + delta as u128) & ((1 << 68) - 1);
+    }
+
+    /// Jumps ahead by `2^64` words.
+    fn jump(&mut self) {
+        self.position = (self.position + (1 << 64)) & ((1 << 68) - 1);
+    }
+
+    /// The new generator uses a key taken from the output of `self`.
+    fn split(&mut self) -> Self {
+        let mut key = [0u8; 32];
+        self.fill_bytes(&mut key);
+        Self::new(key, 0)
+    }
+}
+
+#[cfg(feature = "rand-num")]
+impl_rng_core!(ChaCha20Rng);
+
+/// The seed is the key, and the stream is 0.
+#[cfg(feature = "rand-num")]
+impl SeedableRng for ChaCha20Rng {
+    type Seed = [u8; 32];
+
+    fn from_seed(seed: Self::Seed) -> Self {
+        Self::new(seed, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rfc8439_zero_key() {
+        // RFC8439 A.1, test vector #1
+        let mut rng = ChaCha20Rng::new([0; 32], 0);
+        let expected = [
+            0xade0b876, 0x903df1a0, 0xe56a5d40, 0x28bd8653, 0xb819d2bd, 0x1aed8da0, 0xccef36a8,
+            0xc70d778b, 0x7c5941da, 0x8d485751, 0x3fe02477, 0x374ad8b8, 0xf4b8436a, 0x1ca11815,
+            0x69b687c3, 0x8665eeb2,
+        ];
+        for e in expected {
+            assert_eq!(rng.get_u32(), e);
+        }
+    }
+
+    #[test]
+    fn counter_and_stream() {
+        let key: [u8; 32] = core::array::from_fn(|i| i as u8);
+        let mut rng = ChaCha20Rng::new(key, 7);
+        rng.advance(((1 << 32) + 5) * 16);
+        assert_eq!(rng.stream(), 7);
+        assert_eq!(rng.word_pos(), ((1 << 32) + 5) * 16);
+        for e in [0x2dac0af4, 0xbd17ecf4, 0xcedd3d34, 0x6c643929] {
+            assert_eq!(rng.get_u32(), e);
+        }
+    }
+
+    #[test]
+    fn advance_and_jump() {
+        let mut rng = ChaCha20Rng::new_default(1);
+        let mut rng2 = rng;
+        let outputs: Vec<u32> = (0..100).map(|_| rng.get_u32()).collect();
+        rng2.advance(37);
+        assert_eq!(rng2.get_u32(), outputs[37]);
+        rng2.jump();
+        assert_eq!(rng2.word_pos(), (1 << 64) + 38);
+        // the position wraps around after 2^68 words
+        for _ in 0..15 {
+            rng2.jump();
+        }
+        assert_eq!(rng2.word_pos(), 38);
+        assert_eq!(rng2.get_u32(), outputs[38]);
+    }
+
+    #[test]
+    fn split_streams() {
+        let mut parent = ChaCha20Rng::new_default(3);
+        let mut child = parent.split();
+        assert_ne!(child.key, parent.key);
+        assert_ne!(child.get_u64(), parent.get_u64());
+    }
+}
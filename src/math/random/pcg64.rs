@@ -0,0 +1,173 @@
+//! PCG64, the 128-bit state XSL RR variant of the Permuted Congruential
+//! Generator (`pcg64` in the reference C library).
+//!
+//! - [`PCG: A Family of Simple Fast Space-Efficient Statistically Good Algorithms for Random Number Generation`](https://www.pcg-random.org/paper.html)
+
+#[cfg(feature = "rand-num")]
+use super::SeedableRng;
+use super::Generator;
+
+/// The multiplier of the 128-bit linear congruential generator.
+pub const PCG64_MULTIPLIER: u128 = 0x2360ed051fc65da44385df649fccf645;
+/// The default increment of the reference implementation.
+pub const PCG64_INCREMENT: u128 = 0x5851f42d4c957f2d14057b7ef767814f;
+
+/// PCG64 generator, see the [module documentation](self).
+///
+/// Note that this is _NOT_ intended for serious applications.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PCG64 {
+    state:     u128,
+    increment: u128,
+}
+
+impl PCG64 {
+    /// Creates a new [`PCG64`] on the given stream, seeded the same way as
+    /// `pcg64_srandom_r` of the reference implementation.
+    pub fn new(seed: u128, stream: u128) -> Self {
+        Self::with_increment(seed, (stream << 1) | 1)
+    }
+
+    /// Creates a new [`PCG64`] on the default stream.
+    pub fn new_default(seed: u64) -> Self {
+        Self::with_increment(seed as u128, PCG64_INCREMENT)
+    }
+
+    fn with_increment(seed: u128, increment: u128) -> Self {
+        let mut pcg = Self {
+            state: 0,
+            increment,
+        };
+        pcg.step();
+        pcg.state = pcg.state.wrapping_add(seed);
+        pcg.step();
+        pcg
+    }
+
+    #[inline]
+    fn step(&mut self) {
+        self.state = self
+            .state
+            .wrapping_mul(PCG64_MULTIPLIER)
+            .wrapping_add(self.increment);
+    }
+
+    /// Returns the next random `u64`.
+    #[inline]
+    pub fn get_u64(&mut self) -> u64 {
+        self.step();
+        let rot = (self.state >> 122) as u32;
+        (((self.state >> 64) as u64) ^ (self.state as u64)).rotate_right(rot)
+    }
+
+    /// Advance the PCG by `delta` steps in O(lg(`delta`)) time. By passing
+    /// a negative i128 as u128, it can go back too.
+    pub fn advance(&mut self, mut delta: u128) {
+        let mut acc_mult = 1u128;
+        let mut acc_incr = 0u128;
+        let mut curr_mlt = PCG64_MULTIPLIER;
+        let mut curr_inc = self.increment;
+        while delta > 0 {
+            if delta & 1 != 0 {
+                acc_mult = acc_mult.wrapping_mul(curr_mlt);
+                acc_incr = acc_incr.wrapping_mul(curr_mlt).wrapping_add(curr_inc);
+            }
+            curr_inc = curr_mlt.wrapping_add(1).wrapping_mul(curr_inc);
+            curr_mlt = curr_mlt.wrapping_mul(curr_mlt);
+            delta >>= 1;
+        }
+        self.state = acc_mult.wrapping_mul(self.state).wrapping_add(acc_incr);
+    }
+}
+
+impl Generator for PCG64 {
+    fn get_u32(&mut self) -> u32 {
+        (self.get_u64() >> 32) as u32
+    }
+
+    fn get_u64(&mut self) -> u64 {
+        self.get_u64()
+    }
+
+    fn advance(&mut self, delta: u64) {
+        self.advance(delta as u128);
+    }
+
+    /// Jumps ahead by `2^64` steps.
+    fn jump(&mut self) {
+        self.advance(1 << 64);
+    }
+
+    /// The new generator uses a different increment, i.e. another stream.
+    fn split(&mut self) -> Self {
+        let seed = (self.get_u64() as u128) << 64 | self.get_u64() as u128;
+        let stream = (self.get_u64() as u128) << 64 | self.get_u64() as u128;
+        Self::new(seed, stream)
+    }
+}
+
+#[cfg(feature = "rand-num")]
+impl_rng_core!(PCG64);
+
+/// The seed is the initial state followed by the stream, both little endian.
+#[cfg(feature = "rand-num")]
+impl SeedableRng for PCG64 {
+    type Seed = [u8; 32];
+
+    fn from_seed(seed: Self::Seed) -> Self {
+        let (state, stream) = seed.split_at(16);
+        Self::new(
+            u128::from_le_bytes(state.try_into().unwrap()),
+            u128::from_le_bytes(stream.try_into().unwrap()),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reference() {
+        // `pcg64-global-demo` of the reference implementation, seeded with
+        // 42 on stream 54
+        let mut rng = PCG64::new(42, 54);
+        let expected = [
+            0x86b1da1d72062b68,
+            0x1304aa46c9853d39,
+            0xa3670e9e0dd50358,
+            0xf9090e529a7dae00,
+            0xc85b9fd837996f2c,
+            0x606121f8e3919196,
+        ];
+        for e in expected {
+            assert_eq!(rng.get_u64(), e);
+        }
+    }
+
+    #[test]
+    fn advance_and_jump() {
+        let mut rng = PCG64::new_default(314159);
+        let mut rng2 = rng;
+        let outputs: Vec<u64> = (0..1000).map(|_| rng.get_u64()).collect();
+        rng2.advance(500);
+        assert_eq!(rng2.get_u64(), outputs[500]);
+        rng2.advance(-501_i128 as u128);
+        assert_eq!(rng2.get_u64(), outputs[0]);
+
+        let mut jumped = PCG64::new_default(1);
+        let mut stepped = jumped;
+        Generator::jump(&mut jumped);
+        Generator::advance(&mut stepped, u64::MAX);
+        Generator::advance(&mut stepped, 1);
+        assert_eq!(jumped, stepped);
+    }
+
+    #[test]
+    fn split_streams() {
+        let mut parent = PCG64::new_default(5);
+        let child = parent.split();
+        assert_ne!(child.increment, parent.increment);
+        assert_eq!(child.increment & 1, 1);
+    }
+}
@@ -0,0 +1,153 @@
+//! SplitMix64
+//!
+//! A fast 64-bit generator whose state is a Weyl sequence, scrambled by a
+//! variant of the MurmurHash3 finalizer. Splitting follows Java's
+//! `SplittableRandom`: the child gets a new seed and a new odd increment
+//! ("gamma"), both taken from the parent.
+//!
+//! - [`Fast splittable pseudorandom number generators`](https://doi.org/10.1145/2714064.2660195)
+//! - [`Reference implementation`](https://prng.di.unimi.it/splitmix64.c)
+
+#[cfg(feature = "rand-num")]
+use super::SeedableRng;
+use super::Generator;
+
+/// The default increment, `2^64 / φ` rounded to an odd number.
+pub const GOLDEN_GAMMA: u64 = 0x9e3779b97f4a7c15;
+
+/// SplitMix64 generator, see the [module documentation](self).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SplitMix64 {
+    state: u64,
+    gamma: u64,
+}
+
+#[inline]
+fn mix64(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+/// Derives an odd increment with enough bit transitions to be a good gamma.
+fn mix_gamma(mut z: u64) -> u64 {
+    z = (z ^ (z >> 33)).wrapping_mul(0xff51afd7ed558ccd);
+    z = (z ^ (z >> 33)).wrapping_mul(0xc4ceb9fe1a85ec53);
+    z = (z ^ (z >> 33)) | 1;
+    if (z ^ (z >> 1)).count_ones() < 24 {
+        z ^ 0xaaaaaaaaaaaaaaaa
+    } else {
+        z
+    }
+}
+
+impl SplitMix64 {
+    /// Creates a new [`SplitMix64`] with the default increment.
+    pub fn new_default(seed: u64) -> Self {
+        Self {
+            state: seed,
+            gamma: GOLDEN_GAMMA,
+        }
+    }
+
+    #[inline]
+    fn next_seed(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(self.gamma);
+        self.state
+    }
+
+    /// Returns the next random `u64`.
+    #[inline]
+    pub fn get_u64(&mut self) -> u64 {
+        mix64(self.next_seed())
+    }
+}
+
+impl Generator for SplitMix64 {
+    fn get_u32(&mut self) -> u32 {
+        (self.get_u64() >> 32) as u32
+    }
+
+    fn get_u64(&mut self) -> u64 {
+        self.get_u64()
+    }
+
+    fn advance(&mut self, delta: u64) {
+        self.state = self.state.wrapping_add(delta.wrapping_mul(self.gamma));
+    }
+
+    /// Jumps ahead by `2^32` steps.
+    fn jump(&mut self) {
+        self.advance(1 << 32);
+    }
+
+    fn split(&mut self) -> Self {
+        let state = self.get_u64();
+        let gamma = mix_gamma(self.next_seed());
+        Self { state, gamma }
+    }
+}
+
+#[cfg(feature = "rand-num")]
+impl_rng_core!(SplitMix64);
+
+/// The seed is the initial state, little endian.
+#[cfg(feature = "rand-num")]
+impl SeedableRng for SplitMix64 {
+    type Seed = [u8; 8];
+
+    fn from_seed(seed: Self::Seed) -> Self {
+        Self::new_default(u64::from_le_bytes(seed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reference() {
+        let mut rng = SplitMix64::new_default(1234567);
+        let expected = [
+            6457827717110365317,
+            3203168211198807973,
+            9817491932198370423,
+            4593380528125082431,
+            16408922859458223821,
+        ];
+        for e in expected {
+            assert_eq!(rng.get_u64(), e);
+        }
+    }
+
+    #[test]
+    fn advance_and_jump() {
+        let mut rng = SplitMix64::new_default(42);
+        let mut rng2 = rng;
+        let outputs: Vec<u64> = (0..100).map(|_| rng.get_u64()).collect();
+        rng2.advance(57);
+        assert_eq!(rng2.get_u64(), outputs[57]);
+        rng2.advance(-58_i64 as u64);
+        assert_eq!(rng2.get_u64(), outputs[0]);
+
+        let mut jumped = SplitMix64::new_default(42);
+        jumped.jump();
+        let mut stepped = SplitMix64::new_default(42);
+        stepped.advance(1 << 31);
+        stepped.advance(1 << 31);
+        assert_eq!(jumped, stepped);
+    }
+
+    #[test]
+    fn split_streams() {
+        let mut parent = SplitMix64::new_default(7);
+        let mut child = parent.split();
+        let mut grandchild = child.split();
+        assert_ne!(child.gamma, GOLDEN_GAMMA);
+        assert_eq!(child.gamma & 1, 1);
+        let a: Vec<u64> = (0..1000).map(|_| parent.get_u64()).collect();
+        let b: Vec<u64> = (0..1000).map(|_| child.get_u64()).collect();
+        let c: Vec<u64> = (0..1000).map(|_| grandchild.get_u64()).collect();
+        assert!(a.iter().all(|x| !b.contains(x) && !c.contains(x)));
+    }
+}
@@ -0,0 +1,498 @@
+//! Statistical tests for random number generators
+//!
+//! Each test consumes `u32` words from any iterator, e.g. `PCG32::iter_mut` or
+//! `std::iter::from_fn(|| Some(rng.get_u32()))`, and reports its statistic
+//! together with a p-value: the probability that a perfect generator yields a
+//! statistic at least as extreme. A generator fails a test when the p-value is
+//! smaller than the chosen significance level. With deterministic seeds the
+//! results are reproducible, so they can gate changes to the generators.
+//!
+//! - [`NIST SP 800-22`](https://csrc.nist.gov/publications/detail/sp/800-22/rev-1a/final)
+//! - Knuth, The Art of Computer Programming, Vol. 2, Section 3.3.2
+
+use super::ln_gamma;
+
+/// The outcome of a statistical test.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TestResult {
+    /// Name of the test
+    pub name:      &'static str,
+    /// The test statistic
+    pub statistic: f64,
+    /// Probability of a statistic at least as extreme for a perfect generator
+    pub p_value:   f64,
+}
+
+impl TestResult {
+    /// Whether the test passed at significance level `alpha`.
+    pub fn passed(&self, alpha: f64) -> bool {
+        self.p_value >= alpha
+    }
+}
+
+/// Complementary error function, with a fractional error below `1.2e-7`.
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let poly = [
+        -1.26551223,
+        1.00002368,
+        0.37409196,
+        0.09678418,
+        -0.18628806,
+        0.27886807,
+        -1.13520398,
+        1.48851587,
+        -0.82215223,
+        0.17087277,
+    ]
+    .iter()
+    .rev()
+    .fold(0.0, |acc, &c| acc * t + c);
+    let ans = t * (-z * z + poly).exp();
+    if x >= 0.0 {
+        ans
+    } else {
+        2.0 - ans
+    }
+}
+
+/// Regularized upper incomplete gamma function `Q(a, x)`, using its series
+/// when `x < a + 1` and its continued fraction otherwise.
+fn gamma_q(a: f64, x: f64) -> f64 {
+    const EPS: f64 = 1e-15;
+    const MAX_ITER: usize = 10_000;
+    if x <= 0.0 {
+        return 1.0;
+    }
+    let ln_prefix = -x + a * x.ln() - ln_gamma(a);
+    if x < a + 1.0 {
+        let mut ap = a;
+        let mut del = 1.0 / a;
+        let mut sum = del;
+        for _ in 0..MAX_ITER {
+            ap += 1.0;
+            del *= x / ap;
+            sum += del;
+            if del.abs() < sum.abs() * EPS {
+                break;
+            }
+        }
+        (1.0 - sum * ln_prefix.exp()).max(0.0)
+    } else {
+        // modified Lentz's method
+        let tiny = f64::MIN_POSITIVE / EPS;
+        let mut b = x + 1.0 - a;
+        let mut c = 1.0 / tiny;
+        let mut d = 1.0 / b;
+        let mut h = d;
+        for i in 1..MAX_ITER {
+            let an = -(i as f64) * (i as f64 - a);
+            b += 2.0;
+            d = an * d + b;
+            if d.abs() < tiny {
+                d = tiny;
+            }
+            c = b + an / c;
+            if c.abs() < tiny {
+                c = tiny;
+            }
+            d = 1.0 / d;
+            let del = d * c;
+            h *= del;
+            if (del - 1.0).abs() < EPS {
+                break;
+            }
+        }
+        ln_prefix.exp() * h
+    }
+}
+
+/// Survival function of the chi-square distribution.
+fn chi_square_sf(statistic: f64, degrees_of_freedom: usize) -> f64 {
+    gamma_q(degrees_of_freedom as f64 / 2.0, statistic / 2.0)
+}
+
+/// Survival function of the Kolmogorov distribution.
+fn kolmogorov_sf(lambda: f64) -> f64 {
+    if lambda < 0.2 {
+        return 1.0;
+    }
+    let mut sum = 0.0;
+    let mut sign = 1.0;
+    for j in 1..=100 {
+        let term = sign * (-2.0 * (j * j) as f64 * lambda * lambda).exp();
+        sum += term;
+        if term.abs() < 1e-16 {
+            break;
+        }
+        sign = -sign;
+    }
+    (2.0 * sum).clamp(0.0, 1.0)
+}
+
+/// Maps a word to `[0, 1)`.
+fn to_unit(x: u32) -> f64 {
+    x as f64 / (1_u64 << 32) as f64
+}
+
+/// Takes exactly `n` words from `samples`.
+///
+/// # Panics
+///
+/// Panics if `samples` yields fewer than `n` words.
+fn take<I: IntoIterator<Item = u32>>(samples: I, n: usize) -> Vec<u32> {
+    let words: Vec<u32> = samples.into_iter().take(n).collect();
+    assert_eq!(words.len(), n, "Not enough samples");
+    words
+}
+
+/// Frequency (monobit) test of NIST SP 800-22 on the bits of `n` words:
+/// the numbers of ones and zeros should be about the same.
+pub fn monobit<I: IntoIterator<Item = u32>>(samples: I, n: usize) -> TestResult {
+    let words = take(samples, n);
+    let ones: u64 = words.iter().map(|w| w.count_ones() as u64).sum();
+    let bits = 32.0 * n as f64;
+    let statistic = (2.0 * ones as f64 - bits).abs() / bits.sqrt();
+    TestResult {
+        name: "monobit",
+        statistic,
+        p_value: erfc(statistic / std::f64::consts::SQRT_2),
+    }
+}
+
+/// Runs test of NIST SP 800-22 on the bits of `n` words: the number of
+/// uninterrupted runs of identical bits should be neither too small nor too
+/// large.
+pub fn runs<I: IntoIterator<Item = u32>>(samples: I, n: usize) -> TestResult {
+    let words = take(samples, n);
+    let bits = 32.0 * n as f64;
+    let ones: u64 = words.iter().map(|w| w.count_ones() as u64).sum();
+    let pi = ones as f64 / bits;
+    // the test is only applicable when the frequency test passes
+    if (pi - 0.5).abs() >= 2.0 / bits.sqrt() {
+        return TestResult {
+            name: "runs",
+            statistic: f64::INFINITY,
+            p_value: 0.0,
+        };
+    }
+    let mut transitions: u64 = 0;
+    let mut previous = words[0] >> 31;
+    for &w in &words {
+        // transitions inside the word, then between the words
+        transitions += (w ^ (w << 1)).count_ones() as u64 - ((w ^ (w << 1)) & 1) as u64;
+        transitions += ((w >> 31) ^ previous) as u64;
+        previous = w & 1;
+    }
+    let v = (transitions + 1) as f64;
+    let expected = 2.0 * bits * pi * (1.0 - pi);
+    let statistic = (v - expected).abs() / (2.0 * (2.0 * bits).sqrt() * pi * (1.0 - pi));
+    TestResult {
+        name: "runs",
+        statistic,
+        p_value: erfc(statistic),
+    }
+}
+
+/// Pearson's chi-square test of `n` words distributed in `bins` equiprobable
+/// classes by their most significant bits.
+///
+/// # Panics
+///
+/// Panics if `bins < 2`.
+pub fn chi_square<I: IntoIterator<Item = u32>>(samples: I, n: usize, bins: usize) -> TestResult {
+    assert!(bins >= 2, "At least two bins are needed");
+    let mut counts = vec![0_u64; bins];
+    for w in take(samples, n) {
+        counts[((w as u64 * bins as u64) >> 32) as usize] += 1;
+    }
+    let expected = n as f64 / bins as f64;
+    let statistic = counts
+        .iter()
+        .map(|&c| (c as f64 - expected).powi(2) / expected)
+        .sum();
+    TestResult {
+        name: "chi-square",
+        statistic,
+        p_value: chi_square_sf(statistic, bins - 1),
+    }
+}
+
+/// Kolmogorov–Smirnov test of `n` words, as numbers in `[0, 1)`, against the
+/// uniform distribution.
+pub fn kolmogorov_smirnov<I: IntoIterator<Item = u32>>(samples: I, n: usize) -> TestResult {
+    let mut words = take(samples, n);
+    words.sort_unstable();
+    let len = n as f64;
+    let statistic = words
+        .iter()
+        .enumerate()
+        .map(|(i, &w)| {
+            let u = to_unit(w);
+            ((i + 1) as f64 / len - u).max(u - i as f64 / len)
+        })
+        .fold(0.0, f64::max);
+    let sqrt_n = len.sqrt();
+    TestResult {
+        name: "kolmogorov-smirnov",
+        statistic,
+        p_value: kolmogorov_sf((sqrt_n + 0.12 + 0.11 / sqrt_n) * statistic),
+    }
+}
+
+/// Serial correlation test: the lag-1 correlation coefficient of `n` words,
+/// as numbers in `[0, 1)`, is approximately `N(0, 1/n)` distributed.
+pub fn serial_correlation<I: IntoIterator<Item = u32>>(samples: I, n: usize) -> TestResult {
+    let us: Vec<f64> = take(samples, n).into_iter().map(to_unit).collect();
+    let len = n as f64;
+    let mean = us.iter().sum::<f64>() / len;
+    let variance: f64 = us.iter().map(|u| (u - mean).powi(2)).sum();
+    let covariance: f64 = us
+        .iter()
+        .zip(us.iter().cycle().skip(1))
+        .map(|(a, b)| (a - mean) * (b - mean))
+        .sum();
+    let statistic = covariance / variance;
+    TestResult {
+        name: "serial-correlation",
+        statistic,
+        p_value: erfc(statistic.abs() * len.sqrt() / std::f64::consts::SQRT_2),
+    }
+}
+
+/// Gap test: the numbers of words between two consecutive words in
+/// `[alpha, beta)`, reading words as numbers in `[0, 1)`, are geometrically
+/// distributed. Gaps are counted until `n` of them are found, gaps of length
+/// `max_gap` or more are lumped together.
+///
+/// # Panics
+///
+/// Panics unless `0 <= alpha < beta <= 1` and `max_gap > 0`.
+pub fn gap<I: IntoIterator<Item = u32>>(
+    samples: I,
+    n: usize,
+    alpha: f64,
+    beta: f64,
+    max_gap: usize,
+) -> TestResult {
+    assert!(
+        (0.0..beta).contains(&alpha) && beta <= 1.0,
+        "Invalid interval"
+    );
+    assert!(max_gap > 0, "Maximum gap must be positive");
+    let mut counts = vec![0_u64; max_gap + 1];
+    let mut length = 0;
+    let mut found = 0;
+    for u in samples.into_iter().map(to_unit) {
+        if (alpha..beta).contains(&u) {
+            counts[length.min(max_gap)] += 1;
+            length = 0;
+            found += 1;
+            if found == n {
+                break;
+            }
+        } else {
+            length += 1;
+        }
+    }
+    assert_eq!(found, n, "Not enough samples");
+
+    let p = beta - alpha;
+    let statistic = counts
+        .iter()
+        .enumerate()
+        .map(|(r, &c)| {
+            let probability = if r < max_gap {
+                p * (1.0 - p).powi(r as i32)
+            } else {
+                (1.0 - p).powi(max_gap as i32)
+            };
+            let expected = n as f64 * probability;
+            (c as f64 - expected).powi(2) / expected
+        })
+        .sum();
+    TestResult {
+        name: "gap",
+        statistic,
+        p_value: chi_square_sf(statistic, max_gap),
+    }
+}
+
+/// Marsaglia's birthday spacings test.
+///
+/// In each of `rounds` rounds, `birthdays` words are read as days of a year
+/// with `2^day_bits` days (their most significant bits). The number of
+/// repeated values among the sorted spacings between birthdays is then
+/// approximately Poisson distributed with mean `birthdays^3 / 2^(day_bits+2)`
+/// per round. The p-value is two sided.
+///
+/// # Panics
+///
+/// Panics if `day_bits` is not in `1..=32` or `birthdays < 2`.
+pub fn birthday_spacings<I: IntoIterator<Item = u32>>(
+    samples: I,
+    rounds: usize,
+    birthdays: usize,
+    day_bits: u32,
+) -> TestResult {
+    assert!((1..=32).contains(&day_bits), "Invalid number of days");
+    assert!(birthdays >= 2, "At least two birthdays are needed");
+    let mut iter = samples.into_iter();
+    let mut collisions = 0_u64;
+    for _ in 0..rounds {
+        let mut days: Vec<u32> = take(iter.by_ref(), birthdays)
+            .into_iter()
+            .map(|w| ((w as u64) >> (32 - day_bits)) as u32)
+            .collect();
+        days.sort_unstable();
+        let mut spacings: Vec<u32> = days.windows(2).map(|w| w[1] - w[0]).collect();
+        spacings.sort_unstable();
+        collisions += spacings.windows(2).filter(|w| w[0] == w[1]).count() as u64;
+    }
+    let lambda =
+        rounds as f64 * (birthdays as f64).powi(3) / (1_u64 << (day_bits + 2)) as f64;
+    // P(X <= k) = Q(k + 1, lambda) for X ~ Poisson(lambda)
+    let cdf = gamma_q(collisions as f64 + 1.0, lambda);
+    let sf = if collisions == 0 {
+        1.0
+    } else {
+        1.0 - gamma_q(collisions as f64, lambda)
+    };
+    TestResult {
+        name: "birthday-spacings",
+        statistic: collisions as f64,
+        p_value: (2.0 * cdf.min(sf)).min(1.0),
+    }
+}
+
+/// Runs every test of the module with default parameters, consuming about
+/// 2.5 million words from `samples`.
+pub fn battery<I: Iterator<Item = u32>>(samples: &mut I) -> Vec<TestResult> {
+    vec![
+        monobit(samples.by_ref(), 100_000),
+        runs(samples.by_ref(), 100_000),
+        chi_square(samples.by_ref(), 100_000, 256),
+        kolmogorov_smirnov(samples.by_ref(), 10_000),
+        serial_correlation(samples.by_ref(), 100_000),
+        gap(samples.by_ref(), 10_000, 0.0, 0.25, 16),
+        birthday_spacings(samples.by_ref(), 200, 4096, 32),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        super::{
+            ChaCha20Rng, Generator, SplitMix64, Xoshiro256PlusPlus, Xoshiro256StarStar, PCG32,
+            PCG64,
+        },
+        *,
+    };
+
+    const ALPHA: f64 = 1e-4;
+
+    fn words<G: Generator>(mut rng: G) -> impl Iterator<Item = u32> {
+        std::iter::from_fn(move || Some(rng.get_u32()))
+    }
+
+    fn assert_passes<I: Iterator<Item = u32>>(mut samples: I) {
+        for result in battery(&mut samples) {
+            assert!(result.passed(ALPHA), "{result:?}");
+        }
+    }
+
+    #[test]
+    fn special_functions() {
+        assert!((erfc(0.0) - 1.0).abs() < 1e-7);
+        assert!((erfc(1.0) - 0.157_299_207).abs() < 1e-7);
+        assert!((erfc(-1.0) - 1.842_700_793).abs() < 1e-7);
+        // chi-square critical values
+        assert!((chi_square_sf(3.841_459, 1) - 0.05).abs() < 1e-6);
+        assert!((chi_square_sf(18.307_038, 10) - 0.05).abs() < 1e-6);
+        assert!((chi_square_sf(293.247_835, 255) - 0.05).abs() < 1e-6);
+        assert!((chi_square_sf(6.634_897, 1) - 0.01).abs() < 1e-6);
+        // Kolmogorov critical values
+        assert!((kolmogorov_sf(1.358_099) - 0.05).abs() < 1e-5);
+        assert!((kolmogorov_sf(1.627_624) - 0.01).abs() < 1e-5);
+    }
+
+    #[test]
+    fn pcg32_passes() {
+        assert_passes(PCG32::new_default(42).iter_mut());
+    }
+
+    #[test]
+    fn other_generators_pass() {
+        assert_passes(words(SplitMix64::new_default(42)));
+        assert_passes(words(Xoshiro256StarStar::new_default(42)));
+        assert_passes(words(Xoshiro256PlusPlus::new_default(42)));
+        assert_passes(words(PCG64::new_default(42)));
+        assert_passes(words(ChaCha20Rng::new_default(42)));
+    }
+
+    #[test]
+    fn counter_fails() {
+        let mut counter = 0_u32..;
+        assert!(!monobit(counter.by_ref(), 10_000).passed(ALPHA));
+        assert!(!chi_square(counter.by_ref(), 10_000, 16).passed(ALPHA));
+        assert!(!serial_correlation(counter.by_ref(), 10_000).passed(ALPHA));
+        let spread = (0_u32..).map(|i| i.wrapping_mul(0x9e3779b9));
+        assert!(!serial_correlation(spread.clone(), 10_000).passed(ALPHA));
+        assert!(!birthday_spacings(spread, 100, 1024, 24).passed(ALPHA));
+    }
+
+    #[test]
+    fn biased_generators_fail() {
+        let mut pcg = PCG32::new_default(1);
+        // ones are 3 times as likely as zeros
+        let biased = std::iter::from_fn(|| Some(pcg.get_u32() | pcg.get_u32()));
+        assert!(!monobit(biased, 10_000).passed(ALPHA));
+
+        let mut pcg = PCG32::new_default(3);
+        let mut last = 0_u32;
+        // every word is close to the previous one
+        let walk = std::iter::from_fn(|| {
+            last = last.wrapping_add(pcg.get_u32() >> 8);
+            Some(last)
+        });
+        assert!(!serial_correlation(walk, 10_000).passed(ALPHA));
+
+        let mut pcg = PCG32::new_default(4);
+        let squared = std::iter::from_fn(|| {
+            let u = to_unit(pcg.get_u32());
+            Some((u * u * (1_u64 << 32) as f64) as u32)
+        });
+        assert!(!kolmogorov_smirnov(squared, 1_000).passed(ALPHA));
+    }
+
+    #[test]
+    fn lcg_low_bits_fail() {
+        // the lowest bit of a power-of-two LCG alternates
+        let mut state = 1_u32;
+        let lcg = std::iter::from_fn(|| {
+            state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            Some(state << 16)
+        });
+        let mut lcg = lcg;
+        assert!(!runs(lcg.by_ref(), 10_000).passed(ALPHA));
+        assert!(!birthday_spacings(lcg, 100, 1024, 24).passed(ALPHA));
+    }
+
+    #[test]
+    fn gap_detects_clustering() {
+        let mut pcg = PCG32::new_default(5);
+        // small values come in pairs
+        let mut pending = None;
+        let clustered = std::iter::from_fn(|| {
+            pending.take().or_else(|| {
+                let w = pcg.get_u32();
+                if w < 1 << 30 {
+                    pending = Some(w);
+                }
+                Some(w)
+            })
+        });
+        assert!(!gap(clustered, 10_000, 0.0, 0.25, 16).passed(ALPHA));
+    }
+}
@@ -0,0 +1,301 @@
+//! xoshiro256** and xoshiro256++
+//!
+//! All-purpose 64-bit generators with a 256-bit state, sharing the same linear
+//! engine and differing only in the output scrambler. The published jump
+//! polynomials skip `2^128` and `2^192` steps; arbitrary advances compute
+//! `x^delta` modulo the characteristic polynomial of the engine instead.
+//!
+//! - [`Scrambled linear pseudorandom number generators`](https://arxiv.org/abs/1805.01407)
+//! - [`Reference implementation`](https://prng.di.unimi.it/xoshiro256starstar.c)
+
+#[cfg(feature = "rand-num")]
+use super::SeedableRng;
+use super::{Generator, SplitMix64};
+
+/// `x^(2^128)` modulo the characteristic polynomial.
+const JUMP: [u64; 4] = [
+    0x180ec6d33cfd0aba,
+    0xd5a61266f0c9392c,
+    0xa9582618e03fc9aa,
+    0x39abdc4529b1661c,
+];
+
+/// `x^(2^192)` modulo the characteristic polynomial.
+const LONG_JUMP: [u64; 4] = [
+    0x76e15d3efefdcbbf,
+    0xc5004e441c522fb3,
+    0x77710069854ee241,
+    0x39109bb02acbe635,
+];
+
+/// The characteristic polynomial of the linear engine over GF(2), without its
+/// leading `x^256` term. Found with the Berlekamp–Massey algorithm.
+const CHAR_POLY: [u64; 4] = [
+    0x9d116f2bb0f0f001,
+    0x0280002bcefd1a5e,
+    0x04b4edcf26259f85,
+    0x0003c03c3f3ecb19,
+];
+
+#[inline]
+fn step(s: &mut [u64; 4]) {
+    let t = s[1] << 17;
+    s[2] ^= s[0];
+    s[3] ^= s[1];
+    s[1] ^= s[2];
+    s[0] ^= s[3];
+    s[2] ^= t;
+    s[3] = s[3].rotate_left(45);
+}
+
+/// Replaces the state `s` with `p(T) s`, `T` being the state transition.
+fn apply_polynomial(s: &mut [u64; 4], p: &[u64; 4]) {
+    let mut acc = [0u64; 4];
+    for word in p {
+        for bit in 0..64 {
+            if word >> bit & 1 == 1 {
+                for (a, x) in acc.iter_mut().zip(s.iter()) {
+                    *a ^= x;
+                }
+            }
+            step(s);
+        }
+    }
+    *s = acc;
+}
+
+/// Multiplies two polynomials modulo [`CHAR_POLY`].
+fn mul_mod_poly(a: &[u64; 4], b: &[u64; 4]) -> [u64; 4] {
+    let mut result = [0u64; 4];
+    for bit in (0..256).rev() {
+        // result *= x
+        let carry = result[3] >> 63;
+        for i in (1..4).rev() {
+            result[i] = (result[i] << 1) | (result[i - 1] >> 63);
+        }
+        result[0] <<= 1;
+        if carry == 1 {
+            for (r, c) in result.iter_mut().zip(CHAR_POLY.iter()) {
+                *r ^= c;
+            }
+        }
+        if b[bit / 64] >> (bit % 64) & 1 == 1 {
+            for (r, x) in result.iter_mut().zip(a.iter()) {
+                *r ^= x;
+            }
+        }
+    }
+    result
+}
+
+/// `x^delta` modulo [`CHAR_POLY`].
+fn pow_x(mut delta: u64) -> [u64; 4] {
+    let mut result = [1, 0, 0, 0];
+    let mut base = [2, 0, 0, 0];
+    while delta > 0 {
+        if delta & 1 == 1 {
+            result = mul_mod_poly(&result, &base);
+        }
+        base = mul_mod_poly(&base, &base);
+        delta >>= 1;
+    }
+    result
+}
+
+macro_rules! xoshiro256 {
+    ($(#[$meta:meta])* $name:ident, |$s:ident| $output:expr) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct $name {
+            s: [u64; 4],
+        }
+
+        impl $name {
+            /// Creates a new generator from its raw state.
+            ///
+            /// # Panics
+            ///
+            /// Panics if the state is all zeros.
+            pub fn new(state: [u64; 4]) -> Self {
+                assert!(state != [0; 4], "State must not be all zeros");
+                Self { s: state }
+            }
+
+            /// Creates a new generator, filling its state with [`SplitMix64`]
+            /// outputs as recommended by the authors.
+            pub fn new_default(seed: u64) -> Self {
+                let mut sm = SplitMix64::new_default(seed);
+                Self {
+                    s: [sm.get_u64(), sm.get_u64(), sm.get_u64(), sm.get_u64()],
+                }
+            }
+
+            /// Returns the next random `u64`.
+            #[inline]
+            pub fn get_u64(&mut self) -> u64 {
+                let $s = &self.s;
+                let result = $output;
+                step(&mut self.s);
+                result
+            }
+
+            /// Jumps ahead by `2^192` steps.
+            pub fn long_jump(&mut self) {
+                apply_polynomial(&mut self.s, &LONG_JUMP);
+            }
+        }
+
+        impl Generator for $name {
+            fn get_u32(&mut self) -> u32 {
+                (self.get_u64() >> 32) as u32
+            }
+
+            fn get_u64(&mut self) -> u64 {
+                self.get_u64()
+            }
+
+            fn advance(&mut self, delta: u64) {
+                apply_polynomial(&mut self.s, &pow_x(delta));
+            }
+
+            /// Jumps ahead by `2^128` steps.
+            fn jump(&mut self) {
+                apply_polynomial(&mut self.s, &JUMP);
+            }
+
+            /// The child continues from the current state and the parent jumps
+            /// ahead, so both get `2^128` steps before they overlap.
+            fn split(&mut self) -> Self {
+                let child = *self;
+                Generator::jump(self);
+                child
+            }
+        }
+
+        #[cfg(feature = "rand-num")]
+        impl_rng_core!($name);
+
+        /// The seed is the state as four little endian words. An all-zero seed
+        /// is replaced by `new_default(0)`.
+        #[cfg(feature = "rand-num")]
+        impl SeedableRng for $name {
+            type Seed = [u8; 32];
+
+            fn from_seed(seed: Self::Seed) -> Self {
+                if seed == [0; 32] {
+                    return Self::new_default(0);
+                }
+                let mut s = [0u64; 4];
+                for (word, chunk) in s.iter_mut().zip(seed.chunks_exact(8)) {
+                    *word = u64::from_le_bytes(chunk.try_into().unwrap());
+                }
+                Self::new(s)
+            }
+        }
+    };
+}
+
+xoshiro256!(
+    /// xoshiro256** generator, see the [module documentation](self).
+    Xoshiro256StarStar,
+    |s| s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9)
+);
+
+xoshiro256!(
+    /// xoshiro256++ generator, see the [module documentation](self).
+    Xoshiro256PlusPlus,
+    |s| s[0].wrapping_add(s[3]).rotate_left(23).wrapping_add(s[0])
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reference_star_star() {
+        let mut rng = Xoshiro256StarStar::new([1, 2, 3, 4]);
+        let expected = [
+            11520,
+            0,
+            1509978240,
+            1215971899390074240,
+            1216172134540287360,
+            607988272756665600,
+            16172922978634559625,
+            8476171486693032832,
+            10595114339597558777,
+            2904607092377533576,
+        ];
+        for e in expected {
+            assert_eq!(rng.get_u64(), e);
+        }
+    }
+
+    #[test]
+    fn reference_plus_plus() {
+        let mut rng = Xoshiro256PlusPlus::new([1, 2, 3, 4]);
+        let expected = [
+            41943041,
+            58720359,
+            3588806011781223,
+            3591011842654386,
+            9228616714210784205,
+            9973669472204895162,
+            14011001112246962877,
+            12406186145184390807,
+            15849039046786891736,
+            10450023813501588000,
+        ];
+        for e in expected {
+            assert_eq!(rng.get_u64(), e);
+        }
+    }
+
+    #[test]
+    fn advance_matches_stepping() {
+        let mut rng = Xoshiro256StarStar::new_default(3);
+        let mut rng2 = rng;
+        let outputs: Vec<u64> = (0..1000).map(|_| rng.get_u64()).collect();
+        for delta in [0, 1, 2, 63, 64, 65, 500] {
+            let mut advanced = rng2;
+            advanced.advance(delta);
+            assert_eq!(advanced.get_u64(), outputs[delta as usize]);
+        }
+        rng2.advance(1000);
+        assert_eq!(rng2, rng);
+    }
+
+    #[test]
+    fn jump_polynomials() {
+        // x^(2^128) = (x^(2^64))^(2^64)
+        let mut x = pow_x(1 << 63);
+        x = mul_mod_poly(&x, &x);
+        for _ in 0..64 {
+            x = mul_mod_poly(&x, &x);
+        }
+        assert_eq!(x, JUMP);
+        for _ in 0..64 {
+            x = mul_mod_poly(&x, &x);
+        }
+        assert_eq!(x, LONG_JUMP);
+
+        // jumps commute with ordinary steps
+        let mut a = Xoshiro256PlusPlus::new_default(9);
+        let mut b = a;
+        a.jump();
+        a.advance(5);
+        b.advance(5);
+        b.jump();
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn split_streams() {
+        let mut parent = Xoshiro256StarStar::new_default(1);
+        let mut expected = parent;
+        let child = parent.split();
+        assert_eq!(child, expected);
+        expected.jump();
+        assert_eq!(parent, expected);
+    }
+}